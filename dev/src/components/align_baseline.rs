@@ -0,0 +1,55 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn align_baseline() -> Result<OGImageWriter, Error> {
+    let avatar = OGImageWriter::new(style::WindowStyle {
+        width: 120,
+        height: 120,
+        background_color: Some(style::Rgba([255, 200, 0, 255])),
+        ..style::WindowStyle::default()
+    })?;
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Baseline,
+        justify_content: style::JustifyContent::Center,
+        flex_direction: style::FlexDirection::Row,
+        ..style::WindowStyle::default()
+    })?;
+
+    writer.set_container(
+        avatar,
+        style::Style {
+            margin: style::Margin(180, 20, 0, 20),
+            border_radius: style::BorderRadius(60, 60, 60, 60),
+            ..style::Style::default()
+        },
+    )?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Author",
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            font_size: 100.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    writer.set_text(
+        "@og_image",
+        style::Style {
+            margin: style::Margin(0, 20, 0, 0),
+            font_size: 40.,
+            color: style::Rgba([200, 200, 200, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
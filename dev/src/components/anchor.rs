@@ -0,0 +1,71 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn anchor() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    let mut badge = OGImageWriter::new(style::WindowStyle {
+        width: 120,
+        height: 48,
+        background_color: Some(style::Rgba([230, 60, 80, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+    badge.set_text(
+        "NEW",
+        style::Style {
+            font_size: 28.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    // Badge is set before the cover, but it is pinned to the corner of the cover.
+    writer.set_container(
+        badge,
+        style::Style {
+            border_radius: style::BorderRadius(8, 8, 8, 8),
+            anchor: Some(style::Anchor {
+                to: "cover".to_string(),
+                edge: style::AnchorEdge::TopRight,
+                offset: (24, -16),
+            }),
+            ..style::Style::default()
+        },
+    )?;
+
+    writer.set_img_with_data(
+        include_bytes!("../../../assets/og_template.png"),
+        480,
+        252,
+        ImageInputFormat::Png,
+        style::Style {
+            margin: style::Margin(0, 0, 20, 0),
+            border_radius: style::BorderRadius(16, 16, 16, 16),
+            id: Some("cover".to_string()),
+            ..style::Style::default()
+        },
+    )?;
+
+    writer.set_text(
+        "Anchored Badge",
+        style::Style {
+            font_size: 60.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
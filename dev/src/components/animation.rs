@@ -0,0 +1,32 @@
+use og_image_writer::{style, writer::OGImageWriter, AnimationFormat, Error};
+
+pub fn animation() -> Result<Vec<u8>, Error> {
+    let texts = ["Open", "Graphic", "Image", "Writer"];
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    for text in texts {
+        let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+        writer.set_text(
+            text,
+            style::Style {
+                font_size: 100.,
+                color: style::Rgba([255, 255, 255, 255]),
+                ..style::Style::default()
+            },
+            Some(font),
+        )?;
+
+        writer.push_frame(500)?;
+    }
+
+    writer.encode_animation(AnimationFormat::Gif)
+}
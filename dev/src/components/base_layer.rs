@@ -0,0 +1,61 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn base_layer() -> Result<OGImageWriter, Error> {
+    let mut chrome = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    chrome.set_img_with_data(
+        include_bytes!("../../../assets/frame.png"),
+        984,
+        472,
+        ImageInputFormat::Png,
+        style::Style {
+            image_slice: Some(style::ImageSlice(20, 20, 20, 20)),
+            ..style::Style::default()
+        },
+    )?;
+
+    chrome.paint()?;
+    let base = chrome.snapshot()?;
+
+    let mut writer = OGImageWriter::from_base(
+        style::WindowStyle {
+            align_items: style::AlignItems::Center,
+            justify_content: style::JustifyContent::Center,
+            ..style::WindowStyle::default()
+        },
+        &base,
+    )?;
+
+    writer.set_img_with_data(
+        include_bytes!("../../../assets/thumbnail_circle.png"),
+        120,
+        120,
+        ImageInputFormat::Png,
+        style::Style {
+            margin: style::Margin(0, 0, 20, 0),
+            border_radius: style::BorderRadius(60, 60, 60, 60),
+            ..style::Style::default()
+        },
+    )?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Stamped on Shared Chrome",
+        style::Style {
+            font_size: 64.,
+            color: style::Rgba([250, 235, 180, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,55 @@
+use og_image_writer::{style, writer::OGImageWriter, Error, TextArea};
+
+pub fn char_styles() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let colors = [
+        [255, 80, 80, 255],
+        [255, 170, 50, 255],
+        [255, 240, 60, 255],
+        [90, 220, 100, 255],
+        [80, 170, 255, 255],
+        [190, 120, 255, 255],
+    ];
+
+    let base = || style::Style {
+        font_size: 100.,
+        color: style::Rgba([255, 255, 255, 255]),
+        ..style::Style::default()
+    };
+
+    let mut textarea = TextArea::new();
+    // Rainbow text
+    textarea.push_with_char_styles("Open Graphic", base(), None, |i, _| style::StyleOverride {
+        color: Some(style::Rgba(colors[i % colors.len()])),
+        ..style::StyleOverride::default()
+    })?;
+    textarea.push_line_text("");
+    // Shaky baseline
+    textarea.push_with_char_styles("Image Writer", base(), None, |i, _| style::StyleOverride {
+        font_size: Some(90. + (i % 3) as f32 * 10.),
+        baseline_shift: Some([0, 12, -8, 6][i % 4]),
+        ..style::StyleOverride::default()
+    })?;
+
+    writer.set_textarea(
+        textarea,
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            font_size: 100.,
+            color: style::Rgba([255, 255, 255, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(include_bytes!("../../../fonts/Mplus1-Black.ttf").to_vec()),
+    )?;
+
+    Ok(writer)
+}
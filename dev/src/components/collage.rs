@@ -0,0 +1,45 @@
+use og_image_writer::{
+    collage::{Collage, CollageLayout},
+    img::ImageInputFormat,
+    style,
+    writer::OGImageWriter,
+    Error,
+};
+
+pub fn collage() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let template = include_bytes!("../../../assets/og_template.png");
+    let thumbnail = include_bytes!("../../../assets/thumbnail_circle.png");
+
+    let mut collage = Collage::new(CollageLayout::Hero, 720, 320);
+    collage.gap = 12;
+    collage.border_radius = 16;
+    collage.push_img_with_data(template, ImageInputFormat::Png);
+    collage.push_img_with_data(thumbnail, ImageInputFormat::Png);
+    collage.push_img_with_data(template, ImageInputFormat::Png);
+
+    writer.set_collage(collage, style::Style::default())?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Gallery: 3 photos",
+        style::Style {
+            margin: style::Margin(24, 0, 0, 0),
+            font_size: 50.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,43 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn columns() -> Result<OGImageWriter, Error> {
+    let title = "Open Graphic Image Writer";
+    let description = "Generate an Open Graphic Image using a CSS-like API. You can generate Open Graphic Image dynamically, and create image by using template image. Long description flows into balanced columns.";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        title,
+        style::Style {
+            margin: style::Margin(0, 40, 0, 40),
+            font_size: 60.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    writer.set_text(
+        description,
+        style::Style {
+            margin: style::Margin(30, 40, 0, 40),
+            font_size: 28.,
+            columns: 2,
+            column_gap: 40,
+            color: style::Rgba([220, 220, 220, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
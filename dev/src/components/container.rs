@@ -40,7 +40,7 @@ pub fn container() -> Result<OGImageWriter, Error> {
     })?;
 
     writer.set_container(
-        &mut container,
+        container,
         style::Style {
             margin: style::Margin(0, 0, 10, 0),
             text_align: style::TextAlign::Center,
@@ -0,0 +1,72 @@
+use og_image_writer::{
+    element::CustomElement,
+    style,
+    text_path::{CurvedText, TextPath},
+    writer::OGImageWriter,
+    Error,
+};
+
+pub fn curved_text() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+    let text_style = style::Style {
+        font_size: 44.,
+        letter_spacing: 4,
+        color: style::Rgba([255, 255, 255, 255]),
+        ..style::Style::default()
+    };
+
+    // Badge that has text on the top and the bottom of the circle.
+    let top = CurvedText::new(
+        "OPEN GRAPHIC IMAGE",
+        TextPath::Arc {
+            radius: 170.,
+            angle: 0.,
+            clockwise: true,
+        },
+        &text_style,
+        font.clone(),
+    )?;
+    let (width, height) = top.measure(1024, 512);
+    writer.set_custom(Box::new(top), style::Style::default())?;
+
+    writer.set_curved_text(
+        "WRITER",
+        TextPath::Arc {
+            radius: 170.,
+            angle: 180.,
+            clockwise: false,
+        },
+        style::Style {
+            color: style::Rgba([255, 200, 80, 255]),
+            position: style::Position::Absolute,
+            top: Some((512 - height as i32) / 2),
+            left: Some((1024 - width as i32) / 2),
+            ..text_style.clone()
+        },
+        font.clone(),
+    )?;
+
+    writer.set_text(
+        "OG",
+        style::Style {
+            font_size: 120.,
+            color: style::Rgba([255, 255, 255, 255]),
+            position: style::Position::Absolute,
+            top: Some(176),
+            left: Some(442),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
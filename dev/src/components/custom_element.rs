@@ -0,0 +1,86 @@
+use og_image_writer::{
+    element::CustomElement, painter::Painter, style, writer::OGImageWriter, Error,
+};
+
+// Draw samples as vertical bars.
+struct Waveform {
+    samples: Vec<f32>,
+    bar_width: u32,
+    gap: u32,
+    height: u32,
+    color: image::Rgba<u8>,
+}
+
+impl CustomElement for Waveform {
+    fn measure(&self, _max_width: u32, _max_height: u32) -> (u32, u32) {
+        let width = self.samples.len() as u32 * (self.bar_width + self.gap);
+        (width.saturating_sub(self.gap), self.height)
+    }
+
+    fn paint(
+        &self,
+        painter: &mut dyn Painter,
+        x: u32,
+        y: u32,
+        _width: u32,
+        height: u32,
+    ) -> Result<(), Error> {
+        for (i, sample) in self.samples.iter().enumerate() {
+            let bar_height = ((height as f32 * sample.clamp(0., 1.)) as u32).max(1);
+            painter.fill_rect(
+                x + i as u32 * (self.bar_width + self.gap),
+                y + (height - bar_height) / 2,
+                self.bar_width,
+                bar_height,
+                self.color,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn custom_element() -> Result<OGImageWriter, Error> {
+    let text = "Episode 42: Open Graphic Image Writer";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(0, 20, 40, 20),
+            line_height: 1.8,
+            font_size: 60.,
+            word_break: style::WordBreak::Normal,
+            color: style::Rgba([255, 255, 255, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    let samples = (0..64)
+        .map(|i| ((i as f32 * 0.35).sin() * (i as f32 * 0.11).cos()).abs() * 0.9 + 0.1)
+        .collect();
+
+    writer.set_custom(
+        Box::new(Waveform {
+            samples,
+            bar_width: 8,
+            gap: 4,
+            height: 160,
+            color: image::Rgba([255, 0, 255, 255]),
+        }),
+        style::Style::default(),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,56 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+// Draw simple face instead of real emoji assets.
+fn face(color: image::Rgba<u8>) -> image::RgbaImage {
+    let size = 72;
+    let r = size as f32 / 2.;
+    image::RgbaImage::from_fn(size, size, |x, y| {
+        let (dx, dy) = (x as f32 + 0.5 - r, y as f32 + 0.5 - r);
+        let d = (dx * dx + dy * dy).sqrt();
+        let is_eye = ((dx.abs() - 12.).powi(2) + (dy + 10.).powi(2)).sqrt() < 6.;
+        if d > r {
+            image::Rgba([0, 0, 0, 0])
+        } else if is_eye || (d > 18. && d < 24. && dy > 6.) {
+            image::Rgba([60, 30, 20, 255])
+        } else {
+            color
+        }
+    })
+}
+
+pub fn emoji_image() -> Result<OGImageWriter, Error> {
+    let text = "Launch 😀 day for Open Graphic Image Writer 🤩";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    writer.set_emoji_resolver(Box::new(|emoji: &str| match emoji {
+        "😀" => Some(face(image::Rgba([255, 204, 77, 255]))),
+        "🤩" => Some(face(image::Rgba([255, 120, 200, 255]))),
+        _ => None,
+    }));
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            line_height: 1.8,
+            font_size: 80.,
+            word_break: style::WordBreak::Normal,
+            color: style::Rgba([255, 255, 255, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,60 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn image_crop() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        flex_direction: style::FlexDirection::Row,
+        ..style::WindowStyle::default()
+    })?;
+
+    let data = include_bytes!("../../../assets/thumbnail_circle.png");
+
+    let crops = [
+        // Center
+        style::ImageCrop::Focus {
+            x: 0.5,
+            y: 0.5,
+            zoom: 1.,
+        },
+        // Face
+        style::ImageCrop::Focus {
+            x: 0.5,
+            y: 0.22,
+            zoom: 1.,
+        },
+        // Zoom in to face
+        style::ImageCrop::Focus {
+            x: 0.52,
+            y: 0.22,
+            zoom: 2.5,
+        },
+        // Badge on hoodie
+        style::ImageCrop::Rect {
+            x: 190,
+            y: 200,
+            width: 90,
+            height: 70,
+        },
+    ];
+
+    for crop in crops {
+        writer.set_img_with_data(
+            data,
+            230,
+            140,
+            ImageInputFormat::Png,
+            style::Style {
+                margin: style::Margin(0, 8, 0, 8),
+                border_radius: style::BorderRadius(16, 16, 16, 16),
+                crop: Some(crop),
+                ..style::Style::default()
+            },
+        )?;
+    }
+
+    Ok(writer)
+}
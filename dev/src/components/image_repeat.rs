@@ -0,0 +1,56 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn image_repeat() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let dots = include_bytes!("../../../assets/dots.png");
+
+    // Texture of background
+    writer.set_img_with_data(
+        dots,
+        1024,
+        512,
+        ImageInputFormat::Png,
+        style::Style {
+            position: style::Position::Absolute,
+            top: Some(0),
+            left: Some(0),
+            image_repeat: style::ImageRepeat::Repeat,
+            ..style::Style::default()
+        },
+    )?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Seamless Texture",
+        style::Style {
+            margin: style::Margin(0, 0, 20, 0),
+            font_size: 90.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    // Divider
+    writer.set_img_with_data(
+        include_bytes!("../../../assets/frame.png"),
+        600,
+        60,
+        ImageInputFormat::Png,
+        style::Style {
+            image_repeat: style::ImageRepeat::RepeatX,
+            ..style::Style::default()
+        },
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,39 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn letterpress() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Letterpress",
+        style::Style {
+            font_size: 160.,
+            color: style::Rgba([58, 32, 76, 255]),
+            text_effect: style::TextEffect::Letterpress,
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    writer.set_text(
+        "Open Graphic Image Writer",
+        style::Style {
+            margin: style::Margin(10, 0, 0, 0),
+            font_size: 50.,
+            color: style::Rgba([220, 200, 235, 255]),
+            text_effect: style::TextEffect::Letterpress,
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -1,25 +1,85 @@
 mod absolute;
+mod align_baseline;
+mod anchor;
+mod animation;
 mod background_color;
 mod background_image;
+mod base_layer;
+mod char_styles;
+mod collage;
+mod columns;
 mod container;
+mod curved_text;
+mod custom_element;
 mod ellipsis;
+mod emoji_image;
 mod encode;
 mod font_context;
 mod font_kern;
+mod image_crop;
+mod image_repeat;
 mod into_vec;
+mod letterpress;
+mod nine_slice;
+mod og_image_macro;
+mod output_channels;
+mod palette;
+mod preformatted;
+mod progressive;
 mod row_container;
+mod ruby;
+mod stack;
+mod strips;
+mod svg;
+mod tabular_nums;
+mod templates;
+mod text_indent;
+mod text_wrap;
 mod textarea;
+mod theme;
+mod trim;
+mod vertical_align;
 mod white_space;
 
 pub use absolute::*;
+pub use align_baseline::*;
+pub use anchor::*;
+pub use animation::*;
 pub use background_color::*;
 pub use background_image::*;
+pub use base_layer::*;
+pub use char_styles::*;
+pub use collage::*;
+pub use columns::*;
 pub use container::*;
+pub use curved_text::*;
+pub use custom_element::*;
 pub use ellipsis::*;
+pub use emoji_image::*;
 pub use encode::*;
 pub use font_context::*;
 pub use font_kern::*;
+pub use image_crop::*;
+pub use image_repeat::*;
 pub use into_vec::*;
+pub use letterpress::*;
+pub use nine_slice::*;
+pub use og_image_macro::*;
+pub use output_channels::*;
+pub use palette::*;
+pub use preformatted::*;
+pub use progressive::*;
 pub use row_container::*;
+pub use ruby::*;
+pub use stack::*;
+pub use strips::*;
+pub use svg::*;
+pub use tabular_nums::*;
+pub use templates::*;
+pub use text_indent::*;
+pub use text_wrap::*;
 pub use textarea::*;
+pub use theme::*;
+pub use trim::*;
+pub use vertical_align::*;
 pub use white_space::*;
@@ -0,0 +1,42 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn nine_slice() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let frame = include_bytes!("../../../assets/frame.png");
+
+    for (width, height) in [(800, 200), (300, 100)] {
+        writer.set_img_with_data(
+            frame,
+            width,
+            height,
+            ImageInputFormat::Png,
+            style::Style {
+                margin: style::Margin(10, 0, 10, 0),
+                image_slice: Some(style::ImageSlice(20, 20, 20, 20)),
+                ..style::Style::default()
+            },
+        )?;
+    }
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Framed Title",
+        style::Style {
+            font_size: 80.,
+            color: style::Rgba([250, 235, 180, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
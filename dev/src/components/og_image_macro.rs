@@ -0,0 +1,49 @@
+use og_image_writer::{og_image, style::*, writer::OGImageWriter, Error};
+
+// Same layout as `row_container` that is written with `og_image!` macro.
+pub fn og_image_macro() -> Result<OGImageWriter, Error> {
+    let text = "This is Open Graphic Image Writer for Web Developer.";
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    og_image! {
+        window {
+            width: 1024,
+            height: 512,
+            background_color: Some(Rgba([255, 255, 255, 255])),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Row,
+        }
+        column(
+            width: 500,
+            height: 250,
+            background_color: Some(Rgba([70, 40, 90, 255])),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center;
+            margin: Margin(0, 10, 0, 10),
+            text_align: TextAlign::Center,
+            border_radius: BorderRadius(10, 10, 10, 10),
+        ) {
+            text(text, font.clone()) {
+                margin: Margin(0, 10, 0, 10),
+                line_height: 1.5,
+                font_size: 80.,
+                word_break: WordBreak::Normal,
+                color: Rgba([255, 255, 255, 255]),
+                text_align: TextAlign::Start,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(200),
+            }
+        }
+        text(text, font) {
+            margin: Margin(0, 20, 0, 20),
+            line_height: 1.8,
+            font_size: 100.,
+            word_break: WordBreak::Normal,
+            color: Rgba([0, 0, 0, 255]),
+            text_align: TextAlign::Center,
+            max_width: Some(500),
+            max_height: Some(400),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn preformatted() -> Result<OGImageWriter, Error> {
+    let text = "fn main() {\n\tlet name = \"og_image_writer\";\n\tif true {\n\t\tprintln!(\"{}\", name);\n\t}\n}";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([30, 30, 40, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            font_size: 40.,
+            white_space: style::WhiteSpace::Pre,
+            tab_size: 4,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -34,13 +34,14 @@ pub fn row_container() -> Result<OGImageWriter, Error> {
         width: 1024,
         height: 512,
         background_color: Some(style::Rgba([255, 255, 255, 255])),
+        background_token: None,
         align_items: style::AlignItems::Center,
         justify_content: style::JustifyContent::Center,
         flex_direction: style::FlexDirection::Row,
     })?;
 
     writer.set_container(
-        &mut container,
+        container,
         style::Style {
             margin: style::Margin(0, 10, 0, 10),
             text_align: style::TextAlign::Center,
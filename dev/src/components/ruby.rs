@@ -0,0 +1,49 @@
+use og_image_writer::{style, writer::OGImageWriter, Error, TextArea};
+
+pub fn ruby() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let mut textarea = TextArea::new();
+    textarea.push_ruby_text("今日", "きょう");
+    textarea.push_text("は");
+    textarea.push_ruby(
+        "漢字",
+        "かんじ",
+        style::Style {
+            color: style::Rgba([255, 255, 0, 255]),
+            font_size: 80.,
+            ..style::Style::default()
+        },
+        None,
+    )?;
+    textarea.push_text("の");
+    textarea.push_ruby_text("勉強", "べんきょう");
+    textarea.push_text("をしましょう。");
+    textarea.push_ruby_text("読", "よ");
+    textarea.push_text("み");
+    textarea.push_ruby_text("方", "かた");
+    textarea.push_text("を");
+    textarea.push_ruby_text("覚", "おぼ");
+    textarea.push_text("えます。");
+
+    writer.set_textarea(
+        textarea,
+        style::Style {
+            margin: style::Margin(0, 40, 0, 40),
+            font_size: 80.,
+            color: style::Rgba([255, 255, 255, 255]),
+            word_break: style::WordBreak::BreakAll,
+            ..style::Style::default()
+        },
+        Some(include_bytes!("../../../fonts/Mplus1-Black.ttf").to_vec()),
+    )?;
+
+    Ok(writer)
+}
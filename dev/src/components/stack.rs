@@ -0,0 +1,67 @@
+use og_image_writer::{img::ImageInputFormat, stack::Stack, style, writer::OGImageWriter, Error};
+
+pub fn stack() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    let mut cover = OGImageWriter::new(style::WindowStyle {
+        width: 640,
+        height: 320,
+        ..style::WindowStyle::default()
+    })?;
+    cover.set_img_with_data(
+        include_bytes!("../../../assets/og_template.png"),
+        640,
+        320,
+        ImageInputFormat::Png,
+        style::Style::default(),
+    )?;
+
+    let mut caption = OGImageWriter::new(style::WindowStyle {
+        width: 640,
+        height: 80,
+        background_color: Some(style::Rgba([0, 0, 0, 160])),
+        align_items: style::AlignItems::Start,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+    caption.set_text(
+        "Image with caption overlay",
+        style::Style {
+            margin: style::Margin(0, 24, 0, 24),
+            font_size: 32.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    let tag = OGImageWriter::new(style::WindowStyle {
+        width: 48,
+        height: 48,
+        background_color: Some(style::Rgba([250, 200, 60, 255])),
+        ..style::WindowStyle::default()
+    })?;
+
+    let mut stack = Stack::new(cover);
+    stack.push(caption, style::AnchorEdge::Bottom, (0, 0));
+    stack.push(tag, style::AnchorEdge::TopRight, (-16, 16));
+
+    writer.set_stack(
+        stack,
+        style::Style {
+            border_radius: style::BorderRadius(16, 16, 16, 16),
+            ..style::Style::default()
+        },
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,44 @@
+use og_image_writer::{img::ImageInputFormat, style, writer::OGImageWriter, Error};
+
+pub fn strips() -> Result<Vec<u8>, Error> {
+    let mut writer = OGImageWriter::with_strip_height(
+        style::WindowStyle {
+            width: 2048,
+            height: 1024,
+            background_color: Some(style::Rgba([70, 40, 90, 255])),
+            align_items: style::AlignItems::Center,
+            justify_content: style::JustifyContent::Center,
+            ..style::WindowStyle::default()
+        },
+        64,
+    )?;
+
+    writer.set_img_with_data(
+        include_bytes!("../../../assets/thumbnail_circle.png"),
+        240,
+        240,
+        ImageInputFormat::Png,
+        style::Style {
+            margin: style::Margin(0, 0, 40, 0),
+            border_radius: style::BorderRadius(120, 120, 120, 120),
+            ..style::Style::default()
+        },
+    )?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        "Rendered in Strips",
+        style::Style {
+            font_size: 128.,
+            color: style::Rgba([250, 235, 180, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    let mut buf = vec![];
+    writer.write_png_strips(&mut buf)?;
+
+    Ok(buf)
+}
@@ -0,0 +1,51 @@
+use og_image_writer::{style, writer::OGImageWriter, Error, TextArea};
+
+pub fn tabular_nums() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        flex_direction: style::FlexDirection::Row,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+    let label = style::Style {
+        margin: style::Margin(0, 40, 0, 0),
+        line_height: 1.4,
+        font_size: 50.,
+        white_space: style::WhiteSpace::PreLine,
+        color: style::Rgba([200, 180, 220, 255]),
+        text_align: style::TextAlign::End,
+        ..style::Style::default()
+    };
+
+    writer.set_text(
+        "Stars\nDownloads\nLatency\nUptime",
+        label.clone(),
+        Some(font.clone()),
+    )?;
+
+    let mut textarea = TextArea::new();
+    textarea.push_line_text("1,204.5");
+    textarea.push_line_text("98,310");
+    textarea.push_line_text("3.25 ms");
+    textarea.push_text("99.9 %");
+
+    writer.set_textarea(
+        textarea,
+        style::Style {
+            margin: style::Margin(0, 0, 0, 0),
+            kern_setting: style::KernSetting::Optical,
+            font_variant_numeric: style::FontVariantNumeric::TabularNums,
+            color: style::Rgba([255, 255, 255, 255]),
+            text_align: style::TextAlign::Decimal,
+            ..label
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
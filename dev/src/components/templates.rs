@@ -0,0 +1,41 @@
+use og_image_writer::{
+    img::ImageInputFormat,
+    templates::{ArticleCard, ProductCard, TemplateImage, TitleCard},
+    writer::OGImageWriter,
+    Error,
+};
+
+const FONT: &[u8] = include_bytes!("../../../fonts/Mplus1-Black.ttf");
+const THUMBNAIL: &[u8] = include_bytes!("../../../assets/thumbnail_circle.png");
+
+pub fn title_card() -> Result<OGImageWriter, Error> {
+    TitleCard {
+        subtitle: Some("Generate Open Graphic Image using a CSS-like API."),
+        logo: Some(TemplateImage::new(THUMBNAIL, ImageInputFormat::Png)),
+        ..TitleCard::new("Open Graphic Image Writer", FONT.to_vec())
+    }
+    .build()
+}
+
+pub fn article_card() -> Result<OGImageWriter, Error> {
+    ArticleCard {
+        avatar: Some(TemplateImage::new(THUMBNAIL, ImageInputFormat::Png)),
+        site_name: Some("blog.example.com"),
+        date: Some("2022-01-01"),
+        ..ArticleCard::new(
+            "This is Open Graphic Image Writer for Web Developer.",
+            "keiya01",
+            FONT.to_vec(),
+        )
+    }
+    .build()
+}
+
+pub fn product_card() -> Result<OGImageWriter, Error> {
+    ProductCard {
+        description: Some("Ready-made card layouts for your site."),
+        image: Some(TemplateImage::new(THUMBNAIL, ImageInputFormat::Png)),
+        ..ProductCard::new("OG Image Template", "$19.99", FONT.to_vec())
+    }
+    .build()
+}
@@ -0,0 +1,43 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn text_indent() -> Result<OGImageWriter, Error> {
+    let text = "This is Open Graphic Image Writer for Web Developer.";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            font_size: 50.,
+            text_indent: 100,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    // Hanging indent
+    writer.set_text(
+        "Open Graphic Image Writer: Generate an Open Graphic Image using a CSS-like API.",
+        style::Style {
+            margin: style::Margin(20, 20, 0, 20),
+            font_size: 50.,
+            text_indent: -100,
+            color: style::Rgba([255, 255, 0, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
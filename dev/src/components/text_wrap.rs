@@ -0,0 +1,56 @@
+use og_image_writer::{style, writer::OGImageWriter, Error};
+
+pub fn text_wrap() -> Result<OGImageWriter, Error> {
+    let text = "Generate an Open Graphic Image using a CSS-like API";
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(0, 20, 0, 20),
+            font_size: 56.,
+            color: style::Rgba([255, 255, 255, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(20, 20, 0, 20),
+            font_size: 56.,
+            text_wrap: style::TextWrap::Balance,
+            color: style::Rgba([255, 255, 0, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(font.clone()),
+    )?;
+
+    writer.set_text(
+        text,
+        style::Style {
+            margin: style::Margin(20, 20, 0, 20),
+            font_size: 56.,
+            text_wrap: style::TextWrap::Pretty,
+            color: style::Rgba([0, 255, 255, 255]),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    Ok(writer)
+}
@@ -0,0 +1,62 @@
+use og_image_writer::{style, theme::Theme, writer::OGImageWriter, Error};
+
+pub fn theme() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(theme_window())?;
+
+    // Switch to `Theme::light()` to generate light card.
+    writer.set_theme(Theme {
+        fonts: theme_fonts(),
+        ..Theme::dark()
+    })?;
+
+    theme_card(&mut writer)?;
+
+    Ok(writer)
+}
+
+pub fn theme_window() -> style::WindowStyle {
+    style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_token: Some(style::Token::Background),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    }
+}
+
+pub fn theme_fonts() -> Vec<Vec<u8>> {
+    vec![Vec::from(
+        include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]
+    )]
+}
+
+// Elements are set after theme, because margins are resolved with spacing scale of the theme.
+pub fn theme_card(writer: &mut OGImageWriter) -> Result<(), Error> {
+    writer.set_text(
+        "This is Open Graphic Image Writer",
+        style::Style {
+            margin_token: Some(style::SpaceToken(0, 5, 5, 5)),
+            line_height: 1.8,
+            font_size: 80.,
+            color_token: Some(style::Token::Text),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        None,
+    )?;
+
+    writer.set_text(
+        "for Web Developer.",
+        style::Style {
+            margin_token: Some(style::SpaceToken(0, 5, 0, 5)),
+            font_size: 50.,
+            color_token: Some(style::Token::Accent),
+            text_align: style::TextAlign::Center,
+            ..style::Style::default()
+        },
+        None,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use og_image_writer::{style, writer::OGImageWriter, Error, ImageOutputFormat};
+
+pub fn trim() -> Result<Vec<u8>, Error> {
+    let mut badge = OGImageWriter::new(style::WindowStyle {
+        width: 360,
+        height: 120,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    badge.set_text(
+        "New Release",
+        style::Style {
+            font_size: 48.,
+            color: style::Rgba([255, 255, 255, 255]),
+            ..style::Style::default()
+        },
+        Some(font),
+    )?;
+
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([0, 0, 0, 0])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        ..style::WindowStyle::default()
+    })?;
+
+    writer.set_container(
+        badge,
+        style::Style {
+            border_radius: style::BorderRadius(60, 60, 60, 60),
+            ..style::Style::default()
+        },
+    )?;
+
+    writer.paint()?;
+    writer.trim_transparent()?;
+
+    writer.encode(ImageOutputFormat::Png)
+}
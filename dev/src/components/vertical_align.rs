@@ -0,0 +1,47 @@
+use og_image_writer::{style, writer::OGImageWriter, Error, TextArea};
+
+pub fn vertical_align() -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(style::WindowStyle {
+        width: 1024,
+        height: 512,
+        background_color: Some(style::Rgba([70, 40, 90, 255])),
+        align_items: style::AlignItems::Center,
+        justify_content: style::JustifyContent::Center,
+        flex_direction: style::FlexDirection::Row,
+        ..style::WindowStyle::default()
+    })?;
+
+    let font = Vec::from(include_bytes!("../../../fonts/Mplus1-Black.ttf") as &[u8]);
+
+    for vertical_align in [
+        style::VerticalAlign::Baseline,
+        style::VerticalAlign::Top,
+        style::VerticalAlign::Middle,
+        style::VerticalAlign::Bottom,
+    ] {
+        let small = || style::Style {
+            font_size: 40.,
+            color: style::Rgba([255, 255, 0, 255]),
+            vertical_align,
+            ..style::Style::default()
+        };
+
+        let mut textarea = TextArea::new();
+        textarea.push("$", small(), None)?;
+        textarea.push_text("49");
+        textarea.push("/mo", small(), None)?;
+
+        writer.set_textarea(
+            textarea,
+            style::Style {
+                margin: style::Margin(0, 20, 0, 20),
+                font_size: 100.,
+                color: style::Rgba([255, 255, 255, 255]),
+                ..style::Style::default()
+            },
+            Some(font.clone()),
+        )?;
+    }
+
+    Ok(writer)
+}
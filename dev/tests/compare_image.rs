@@ -46,6 +46,21 @@ fn compare_absolute() {
     assert_component!(absolute);
 }
 
+#[test]
+fn compare_align_baseline() {
+    assert_component!(align_baseline);
+}
+
+#[test]
+fn compare_anchor() {
+    assert_component!(anchor);
+}
+
+#[test]
+fn compare_article_card() {
+    assert_component!(article_card);
+}
+
 #[test]
 fn compare_background_color() {
     assert_component!(background_color);
@@ -56,16 +71,51 @@ fn compare_background_image() {
     assert_component!(background_image);
 }
 
+#[test]
+fn compare_base_layer() {
+    assert_component!(base_layer);
+}
+
+#[test]
+fn compare_char_styles() {
+    assert_component!(char_styles);
+}
+
+#[test]
+fn compare_collage() {
+    assert_component!(collage);
+}
+
+#[test]
+fn compare_columns() {
+    assert_component!(columns);
+}
+
 #[test]
 fn compare_container() {
     assert_component!(container);
 }
 
+#[test]
+fn compare_curved_text() {
+    assert_component!(curved_text);
+}
+
+#[test]
+fn compare_custom_element() {
+    assert_component!(custom_element);
+}
+
 #[test]
 fn compare_ellipsis() {
     assert_component!(ellipsis);
 }
 
+#[test]
+fn compare_emoji_image() {
+    assert_component!(emoji_image);
+}
+
 #[test]
 fn compare_font_context() {
     assert_component!(font_context);
@@ -75,16 +125,100 @@ fn compare_font_context() {
 fn compare_font_kern() {
     assert_component!(font_kern);
 }
+
+#[test]
+fn compare_image_crop() {
+    assert_component!(image_crop);
+}
+
+#[test]
+fn compare_image_repeat() {
+    assert_component!(image_repeat);
+}
+
+#[test]
+fn compare_letterpress() {
+    assert_component!(letterpress);
+}
+
+#[test]
+fn compare_nine_slice() {
+    assert_component!(nine_slice);
+}
+
+#[test]
+fn compare_og_image_macro() {
+    let mut w = og_image_macro().unwrap();
+    w.paint().unwrap();
+    let rgba = w.into_rgba().unwrap();
+    // `og_image!` macro generates the same image as `row_container`.
+    let path = format!(
+        "{}/snapshots/output_row_container.png",
+        current_dir().unwrap().display(),
+    );
+    assert_image(&read(path).unwrap(), rgba);
+}
+
+#[test]
+fn compare_preformatted() {
+    assert_component!(preformatted);
+}
+
+#[test]
+fn compare_product_card() {
+    assert_component!(product_card);
+}
+
 #[test]
 fn compare_row_container() {
     assert_component!(row_container);
 }
 
+#[test]
+fn compare_ruby() {
+    assert_component!(ruby);
+}
+
+#[test]
+fn compare_stack() {
+    assert_component!(stack);
+}
+
+#[test]
+fn compare_tabular_nums() {
+    assert_component!(tabular_nums);
+}
+
+#[test]
+fn compare_text_indent() {
+    assert_component!(text_indent);
+}
+
+#[test]
+fn compare_text_wrap() {
+    assert_component!(text_wrap);
+}
+
 #[test]
 fn compare_textarea() {
     assert_component!(textarea);
 }
 
+#[test]
+fn compare_theme() {
+    assert_component!(theme);
+}
+
+#[test]
+fn compare_title_card() {
+    assert_component!(title_card);
+}
+
+#[test]
+fn compare_vertical_align() {
+    assert_component!(vertical_align);
+}
+
 #[test]
 fn compare_white_space() {
     assert_component!(white_space);
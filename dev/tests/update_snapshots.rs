@@ -17,13 +17,37 @@ macro_rules! snapshot {
 #[ignore]
 fn update_snapshots() {
     snapshot!(absolute);
+    snapshot!(align_baseline);
+    snapshot!(anchor);
+    snapshot!(article_card);
     snapshot!(background_color);
     snapshot!(background_image);
+    snapshot!(base_layer);
+    snapshot!(char_styles);
+    snapshot!(collage);
+    snapshot!(columns);
     snapshot!(container);
+    snapshot!(curved_text);
+    snapshot!(custom_element);
     snapshot!(ellipsis);
+    snapshot!(emoji_image);
     snapshot!(font_context);
     snapshot!(font_kern);
+    snapshot!(image_crop);
+    snapshot!(image_repeat);
+    snapshot!(letterpress);
+    snapshot!(nine_slice);
+    snapshot!(preformatted);
+    snapshot!(product_card);
     snapshot!(row_container);
+    snapshot!(ruby);
+    snapshot!(stack);
+    snapshot!(tabular_nums);
+    snapshot!(text_indent);
+    snapshot!(text_wrap);
     snapshot!(textarea);
+    snapshot!(theme);
+    snapshot!(title_card);
+    snapshot!(vertical_align);
     snapshot!(white_space);
 }
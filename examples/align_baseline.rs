@@ -0,0 +1,13 @@
+use dev::components::align_baseline;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = align_baseline()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_align_baseline.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
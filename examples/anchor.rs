@@ -0,0 +1,13 @@
+use dev::components::anchor;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = anchor()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_anchor.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use dev::components::animation;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let buf = animation()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_animation.gif";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), &buf)?;
+
+    Ok(())
+}
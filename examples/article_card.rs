@@ -0,0 +1,13 @@
+use dev::components::article_card;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = article_card()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_article_card.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::base_layer;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = base_layer()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_base_layer.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
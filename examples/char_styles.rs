@@ -0,0 +1,13 @@
+use dev::components::char_styles;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = char_styles()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_char_styles.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::collage;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = collage()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_collage.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
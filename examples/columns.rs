@@ -0,0 +1,13 @@
+use dev::components::columns;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = columns()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_columns.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
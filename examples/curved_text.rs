@@ -0,0 +1,13 @@
+use dev::components::curved_text;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = curved_text()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_curved_text.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
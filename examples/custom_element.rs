@@ -0,0 +1,13 @@
+use dev::components::custom_element;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = custom_element()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_custom_element.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
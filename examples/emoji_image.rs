@@ -0,0 +1,13 @@
+use dev::components::emoji_image;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = emoji_image()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_emoji_image.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
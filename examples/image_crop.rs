@@ -0,0 +1,13 @@
+use dev::components::image_crop;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = image_crop()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_image_crop.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
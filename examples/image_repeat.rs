@@ -0,0 +1,13 @@
+use dev::components::image_repeat;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = image_repeat()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_image_repeat.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::letterpress;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = letterpress()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_letterpress.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::nine_slice;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = nine_slice()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_nine_slice.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::og_image_macro;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = og_image_macro()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_og_image_macro.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use dev::components::output_channels;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let buf = output_channels()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_output_channels.jpg";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), &buf)?;
+
+    Ok(())
+}
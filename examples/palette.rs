@@ -0,0 +1,14 @@
+use dev::components::palette;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let buf = palette()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_palette.png";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), &buf)?;
+
+    Ok(())
+}
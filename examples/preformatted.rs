@@ -0,0 +1,13 @@
+use dev::components::preformatted;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = preformatted()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_preformatted.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
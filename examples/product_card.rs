@@ -0,0 +1,13 @@
+use dev::components::product_card;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = product_card()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_product_card.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use dev::components::progressive;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let buf = progressive()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_progressive.jpg";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), &buf)?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::ruby;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = ruby()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_ruby.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::stack;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = stack()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_stack.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
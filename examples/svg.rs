@@ -0,0 +1,14 @@
+use dev::components::svg;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let doc = svg()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_svg.svg";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), doc)?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::tabular_nums;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = tabular_nums()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_tabular_nums.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::text_indent;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = text_indent()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_text_indent.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::text_wrap;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = text_wrap()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_text_wrap.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::theme;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = theme()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_theme.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use dev::components::{theme_card, theme_fonts, theme_window};
+use og_image_writer::{theme::Theme, writer::OGImageWriter};
+
+fn main() -> anyhow::Result<()> {
+    let mut w = OGImageWriter::new(theme_window())?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_theme_{theme}.png";
+
+    let themes = [Theme::light(), Theme::dark()].map(|theme| Theme {
+        fonts: theme_fonts(),
+        ..theme
+    });
+    // Themes share fonts and scales, so the card is laid out once and painted with colors of each theme.
+    w.set_theme(themes[0].clone())?;
+    theme_card(&mut w)?;
+    w.generate_variants(&themes, &format!("{}/{}", out_dir, out_filename))?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::title_card;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = title_card()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_title_card.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use dev::components::trim;
+use std::fs::write;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let buf = trim()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_trim.png";
+
+    write(Path::new(&format!("{}/{}", out_dir, out_filename)), &buf)?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use dev::components::vertical_align;
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let mut w = vertical_align()?;
+
+    let out_dir = "./examples";
+    let out_filename = "output_vertical_align.png";
+
+    w.generate(Path::new(&format!("{}/{}", out_dir, out_filename)))?;
+
+    Ok(())
+}
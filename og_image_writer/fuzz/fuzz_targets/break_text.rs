@@ -0,0 +1,56 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use og_image_writer::style::{Style, TextOverflow, TextWrap, WhiteSpace, WindowStyle, WordBreak};
+use og_image_writer::writer::OGImageWriter;
+
+// Break arbitrary text with style that is selected by the first bytes.
+// Layout may return error for text that can not be drawn, but must not panic.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 4 {
+        return;
+    }
+    let (options, text) = data.split_at(4);
+    let text = match std::str::from_utf8(text) {
+        Ok(text) => text,
+        Err(_) => return,
+    };
+
+    let style = Style {
+        font_size: (options[0] % 64) as f32 + 0.5,
+        max_width: Some(options[1] as u32),
+        max_height: Some(options[2] as u32),
+        word_break: match options[3] & 1 {
+            0 => WordBreak::Normal,
+            _ => WordBreak::BreakAll,
+        },
+        text_wrap: match (options[3] >> 1) % 3 {
+            0 => TextWrap::Wrap,
+            1 => TextWrap::Balance,
+            _ => TextWrap::Pretty,
+        },
+        white_space: match (options[3] >> 3) % 3 {
+            0 => WhiteSpace::Normal,
+            1 => WhiteSpace::PreLine,
+            _ => WhiteSpace::Pre,
+        },
+        text_overflow: match (options[3] >> 5) % 3 {
+            0 => TextOverflow::Clip,
+            1 => TextOverflow::Ellipsis,
+            _ => TextOverflow::Content("\u{0301}…".to_string()),
+        },
+        columns: (options[3] >> 7) as u32 + 1,
+        ..Style::default()
+    };
+
+    let font = Vec::from(include_bytes!("../../../fonts/Roboto-Light.ttf") as &[u8]);
+    let mut writer = OGImageWriter::new(WindowStyle {
+        width: 200,
+        height: 100,
+        ..WindowStyle::default()
+    })
+    .unwrap();
+    if writer.set_text(text, style, Some(font)).is_ok() {
+        let _ = writer.paint();
+    }
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use og_image_writer::style::{Style, TextWrap, WindowStyle};
+use og_image_writer::writer::OGImageWriter;
+use og_image_writer::TextArea;
+
+// Break arbitrary spans that are split at NUL, and styled in turn,
+// so that glyph runs of each span are segmented across span boundaries.
+fuzz_target!(|text: &str| {
+    let font = Vec::from(include_bytes!("../../../fonts/Roboto-Light.ttf") as &[u8]);
+    let mut textarea = TextArea::new();
+    for (i, span) in text.split('\0').enumerate() {
+        if i % 2 == 0 {
+            textarea.push_text(span);
+        } else {
+            let style = Style {
+                font_size: 24.,
+                letter_spacing: -2,
+                ..Style::default()
+            };
+            if textarea.push(span, style, None).is_err() {
+                return;
+            }
+        }
+    }
+
+    let style = Style {
+        max_width: Some(80),
+        max_height: Some(60),
+        text_wrap: TextWrap::Balance,
+        ..Style::default()
+    };
+    let mut writer = OGImageWriter::new(WindowStyle {
+        width: 100,
+        height: 80,
+        ..WindowStyle::default()
+    })
+    .unwrap();
+    if writer.set_textarea(textarea, style, Some(font)).is_ok() {
+        let _ = writer.paint();
+    }
+});
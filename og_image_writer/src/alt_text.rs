@@ -0,0 +1,129 @@
+//! Rendered texts returned by [`OGImageWriter::alt_text()`](crate::writer::OGImageWriter::alt_text).
+//!
+//! The texts are collected from the same layout as the image, so alt text of the image
+//! can be generated with it and doesn't get out of date with the rendered content.
+
+use crate::element::Element;
+use crate::region::json_string;
+use std::fmt;
+
+/// Texts of elements in reading order, from top to bottom and left to right.
+/// Use `to_string()` to get them as a single alt text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AltText {
+    pub texts: Vec<String>,
+}
+
+impl AltText {
+    pub(crate) fn new(elements: &[&Element]) -> Self {
+        let mut elements: Vec<&Element> = elements
+            .iter()
+            .copied()
+            .filter(|elm| !matches!(elm, Element::Img(_)))
+            .collect();
+        // Stable sort keeps the order of elements that are set on the same position.
+        elements.sort_by_key(|elm| {
+            let rect = elm.border_box();
+            (rect.y, rect.x)
+        });
+        let texts = elements
+            .into_iter()
+            .flat_map(|elm| match elm {
+                Element::Text(Some(text)) => vec![text.text().to_string()],
+                Element::Custom(Some(custom)) => custom.element().texts(),
+                _ => vec![],
+            })
+            .map(|text| normalize(&text))
+            .filter(|text| !text.is_empty())
+            .collect();
+        AltText { texts }
+    }
+
+    /// Serialize texts to JSON like `{"alt":"Title. Subtitle","texts":["Title","Subtitle"]}`.
+    pub fn to_json(&self) -> String {
+        let texts: Vec<String> = self
+            .texts
+            .iter()
+            .map(|text| json_string(Some(text)))
+            .collect();
+        format!(
+            r#"{{"alt":{},"texts":[{}]}}"#,
+            json_string(Some(&self.to_string())),
+            texts.join(",")
+        )
+    }
+}
+
+impl fmt::Display for AltText {
+    // Join texts as sentences, because texts like a title and a subtitle usually don't have periods.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut texts = self.texts.iter().peekable();
+        while let Some(text) = texts.next() {
+            f.write_str(text)?;
+            if texts.peek().is_some() {
+                let ends_sentence = matches!(
+                    text.chars().last(),
+                    Some('.' | '!' | '?' | ':' | ';' | '…' | '。' | '！' | '？')
+                );
+                f.write_str(if ends_sentence { " " } else { ". " })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Line breaks and runs of whitespace are read as a single space.
+fn normalize(text: &str) -> String {
+    text.split(char::is_whitespace)
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::img::DecodedImage;
+    use crate::style::{FlexDirection, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_alt_text() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            flex_direction: FlexDirection::Column,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_text("Hello\u{2028}  world", Style::default(), Some(font.clone()))
+            .unwrap();
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+        let mut child = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 50,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        child
+            .set_text("Say \"hi\"!", Style::default(), Some(font.clone()))
+            .unwrap();
+        writer.set_container(child, Style::default()).unwrap();
+        writer
+            .set_text("Bye", Style::default(), Some(font))
+            .unwrap();
+
+        let alt = writer.alt_text().unwrap();
+        assert_eq!(alt.texts, vec!["Hello world", "Say \"hi\"!", "Bye"]);
+        assert_eq!(alt.to_string(), "Hello world. Say \"hi\"! Bye");
+        assert_eq!(
+            alt.to_json(),
+            r#"{"alt":"Hello world. Say \"hi\"! Bye","texts":["Hello world","Say \"hi\"!","Bye"]}"#
+        );
+    }
+}
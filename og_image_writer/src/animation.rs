@@ -0,0 +1,220 @@
+use crate::Error;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, Delay, Frame, RgbaImage};
+use wasm_bindgen::prelude::*;
+
+/// Format of animated image generated by [`OGImageWriter::encode_animation()`](crate::writer::OGImageWriter::encode_animation).
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum AnimationFormat {
+    Apng,
+    Gif,
+}
+
+pub(super) struct AnimationFrame {
+    pub(super) image: RgbaImage,
+    // Delay in milliseconds.
+    pub(super) delay: u32,
+}
+
+impl AnimationFrame {
+    pub(super) fn new(image: RgbaImage, delay: u32) -> Self {
+        AnimationFrame { image, delay }
+    }
+}
+
+pub(super) fn encode_frames(
+    frames: Vec<AnimationFrame>,
+    format: AnimationFormat,
+) -> Result<Vec<u8>, Error> {
+    if frames.is_empty() {
+        return Err(Error::EmptyAnimation);
+    }
+
+    match format {
+        AnimationFormat::Apng => encode_apng(frames),
+        AnimationFormat::Gif => encode_gif(frames),
+    }
+}
+
+fn encode_gif(frames: Vec<AnimationFrame>) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    {
+        let mut encoder = GifEncoder::new(&mut buf);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(frames.into_iter().map(|frame| {
+            Frame::from_parts(
+                frame.image,
+                0,
+                0,
+                Delay::from_numer_denom_ms(frame.delay, 1),
+            )
+        }))?;
+    }
+    Ok(buf)
+}
+
+pub(super) const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+// APNG is not supported by png encoder that is used in image crate.
+// So each frame is encoded as PNG, and IDAT chunks are rewritten to APNG frame chunks.
+// See https://wiki.mozilla.org/APNG_Specification
+fn encode_apng(frames: Vec<AnimationFrame>) -> Result<Vec<u8>, Error> {
+    let mut buf = PNG_SIGNATURE.to_vec();
+    let num_frames = frames.len() as u32;
+    let mut sequence_number = 0;
+
+    for (i, frame) in frames.iter().enumerate() {
+        let (width, height) = frame.image.dimensions();
+
+        let mut png = vec![];
+        PngEncoder::new(&mut png).encode(frame.image.as_raw(), width, height, ColorType::Rgba8)?;
+
+        let mut has_frame_control = false;
+        for (chunk_type, data) in PngChunks::new(&png[PNG_SIGNATURE.len()..]) {
+            match &chunk_type {
+                b"IHDR" if i == 0 => {
+                    write_chunk(&mut buf, b"IHDR", data);
+
+                    let mut actl = Vec::with_capacity(8);
+                    actl.extend_from_slice(&num_frames.to_be_bytes());
+                    // Loop forever.
+                    actl.extend_from_slice(&0u32.to_be_bytes());
+                    write_chunk(&mut buf, b"acTL", &actl);
+                }
+                b"IDAT" => {
+                    if !has_frame_control {
+                        write_chunk(
+                            &mut buf,
+                            b"fcTL",
+                            &frame_control(sequence_number, width, height, frame.delay),
+                        );
+                        sequence_number += 1;
+                        has_frame_control = true;
+                    }
+
+                    if i == 0 {
+                        write_chunk(&mut buf, b"IDAT", data);
+                    } else {
+                        let mut fdat = Vec::with_capacity(data.len() + 4);
+                        fdat.extend_from_slice(&sequence_number.to_be_bytes());
+                        fdat.extend_from_slice(data);
+                        write_chunk(&mut buf, b"fdAT", &fdat);
+                        sequence_number += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    write_chunk(&mut buf, b"IEND", &[]);
+
+    Ok(buf)
+}
+
+fn frame_control(sequence_number: u32, width: u32, height: u32, delay: u32) -> Vec<u8> {
+    let mut fctl = Vec::with_capacity(26);
+    fctl.extend_from_slice(&sequence_number.to_be_bytes());
+    fctl.extend_from_slice(&width.to_be_bytes());
+    fctl.extend_from_slice(&height.to_be_bytes());
+    // x_offset and y_offset
+    fctl.extend_from_slice(&0u32.to_be_bytes());
+    fctl.extend_from_slice(&0u32.to_be_bytes());
+    // Delay is represented as fraction of seconds.
+    fctl.extend_from_slice(&(delay.min(u16::MAX as u32) as u16).to_be_bytes());
+    fctl.extend_from_slice(&1000u16.to_be_bytes());
+    // dispose_op: APNG_DISPOSE_OP_NONE
+    fctl.push(0);
+    // blend_op: APNG_BLEND_OP_SOURCE
+    fctl.push(0);
+    fctl
+}
+
+pub(super) fn write_chunk(buf: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(chunk_type);
+    buf.extend_from_slice(data);
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(chunk_type);
+    hasher.update(data);
+    buf.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+// Iterate chunks of PNG data that does not include PNG signature.
+pub(super) struct PngChunks<'a>(&'a [u8]);
+
+impl<'a> PngChunks<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        PngChunks(data)
+    }
+}
+
+impl<'a> Iterator for PngChunks<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.0;
+        if data.len() < 12 {
+            return None;
+        }
+
+        let len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if data.len() < 12 + len {
+            return None;
+        }
+
+        let chunk_type = [data[4], data[5], data[6], data[7]];
+        let chunk_data = &data[8..8 + len];
+        // Skip length, type, data and crc.
+        self.0 = &data[12 + len..];
+
+        Some((chunk_type, chunk_data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    fn frames() -> Vec<AnimationFrame> {
+        vec![
+            AnimationFrame::new(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])), 100),
+            AnimationFrame::new(RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255])), 200),
+        ]
+    }
+
+    #[test]
+    fn test_encode_apng() {
+        let buf = encode_frames(frames(), AnimationFormat::Apng).unwrap();
+
+        let chunk_types: Vec<[u8; 4]> = PngChunks::new(&buf[PNG_SIGNATURE.len()..])
+            .map(|(chunk_type, _)| chunk_type)
+            .collect();
+
+        assert_eq!(&chunk_types[0], b"IHDR");
+        assert_eq!(&chunk_types[1], b"acTL");
+        assert_eq!(&chunk_types[2], b"fcTL");
+        assert_eq!(&chunk_types[3], b"IDAT");
+        assert_eq!(&chunk_types[4], b"fcTL");
+        assert_eq!(&chunk_types[5], b"fdAT");
+        assert_eq!(chunk_types.last().unwrap(), b"IEND");
+
+        // APNG is still decodable as static PNG.
+        let image = image::load_from_memory_with_format(&buf, image::ImageFormat::Png)
+            .unwrap()
+            .into_rgba8();
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_encode_empty_animation() {
+        assert!(matches!(
+            encode_frames(vec![], AnimationFormat::Gif),
+            Err(Error::EmptyAnimation)
+        ));
+    }
+}
@@ -0,0 +1,124 @@
+//! Resolve assets that are referenced by name, like `asset://logo.png`.
+//!
+//! Set [AssetResolver] with [`OGImageWriter::set_asset_resolver()`](crate::writer::OGImageWriter::set_asset_resolver),
+//! then templates can refer to images, icons and font families by name, and the host app decides
+//! where their data comes from, like disk, object storage, HTTP or data embedded in the binary.
+
+use std::collections::HashMap;
+
+/// Scheme of image source passed to [`OGImageWriter::set_img()`](crate::writer::OGImageWriter::set_img)
+/// that is resolved with [AssetResolver::resolve_image].
+pub const ASSET_SCHEME: &str = "asset://";
+
+/// Return data of assets by name. `None` means that the resolver doesn't have the asset.
+/// Images must be PNG, JPEG or GIF, and fonts must be TrueType fonts.
+pub trait AssetResolver {
+    /// Return image data for URI without scheme, like `logo.png` for `asset://logo.png`.
+    fn resolve_image(&self, _uri: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Return image data of icon like `github`.
+    fn resolve_icon(&self, _name: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Return font data of font family like `Roboto`.
+    fn resolve_font(&self, _family: &str) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Assets kept in memory, like data embedded by `include_bytes!`.
+#[derive(Debug, Clone, Default)]
+pub struct AssetMap {
+    pub images: HashMap<String, Vec<u8>>,
+    pub icons: HashMap<String, Vec<u8>>,
+    pub fonts: HashMap<String, Vec<u8>>,
+}
+
+impl AssetMap {
+    pub fn new() -> Self {
+        AssetMap::default()
+    }
+
+    pub fn image(mut self, uri: impl Into<String>, data: Vec<u8>) -> Self {
+        self.images.insert(uri.into(), data);
+        self
+    }
+
+    pub fn icon(mut self, name: impl Into<String>, data: Vec<u8>) -> Self {
+        self.icons.insert(name.into(), data);
+        self
+    }
+
+    pub fn font(mut self, family: impl Into<String>, data: Vec<u8>) -> Self {
+        self.fonts.insert(family.into(), data);
+        self
+    }
+}
+
+impl AssetResolver for AssetMap {
+    fn resolve_image(&self, uri: &str) -> Option<Vec<u8>> {
+        self.images.get(uri).cloned()
+    }
+
+    fn resolve_icon(&self, name: &str) -> Option<Vec<u8>> {
+        self.icons.get(name).cloned()
+    }
+
+    fn resolve_font(&self, family: &str) -> Option<Vec<u8>> {
+        self.fonts.get(family).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AssetMap;
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use crate::Error;
+    use image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+
+    fn png(color: [u8; 4]) -> Vec<u8> {
+        let mut data = vec![];
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba(color)))
+            .write_to(&mut data, ImageOutputFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_asset_resolver() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 50,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        writer.set_asset_resolver(Box::new(
+            AssetMap::new()
+                .image("logo.png", png([255, 0, 0, 255]))
+                .icon("dot", png([0, 0, 255, 255]))
+                .font("Roboto", font),
+        ));
+        writer
+            .set_img("asset://logo.png", 20, 20, Style::default())
+            .unwrap();
+        writer.set_icon("dot", 10, Style::default()).unwrap();
+        writer
+            .set_text_with_font_family("Hi", Style::default(), "Roboto")
+            .unwrap();
+        let err = writer
+            .set_img("asset://missing.png", 20, 20, Style::default())
+            .unwrap_err();
+        assert!(matches!(err.inner(), Error::NotFoundAsset(name) if name == "asset://missing.png"));
+        assert_eq!(err.context().unwrap().index, Some(3));
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        assert_eq!(image.get_pixel(10, 10), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(5, 25), &Rgba([0, 0, 255, 255]));
+    }
+}
@@ -0,0 +1,159 @@
+//! Encode many images on a thread pool.
+//!
+//! Painting is done on the calling thread, and painted images are encoded by workers of [BatchEncoder],
+//! so the next image is painted while the previous images are encoded.
+//! The queue has a fixed capacity, so painting waits for workers instead of piling up images in memory.
+//!
+//! ```rust
+//! use og_image_writer::batch::BatchEncoder;
+//! use og_image_writer::templates::TitleCard;
+//! use og_image_writer::ImageOutputFormat;
+//! use std::sync::mpsc::channel;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let font = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+//!     let (sender, receiver) = channel();
+//!
+//!     let encoder = BatchEncoder::new(4, 8);
+//!     for title in ["First Post", "Second Post"] {
+//!         let mut writer = TitleCard::new(title, font.clone()).build()?;
+//!         writer.paint()?;
+//!         let sender = sender.clone();
+//!         encoder.push(writer, ImageOutputFormat::Png, move |buf| {
+//!             sender.send((title, buf)).unwrap();
+//!         })?;
+//!     }
+//!     encoder.finish();
+//!
+//!     drop(sender);
+//!     for (_, buf) in receiver {
+//!         assert!(!buf?.is_empty());
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::writer::OGImageWriter;
+use crate::Error;
+use image::ImageOutputFormat;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Thread pool that encodes painted images.
+#[derive(Debug)]
+pub struct BatchEncoder {
+    jobs: Option<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl BatchEncoder {
+    /// Start `threads` workers. At most `capacity` images wait to be encoded.
+    pub fn new(threads: usize, capacity: usize) -> Self {
+        let (sender, receiver) = sync_channel::<Job>(capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || work(&receiver))
+            })
+            .collect();
+        BatchEncoder {
+            jobs: Some(sender),
+            workers,
+        }
+    }
+
+    /// Queue painted writer to be encoded, and call `done` with the result on the worker.
+    /// This blocks while the queue is full.
+    pub fn push<F>(
+        &self,
+        writer: OGImageWriter,
+        format: ImageOutputFormat,
+        done: F,
+    ) -> Result<(), Error>
+    where
+        F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static,
+    {
+        let raster = writer.into_raster()?;
+        let job: Job = Box::new(move || done(raster.encode(format)));
+        match &self.jobs {
+            Some(jobs) => jobs.send(job).map_err(|_| Error::BatchEncoderStopped),
+            None => Err(Error::BatchEncoderStopped),
+        }
+    }
+
+    /// Wait until all queued images are encoded.
+    pub fn finish(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        // Workers stop when the queue is closed and empty.
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            // Panic of `done` is not propagated, and the other images are still encoded.
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for BatchEncoder {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        // Lock is released before the job runs, so that workers encode in parallel.
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{Rgba, WindowStyle};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_batch_encoder() {
+        let (sender, receiver) = channel();
+        let encoder = BatchEncoder::new(3, 1);
+        for i in 0..10u8 {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 40,
+                height: 20,
+                background_color: Some(Rgba([i, 0, 0, 255])),
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer.paint().unwrap();
+            let sender = sender.clone();
+            encoder
+                .push(writer, ImageOutputFormat::Png, move |buf| {
+                    sender.send((i, buf.unwrap())).unwrap();
+                })
+                .unwrap();
+        }
+        encoder.finish();
+        drop(sender);
+
+        let mut results: Vec<(u8, Vec<u8>)> = receiver.into_iter().collect();
+        assert_eq!(results.len(), 10);
+        results.sort_by_key(|(i, _)| *i);
+        let image = image::load_from_memory(&results[7].1).unwrap().to_rgba8();
+        assert_eq!(image.get_pixel(0, 0).0, [7, 0, 0, 255]);
+    }
+}
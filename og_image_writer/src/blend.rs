@@ -0,0 +1,201 @@
+use image::{Pixel, Rgba, RgbaImage};
+
+// Composite `top` over `bottom` at (x, y). Output is the same as `image::imageops::overlay`.
+pub(super) fn overlay(bottom: &mut RgbaImage, top: &RgbaImage, x: u32, y: u32) {
+    overlay_cropped(bottom, top, x, y, 0)
+}
+
+// Same as `overlay`, but the first `skip` rows of `top` are cut off.
+pub(super) fn overlay_cropped(bottom: &mut RgbaImage, top: &RgbaImage, x: u32, y: u32, skip: u32) {
+    let (bottom_width, bottom_height) = bottom.dimensions();
+    if x >= bottom_width || y >= bottom_height || skip >= top.height() {
+        return;
+    }
+    let width = top.width().min(bottom_width - x) as usize * 4;
+    let height = (top.height() - skip).min(bottom_height - y) as usize;
+    let bottom_stride = bottom_width as usize * 4;
+    let top_stride = top.width() as usize * 4;
+    let offset = y as usize * bottom_stride + x as usize * 4;
+    let bottom: &mut [u8] = bottom;
+    let top: &[u8] = top;
+    for row in 0..height {
+        let dst = offset + row * bottom_stride;
+        let src = (skip as usize + row) * top_stride;
+        blend_row(&mut bottom[dst..dst + width], &top[src..src + width]);
+    }
+}
+
+// Blend `src` pixels over `dst` pixels in the same way as `Blend` of image crate.
+pub(super) fn blend_row(dst: &mut [u8], src: &[u8]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is supported by the running CPU.
+        return unsafe { avx2::blend_row(dst, src) };
+    }
+    for (dst, src) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+        blend_pixel(dst, src);
+    }
+}
+
+// Mix `color` into `dst` pixels by coverage in the same way as `imageproc::pixelops::weighted_sum`.
+pub(super) fn mix_row(dst: &mut [u8], color: Rgba<u8>, coverage: &[f32]) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is supported by the running CPU.
+        return unsafe { avx2::mix_row(dst, color, coverage) };
+    }
+    for (dst, &coverage) in dst.chunks_exact_mut(4).zip(coverage) {
+        mix_pixel(dst, color, coverage);
+    }
+}
+
+fn blend_pixel(dst: &mut [u8], src: &[u8]) {
+    Rgba::from_slice_mut(dst).blend(Rgba::from_slice(src));
+}
+
+fn mix_pixel(dst: &mut [u8], color: Rgba<u8>, coverage: f32) {
+    for (value, &color) in dst.iter_mut().zip(&color.0) {
+        *value = (*value as f32 * (1.0 - coverage) + color as f32 * coverage) as u8;
+    }
+}
+
+// 8 pixels are processed at once as 32 bit lanes. Float operations are done in the same order
+// as the scalar code, so that output is exactly the same.
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::{blend_pixel, mix_pixel};
+    use image::Rgba;
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn blend_row(dst: &mut [u8], src: &[u8]) {
+        let max = _mm256_set1_ps(255.);
+        let one = _mm256_set1_ps(1.);
+        let mut dst_chunks = dst.chunks_exact_mut(LANES * 4);
+        let mut src_chunks = src.chunks_exact(LANES * 4);
+        for (dst, src) in (&mut dst_chunks).zip(&mut src_chunks) {
+            let bg = _mm256_loadu_si256(dst.as_ptr() as *const __m256i);
+            let fg = _mm256_loadu_si256(src.as_ptr() as *const __m256i);
+            let bg_a = _mm256_div_ps(channel(bg, 3), max);
+            let fg_a = _mm256_div_ps(channel(fg, 3), max);
+            let alpha_final = _mm256_sub_ps(_mm256_add_ps(bg_a, fg_a), _mm256_mul_ps(bg_a, fg_a));
+            let bg_weight = _mm256_sub_ps(one, fg_a);
+
+            let mut out = to_byte(_mm256_mul_ps(max, alpha_final), 3);
+            for c in 0..3 {
+                let bg_c = _mm256_mul_ps(_mm256_div_ps(channel(bg, c), max), bg_a);
+                let fg_c = _mm256_mul_ps(_mm256_div_ps(channel(fg, c), max), fg_a);
+                let out_a = _mm256_add_ps(fg_c, _mm256_mul_ps(bg_c, bg_weight));
+                let value = _mm256_mul_ps(max, _mm256_div_ps(out_a, alpha_final));
+                out = _mm256_or_si256(out, to_byte(value, c));
+            }
+            // Pixel is not changed when both are transparent.
+            let keep =
+                _mm256_castps_si256(_mm256_cmp_ps(alpha_final, _mm256_setzero_ps(), _CMP_EQ_OQ));
+            let out = _mm256_blendv_epi8(out, bg, keep);
+            _mm256_storeu_si256(dst.as_mut_ptr() as *mut __m256i, out);
+        }
+        let dst = dst_chunks.into_remainder();
+        for (dst, src) in dst
+            .chunks_exact_mut(4)
+            .zip(src_chunks.remainder().chunks_exact(4))
+        {
+            blend_pixel(dst, src);
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn mix_row(dst: &mut [u8], color: Rgba<u8>, coverage: &[f32]) {
+        let one = _mm256_set1_ps(1.);
+        let mut dst_chunks = dst.chunks_exact_mut(LANES * 4);
+        let mut coverage_chunks = coverage.chunks_exact(LANES);
+        for (dst, coverage) in (&mut dst_chunks).zip(&mut coverage_chunks) {
+            let pixels = _mm256_loadu_si256(dst.as_ptr() as *const __m256i);
+            let coverage = _mm256_loadu_ps(coverage.as_ptr());
+            let weight = _mm256_sub_ps(one, coverage);
+            let mut out = _mm256_setzero_si256();
+            for c in 0..4 {
+                let color = _mm256_set1_ps(color[c] as f32);
+                let value = _mm256_add_ps(
+                    _mm256_mul_ps(channel(pixels, c), weight),
+                    _mm256_mul_ps(color, coverage),
+                );
+                out = _mm256_or_si256(out, to_byte(value, c));
+            }
+            _mm256_storeu_si256(dst.as_mut_ptr() as *mut __m256i, out);
+        }
+        let dst = dst_chunks.into_remainder();
+        for (dst, &coverage) in dst.chunks_exact_mut(4).zip(coverage_chunks.remainder()) {
+            mix_pixel(dst, color, coverage);
+        }
+    }
+
+    // Channel `c` of each pixel as float.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn channel(pixels: __m256i, c: usize) -> __m256 {
+        let value = _mm256_srlv_epi32(pixels, _mm256_set1_epi32(c as i32 * 8));
+        _mm256_cvtepi32_ps(_mm256_and_si256(value, _mm256_set1_epi32(0xff)))
+    }
+
+    // Truncate and saturate to byte, like `as u8`, and move it to channel `c`.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn to_byte(value: __m256, c: usize) -> __m256i {
+        let value = _mm256_cvttps_epi32(value);
+        let value = _mm256_min_epi32(
+            _mm256_max_epi32(value, _mm256_setzero_si256()),
+            _mm256_set1_epi32(0xff),
+        );
+        _mm256_sllv_epi32(value, _mm256_set1_epi32(c as i32 * 8))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use imageproc::pixelops::weighted_sum;
+
+    // Pixels that cover transparent, opaque and rounding edge cases.
+    fn pixels(seed: u32) -> Vec<u8> {
+        (0..203u32)
+            .flat_map(|i| {
+                let value = i.wrapping_mul(seed).wrapping_add(i * i) as u8;
+                let alpha = match i % 5 {
+                    0 => 0,
+                    1 => 255,
+                    _ => value ^ 0x5a,
+                };
+                [value, value.wrapping_mul(3), !value, alpha]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_blend_row() {
+        let src = pixels(31);
+        let mut dst = pixels(7);
+        let mut expected = dst.clone();
+        for (dst, src) in expected.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+            blend_pixel(dst, src);
+        }
+        blend_row(&mut dst, &src);
+        assert_eq!(dst, expected);
+    }
+
+    #[test]
+    fn test_mix_row() {
+        let color = Rgba([200, 30, 90, 255]);
+        let mut dst = pixels(7);
+        let coverage: Vec<f32> = (0..dst.len() / 4).map(|i| (i % 11) as f32 / 10.).collect();
+        let mut expected = dst.clone();
+        for (dst, &gv) in expected.chunks_exact_mut(4).zip(&coverage) {
+            let pixel = *Rgba::from_slice(dst);
+            dst.copy_from_slice(&weighted_sum(pixel, color, 1.0 - gv, gv).0);
+        }
+        mix_row(&mut dst, color, &coverage);
+        assert_eq!(dst, expected);
+    }
+}
@@ -0,0 +1,503 @@
+//! Reuse encoded images whose inputs are unchanged.
+//!
+//! [CacheKey] is a hash of template name, data and fonts.
+//! [RenderCache] returns the bytes stored for the key, or renders and stores them,
+//! so unchanged pages skip rendering entirely in static site builds.
+//! [ElementCache] keeps images of elements across renders, so unchanged elements of changed pages skip decoding and resizing.
+//!
+//! ```rust
+//! use og_image_writer::cache::{CacheKey, MemoryCache, RenderCache};
+//! use og_image_writer::templates::TitleCard;
+//! use og_image_writer::ImageOutputFormat;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let font = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+//!     let title = "Open Graphic Image Writer";
+//!
+//!     let mut cache = RenderCache::new(MemoryCache::new());
+//!     let key = CacheKey::new("title_card").data(title.as_bytes()).font(&font);
+//!
+//!     let buf = cache.get_or_render(key, || {
+//!         let mut writer = TitleCard::new(title, font.clone()).build()?;
+//!         writer.paint()?;
+//!         writer.encode(ImageOutputFormat::Png)
+//!     })?;
+//!     assert!(!buf.is_empty());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::element::{Element, Img, Rect};
+use crate::writer::OGImageWriter;
+use crate::Error;
+use image::ImageError;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of inputs of a render.
+/// This is stable across builds and platforms, so it can be used for [DiskCache].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(u64);
+
+impl CacheKey {
+    /// Start key with template name, which should be changed when the layout code is changed.
+    pub fn new(template: &str) -> Self {
+        CacheKey(FNV_OFFSET_BASIS).update(b't', template.as_bytes())
+    }
+
+    /// Add data that is passed to template, like title and images.
+    pub fn data(self, data: &[u8]) -> Self {
+        self.update(b'd', data)
+    }
+
+    /// Add font data.
+    pub fn font(self, font: &[u8]) -> Self {
+        self.update(b'f', font)
+    }
+
+    // Tag and length are hashed too, so that ("ab", "c") and ("a", "bc") are different keys.
+    fn update(self, tag: u8, bytes: &[u8]) -> Self {
+        let len = (bytes.len() as u64).to_le_bytes();
+        let hash = [tag]
+            .iter()
+            .chain(len.iter())
+            .chain(bytes)
+            .fold(self.0, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            });
+        CacheKey(hash)
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Storage of encoded images.
+pub trait CacheStorage {
+    fn get(&self, key: &CacheKey) -> Option<Vec<u8>>;
+    fn put(&mut self, key: &CacheKey, data: &[u8]) -> Result<(), Error>;
+}
+
+/// Storage that is kept while the process is running.
+#[derive(Debug, Default)]
+pub struct MemoryCache(HashMap<CacheKey, Vec<u8>>);
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        MemoryCache::default()
+    }
+}
+
+impl CacheStorage for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        self.0.get(key).cloned()
+    }
+
+    fn put(&mut self, key: &CacheKey, data: &[u8]) -> Result<(), Error> {
+        self.0.insert(*key, data.to_vec());
+        Ok(())
+    }
+}
+
+/// Storage that saves each image as a file named by the key in the directory.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// The directory is created when the first image is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DiskCache { dir: dir.into() }
+    }
+}
+
+impl CacheStorage for DiskCache {
+    fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        fs::read(self.dir.join(key.to_string())).ok()
+    }
+
+    // Write to temporary file and rename it, so that other builds never read half written file.
+    fn put(&mut self, key: &CacheKey, data: &[u8]) -> Result<(), Error> {
+        let path = self.dir.join(key.to_string());
+        let tmp = self.dir.join(format!("{}.tmp", key));
+        fs::create_dir_all(&self.dir).map_err(ImageError::IoError)?;
+        fs::write(&tmp, data).map_err(ImageError::IoError)?;
+        fs::rename(&tmp, &path).map_err(ImageError::IoError)?;
+        Ok(())
+    }
+}
+
+/// Cache layer in front of rendering.
+#[derive(Debug)]
+pub struct RenderCache<S: CacheStorage> {
+    storage: S,
+    hits: usize,
+    misses: usize,
+}
+
+impl<S: CacheStorage> RenderCache<S> {
+    pub fn new(storage: S) -> Self {
+        RenderCache {
+            storage,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Return the stored bytes for the key, or call `render` and store its result.
+    /// Errors of `render` are returned without being stored.
+    pub fn get_or_render(
+        &mut self,
+        key: CacheKey,
+        render: impl FnOnce() -> Result<Vec<u8>, Error>,
+    ) -> Result<Vec<u8>, Error> {
+        if let Some(buf) = self.storage.get(&key) {
+            self.hits += 1;
+            return Ok(buf);
+        }
+        self.misses += 1;
+        let buf = render()?;
+        self.storage.put(&key, &buf)?;
+        Ok(buf)
+    }
+
+    /// Number of renders that were skipped.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of renders that were called.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+}
+
+/// Images of elements that are kept across renders in the process, like a photo that is decoded, resized and rounded.
+/// Each image is stored by id of element with [CacheKey] of its inputs, and replaced when the key is changed.
+/// Clones share the same images, so a clone can be set to the writer of each render.
+/// Pixels of stored images are shared with elements instead of being copied.
+/// At most `capacity` images are kept, and the least recently used image is removed when another image is stored.
+#[derive(Debug, Clone, Default)]
+pub struct ElementCache(Arc<Mutex<ElementCacheInner>>);
+
+// Number of images that are kept by `ElementCache::new()`.
+const DEFAULT_CAPACITY: usize = 64;
+
+#[derive(Debug)]
+struct ElementCacheInner {
+    images: HashMap<String, CachedImg>,
+    capacity: usize,
+    // Count of uses, which orders images by their last use.
+    clock: u64,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug)]
+struct CachedImg {
+    key: CacheKey,
+    img: Img,
+    last_used: u64,
+}
+
+impl Default for ElementCacheInner {
+    fn default() -> Self {
+        ElementCacheInner {
+            images: HashMap::new(),
+            capacity: DEFAULT_CAPACITY,
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl ElementCacheInner {
+    fn get(&mut self, id: &str, key: CacheKey) -> Option<Img> {
+        self.clock += 1;
+        let clock = self.clock;
+        let cached = self.images.get_mut(id).filter(|cached| cached.key == key)?;
+        cached.last_used = clock;
+        Some(share_img(&cached.img))
+    }
+
+    fn insert(&mut self, id: &str, key: CacheKey, img: Img) {
+        self.clock += 1;
+        if !self.images.contains_key(id) {
+            while self.images.len() >= self.capacity.max(1) {
+                let lru = self
+                    .images
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.last_used)
+                    .map(|(id, _)| id.clone());
+                match lru {
+                    Some(id) => self.images.remove(&id),
+                    None => break,
+                };
+            }
+        }
+        if self.capacity > 0 {
+            let cached = CachedImg {
+                key,
+                img,
+                last_used: self.clock,
+            };
+            self.images.insert(id.to_string(), cached);
+        }
+    }
+}
+
+impl ElementCache {
+    /// Cache that keeps 64 images.
+    pub fn new() -> Self {
+        ElementCache::default()
+    }
+
+    /// Cache that keeps `capacity` images. Nothing is stored when it is 0.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ElementCache(Arc::new(Mutex::new(ElementCacheInner {
+            capacity,
+            ..ElementCacheInner::default()
+        })))
+    }
+
+    /// Max number of stored images.
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// Number of elements that were set from the cache.
+    pub fn hits(&self) -> usize {
+        self.lock().hits
+    }
+
+    /// Number of elements that were set by the setter.
+    pub fn misses(&self) -> usize {
+        self.lock().misses
+    }
+
+    /// Number of stored images.
+    pub fn len(&self) -> usize {
+        self.lock().images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove image of the element.
+    pub fn remove(&self, id: &str) {
+        self.lock().images.remove(id);
+    }
+
+    pub fn clear(&self) {
+        self.lock().images.clear();
+    }
+
+    // Cached images are only replaced as a whole, so they are still valid after a panic in another thread.
+    fn lock(&self) -> std::sync::MutexGuard<'_, ElementCacheInner> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+// Image that shares pixels with `img`, so hits and inserts don't copy them.
+fn share_img(img: &Img) -> Img {
+    Img {
+        buf: img.buf.clone(),
+        rect: Rect::new(0, 0, img.rect.width, img.rect.height),
+        style: img.style.clone(),
+        scale: img.scale,
+    }
+}
+
+impl OGImageWriter {
+    /// Share images of elements with other writers through the cache. See [set_cached](Self::set_cached).
+    pub fn set_element_cache(&mut self, cache: ElementCache) {
+        self.element_cache = Some(cache);
+    }
+
+    /// Set image element with `set` like [set_img](Self::set_img) or [set_collage](Self::set_collage),
+    /// or reuse its image that is stored by `id` if the stored `key` is the same.
+    /// When `set` doesn't set an image element, nothing is stored. Without [ElementCache], `set` is always called.
+    pub fn set_cached(
+        &mut self,
+        id: &str,
+        key: CacheKey,
+        set: impl FnOnce(&mut OGImageWriter) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let cache = match &self.element_cache {
+            Some(cache) => cache.clone(),
+            None => return set(self),
+        };
+
+        let cached = cache.lock().get(id, key);
+        if let Some(img) = cached {
+            let index = self.tree.0.len();
+            self.check_element(0)
+                .map_err(|err| err.in_element(index, None))?;
+            let (width, height) = (img.rect.width, img.rect.height);
+            self.process_img(Element::Img(Some(img)), width, height);
+            cache.lock().hits += 1;
+            return Ok(());
+        }
+
+        let len = self.tree.0.len();
+        set(self)?;
+        let mut inner = cache.lock();
+        inner.misses += 1;
+        match self.tree.0.get(len) {
+            Some(Element::Img(Some(img))) if self.tree.0.len() == len + 1 => {
+                inner.insert(id, key, share_img(img));
+            }
+            _ => {
+                inner.images.remove(id);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cache_key() {
+        let key = CacheKey::new("card").data(b"ab").data(b"c");
+        assert_eq!(key, CacheKey::new("card").data(b"ab").data(b"c"));
+        assert_ne!(key, CacheKey::new("card").data(b"a").data(b"bc"));
+        assert_ne!(key, CacheKey::new("card").data(b"ab").font(b"c"));
+        assert_eq!(key.to_string().len(), 16);
+    }
+
+    #[test]
+    fn test_get_or_render() {
+        let mut cache = RenderCache::new(MemoryCache::new());
+        let key = CacheKey::new("card");
+
+        let buf = cache.get_or_render(key, || Ok(vec![1, 2, 3])).unwrap();
+        assert_eq!(buf, vec![1, 2, 3]);
+        let buf = cache
+            .get_or_render(key, || panic!("unchanged key must not be rendered"))
+            .unwrap();
+        assert_eq!(buf, vec![1, 2, 3]);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        let err = cache.get_or_render(CacheKey::new("other"), || Err(Error::NullElement));
+        assert!(matches!(err, Err(Error::NullElement)));
+        assert!(cache.into_storage().get(&CacheKey::new("other")).is_none());
+    }
+
+    #[test]
+    fn test_set_cached() {
+        use crate::img::DecodedImage;
+        use crate::style::{Style, WindowStyle};
+        use image::{Rgba, RgbaImage};
+
+        let cache = ElementCache::new();
+        let photo = DecodedImage::from(RgbaImage::from_pixel(40, 40, Rgba([200, 0, 0, 255])));
+        let render = |key: CacheKey| {
+            let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+            writer.set_element_cache(cache.clone());
+            let mut calls = 0;
+            writer
+                .set_cached("photo", key, |writer| {
+                    calls += 1;
+                    writer.set_img_with_decoded(&photo, 20, 20, Style::default())
+                })
+                .unwrap();
+            let size = match writer.elements().unwrap()[0] {
+                Element::Img(Some(img)) => (img.rect.width, img.rect.height),
+                _ => unreachable!(),
+            };
+            (calls, size)
+        };
+
+        let key = CacheKey::new("photo").data(b"v1");
+        assert_eq!(render(key), (1, (20, 20)));
+        assert_eq!(render(key), (0, (20, 20)));
+        assert_eq!(render(CacheKey::new("photo").data(b"v2")), (1, (20, 20)));
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 1));
+    }
+
+    #[test]
+    fn test_element_cache_capacity() {
+        use crate::img::DecodedImage;
+        use crate::style::{Style, WindowStyle};
+        use image::RgbaImage;
+
+        let cache = ElementCache::with_capacity(2);
+        let photo = DecodedImage::from(RgbaImage::new(40, 40));
+        // Pointer to pixels of the element, and whether the element is set by the setter.
+        let render = |id: &str| {
+            let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+            writer.set_element_cache(cache.clone());
+            let mut is_set = false;
+            writer
+                .set_cached(id, CacheKey::new(id), |writer| {
+                    is_set = true;
+                    writer.set_img_with_decoded(&photo, 20, 20, Style::default())
+                })
+                .unwrap();
+            let pixels = match writer.elements().unwrap()[0] {
+                Element::Img(Some(img)) => img.image().as_ptr(),
+                _ => unreachable!(),
+            };
+            (pixels, is_set)
+        };
+
+        // Pixels are shared with the stored image.
+        let (pixels, is_set) = render("a");
+        assert!(is_set);
+        assert_eq!(render("a"), (pixels, false));
+
+        render("b");
+        // `b` is the least recently used after `a` is used.
+        render("a");
+        render("c");
+        assert_eq!(cache.len(), 2);
+        assert!(!render("a").1);
+        assert!(!render("c").1);
+        assert!(render("b").1);
+
+        let cache = ElementCache::with_capacity(0);
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        writer.set_element_cache(cache.clone());
+        writer
+            .set_cached("a", CacheKey::new("a"), |writer| {
+                writer.set_img_with_decoded(&photo, 20, 20, Style::default())
+            })
+            .unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(ElementCache::new().capacity(), 64);
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let dir =
+            std::env::temp_dir().join(format!("og_image_writer_cache_{}", std::process::id()));
+        let key = CacheKey::new("card").data(b"title");
+
+        let mut storage = DiskCache::new(&dir);
+        assert!(storage.get(&key).is_none());
+        storage.put(&key, b"image").unwrap();
+        assert_eq!(DiskCache::new(&dir).get(&key), Some(b"image".to_vec()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
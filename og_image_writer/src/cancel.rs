@@ -0,0 +1,103 @@
+//! Abort rendering from outside.
+//!
+//! Set [CancellationToken] with [`OGImageWriter::set_cancellation_token()`](crate::writer::OGImageWriter::set_cancellation_token)
+//! or deadline with [`OGImageWriter::set_deadline()`](crate::writer::OGImageWriter::set_deadline).
+//! They are checked when element is set, before each element is painted and before each line of text is painted.
+
+use crate::Error;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Instant;
+
+/// Token that can be cancelled from other thread.
+/// Cloned token shares the state, so you can pass the same token to container writers.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Budget {
+    pub(crate) token: Option<CancellationToken>,
+    pub(crate) deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if let Some(token) = &self.token {
+            if token.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(Error::DeadlineExceeded);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cancel() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        writer
+            .set_text("Hello", Style::default(), Some(font.clone()))
+            .unwrap();
+
+        let token = CancellationToken::new();
+        writer.set_cancellation_token(token.clone());
+        token.cancel();
+
+        assert!(matches!(writer.paint(), Err(Error::Cancelled)));
+        assert!(matches!(
+            writer
+                .set_text("Hello", Style::default(), Some(font))
+                .unwrap_err()
+                .inner(),
+            Error::Cancelled
+        ));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let budget = Budget {
+            token: None,
+            deadline: Some(Instant::now()),
+        };
+        assert!(matches!(budget.check(), Err(Error::DeadlineExceeded)));
+
+        let budget = Budget {
+            token: None,
+            deadline: Some(Instant::now() + Duration::from_secs(60)),
+        };
+        assert!(budget.check().is_ok());
+    }
+}
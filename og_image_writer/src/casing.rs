@@ -0,0 +1,95 @@
+use crate::style::{LanguageTag, TextTransform};
+use std::borrow::Cow;
+
+// Transform case of text like CSS `text-transform` with language specific rules.
+// `is_word_start` is true when text is at the start of word, like after whitespace of previous text.
+pub(crate) fn transform_text<'a>(
+    text: &'a str,
+    transform: TextTransform,
+    lang: Option<&LanguageTag>,
+    is_word_start: bool,
+) -> Cow<'a, str> {
+    let lang = lang
+        .map(|lang| lang.language().to_ascii_lowercase())
+        .unwrap_or_default();
+    let lang = lang.as_str();
+    match transform {
+        TextTransform::None => Cow::Borrowed(text),
+        TextTransform::Uppercase => Cow::Owned(text.chars().map(|ch| upper(ch, lang)).collect()),
+        TextTransform::Lowercase => Cow::Owned(text.chars().map(|ch| lower(ch, lang)).collect()),
+        TextTransform::Capitalize => {
+            let mut result = String::with_capacity(text.len());
+            let mut is_word_start = is_word_start;
+            let mut chars = text.chars().peekable();
+            while let Some(ch) = chars.next() {
+                if is_word_start && ch.is_alphabetic() {
+                    // Dutch digraph `ij` is capitalized together.
+                    if lang == "nl" && ch == 'i' && chars.peek() == Some(&'j') {
+                        chars.next();
+                        result.push_str("IJ");
+                    } else {
+                        result.push_str(&upper(ch, lang));
+                    }
+                    is_word_start = false;
+                } else {
+                    result.push(ch);
+                    is_word_start = ch.is_whitespace();
+                }
+            }
+            Cow::Owned(result)
+        }
+    }
+}
+
+fn upper(ch: char, lang: &str) -> String {
+    match (lang, ch) {
+        // Dotted capital I
+        ("tr" | "az", 'i') => "\u{130}".to_string(),
+        _ => ch.to_uppercase().collect(),
+    }
+}
+
+fn lower(ch: char, lang: &str) -> String {
+    match (lang, ch) {
+        // Dotless small i
+        ("tr" | "az", 'I') => "\u{131}".to_string(),
+        ("tr" | "az", '\u{130}') => "i".to_string(),
+        _ => ch.to_lowercase().collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transform_text() {
+        let tr = LanguageTag::new("tr-TR");
+        assert_eq!(
+            transform_text("istanbul", TextTransform::Uppercase, Some(&tr), true),
+            "İSTANBUL"
+        );
+        assert_eq!(
+            transform_text("istanbul", TextTransform::Uppercase, None, true),
+            "ISTANBUL"
+        );
+        assert_eq!(
+            transform_text("DİYARBAKIR", TextTransform::Lowercase, Some(&tr), true),
+            "diyarbakır"
+        );
+        assert_eq!(
+            transform_text("straße", TextTransform::Uppercase, None, true),
+            "STRASSE"
+        );
+
+        let nl = LanguageTag::new("nl");
+        assert_eq!(
+            transform_text("het ijsselmeer", TextTransform::Capitalize, Some(&nl), true),
+            "Het IJsselmeer"
+        );
+        assert_eq!(
+            transform_text("open graph", TextTransform::Capitalize, None, false),
+            "open Graph"
+        );
+    }
+}
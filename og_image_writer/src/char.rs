@@ -1,6 +1,110 @@
+use std::borrow::Cow;
 use std::iter::{Iterator, Peekable, Rev};
 use std::str::CharIndices;
 
+// Invisible break opportunity that is rendered as hyphen only when line is broken there.
+pub(super) const SOFT_HYPHEN: char = '\u{AD}';
+// Whitespace that does not break line.
+pub(super) const NO_BREAK_SPACE: char = '\u{A0}';
+// Line break that is forced regardless of `white_space`.
+pub(super) const LINE_SEPARATOR: char = '\u{2028}';
+
+// Bidi formatting chars like RLO and RLI. Glyphs are not reordered, so they are removed from text
+// instead of being drawn as missing glyphs. Isolates still take part in paragraph direction.
+pub(super) fn is_bidi_control(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+pub(super) fn strip_bidi_controls(text: &str) -> Cow<'_, str> {
+    if !text.contains(is_bidi_control) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.chars().filter(|&ch| !is_bidi_control(ch)).collect())
+}
+
+// Chars that are not drawn with glyphs, like newline.
+pub(super) fn has_no_glyph(ch: char) -> bool {
+    ch.is_control() || ch == LINE_SEPARATOR
+}
+
+// Char that is used to select font and measure width instead of `ch`.
+// Fonts often do not have glyphs for soft hyphen and no-break space.
+pub(super) fn layout_char(ch: char) -> char {
+    match ch {
+        SOFT_HYPHEN => '-',
+        NO_BREAK_SPACE => ' ',
+        _ => ch,
+    }
+}
+
+// Text that is drawn for text in a line.
+// Soft hyphen is drawn as hyphen only at the end of line.
+pub(super) fn display_text(text: &str, is_line_end: bool) -> Cow<'_, str> {
+    if !text.contains([SOFT_HYPHEN, NO_BREAK_SPACE]) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    for (i, ch) in text.char_indices() {
+        match ch {
+            SOFT_HYPHEN if is_line_end && i + ch.len_utf8() == text.len() => result.push('-'),
+            SOFT_HYPHEN => {}
+            _ => result.push(layout_char(ch)),
+        }
+    }
+    Cow::Owned(result)
+}
+
+// Byte offset in `text` of each char of `display_text`.
+pub(super) fn display_offsets(text: &str, is_line_end: bool) -> Vec<usize> {
+    text.char_indices()
+        .filter(|&(i, ch)| ch != SOFT_HYPHEN || (is_line_end && i + ch.len_utf8() == text.len()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// Replace tabs with spaces to next tab stop when `tab_size` is specified.
+// `column` is number of chars from the start of line, and it is updated to the end of text.
+pub(super) fn expand_tabs<'a>(
+    text: &'a str,
+    tab_size: Option<usize>,
+    column: &mut usize,
+) -> Cow<'a, str> {
+    let tab_size = match tab_size {
+        Some(tab_size) if text.contains('\t') => tab_size.max(1),
+        _ => {
+            *column = match text.rfind(['\n', LINE_SEPARATOR]) {
+                Some(idx) => text[idx..].chars().count() - 1,
+                None => *column + text.chars().count(),
+            };
+            return Cow::Borrowed(text);
+        }
+    };
+
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\t' => {
+                let spaces = tab_size - *column % tab_size;
+                result.extend(std::iter::repeat_n(' ', spaces));
+                *column += spaces;
+            }
+            '\n' | LINE_SEPARATOR => {
+                result.push(ch);
+                *column = 0;
+            }
+            _ => {
+                result.push(ch);
+                *column += 1;
+            }
+        }
+    }
+    Cow::Owned(result)
+}
+
 pub(super) fn is_newline(cur_char: char) -> bool {
     cur_char == '\n'
 }
@@ -19,6 +123,17 @@ pub(super) fn is_newline_as_whitespace(is_pre: bool, flag: &Option<CharFlags>) -
 
 pub(super) enum CharFlags {
     Newline,
+    LineSeparator,
+}
+
+impl CharFlags {
+    // Whether line is broken after this char.
+    // Newline breaks line regardless of `white_space`, so pushed text can have paragraphs.
+    pub(super) fn is_line_break(&self) -> bool {
+        match self {
+            Self::Newline | Self::LineSeparator => true,
+        }
+    }
 }
 
 type RenderingCharIndicesItem = (Option<CharFlags>, usize, char, usize);
@@ -47,9 +162,15 @@ impl<'a> Iterator for RenderingCharIndices<'a> {
         let v = chars.next();
         match v {
             Some((i, ch)) if is_newline(ch) => {
-                Some((Some(CharFlags::Newline), i, ' ', ch.to_string().len()))
+                Some((Some(CharFlags::Newline), i, ' ', ch.len_utf8()))
             }
-            _ => v.map(|t| (None, t.0, t.1, t.1.to_string().len())),
+            Some((i, LINE_SEPARATOR)) => Some((
+                Some(CharFlags::LineSeparator),
+                i,
+                ' ',
+                LINE_SEPARATOR.len_utf8(),
+            )),
+            _ => v.map(|t| (None, t.0, t.1, t.1.len_utf8())),
         }
     }
 }
@@ -76,9 +197,15 @@ impl<'a> Iterator for RevRenderingCharIndices<'a> {
         let v = chars.next();
         match v {
             Some((i, ch)) if is_rev_newline(ch) => {
-                Some((Some(CharFlags::Newline), i, ' ', ch.to_string().len()))
+                Some((Some(CharFlags::Newline), i, ' ', ch.len_utf8()))
             }
-            _ => v.map(|t| (None, t.0, t.1, t.1.to_string().len())),
+            Some((i, LINE_SEPARATOR)) => Some((
+                Some(CharFlags::LineSeparator),
+                i,
+                ' ',
+                LINE_SEPARATOR.len_utf8(),
+            )),
+            _ => v.map(|t| (None, t.0, t.1, t.1.len_utf8())),
         }
     }
 }
@@ -100,6 +227,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_display_text() {
+        assert_eq!(display_text("hy\u{AD}phen", true), "hyphen");
+        assert_eq!(display_text("hy\u{AD}", true), "hy-");
+        assert_eq!(display_text("hy\u{AD}", false), "hy");
+        assert_eq!(display_text("10\u{A0}km", false), "10 km");
+        assert!(matches!(display_text("plain", true), Cow::Borrowed(_)));
+        assert_eq!(display_offsets("hy\u{AD}phen", true), [0, 1, 4, 5, 6, 7]);
+        assert_eq!(display_offsets("hy\u{AD}", true), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_strip_bidi_controls() {
+        assert_eq!(
+            strip_bidi_controls("\u{202E}txt.exe\u{202C} \u{2067}שלום\u{2069}\u{200F}"),
+            "txt.exe שלום"
+        );
+        assert!(matches!(strip_bidi_controls("plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_expand_tabs() {
+        let mut column = 0;
+        assert_eq!(expand_tabs("a\tb", Some(4), &mut column), "a   b");
+        assert_eq!(column, 5);
+        assert_eq!(expand_tabs("\tc\n\td", Some(4), &mut column), "   c\n    d");
+        assert_eq!(column, 5);
+
+        let mut column = 0;
+        assert!(matches!(
+            expand_tabs("a\tb", None, &mut column),
+            Cow::Borrowed("a\tb")
+        ));
+        assert_eq!(column, 3);
+    }
+
     #[test]
     fn test_rendering_char_indices() {
         let input = "Hello\nWorld Test";
@@ -0,0 +1,213 @@
+//! Collage composes images into a single image element, for gallery posts.
+
+use super::blend::overlay;
+use super::element::Rect;
+use super::img::{round, ImageInputFormat};
+use super::style::BorderRadius;
+use crate::Error;
+use image::imageops::{self, FilterType};
+use image::{load_from_memory_with_format, open, RgbaImage};
+
+/// How images are placed in [Collage].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollageLayout {
+    /// Cells that have the same size are placed in rows of `columns` cells.
+    Grid { columns: u32 },
+    /// Images keep their aspect ratio in `columns` columns, and each image is put in the shortest column.
+    /// Images beyond the height of collage are cut off.
+    Masonry { columns: u32 },
+    /// The first image fills two thirds of width, and the other images are stacked on the right.
+    Hero,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum CollageImage {
+    Src(String),
+    Data(Vec<u8>, ImageInputFormat),
+}
+
+/// Images that are composed with `layout` in `width` and `height`.
+/// Each image is scaled to cover its cell and cropped around its center.
+/// Set it by [`OGImageWriter::set_collage()`](crate::writer::OGImageWriter::set_collage).
+#[derive(Debug, Clone)]
+pub struct Collage {
+    pub layout: CollageLayout,
+    pub width: u32,
+    pub height: u32,
+    /// Space between cells.
+    pub gap: u32,
+    /// Radius of rounded corners of each cell.
+    pub border_radius: u32,
+    pub(super) images: Vec<CollageImage>,
+}
+
+impl Collage {
+    pub fn new(layout: CollageLayout, width: u32, height: u32) -> Self {
+        Collage {
+            layout,
+            width,
+            height,
+            gap: 0,
+            border_radius: 0,
+            images: vec![],
+        }
+    }
+
+    pub fn push_img(&mut self, src: &str) {
+        self.images.push(CollageImage::Src(src.to_string()));
+    }
+
+    pub fn push_img_with_data(&mut self, data: &[u8], format: ImageInputFormat) {
+        self.images.push(CollageImage::Data(data.to_vec(), format));
+    }
+
+    // Rectangles of cells for images that have `sizes`.
+    pub(super) fn cells(&self, sizes: &[(u32, u32)]) -> Vec<Rect> {
+        let len = sizes.len() as u32;
+        if len == 0 {
+            return vec![];
+        }
+        let gap = self.gap;
+        // Length of each cell when `count` cells and gaps are placed in `total`.
+        let split = |total: u32, count: u32| total.saturating_sub(gap * (count - 1)) / count;
+
+        match self.layout {
+            CollageLayout::Grid { columns } => {
+                let columns = columns.clamp(1, len);
+                let rows = len.div_ceil(columns);
+                let (width, height) = (split(self.width, columns), split(self.height, rows));
+                (0..len)
+                    .map(|i| {
+                        Rect::new(
+                            i % columns * (width + gap),
+                            i / columns * (height + gap),
+                            width,
+                            height,
+                        )
+                    })
+                    .collect()
+            }
+            CollageLayout::Masonry { columns } => {
+                let columns = columns.clamp(1, len);
+                let width = split(self.width, columns);
+                let mut bottoms = vec![0; columns as usize];
+                sizes
+                    .iter()
+                    .map(|&(w, h)| {
+                        let (column, y) = bottoms
+                            .iter()
+                            .copied()
+                            .enumerate()
+                            .min_by_key(|&(_, y)| y)
+                            .unwrap();
+                        let height = (width as u64 * h as u64 / w.max(1) as u64) as u32;
+                        bottoms[column] = y + height + gap;
+                        Rect::new(column as u32 * (width + gap), y, width, height)
+                    })
+                    .collect()
+            }
+            CollageLayout::Hero => {
+                if len == 1 {
+                    return vec![Rect::new(0, 0, self.width, self.height)];
+                }
+                let rest = len - 1;
+                let hero_width = self.width.saturating_sub(gap) * 2 / 3;
+                let x = hero_width + gap;
+                let (width, height) = (self.width.saturating_sub(x), split(self.height, rest));
+                let mut cells = vec![Rect::new(0, 0, hero_width, self.height)];
+                cells.extend((0..rest).map(|i| Rect::new(x, i * (height + gap), width, height)));
+                cells
+            }
+        }
+    }
+
+    pub(super) fn compose(&self) -> Result<RgbaImage, Error> {
+        let images = self
+            .images
+            .iter()
+            .map(|image| match image {
+                CollageImage::Src(src) => Ok(open(src)?.into_rgba8()),
+                CollageImage::Data(data, format) => {
+                    Ok(load_from_memory_with_format(data, format.as_image_format())?.into_rgba8())
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let sizes: Vec<_> = images.iter().map(|image| image.dimensions()).collect();
+        let mut canvas = RgbaImage::new(self.width, self.height);
+        for (image, cell) in images.iter().zip(self.cells(&sizes)) {
+            if cell.width == 0 || cell.height == 0 || cell.y >= self.height {
+                continue;
+            }
+            let mut cell_image = cover(image, cell.width, cell.height);
+            let radius = self.border_radius.min(cell.width / 2).min(cell.height / 2);
+            round(
+                &mut cell_image,
+                &mut BorderRadius(radius, radius, radius, radius),
+            );
+            overlay(&mut canvas, &cell_image, cell.x, cell.y);
+        }
+
+        Ok(canvas)
+    }
+}
+
+// Scale image to cover `width` and `height`, and crop it around its center.
+fn cover(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let (w, h) = image.dimensions();
+    let scale = (width as f32 / w.max(1) as f32).max(height as f32 / h.max(1) as f32);
+    let scaled_width = ((w as f32 * scale).ceil() as u32).max(width);
+    let scaled_height = ((h as f32 * scale).ceil() as u32).max(height);
+    let scaled = imageops::resize(image, scaled_width, scaled_height, FilterType::Triangle);
+    imageops::crop_imm(
+        &scaled,
+        (scaled_width - width) / 2,
+        (scaled_height - height) / 2,
+        width,
+        height,
+    )
+    .to_image()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rects(cells: Vec<Rect>) -> Vec<(u32, u32, u32, u32)> {
+        cells
+            .into_iter()
+            .map(|r| (r.x, r.y, r.width, r.height))
+            .collect()
+    }
+
+    #[test]
+    fn test_collage_cells() {
+        let sizes = [(100, 100), (100, 200), (200, 100)];
+
+        let mut collage = Collage::new(CollageLayout::Grid { columns: 2 }, 210, 110);
+        collage.gap = 10;
+        assert_eq!(
+            rects(collage.cells(&sizes)),
+            vec![(0, 0, 100, 50), (110, 0, 100, 50), (0, 60, 100, 50)]
+        );
+
+        collage.layout = CollageLayout::Masonry { columns: 2 };
+        assert_eq!(
+            rects(collage.cells(&sizes)),
+            vec![(0, 0, 100, 100), (110, 0, 100, 200), (0, 110, 100, 50)]
+        );
+
+        collage.layout = CollageLayout::Hero;
+        assert_eq!(
+            rects(collage.cells(&sizes)),
+            vec![(0, 0, 133, 110), (143, 0, 67, 50), (143, 60, 67, 50)]
+        );
+    }
+
+    #[test]
+    fn test_cover() {
+        let image = RgbaImage::new(200, 100);
+        assert_eq!(cover(&image, 50, 50).dimensions(), (50, 50));
+        assert_eq!(cover(&image, 300, 50).dimensions(), (300, 50));
+    }
+}
@@ -1,54 +1,133 @@
+use super::element::CustomElement;
 use super::font_trait::Font;
-use super::img::ImageInputFormat;
-use crate::renderer::{draw_text_mut, FontSetting};
+use super::img::{check_size, content_bounds, flatten, ImageInputFormat};
+#[cfg(feature = "wgpu")]
+use crate::gpu::GpuBatch;
+use crate::painter::{GlyphPath, Painter, TextExtents, TextRun};
+use crate::palette::{encode_palette, PaletteOptions};
+use crate::progressive::{encode_progressive, ProgressiveFormat};
+use crate::renderer::FontSetting;
+use crate::style;
+use crate::svg::SvgDocument;
 use crate::Error;
-use image::imageops::overlay;
-use image::{load_from_memory_with_format, DynamicImage, ImageBuffer, Rgba, RgbaImage};
-use imageproc::map::map_colors;
+use image::{imageops, load_from_memory_with_format, DynamicImage, ImageError, Rgba, RgbaImage};
+use std::fs;
 use std::path::Path;
 
 pub use image::ImageOutputFormat;
 
+/// Channels of the encoded image.
+#[derive(Debug, Copy, Clone)]
+pub enum OutputChannels {
+    /// Keep alpha channel. Formats without alpha channel like JPEG drop it without blending.
+    Rgba,
+    /// Drop alpha channel after blending pixels onto the background color.
+    Rgb(style::Rgba),
+}
+
 pub(super) struct Context {
     pub image: Option<RgbaImage>,
+    // Draw calls are sent to this painter instead of image when this is set.
+    pub painter: Option<Box<dyn Painter>>,
+    // Draw calls are recorded as SVG when this is set.
+    pub svg: Option<SvgDocument>,
+    // Draw calls are composited on GPU when this is set.
+    #[cfg(feature = "wgpu")]
+    pub gpu: Option<GpuBatch>,
+    pub channels: OutputChannels,
 }
 
 impl Context {
     pub fn new(w: u32, h: u32) -> Self {
         let image = RgbaImage::new(w, h);
-        Self { image: Some(image) }
+        Self {
+            image: Some(image),
+            painter: None,
+            svg: None,
+            #[cfg(feature = "wgpu")]
+            gpu: GpuBatch::new(w, h),
+            channels: OutputChannels::Rgba,
+        }
     }
 
     pub fn from_data(data: &[u8], format: ImageInputFormat) -> Result<Self, Error> {
-        let image = load_from_memory_with_format(data, format.as_image_format())?;
-        Ok(Self {
-            image: Some(image.into_rgba8()),
-        })
+        let image = load_from_memory_with_format(data, format.as_image_format())?.into_rgba8();
+        Ok(Self::from_image(image))
+    }
+
+    pub fn from_image(image: RgbaImage) -> Self {
+        Self {
+            #[cfg(feature = "wgpu")]
+            gpu: GpuBatch::new(image.width(), image.height()),
+            image: Some(image),
+            painter: None,
+            svg: None,
+            channels: OutputChannels::Rgba,
+        }
     }
 
     pub fn draw_background_color(&mut self, rgba: Rgba<u8>) -> Result<(), Error> {
+        // Image is replaced directly, so pending draw calls are applied first like on CPU.
+        self.flush()?;
         let image = match &self.image {
             Some(image) => image,
             None => return Err(Error::NotFoundContainerImage),
         };
-        self.image = Some(map_colors(image, |_| rgba));
+        let (width, height) = image.dimensions();
+        if let Some(painter) = &mut self.painter {
+            return painter.fill_rect(0, 0, width, height, rgba);
+        }
+        if let Some(svg) = &mut self.svg {
+            svg.fill_rect(0, 0, width, height, rgba)?;
+        }
+        self.image = Some(RgbaImage::from_pixel(width, height, rgba));
 
         Ok(())
     }
 
-    pub fn draw_image(
+    pub fn fill_rect(
         &mut self,
-        buf: ImageBuffer<Rgba<u8>, Vec<u8>>,
         x: u32,
         y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
     ) -> Result<(), Error> {
         let image = match &mut self.image {
             Some(image) => image,
             None => return Err(Error::NotFoundContainerImage),
         };
-        overlay(image, &buf, x, y);
+        if let Some(painter) = &mut self.painter {
+            return painter.fill_rect(x, y, width, height, color);
+        }
+        if let Some(svg) = &mut self.svg {
+            svg.fill_rect(x, y, width, height, color)?;
+        }
+        #[cfg(feature = "wgpu")]
+        if let Some(gpu) = &mut self.gpu {
+            gpu.draw_image(RgbaImage::from_pixel(width, height, color), x, y);
+            return Ok(());
+        }
+        image.fill_rect(x, y, width, height, color)
+    }
 
-        Ok(())
+    pub fn draw_image(&mut self, buf: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        let image = match &mut self.image {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        if let Some(painter) = &mut self.painter {
+            return painter.draw_image(buf, x, y);
+        }
+        if let Some(svg) = &mut self.svg {
+            svg.draw_image(buf, x, y)?;
+        }
+        #[cfg(feature = "wgpu")]
+        if let Some(gpu) = &mut self.gpu {
+            gpu.draw_image(buf.clone(), x, y);
+            return Ok(());
+        }
+        image.draw_image(buf, x, y)
     }
 
     pub fn draw_text(
@@ -64,22 +143,192 @@ impl Context {
             Some(image) => image,
             None => return Err(Error::NotFoundContainerImage),
         };
-        draw_text_mut(image, color, x, y, font, setting, text);
+        let run = TextRun::new(text, font, setting, color, x, y);
+        if let Some(painter) = &mut self.painter {
+            return painter.draw_text(&run);
+        }
+        if let Some(svg) = &mut self.svg {
+            svg.draw_text(&run)?;
+        }
+        #[cfg(feature = "wgpu")]
+        if let Some(gpu) = &mut self.gpu {
+            gpu.draw_text(color, x, y, font, setting, text);
+            if gpu.is_atlas_full() {
+                gpu.flush(image)?;
+            }
+            return Ok(());
+        }
+        image.draw_text(&run)
+    }
 
-        Ok(())
+    pub fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        let image = match &mut self.image {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        if let Some(painter) = &mut self.painter {
+            return painter.draw_glyph_path(path);
+        }
+        if let Some(svg) = &mut self.svg {
+            svg.draw_glyph_path(path)?;
+        }
+        // Glyph path is filled on image directly, so pending draw calls are applied first.
+        #[cfg(feature = "wgpu")]
+        if let Some(gpu) = &mut self.gpu {
+            gpu.flush(image)?;
+        }
+        image.draw_glyph_path(path)
+    }
+
+    pub fn draw_custom(
+        &mut self,
+        element: &dyn CustomElement,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Error> {
+        let image = match &mut self.image {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        if let Some(painter) = &mut self.painter {
+            return element.paint(painter.as_mut(), x, y, width, height);
+        }
+        // Custom element is painted on image directly, so pending draw calls are applied first.
+        #[cfg(feature = "wgpu")]
+        if let Some(gpu) = &mut self.gpu {
+            gpu.flush(image)?;
+        }
+        match &mut self.svg {
+            Some(svg) => element.paint(&mut Tee(image, svg), x, y, width, height),
+            None => element.paint(image, x, y, width, height),
+        }
+    }
+
+    pub fn measure_text(&self, font: &dyn Font, setting: &FontSetting, text: &str) -> TextExtents {
+        let run = TextRun::new(text, font, setting, Rgba([0, 0, 0, 0]), 0, 0);
+        match &self.painter {
+            Some(painter) => painter.measure(&run),
+            None => run.extents(),
+        }
     }
 
-    pub fn save(&self, path: &Path) -> Result<(), Error> {
+    // Return byte length of the saved file.
+    pub fn save(&mut self, path: &Path) -> Result<u64, Error> {
+        self.flush()?;
         match &self.image {
             Some(image) => {
-                image.save(path)?;
-                Ok(())
+                match self.channels {
+                    OutputChannels::Rgba => image.save(path)?,
+                    OutputChannels::Rgb(background) => {
+                        DynamicImage::ImageRgba8(flatten(image, background.as_image_rgba()))
+                            .into_rgb8()
+                            .save(path)?
+                    }
+                }
+                Ok(fs::metadata(path).map_err(ImageError::IoError)?.len())
+            }
+            None => Err(Error::NotFoundContainerImage),
+        }
+    }
+
+    pub fn save_palette(&mut self, path: &Path, options: PaletteOptions) -> Result<u64, Error> {
+        self.flush()?;
+        match &self.image {
+            Some(image) => {
+                let buf = encode_palette(&self.output_rgba(image), options)?;
+                fs::write(path, &buf).map_err(ImageError::IoError)?;
+                Ok(buf.len() as u64)
             }
             None => Err(Error::NotFoundContainerImage),
         }
     }
 
+    pub fn save_progressive(
+        &mut self,
+        path: &Path,
+        format: ProgressiveFormat,
+    ) -> Result<u64, Error> {
+        self.flush()?;
+        match &self.image {
+            Some(image) => {
+                let buf = encode_progressive(&self.output_rgba(image), format);
+                fs::write(path, &buf).map_err(ImageError::IoError)?;
+                Ok(buf.len() as u64)
+            }
+            None => Err(Error::NotFoundContainerImage),
+        }
+    }
+
+    // Image that is blended onto the background color when alpha channel is dropped.
+    fn output_rgba(&self, image: &RgbaImage) -> RgbaImage {
+        match self.channels {
+            OutputChannels::Rgba => image.clone(),
+            OutputChannels::Rgb(background) => flatten(image, background.as_image_rgba()),
+        }
+    }
+
+    // Crop image to the bounding box of pixels where `is_content` returns true.
+    // Image is kept as it is when it has no content.
+    pub(super) fn trim(&mut self, is_content: impl Fn(&Rgba<u8>) -> bool) -> Result<(), Error> {
+        self.flush()?;
+        let image = self.image.as_mut().ok_or(Error::NotFoundContainerImage)?;
+        if let Some((x, y, width, height)) = content_bounds(image, is_content) {
+            *image = imageops::crop_imm(image, x, y, width, height).to_image();
+        }
+        Ok(())
+    }
+
+    // Apply pending draw calls to image.
+    // This is no-op unless draw calls are deferred by GPU compositing.
+    // Image must be flushed before it is read or written directly.
+    pub(super) fn flush(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "wgpu")]
+        if let (Some(gpu), Some(image)) = (&mut self.gpu, &mut self.image) {
+            gpu.flush(image)?;
+        }
+        Ok(())
+    }
+
+    // Start recording draw calls as SVG.
+    // Current image is recorded as background.
+    pub(super) fn start_svg(&mut self, background_color: Option<Rgba<u8>>) -> Result<(), Error> {
+        self.flush()?;
+        let image = match &self.image {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+
+        let mut svg = SvgDocument::new(image.width(), image.height());
+        match background_color {
+            Some(color) => svg.fill_rect(0, 0, image.width(), image.height(), color)?,
+            None => svg.draw_image(image, 0, 0)?,
+        }
+        self.svg = Some(svg);
+
+        Ok(())
+    }
+
+    pub(super) fn save_svg(&self, path: &Path) -> Result<(), Error> {
+        match &self.svg {
+            Some(svg) => {
+                fs::write(path, svg.finish()).map_err(ImageError::IoError)?;
+                Ok(())
+            }
+            None => Err(Error::NotFoundSvgDocument),
+        }
+    }
+
+    pub(super) fn into_svg(mut self) -> Result<String, Error> {
+        match self.svg.take() {
+            None => Err(Error::NotFoundSvgDocument),
+            Some(svg) => Ok(svg.finish()),
+        }
+    }
+
     pub(super) fn into_vec(mut self) -> Result<Vec<u8>, Error> {
+        self.flush()?;
         match self.image.take() {
             None => Err(Error::NullElement),
             Some(img) => Ok(img.into_vec()),
@@ -87,6 +336,7 @@ impl Context {
     }
 
     pub(super) fn into_rgba(mut self) -> Result<RgbaImage, Error> {
+        self.flush()?;
         match self.image.take() {
             None => Err(Error::NullElement),
             Some(img) => Ok(img),
@@ -94,13 +344,93 @@ impl Context {
     }
 
     pub(super) fn encode(mut self, f: ImageOutputFormat) -> Result<Vec<u8>, Error> {
+        self.flush()?;
+        match self.image.take() {
+            None => Err(Error::NullElement),
+            Some(img) => encode_image(img, self.channels, f),
+        }
+    }
+
+    pub(super) fn into_output(mut self) -> Result<(RgbaImage, OutputChannels), Error> {
+        self.flush()?;
+        match self.image.take() {
+            None => Err(Error::NullElement),
+            Some(img) => Ok((img, self.channels)),
+        }
+    }
+
+    pub(super) fn encode_palette(mut self, options: PaletteOptions) -> Result<Vec<u8>, Error> {
+        self.flush()?;
         match self.image.take() {
             None => Err(Error::NullElement),
             Some(img) => {
-                let mut buf = vec![];
-                DynamicImage::ImageRgba8(img).write_to(&mut buf, f)?;
-                Ok(buf)
+                check_size("output image", img.width(), img.height())?;
+                encode_palette(&self.output_rgba(&img), options)
             }
         }
     }
+
+    pub(super) fn encode_progressive(
+        mut self,
+        format: ProgressiveFormat,
+    ) -> Result<Vec<u8>, Error> {
+        self.flush()?;
+        match self.image.take() {
+            None => Err(Error::NullElement),
+            Some(img) => {
+                check_size("output image", img.width(), img.height())?;
+                Ok(encode_progressive(&self.output_rgba(&img), format))
+            }
+        }
+    }
+}
+
+pub(super) fn encode_image(
+    img: RgbaImage,
+    channels: OutputChannels,
+    f: ImageOutputFormat,
+) -> Result<Vec<u8>, Error> {
+    // Encoders reject or silently corrupt images without pixels.
+    check_size("output image", img.width(), img.height())?;
+    let img = match channels {
+        OutputChannels::Rgba => DynamicImage::ImageRgba8(img),
+        OutputChannels::Rgb(background) => DynamicImage::ImageRgb8(
+            DynamicImage::ImageRgba8(flatten(&img, background.as_image_rgba())).into_rgb8(),
+        ),
+    };
+    let mut buf = vec![];
+    img.write_to(&mut buf, f)?;
+    Ok(buf)
+}
+
+// Tee sends draw calls to both painters.
+struct Tee<'a>(&'a mut dyn Painter, &'a mut dyn Painter);
+
+impl<'a> Painter for Tee<'a> {
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        self.0.fill_rect(x, y, width, height, color)?;
+        self.1.fill_rect(x, y, width, height, color)
+    }
+
+    fn draw_image(&mut self, image: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        self.0.draw_image(image, x, y)?;
+        self.1.draw_image(image, x, y)
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        self.0.draw_text(run)?;
+        self.1.draw_text(run)
+    }
+
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        self.0.draw_glyph_path(path)?;
+        self.1.draw_glyph_path(path)
+    }
 }
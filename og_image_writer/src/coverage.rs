@@ -0,0 +1,98 @@
+//! Find chars that fonts don't have before painting.
+
+use super::element::Element;
+use super::font_context::FontContext;
+use super::font_trait::Font;
+use super::script::script;
+use super::writer::OGImageWriter;
+use crate::Error;
+
+/// Char without glyph in fonts, found by [`OGImageWriter::check_coverage()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingGlyph {
+    /// Index of element in the order it was set to the writer.
+    /// Chars in a container have index of the container.
+    pub index: usize,
+    pub ch: char,
+    /// Script of the char like `Arabic` or `CJK`, to look for a font that covers it.
+    /// This is `None` for punctuation and chars of unknown script.
+    pub script: Option<&'static str>,
+}
+
+impl OGImageWriter {
+    /// Lay out elements and return every char that is drawn without glyph, including ruby,
+    /// curved texts and texts in containers. Each char is reported once for each element.
+    /// Run this when templates are built, so that missing fonts are caught before tofu is rendered.
+    /// Call this before painting like [validate](Self::validate).
+    pub fn check_coverage(&mut self) -> Result<Vec<MissingGlyph>, Error> {
+        let font_context = FontContext::new();
+        let mut missing: Vec<MissingGlyph> = vec![];
+        for (index, elm) in self.elements()?.into_iter().enumerate() {
+            let chars = match elm {
+                Element::Text(Some(text)) => {
+                    let parent_font = text.font.as_ref().map(|font| font as &dyn Font);
+                    text.textarea.missing_chars(parent_font, &font_context)
+                }
+                Element::Custom(Some(custom)) => custom.element().missing_chars(),
+                _ => vec![],
+            };
+            for ch in chars {
+                if !missing.iter().any(|m| m.index == index && m.ch == ch) {
+                    missing.push(MissingGlyph {
+                        index,
+                        ch,
+                        script: script(ch).name(),
+                    });
+                }
+            }
+        }
+        Ok(missing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MissingGlyph;
+    use crate::font_context::FontContext;
+    use crate::style::{Style, WindowStyle};
+    use crate::text_path::TextPath;
+    use crate::writer::OGImageWriter;
+    use crate::TextArea;
+
+    #[test]
+    fn test_check_coverage() {
+        FontContext::new().clear();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let mut textarea = TextArea::new();
+        textarea.push_ruby_text("Tokyo", "東京");
+        writer
+            .set_textarea(textarea, Style::default(), Some(font.clone()))
+            .unwrap();
+        let path = TextPath::Polyline(vec![(0., 50.), (300., 50.)]);
+        writer
+            .set_curved_text("Shalom שלום, שלום", path, Style::default(), font.clone())
+            .unwrap();
+        writer
+            .set_text("Hello\nworld", Style::default(), Some(font))
+            .unwrap();
+
+        let missing = |index, ch, script| MissingGlyph { index, ch, script };
+        assert_eq!(
+            writer.check_coverage().unwrap(),
+            vec![
+                missing(0, '東', Some("CJK")),
+                missing(0, '京', Some("CJK")),
+                missing(1, 'ש', Some("Hebrew")),
+                missing(1, 'ל', Some("Hebrew")),
+                missing(1, 'ו', Some("Hebrew")),
+                missing(1, 'ם', Some("Hebrew")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Font bundled with `default-font` feature, so that texts can be rendered without passing font data.
+//!
+//! The font is [Roboto](https://github.com/googlefonts/roboto) Light under Apache License 2.0,
+//! which covers Latin, Cyrillic and Greek. It is used after fonts pushed to
+//! [FontContext](crate::font_context::FontContext), when they don't have the character.
+//!
+//! ```rust
+//! use og_image_writer::{style, writer::OGImageWriter};
+//!
+//! let mut writer = OGImageWriter::new(style::WindowStyle::default())?;
+//! writer.set_text("Hello, Привет, Γειά", style::Style::default(), None)?;
+//! writer.paint()?;
+//! # Ok::<(), og_image_writer::Error>(())
+//! ```
+
+use crate::font::{create_font_from_static, FontArc};
+use crate::font_context::SharedFont;
+
+/// Data of the bundled font.
+pub static DEFAULT_FONT: &[u8] = include_bytes!("../fonts/Roboto-Light.ttf");
+
+/// The bundled font that can be passed to [`OGImageWriter::set_text_with_font()`](crate::writer::OGImageWriter::set_text_with_font).
+pub fn default_font() -> SharedFont {
+    SharedFont(font())
+}
+
+pub(crate) fn font() -> FontArc {
+    create_font_from_static(DEFAULT_FONT).expect("bundled font is valid")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::font_context::FontContext;
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[test]
+    fn test_default_font() {
+        let font_context = FontContext::new();
+        font_context.clear();
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_text("Hello, Привет, Γειά", Style::default(), None)
+            .unwrap();
+        assert!(writer.set_text("あ", Style::default(), None).is_err());
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        assert!(image.pixels().any(|pixel| pixel.0[3] != 0));
+    }
+}
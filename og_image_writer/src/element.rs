@@ -1,21 +1,121 @@
 use super::font::FontArc;
+use super::font_context::FontContext;
 use super::layout::TextArea;
+use super::painter::Painter;
 use super::style::{Margin, Position, Style};
+use crate::Error;
 use image::{ImageBuffer, Rgba};
+use std::fmt;
 use std::ops::Range;
+use std::sync::Arc;
 
+/// Element that is implemented by user.
+/// Custom element is laid out like image, and painted with [Painter].
+pub trait CustomElement {
+    /// Return width and height of the element.
+    /// `max_width` and `max_height` are size of the window.
+    fn measure(&self, max_width: u32, max_height: u32) -> (u32, u32);
+
+    /// Paint the element into the rectangle that is decided by layout.
+    fn paint(
+        &self,
+        painter: &mut dyn Painter,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Error>;
+
+    /// Return texts drawn by the element in reading order, which are collected as alt text.
+    fn texts(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Return chars drawn by the element that its fonts don't have,
+    /// which are reported by [OGImageWriter::check_coverage](crate::writer::OGImageWriter::check_coverage).
+    fn missing_chars(&self) -> Vec<char> {
+        vec![]
+    }
+}
+
+/// Laid out element of the tree that is returned by [OGImageWriter::elements](crate::writer::OGImageWriter::elements).
+/// `None` is an element that is already painted.
 #[derive(Debug)]
-pub(super) enum Element {
+pub enum Element {
     Img(Option<Img>),
     Text(Option<Text>),
+    Custom(Option<Custom>),
 }
 
 impl Element {
+    /// Style that is set with the element.
+    pub fn style(&self) -> Option<&Style> {
+        match self {
+            Element::Img(Some(img)) => Some(&img.style),
+            Element::Text(Some(text)) => Some(&text.style),
+            Element::Custom(Some(custom)) => Some(&custom.style),
+            _ => None,
+        }
+    }
+
+    // Anchored element is also out of the flow.
     pub(super) fn is_absolute(&self) -> bool {
+        matches!(
+            self.style(),
+            Some(style) if matches!(style.position, Position::Absolute) || style.anchor.is_some()
+        )
+    }
+
+    // Box that contains the element. Box of text is union of its lines.
+    pub(super) fn border_box(&self) -> Rect {
+        let rects: Vec<&Rect> = match self {
+            Element::Img(Some(img)) => vec![&img.rect],
+            Element::Custom(Some(custom)) => vec![&custom.rect],
+            Element::Text(Some(text)) => text.fragments.iter().map(|f| &f.rect).collect(),
+            _ => vec![],
+        };
+        let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+        for rect in &rects {
+            left = left.min(rect.x);
+            top = top.min(rect.y);
+            right = right.max(rect.x + rect.width);
+            bottom = bottom.max(rect.y + rect.height);
+        }
+        match rects.is_empty() {
+            true => Rect::default(),
+            false => Rect::new(left, top, right - left, bottom - top),
+        }
+    }
+
+    // Move the element by (dx, dy). Position is clamped to the window origin.
+    // Return false when the element is moved over the start of the window and clamped.
+    pub(super) fn translate(&mut self, dx: i32, dy: i32) -> bool {
+        let rects: Vec<&mut Rect> = match self {
+            Element::Img(Some(img)) => vec![&mut img.rect],
+            Element::Custom(Some(custom)) => vec![&mut custom.rect],
+            Element::Text(Some(text)) => text.fragments.iter_mut().map(|f| &mut f.rect).collect(),
+            _ => vec![],
+        };
+        let mut is_inside = true;
+        for rect in rects {
+            let (x, y) = (rect.x as i32 + dx, rect.y as i32 + dy);
+            is_inside &= x >= 0 && y >= 0;
+            rect.x = x.max(0) as u32;
+            rect.y = y.max(0) as u32;
+        }
+        is_inside
+    }
+
+    // Distance from the top of margin box to baseline.
+    // Baseline of image and custom element is their bottom edge like CSS.
+    pub(super) fn baseline(&self) -> i32 {
         match self {
-            Element::Img(Some(img)) => matches!(img.style.position, Position::Absolute),
-            Element::Text(Some(text)) => matches!(text.style.position, Position::Absolute),
-            _ => false,
+            Element::Img(Some(Img { rect, style, .. }))
+            | Element::Custom(Some(Custom { rect, style, .. })) => {
+                style.margin.0 + rect.height as i32
+            }
+            Element::Text(Some(text)) => text.style.margin.0 + text.metrics.baseline as i32,
+            _ => 0,
         }
     }
 
@@ -23,13 +123,15 @@ impl Element {
         match self {
             Element::Img(Some(img)) => img.style.margin,
             Element::Text(Some(text)) => text.style.margin,
+            Element::Custom(Some(custom)) => custom.style.margin,
             _ => Margin::default(),
         }
     }
 }
 
-#[derive(Debug, Default)]
-pub(super) struct Rect {
+/// Rectangle in the window.
+#[derive(Debug, Default, PartialEq)]
+pub struct Rect {
     pub(super) x: u32,
     pub(super) y: u32,
     pub(super) width: u32,
@@ -45,30 +147,142 @@ impl Rect {
             height,
         }
     }
+
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
 }
 
+/// Image element, including collage.
 #[derive(Debug)]
-pub(super) struct Img {
-    pub(super) buf: ImageBuffer<Rgba<u8>, Vec<u8>>,
+pub struct Img {
+    // Pixels are shared with images of the same element in ElementCache.
+    pub(super) buf: Arc<ImageBuffer<Rgba<u8>, Vec<u8>>>,
     pub(super) rect: Rect,
     pub(super) style: Style,
+    // Ratio of displayed size to source size.
+    pub(super) scale: f32,
 }
 
 impl Img {
     pub fn new(buf: ImageBuffer<Rgba<u8>, Vec<u8>>, rect: Rect, style: Style) -> Self {
-        Img { buf, rect, style }
+        Img {
+            buf: Arc::new(buf),
+            rect,
+            style,
+            scale: 1.,
+        }
+    }
+
+    /// Resized image that is painted.
+    pub fn image(&self) -> &ImageBuffer<Rgba<u8>, Vec<u8>> {
+        &self.buf
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Ratio of displayed size to source size.
+    pub fn scale(&self) -> f32 {
+        self.scale
     }
 }
 
+/// Element that is set with [CustomElement].
+pub struct Custom {
+    pub(super) element: Box<dyn CustomElement>,
+    pub(super) rect: Rect,
+    pub(super) style: Style,
+}
+
+impl Custom {
+    pub fn new(element: Box<dyn CustomElement>, rect: Rect, style: Style) -> Self {
+        Custom {
+            element,
+            rect,
+            style,
+        }
+    }
+
+    pub fn element(&self) -> &dyn CustomElement {
+        self.element.as_ref()
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+}
+
+impl fmt::Debug for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Custom")
+            .field("rect", &self.rect)
+            .field("style", &self.style)
+            .finish()
+    }
+}
+
+/// Laid out line of text.
 #[derive(Debug)]
-pub(super) struct Fragment {
+pub struct Fragment {
     pub(super) range: Range<usize>,
     pub(super) rect: Rect,
+    // Index of column that has this fragment.
+    pub(super) column: usize,
+    // Distance from the start of column to the line, that is indent and shift by `text_align`.
+    pub(super) inset: u32,
+    // Ascent of the line that is computed in layout, so that painting doesn't walk glyphs again.
+    pub(super) ascent: Option<f32>,
+    // Advance of each glyph run in the line that is measured in layout, so that painting doesn't measure texts again.
+    pub(super) advances: Option<Vec<u32>>,
 }
 
 impl Fragment {
     pub fn new(range: Range<usize>, rect: Rect) -> Self {
-        Fragment { range, rect }
+        Fragment {
+            range,
+            rect,
+            column: 0,
+            inset: 0,
+            ascent: None,
+            advances: None,
+        }
+    }
+
+    /// Byte range of the line in [Text::text].
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Line box in the window.
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    /// Index of column that has this line when `column_count` is set.
+    pub fn column(&self) -> usize {
+        self.column
     }
 }
 
@@ -77,6 +291,10 @@ pub(super) struct LineMetrics {
     pub total_height: u32,
     pub max_line_height: f32,
     pub max_line_width: f32,
+    pub column_width: f32,
+    pub column_gap: f32,
+    // Position of baseline of the first line from the top of text.
+    pub baseline: f32,
 }
 
 impl LineMetrics {
@@ -85,12 +303,21 @@ impl LineMetrics {
             total_height,
             max_line_height,
             max_line_width,
+            column_width: max_line_width,
+            column_gap: 0.,
+            baseline: 0.,
         }
     }
+
+    // End position of column that is used to align text in the column.
+    pub fn column_end(&self, column: usize) -> f32 {
+        column as f32 * (self.column_width + self.column_gap) + self.column_width
+    }
 }
 
+/// Text element that is broken into lines.
 #[derive(Debug)]
-pub(super) struct Text {
+pub struct Text {
     pub(super) text: String,
     pub(super) metrics: LineMetrics,
     pub(super) fragments: Vec<Fragment>,
@@ -98,10 +325,14 @@ pub(super) struct Text {
     // TODO: optimize static lifetime
     pub(super) font: Option<FontArc>,
     pub(super) textarea: TextArea,
+    // Whether text is cut by `max_height`.
+    pub(super) is_overflow: bool,
+    // Direction of paragraph that `text_align` is resolved against.
+    pub(super) is_rtl: bool,
 }
 
 impl Text {
-    pub fn new(
+    pub(super) fn new(
         text: String,
         fragments: Vec<Fragment>,
         metrics: LineMetrics,
@@ -116,6 +347,378 @@ impl Text {
             style,
             font,
             textarea,
+            is_overflow: false,
+            is_rtl: false,
+        }
+    }
+
+    // The largest ascent of texts in the line, that is used as baseline of line.
+    pub(super) fn line_ascent(&self, fragment: &Fragment, font_context: &FontContext) -> f32 {
+        match fragment.ascent {
+            Some(ascent) => ascent,
+            None => self.textarea.line_ascent(
+                fragment.range.clone(),
+                &self.style,
+                &self.font,
+                font_context,
+            ),
+        }
+    }
+
+    /// Text after white space is processed and ellipsis is applied.
+    // Cut off lines so that text is shorter by `height`, and return the height that is actually reduced.
+    pub(super) fn shrink(&mut self, height: u32) -> u32 {
+        let line_height = self.metrics.max_line_height as u32;
+        let limit = self.metrics.total_height.saturating_sub(height);
+        let len = self.fragments.len();
+        self.fragments
+            .retain(|fragment| fragment.rect.y + line_height <= limit);
+        if self.fragments.len() == len {
+            return 0;
+        }
+        self.is_overflow = true;
+        let total_height = self
+            .fragments
+            .iter()
+            .map(|fragment| fragment.rect.y + line_height)
+            .max()
+            .unwrap_or(0);
+        let reduced = self.metrics.total_height - total_height;
+        self.metrics.total_height = total_height;
+        reduced
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> &[Fragment] {
+        &self.fragments
+    }
+
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Whether text is cut by `max_height`.
+    pub fn is_overflow(&self) -> bool {
+        self.is_overflow
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::img::DecodedImage;
+    use crate::style::{
+        Direction, JustifyContent, LineNumbers, Rgba, TextAlign, TextBar, WhiteSpace, WindowStyle,
+    };
+    use crate::writer::OGImageWriter;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_elements() {
+        for justify_content in [JustifyContent::Start, JustifyContent::End] {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 200,
+                justify_content,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+            let style = Style {
+                max_width: Some(100),
+                ..Style::default()
+            };
+            writer
+                .set_text("Hello World Hello World", style, Some(font))
+                .unwrap();
+            let img = DecodedImage::from(RgbaImage::new(20, 10));
+            writer
+                .set_img_with_decoded(&img, 40, 20, Style::default())
+                .unwrap();
+
+            let elements = writer.elements().unwrap();
+            assert_eq!(elements.len(), 2);
+            match elements[0] {
+                Element::Text(Some(text)) => {
+                    assert!(text.lines().len() > 1);
+                    let line = &text.lines()[0];
+                    assert!(text.text()[line.range()].starts_with("Hello"));
+                }
+                elm => panic!("unexpected element: {:?}", elm),
+            }
+            match elements[1] {
+                Element::Img(Some(img)) => {
+                    assert_eq!((img.rect().width(), img.rect().height()), (40, 20));
+                    assert_eq!(img.scale(), 2.);
+                }
+                elm => panic!("unexpected element: {:?}", elm),
+            }
+        }
+    }
+
+    #[test]
+    fn test_text_align_direction() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 300,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let aligns = [
+            (TextAlign::Start, Direction::Ltr),
+            (TextAlign::Start, Direction::Rtl),
+            (TextAlign::End, Direction::Rtl),
+            (TextAlign::Left, Direction::Rtl),
+        ];
+        for (text_align, direction) in aligns {
+            let style = Style {
+                text_align,
+                direction,
+                white_space: WhiteSpace::PreLine,
+                ..Style::default()
+            };
+            writer
+                .set_text("Hi\nHello", style, Some(font.clone()))
+                .unwrap();
         }
+
+        // Left and right edges of the short line and the long line.
+        let edges: Vec<_> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| match elm {
+                Element::Text(Some(text)) => {
+                    let edges =
+                        |line: &Fragment| (line.rect().x(), line.rect().x() + line.rect().width());
+                    (edges(&text.lines()[0]), edges(&text.lines()[1]))
+                }
+                elm => panic!("unexpected element: {:?}", elm),
+            })
+            .collect();
+        let is_left = |((short, _), (long, _)): ((u32, u32), (u32, u32))| short == long;
+        let is_right = |((_, short), (_, long)): ((u32, u32), (u32, u32))| short == long;
+        assert!(is_left(edges[0]) && !is_right(edges[0]));
+        assert!(is_right(edges[1]) && !is_left(edges[1]));
+        assert!(is_left(edges[2]) && !is_right(edges[2]));
+        assert!(is_left(edges[3]));
+    }
+
+    #[test]
+    fn test_strip_bidi_controls() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        let mut textarea = TextArea::new();
+        textarea
+            .push(
+                "\u{202E}gnp.exe\u{202C}",
+                Style::default(),
+                Some(font.clone()),
+            )
+            .unwrap();
+        textarea.push_text(" \u{2067}title\u{2069}");
+        writer
+            .set_textarea(textarea, Style::default(), Some(font))
+            .unwrap();
+        match writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => assert_eq!(text.text(), "gnp.exe title"),
+            elm => panic!("unexpected element: {:?}", elm),
+        }
+    }
+
+    #[test]
+    fn test_line_ascent_of_mixed_fonts() {
+        let roboto = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mplus = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            background_color: Some(Rgba([255, 255, 255, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let mut textarea = TextArea::new();
+        textarea
+            .push_line(
+                "HHH",
+                Style {
+                    font_size: 20.,
+                    ..Style::default()
+                },
+                None,
+            )
+            .unwrap();
+        textarea
+            .push(
+                "HHH",
+                Style {
+                    font_size: 40.,
+                    ..Style::default()
+                },
+                Some(mplus),
+            )
+            .unwrap();
+        writer
+            .set_textarea(textarea, Style::default(), Some(roboto))
+            .unwrap();
+
+        let baselines: Vec<(u32, u32)> = match writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => {
+                assert_eq!(text.lines().len(), 2);
+                text.lines()
+                    .iter()
+                    .map(|line| {
+                        let ascent = line.ascent.unwrap();
+                        assert_eq!(
+                            ascent,
+                            text.textarea.line_ascent(
+                                line.range(),
+                                &text.style,
+                                &text.font,
+                                &FontContext::new(),
+                            )
+                        );
+                        let rect = line.rect();
+                        (rect.y(), rect.y() + ascent.round() as u32)
+                    })
+                    .collect()
+            }
+            elm => panic!("unexpected element: {:?}", elm),
+        };
+        // Each line has its own ascent, not the ascent of the other line.
+        let ascents: Vec<u32> = baselines.iter().map(|(top, base)| base - top).collect();
+        assert!(ascents[1] > ascents[0] * 3 / 2);
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        let is_inked = |y: u32| (0..image.width()).any(|x| image.get_pixel(x, y).0[0] < 128);
+        for (top, baseline) in baselines {
+            // `H` has no descent, so its lowest inked row sits on the baseline.
+            let bottom = (top..baseline + 10).filter(|y| is_inked(*y)).max().unwrap();
+            assert!(bottom + 2 >= baseline && bottom <= baseline + 1);
+        }
+    }
+
+    #[test]
+    fn test_text_bar() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            text_bar: Some(TextBar {
+                color: Rgba([255, 0, 0, 255]),
+                width: 4,
+                gap: 6,
+            }),
+            text_align: TextAlign::Center,
+            max_width: Some(200),
+            ..Style::default()
+        };
+        writer
+            .set_text("Hello World Hello World", style, Some(font))
+            .unwrap();
+
+        let (top, bottom) = match writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => {
+                assert!(text.lines().len() > 1);
+                for line in text.lines() {
+                    assert!(line.rect().x() >= 10);
+                    assert_eq!(line.rect().x() - line.inset, 0);
+                }
+                let last = text.lines().last().unwrap().rect();
+                (text.lines()[0].rect().y(), last.y() + last.height())
+            }
+            elm => panic!("unexpected element: {:?}", elm),
+        };
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        let red = image::Rgba([255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, top), &red);
+        assert_eq!(image.get_pixel(3, bottom - 1), &red);
+        assert_ne!(image.get_pixel(4, top), &red);
+        assert_ne!(image.get_pixel(0, bottom), &red);
+    }
+
+    #[test]
+    fn test_emoji_only_scale() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let size = |text: &str, emoji_only_scale: f32| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 200,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer.set_emoji_resolver(Box::new(|_: &str| Some(RgbaImage::new(8, 8))));
+            let style = Style {
+                font_size: 20.,
+                emoji_only_scale,
+                ..Style::default()
+            };
+            writer.set_text(text, style, Some(font.clone())).unwrap();
+            let rect = writer.elements().unwrap()[0].border_box();
+            (rect.width(), rect.height())
+        };
+
+        let (width, height) = size("🚀🚀", 1.);
+        let (scaled_width, scaled_height) = size("🚀🚀", 2.);
+        assert_eq!(scaled_width, width * 2);
+        assert!(scaled_height > height);
+        assert_eq!(size("Go🚀", 2.), size("Go🚀", 1.));
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 300,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            line_numbers: Some(LineNumbers {
+                start: 9,
+                color: Rgba([255, 0, 0, 255]),
+                width: 40,
+                gap: 8,
+            }),
+            white_space: WhiteSpace::PreLine,
+            max_width: Some(200),
+            ..Style::default()
+        };
+        writer
+            .set_text("one\ntwo two two two two\nthree", style, Some(font))
+            .unwrap();
+
+        let rows = match writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => {
+                assert!(text.lines().iter().all(|line| line.rect().x() == 48));
+                text.lines().len()
+            }
+            elm => panic!("unexpected element: {:?}", elm),
+        };
+        assert_eq!(rows, 4);
+
+        // `9`, `10` and `11` are drawn, because the wrapped line is not numbered.
+        let red = image::Rgba([255, 0, 0, 255]);
+        let digits: Vec<f32> = writer
+            .glyph_paths()
+            .unwrap()
+            .into_iter()
+            .filter(|path| path.color == red)
+            .map(|path| path.x)
+            .collect();
+        assert_eq!(digits.len(), 5);
+        assert!(digits.iter().all(|&x| x < 40.));
     }
 }
@@ -0,0 +1,167 @@
+//! Replace emoji in texts with images.
+//!
+//! Color emoji fonts are not supported, so emoji can be drawn with image assets like [Twemoji](https://github.com/twitter/twemoji) instead.
+//! Set [EmojiResolver] with [`OGImageWriter::set_emoji_resolver()`](crate::writer::OGImageWriter::set_emoji_resolver),
+//! then each emoji cluster is laid out as a square of font size, and painted with the resolved image.
+//! Emoji that is not resolved is drawn with fonts as usual.
+
+use image::RgbaImage;
+use std::path::PathBuf;
+
+/// Return image for emoji cluster like `"🚀"` or `"👍🏽"`.
+/// The image is resized to font size when painting.
+pub trait EmojiResolver {
+    fn resolve(&self, emoji: &str) -> Option<RgbaImage>;
+}
+
+impl<F> EmojiResolver for F
+where
+    F: Fn(&str) -> Option<RgbaImage>,
+{
+    fn resolve(&self, emoji: &str) -> Option<RgbaImage> {
+        self(emoji)
+    }
+}
+
+/// Load PNG images from directory with Twemoji file names like `1f680.png`.
+/// Rasterize SVG assets to PNG in advance.
+#[derive(Debug, Clone)]
+pub struct AssetDirResolver {
+    pub dir: PathBuf,
+}
+
+impl AssetDirResolver {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        AssetDirResolver { dir: dir.into() }
+    }
+}
+
+impl EmojiResolver for AssetDirResolver {
+    fn resolve(&self, emoji: &str) -> Option<RgbaImage> {
+        let path = self.dir.join(format!("{}.png", twemoji_name(emoji)));
+        image::open(path).ok().map(|image| image.into_rgba8())
+    }
+}
+
+/// File name of emoji in Twemoji, like `1f468-200d-1f4bb` for `"👨‍💻"`.
+/// Variation selector is removed unless emoji is ZWJ sequence like Twemoji does.
+pub fn twemoji_name(emoji: &str) -> String {
+    let has_zwj = emoji.contains(ZWJ);
+    emoji
+        .chars()
+        .filter(|&ch| has_zwj || ch != VS16)
+        .map(|ch| format!("{:x}", ch as u32))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+const ZWJ: char = '\u{200D}';
+const VS16: char = '\u{FE0F}';
+const KEYCAP: char = '\u{20E3}';
+
+// Return byte length of emoji cluster at the start of text.
+pub(crate) fn emoji_cluster_len(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    let (_, first) = chars.next()?;
+
+    if is_regional_indicator(first) {
+        return match chars.next() {
+            Some((i, ch)) if is_regional_indicator(ch) => Some(i + ch.len_utf8()),
+            _ => None,
+        };
+    }
+
+    if matches!(first, '0'..='9' | '#' | '*') {
+        // Keycap sequence like `1️⃣`
+        let rest = &text[1..];
+        let rest = rest.strip_prefix(VS16).unwrap_or(rest);
+        return rest
+            .strip_prefix(KEYCAP)
+            .map(|after| text.len() - after.len());
+    }
+
+    if !is_emoji_base(first) {
+        return None;
+    }
+
+    let mut len = first.len_utf8();
+    while let Some(&(i, ch)) = chars.peek() {
+        match ch {
+            // Variation selectors, skin tone modifiers, tags and keycap.
+            '\u{FE0E}' | VS16 | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}' | KEYCAP => {
+                chars.next();
+                len = i + ch.len_utf8();
+            }
+            ZWJ => {
+                chars.next();
+                match chars.next() {
+                    Some((j, next)) if is_emoji_base(next) => len = j + next.len_utf8(),
+                    _ => break,
+                }
+            }
+            _ => break,
+        }
+    }
+
+    Some(len)
+}
+
+// Whether text has only emoji clusters and whitespace.
+pub(crate) fn is_emoji_only(text: &str) -> bool {
+    let mut rest = text.trim_start();
+    if rest.is_empty() {
+        return false;
+    }
+    while !rest.is_empty() {
+        match emoji_cluster_len(rest) {
+            Some(len) => rest = rest[len..].trim_start(),
+            None => return false,
+        }
+    }
+    true
+}
+
+fn is_regional_indicator(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch)
+}
+
+fn is_emoji_base(ch: char) -> bool {
+    matches!(ch,
+        '\u{1F000}'..='\u{1FAFF}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2300}'..='\u{23FF}'
+        | '\u{2B00}'..='\u{2BFF}'
+    ) && !is_regional_indicator(ch)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_emoji_cluster_len() {
+        assert_eq!(emoji_cluster_len("🚀 launch"), Some("🚀".len()));
+        assert_eq!(emoji_cluster_len("👍🏽!"), Some("👍🏽".len()));
+        assert_eq!(emoji_cluster_len("👨‍💻a"), Some("👨‍💻".len()));
+        assert_eq!(emoji_cluster_len("🇯🇵🇺🇸"), Some("🇯🇵".len()));
+        assert_eq!(emoji_cluster_len("1️⃣"), Some("1️⃣".len()));
+        assert_eq!(emoji_cluster_len("1 apple"), None);
+        assert_eq!(emoji_cluster_len("a🚀"), None);
+    }
+
+    #[test]
+    fn test_is_emoji_only() {
+        assert!(is_emoji_only("🚀"));
+        assert!(is_emoji_only(" 👍🏽 🇯🇵\n"));
+        assert!(!is_emoji_only("🚀 launch"));
+        assert!(!is_emoji_only("1"));
+        assert!(!is_emoji_only(" "));
+    }
+
+    #[test]
+    fn test_twemoji_name() {
+        assert_eq!(twemoji_name("🚀"), "1f680");
+        assert_eq!(twemoji_name("❤️"), "2764");
+        assert_eq!(twemoji_name("👨‍💻"), "1f468-200d-1f4bb");
+    }
+}
@@ -1,6 +1,37 @@
+use crate::limits::Resource;
 use image::ImageError;
+use std::fmt;
 use thiserror::Error;
 
+const SNIPPET_LEN: usize = 20;
+
+/// Where the error occurred.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementContext {
+    /// Index of element in the order it was set to the writer.
+    pub index: Option<usize>,
+    /// Beginning of text of the element.
+    pub snippet: Option<String>,
+    /// Byte position of the character in text of the element.
+    pub char_position: Option<usize>,
+}
+
+impl fmt::Display for ElementContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "element #{}", index)?,
+            None => write!(f, "element")?,
+        }
+        if let Some(snippet) = &self.snippet {
+            write!(f, " {:?}", snippet)?;
+        }
+        if let Some(position) = self.char_position {
+            write!(f, " at character position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("null element kind")]
@@ -9,10 +40,145 @@ pub enum Error {
     ImageError(#[from] ImageError),
     #[error("failed to parse invalid font bytes")]
     InvalidFontBytes,
+    #[error("Font must be TrueType font with `glyf` table")]
+    UnsupportedFontFormat,
     #[error("Container image could not found")]
     NotFoundContainerImage,
     #[error("Could not found text within range")]
     OutOfRangeText,
     #[error("Could not found specified font family")]
     NotFoundSpecifiedFontFamily,
+    #[error("Animation must have at least one frame")]
+    EmptyAnimation,
+    #[error("SVG document could not found. Call `record_svg` before painting")]
+    NotFoundSvgDocument,
+    #[error("{resource} {value} exceeds limit {limit}")]
+    LimitExceeded {
+        resource: Resource,
+        value: u64,
+        limit: u64,
+    },
+    #[error("{target} must not be empty, but its size is {width}x{height}")]
+    InvalidDimensions {
+        target: &'static str,
+        width: u32,
+        height: u32,
+    },
+    #[error("aspect ratio must be finite and greater than 0, but it is {0}")]
+    InvalidAspectRatio(f32),
+    #[error("Element `{0}` could not found")]
+    NotFoundElementId(String),
+    #[error("Asset `{0}` could not found")]
+    NotFoundAsset(String),
+    #[error("Elements are already laid out. Update elements before painting")]
+    AlreadyLaidOut,
+    #[error("Theme `{0}` changes fonts or scales of layout. Use `generate_variants_with` for it")]
+    ThemeChangesLayout(String),
+    #[error("Batch encoder has stopped")]
+    BatchEncoderStopped,
+    #[error("Rendering was cancelled")]
+    Cancelled,
+    #[error("Rendering exceeded the deadline")]
+    DeadlineExceeded,
+    // Cause is reported by `source`, so it is not repeated in the message of error chain.
+    #[error("error in {context}")]
+    Element {
+        context: ElementContext,
+        source: Box<Error>,
+    },
+    #[cfg(feature = "wgpu")]
+    #[error("gpu error: {0}")]
+    GpuError(String),
+    #[cfg(feature = "tera")]
+    #[error("template error: {0}")]
+    TemplateError(#[from] tera::Error),
+    #[cfg(feature = "html")]
+    #[error("invalid html: {0}")]
+    InvalidHtml(String),
+    #[cfg(feature = "figma")]
+    #[error("invalid figma json: {0}")]
+    InvalidFigma(String),
+}
+
+impl Error {
+    /// Context of element where the error occurred.
+    pub fn context(&self) -> Option<&ElementContext> {
+        match self {
+            Error::Element { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Error without element context.
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::Element { source, .. } => source.inner(),
+            _ => self,
+        }
+    }
+
+    // Attach index and text of element. Context that is already attached is kept.
+    pub(crate) fn in_element(self, index: usize, text: Option<&str>) -> Self {
+        self.with_context(|context| {
+            context.index.get_or_insert(index);
+            if context.snippet.is_none() {
+                context.snippet = text.map(snippet);
+            }
+        })
+    }
+
+    pub(crate) fn at_char(self, position: usize) -> Self {
+        self.with_context(|context| {
+            context.char_position.get_or_insert(position);
+        })
+    }
+
+    fn with_context<F: FnOnce(&mut ElementContext)>(self, f: F) -> Self {
+        match self {
+            Error::Element {
+                mut context,
+                source,
+            } => {
+                f(&mut context);
+                Error::Element { context, source }
+            }
+            err => {
+                let mut context = ElementContext::default();
+                f(&mut context);
+                Error::Element {
+                    context,
+                    source: Box::new(err),
+                }
+            }
+        }
+    }
+}
+
+fn snippet(text: &str) -> String {
+    match text.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_element_context() {
+        let err = Error::NotFoundSpecifiedFontFamily
+            .at_char(3)
+            .in_element(1, Some("Hello, Open Graphic Image Writer"))
+            .in_element(5, None);
+
+        assert!(matches!(err.inner(), Error::NotFoundSpecifiedFontFamily));
+        assert_eq!(
+            err.to_string(),
+            "error in element #1 \"Hello, Open Graphic …\" at character position 3"
+        );
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(source.to_string(), "Could not found specified font family");
+        assert!(std::error::Error::source(source).is_none());
+    }
 }
@@ -0,0 +1,409 @@
+use crate::img::DecodedImage;
+use crate::limits::Limits;
+use crate::style::{
+    AlignItems, BorderRadius, FlexDirection, JustifyContent, Margin, MarginAuto, Position, Rgba,
+    Style, TextAlign, TextTransform, WindowStyle,
+};
+use crate::writer::OGImageWriter;
+use crate::Error;
+use image::RgbaImage;
+use serde_json::Value;
+
+// Node types that are imported. The other nodes like vectors are skipped.
+const SUPPORTED_TYPES: [&str; 5] = ["FRAME", "COMPONENT", "INSTANCE", "RECTANGLE", "TEXT"];
+
+struct Bounds {
+    x: f32,
+    y: f32,
+    width: u32,
+    height: u32,
+}
+
+impl OGImageWriter {
+    /// Experimental: build writer from frame of Figma REST API JSON, like response of `GET /v1/files/:key`.
+    /// The first frame is used, or the frame named `frame` when it is passed.
+    ///
+    /// Frames, rectangles and texts are imported with their solid fills and corner radius.
+    /// Frames with auto layout are laid out with flexbox, and children of the other frames are positioned absolutely.
+    /// Padding and item spacing of auto layout are margins of children.
+    /// The other nodes like vectors and images are skipped. All texts are drawn with `font`.
+    pub fn from_figma(json: &str, frame: Option<&str>, font: Vec<u8>) -> Result<Self, Error> {
+        OGImageWriter::from_figma_with_limits(json, frame, font, Limits::default())
+    }
+
+    /// Same as [from_figma](Self::from_figma), but frames and rectangles are checked with [Limits]
+    /// before their pixels are allocated.
+    pub fn from_figma_with_limits(
+        json: &str,
+        frame: Option<&str>,
+        font: Vec<u8>,
+        limits: Limits,
+    ) -> Result<Self, Error> {
+        let json: Value = serde_json::from_str(json).map_err(|err| invalid(err.to_string()))?;
+        let frame = find_frame(&json, frame).ok_or_else(|| invalid("frame could not found"))?;
+        build_frame(frame, &font, limits)
+    }
+}
+
+// Find frame in depth-first order. Frames in frames are not searched.
+fn find_frame<'a>(node: &'a Value, name: Option<&str>) -> Option<&'a Value> {
+    match node {
+        Value::Object(object) => {
+            let is_frame = object.get("type").and_then(Value::as_str) == Some("FRAME");
+            let is_named = name.is_none() || object.get("name").and_then(Value::as_str) == name;
+            match (is_frame, is_named) {
+                (true, true) => Some(node),
+                (true, false) => None,
+                // Frames are in `document`, `nodes` or `children`.
+                _ => object.values().find_map(|value| find_frame(value, name)),
+            }
+        }
+        Value::Array(array) => array.iter().find_map(|value| find_frame(value, name)),
+        _ => None,
+    }
+}
+
+fn build_frame(frame: &Value, font: &[u8], limits: Limits) -> Result<OGImageWriter, Error> {
+    let frame_bounds = bounds(frame)?;
+    let layout = str_of(frame, "layoutMode").unwrap_or("NONE");
+    let is_row = layout == "HORIZONTAL";
+    let window = WindowStyle {
+        width: frame_bounds.width,
+        height: frame_bounds.height,
+        background_color: fill(frame),
+        flex_direction: match is_row {
+            true => FlexDirection::Row,
+            false => FlexDirection::Column,
+        },
+        justify_content: match str_of(frame, "primaryAxisAlignItems") {
+            Some("CENTER") => JustifyContent::Center,
+            Some("MAX") => JustifyContent::End,
+            _ => JustifyContent::Start,
+        },
+        align_items: match str_of(frame, "counterAxisAlignItems") {
+            Some("CENTER") => AlignItems::Center,
+            Some("MAX") => AlignItems::End,
+            Some("BASELINE") => AlignItems::Baseline,
+            _ => AlignItems::Start,
+        },
+        ..WindowStyle::default()
+    };
+    let mut writer = OGImageWriter::with_limits(window, limits)?;
+
+    let children: Vec<&Value> = frame
+        .get("children")
+        .and_then(Value::as_array)
+        .map(|children| {
+            children
+                .iter()
+                .filter(|child| child.get("visible").and_then(Value::as_bool) != Some(false))
+                .filter(|child| {
+                    matches!(str_of(child, "type"), Some(kind) if SUPPORTED_TYPES.contains(&kind))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for (i, child) in children.iter().enumerate() {
+        let mut style = Style::default();
+        if layout == "NONE" {
+            let child_bounds = bounds(child)?;
+            style.position = Position::Absolute;
+            style.left = Some((child_bounds.x - frame_bounds.x).round() as i32);
+            style.top = Some((child_bounds.y - frame_bounds.y).round() as i32);
+        } else {
+            let is_first = i == 0;
+            let is_last = i + 1 == children.len();
+            let padding = |key: &str| number_of(frame, key).round() as i32;
+            let (main_padding, cross_padding) = match is_row {
+                true => (
+                    ("paddingLeft", "paddingRight"),
+                    ("paddingTop", "paddingBottom"),
+                ),
+                false => (
+                    ("paddingTop", "paddingBottom"),
+                    ("paddingLeft", "paddingRight"),
+                ),
+            };
+            let is_space_between = str_of(frame, "primaryAxisAlignItems") == Some("SPACE_BETWEEN");
+
+            let main_start = if is_first { padding(main_padding.0) } else { 0 };
+            let main_end = match is_last {
+                true => padding(main_padding.1),
+                false if is_space_between => 0,
+                false => padding("itemSpacing"),
+            };
+            let (cross_start, cross_end) = (padding(cross_padding.0), padding(cross_padding.1));
+            // Space between items is taken by auto margins.
+            let is_auto = is_space_between && !is_first;
+            (style.margin, style.margin_auto) = match is_row {
+                true => (
+                    Margin(cross_start, main_end, cross_end, main_start),
+                    MarginAuto(false, false, false, is_auto),
+                ),
+                false => (
+                    Margin(main_start, cross_end, main_end, cross_start),
+                    MarginAuto(is_auto, false, false, false),
+                ),
+            };
+        }
+        set_node(&mut writer, child, style, font, limits)?;
+    }
+    Ok(writer)
+}
+
+fn set_node(
+    writer: &mut OGImageWriter,
+    node: &Value,
+    mut style: Style,
+    font: &[u8],
+    limits: Limits,
+) -> Result<(), Error> {
+    let node_bounds = bounds(node)?;
+    match str_of(node, "type") {
+        Some("RECTANGLE") => {
+            style.border_radius = radius(node);
+            let Rgba(color) = fill(node).unwrap_or(Rgba([0, 0, 0, 0]));
+            let (width, height) = (node_bounds.width.max(1), node_bounds.height.max(1));
+            // Size comes from the file, so it is checked before pixels are allocated.
+            limits.check_element_size(width, height)?;
+            let image = RgbaImage::from_pixel(width, height, image::Rgba(color));
+            writer.set_img_with_decoded(
+                &DecodedImage::from(image),
+                node_bounds.width,
+                node_bounds.height,
+                style,
+            )
+        }
+        Some("TEXT") => {
+            let text = str_of(node, "characters").unwrap_or("");
+            let text_style = node.get("style").unwrap_or(&Value::Null);
+            style.font_size = number_of(text_style, "fontSize").max(1.);
+            style.letter_spacing = number_of(text_style, "letterSpacing").round() as i32;
+            if let Some(line_height) = text_style.get("lineHeightPx").and_then(Value::as_f64) {
+                style.line_height = line_height as f32 / style.font_size;
+            }
+            style.text_align = match str_of(text_style, "textAlignHorizontal") {
+                Some("CENTER") => TextAlign::Center,
+                Some("RIGHT") => TextAlign::Right,
+                _ => TextAlign::Left,
+            };
+            style.text_transform = match str_of(text_style, "textCase") {
+                Some("UPPER") => TextTransform::Uppercase,
+                Some("LOWER") => TextTransform::Lowercase,
+                Some("TITLE") => TextTransform::Capitalize,
+                _ => TextTransform::None,
+            };
+            if let Some(color) = fill(node) {
+                style.color = color;
+            }
+            // Text is wrapped in the width of the node like Figma.
+            style.max_width = Some(node_bounds.width.max(1));
+            writer.set_text(text, style, Some(font.to_vec()))
+        }
+        _ => {
+            style.border_radius = radius(node);
+            let container = build_frame(node, font, limits)?;
+            writer.set_container(container, style)
+        }
+    }
+}
+
+fn bounds(node: &Value) -> Result<Bounds, Error> {
+    let bounds = node
+        .get("absoluteBoundingBox")
+        .ok_or_else(|| invalid("node must have `absoluteBoundingBox`"))?;
+    Ok(Bounds {
+        x: number_of(bounds, "x"),
+        y: number_of(bounds, "y"),
+        width: number_of(bounds, "width").round() as u32,
+        height: number_of(bounds, "height").round() as u32,
+    })
+}
+
+// The first visible solid fill. Opacity of fill is multiplied to alpha.
+fn fill(node: &Value) -> Option<Rgba> {
+    let fill = node.get("fills")?.as_array()?.iter().find(|fill| {
+        fill.get("visible").and_then(Value::as_bool) != Some(false)
+            && str_of(fill, "type") == Some("SOLID")
+    })?;
+    let color = fill.get("color")?;
+    let opacity = fill.get("opacity").and_then(Value::as_f64).unwrap_or(1.) as f32;
+    let channel = |value: f32| (value * 255.).round().clamp(0., 255.) as u8;
+    Some(Rgba([
+        channel(number_of(color, "r")),
+        channel(number_of(color, "g")),
+        channel(number_of(color, "b")),
+        channel(color.get("a").and_then(Value::as_f64).unwrap_or(1.) as f32 * opacity),
+    ]))
+}
+
+// Radius of each corner from top left, or the same radius of all corners.
+fn radius(node: &Value) -> BorderRadius {
+    let corners = node
+        .get("rectangleCornerRadii")
+        .and_then(Value::as_array)
+        .map(|corners| {
+            corners
+                .iter()
+                .map(|corner| corner.as_f64().unwrap_or(0.).round() as u32)
+                .collect::<Vec<_>>()
+        });
+    match corners.as_deref() {
+        Some([top_left, top_right, bottom_right, bottom_left]) => {
+            BorderRadius(*top_left, *top_right, *bottom_right, *bottom_left)
+        }
+        _ => {
+            let radius = number_of(node, "cornerRadius").round() as u32;
+            BorderRadius(radius, radius, radius, radius)
+        }
+    }
+}
+
+fn str_of<'a>(node: &'a Value, key: &str) -> Option<&'a str> {
+    node.get(key).and_then(Value::as_str)
+}
+
+fn number_of(node: &Value, key: &str) -> f32 {
+    node.get(key).and_then(Value::as_f64).unwrap_or(0.) as f32
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidFigma(message.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::limits::Resource;
+
+    const FONT: &[u8] = include_bytes!("../../fonts/Roboto-Light.ttf");
+
+    const FILE: &str = r#"{
+        "document": {
+            "type": "DOCUMENT",
+            "children": [{
+                "type": "CANVAS",
+                "children": [
+                    {
+                        "type": "FRAME",
+                        "name": "Draft",
+                        "absoluteBoundingBox": { "x": 0, "y": 0, "width": 10, "height": 10 }
+                    },
+                    {
+                        "type": "FRAME",
+                        "name": "Card",
+                        "absoluteBoundingBox": { "x": 100, "y": 100, "width": 400, "height": 200 },
+                        "fills": [{ "type": "SOLID", "color": { "r": 0.2, "g": 0.1, "b": 0.4, "a": 1 } }],
+                        "layoutMode": "VERTICAL",
+                        "primaryAxisAlignItems": "SPACE_BETWEEN",
+                        "paddingLeft": 24,
+                        "paddingTop": 20,
+                        "paddingBottom": 20,
+                        "itemSpacing": 8,
+                        "children": [
+                            {
+                                "type": "TEXT",
+                                "characters": "Figma card",
+                                "absoluteBoundingBox": { "x": 124, "y": 120, "width": 300, "height": 40 },
+                                "style": { "fontSize": 32, "lineHeightPx": 40, "textAlignHorizontal": "LEFT" },
+                                "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 1, "b": 1, "a": 1 } }]
+                            },
+                            {
+                                "type": "VECTOR",
+                                "absoluteBoundingBox": { "x": 124, "y": 170, "width": 10, "height": 10 }
+                            },
+                            {
+                                "type": "FRAME",
+                                "absoluteBoundingBox": { "x": 124, "y": 240, "width": 120, "height": 40 },
+                                "cornerRadius": 8,
+                                "fills": [{ "type": "SOLID", "color": { "r": 1, "g": 0, "b": 0, "a": 1 }, "opacity": 0.5 }],
+                                "children": [{
+                                    "type": "RECTANGLE",
+                                    "absoluteBoundingBox": { "x": 134, "y": 250, "width": 20, "height": 20 },
+                                    "fills": [{ "type": "SOLID", "color": { "r": 0, "g": 1, "b": 0, "a": 1 } }]
+                                }]
+                            }
+                        ]
+                    }
+                ]
+            }]
+        }
+    }"#;
+
+    #[test]
+    fn test_from_figma() {
+        let mut writer = OGImageWriter::from_figma(FILE, Some("Card"), FONT.to_vec()).unwrap();
+
+        let rects: Vec<(u32, u32, u32, u32)> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| {
+                let rect = elm.border_box();
+                (rect.x, rect.y, rect.width, rect.height)
+            })
+            .collect();
+        assert_eq!(rects.len(), 2);
+        // Footer frame is pushed to the bottom padding by space between.
+        assert_eq!(rects[1], (24, 140, 120, 40));
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        assert_eq!(image.get_pixel(2, 2).0, [51, 26, 102, 255]);
+        // Rectangle is resized as image, so the color may differ slightly.
+        let [r, g, b, _] = image.get_pixel(44, 160).0;
+        assert!(r < 4 && g > 250 && b < 4);
+    }
+
+    #[test]
+    fn test_from_figma_with_limits() {
+        let file = FILE.replace(
+            r#""width": 20, "height": 20"#,
+            r#""width": 100000, "height": 100000"#,
+        );
+        let limits = Limits {
+            max_window_pixels: Some(400 * 200),
+            ..Limits::default()
+        };
+        // Rectangle larger than window is rejected before it is allocated.
+        let err = OGImageWriter::from_figma_with_limits(&file, Some("Card"), FONT.to_vec(), limits);
+        assert!(matches!(
+            err,
+            Err(Error::LimitExceeded {
+                resource: Resource::WindowPixels,
+                value: 10_000_000_000,
+                ..
+            })
+        ));
+
+        let limits = Limits {
+            max_image_pixels: Some(100),
+            ..Limits::default()
+        };
+        let err = OGImageWriter::from_figma_with_limits(FILE, Some("Card"), FONT.to_vec(), limits);
+        assert!(matches!(
+            err,
+            Err(Error::LimitExceeded {
+                resource: Resource::ImagePixels,
+                value: 400,
+                ..
+            })
+        ));
+        assert!(OGImageWriter::from_figma_with_limits(
+            FILE,
+            Some("Card"),
+            FONT.to_vec(),
+            Limits::recommended()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_find_frame() {
+        let json: Value = serde_json::from_str(FILE).unwrap();
+        let frame = find_frame(&json, None).unwrap();
+        assert_eq!(str_of(frame, "name"), Some("Draft"));
+        assert!(find_frame(&json, Some("Missing")).is_none());
+    }
+}
@@ -0,0 +1,262 @@
+use super::element::{Fragment, Rect};
+use super::img::{crop_rect, round};
+use super::style::{BorderRadius, ImageCrop, ImageFit, KnockoutBar, Paint, Rgba};
+use crate::Error;
+use image::{imageops, RgbaImage};
+
+// Fill pixels of `mask` with `paint` that is placed over `rect`, keeping alpha of the mask.
+// Return the image cropped to the pixels that are drawn, and its position.
+pub(super) fn fill_mask(
+    mask: &RgbaImage,
+    paint: &Paint,
+    rect: &Rect,
+) -> Result<Option<(RgbaImage, u32, u32)>, Error> {
+    let (x, y, width, height) = match alpha_bounds(mask) {
+        Some(bounds) => bounds,
+        None => return Ok(None),
+    };
+    let (w, h) = (rect.width.max(1), rect.height.max(1));
+
+    // Color of paint at position relative to `rect`.
+    let color_at: Box<dyn Fn(u32, u32) -> [u8; 4]> = match paint {
+        Paint::Image(data, fit) => {
+            let image = image::load_from_memory(data)?.into_rgba8();
+            if image.width() == 0 || image.height() == 0 {
+                return Ok(None);
+            }
+            let image = match fit {
+                ImageFit::Cover => {
+                    let focus = ImageCrop::Focus {
+                        x: 0.5,
+                        y: 0.5,
+                        zoom: 1.,
+                    };
+                    let ((cx, cy, cw, ch), _) = crop_rect(image.dimensions(), w, h, focus);
+                    imageops::crop_imm(&image, cx, cy, cw, ch).to_image()
+                }
+                ImageFit::Fill => image,
+            };
+            let image = imageops::resize(&image, w, h, imageops::FilterType::Triangle);
+            Box::new(move |x, y| image.get_pixel(x, y).0)
+        }
+        &Paint::LinearGradient { from, to, angle } => Box::new(move |x, y| {
+            gradient_color(from, to, angle, x as f32 + 0.5, y as f32 + 0.5, w, h)
+        }),
+    };
+
+    let mut filled = RgbaImage::new(width, height);
+    for (px, py, pixel) in filled.enumerate_pixels_mut() {
+        let alpha = mask.get_pixel(x + px, y + py)[3];
+        if alpha == 0 {
+            continue;
+        }
+        // Glyphs can overhang the box, so the edge of paint is extended.
+        let rx = (x + px).saturating_sub(rect.x).min(w - 1);
+        let ry = (y + py).saturating_sub(rect.y).min(h - 1);
+        let [r, g, b, a] = color_at(rx, ry);
+        *pixel = image::Rgba([r, g, b, (a as u32 * alpha as u32 / 255) as u8]);
+    }
+
+    Ok(Some((filled, x, y)))
+}
+
+// Fill bar around `rect` with color of `bar`, and cut pixels of `mask` out of it.
+// Return the bar image and its position.
+pub(super) fn knockout_mask(
+    mask: &RgbaImage,
+    bar: &KnockoutBar,
+    rect: &Rect,
+) -> Option<(RgbaImage, u32, u32)> {
+    let x = rect.x.saturating_sub(bar.padding);
+    let y = rect.y.saturating_sub(bar.padding);
+    let right = (rect.x + rect.width + bar.padding).min(mask.width());
+    let bottom = (rect.y + rect.height + bar.padding).min(mask.height());
+    if right <= x || bottom <= y {
+        return None;
+    }
+
+    let (width, height) = (right - x, bottom - y);
+    let mut image = RgbaImage::from_pixel(width, height, bar.color.as_image_rgba());
+    // Radius is limited so that corners don't overlap, like a pill of CSS.
+    let limit = width.min(height) / 2;
+    let BorderRadius(top_left, top_right, bottom_right, bottom_left) = bar.border_radius;
+    round(
+        &mut image,
+        &mut BorderRadius(
+            top_left.min(limit),
+            top_right.min(limit),
+            bottom_right.min(limit),
+            bottom_left.min(limit),
+        ),
+    );
+    for (px, py, pixel) in image.enumerate_pixels_mut() {
+        let alpha = mask.get_pixel(x + px, y + py)[3];
+        pixel[3] = (pixel[3] as u32 * (255 - alpha as u32) / 255) as u8;
+    }
+    Some((image, x, y))
+}
+
+// Box of all lines.
+pub(super) fn lines_rect(fragments: &[Fragment]) -> Rect {
+    let left = fragments.iter().map(|f| f.rect.x).min().unwrap_or(0);
+    let top = fragments.iter().map(|f| f.rect.y).min().unwrap_or(0);
+    let right = fragments
+        .iter()
+        .map(|f| f.rect.x + f.rect.width)
+        .max()
+        .unwrap_or(0);
+    let bottom = fragments
+        .iter()
+        .map(|f| f.rect.y + f.rect.height)
+        .max()
+        .unwrap_or(0);
+    Rect::new(left, top, right - left, bottom - top)
+}
+
+// Color of CSS linear gradient at `(x, y)` in box of `width` and `height`.
+// Gradient line passes the center, and its length is decided so that the corners get `from` and `to`.
+fn gradient_color(
+    from: Rgba,
+    to: Rgba,
+    angle: f32,
+    x: f32,
+    y: f32,
+    width: u32,
+    height: u32,
+) -> [u8; 4] {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (width, height) = (width as f32, height as f32);
+    let length = (width * sin).abs() + (height * cos).abs();
+    let position = ((x - width / 2.) * sin - (y - height / 2.) * cos) / length.max(1.) + 0.5;
+    let t = position.clamp(0., 1.);
+
+    let mut color = [0; 4];
+    for (i, channel) in color.iter_mut().enumerate() {
+        *channel = (from.0[i] as f32 + (to.0[i] as f32 - from.0[i] as f32) * t).round() as u8;
+    }
+    color
+}
+
+// Smallest rectangle that has all pixels with alpha.
+fn alpha_bounds(image: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+    }
+    (left < right).then(|| (left, top, right - left, bottom - top))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gradient_color() {
+        let (black, white) = (Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255]));
+        assert_eq!(
+            gradient_color(black, white, 90., 0., 5., 100, 10),
+            [0, 0, 0, 255]
+        );
+        assert_eq!(
+            gradient_color(black, white, 90., 100., 5., 100, 10),
+            [255, 255, 255, 255]
+        );
+        assert_eq!(
+            gradient_color(black, white, 180., 50., 5., 100, 10),
+            [128, 128, 128, 255]
+        );
+    }
+
+    #[test]
+    fn test_fill_text() {
+        use crate::style::{Style, WindowStyle};
+        use crate::writer::OGImageWriter;
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            font_size: 60.,
+            fill: Some(Paint::LinearGradient {
+                from: Rgba([255, 0, 0, 255]),
+                to: Rgba([0, 0, 255, 255]),
+                angle: 90.,
+            }),
+            ..Style::default()
+        };
+        writer.set_text("HHHHHH", style, Some(font)).unwrap();
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+
+        let inked: Vec<(u32, [u8; 4])> = image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[3] == 255 && pixel.0 != [255, 255, 255, 255])
+            .map(|(x, _, pixel)| (x, pixel.0))
+            .collect();
+        let (left, left_color) = inked.iter().min_by_key(|(x, _)| *x).unwrap();
+        let (right, right_color) = inked.iter().max_by_key(|(x, _)| *x).unwrap();
+        assert!(left < right);
+        assert!(left_color[0] > left_color[2]);
+        assert!(right_color[2] > right_color[0]);
+    }
+
+    #[test]
+    fn test_knockout() {
+        use crate::style::{Margin, Style, WindowStyle};
+        use crate::writer::OGImageWriter;
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            background_color: Some(Rgba([0, 255, 0, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            font_size: 60.,
+            margin: Margin(20, 0, 0, 20),
+            knockout: Some(KnockoutBar {
+                color: Rgba([0, 0, 0, 255]),
+                padding: 10,
+                border_radius: BorderRadius(200, 200, 200, 200),
+            }),
+            ..Style::default()
+        };
+        writer.set_text("HHH", style, Some(font)).unwrap();
+        let rect = writer.elements().unwrap()[0].border_box();
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+
+        let green = image::Rgba([0, 255, 0, 255]);
+        let black = image::Rgba([0, 0, 0, 255]);
+        // Corners of bar are rounded, and text shows background through the bar.
+        assert_eq!(image.get_pixel(rect.x - 10, rect.y - 10), &green);
+        assert_eq!(image.get_pixel(rect.x + rect.width / 2, rect.y - 9), &black);
+        let row = rect.y + rect.height / 2;
+        let pixels: Vec<_> = (rect.x..rect.x + rect.width)
+            .map(|x| image.get_pixel(x, row))
+            .collect();
+        assert!(pixels.contains(&&green));
+        assert!(pixels.contains(&&black));
+        assert!(!pixels.contains(&&image::Rgba([255, 255, 255, 255])));
+    }
+
+    #[test]
+    fn test_alpha_bounds() {
+        let mut image = RgbaImage::new(10, 10);
+        assert_eq!(alpha_bounds(&image), None);
+        image.put_pixel(2, 3, image::Rgba([0, 0, 0, 1]));
+        image.put_pixel(5, 4, image::Rgba([0, 0, 0, 1]));
+        assert_eq!(alpha_bounds(&image), Some((2, 3, 4, 2)));
+    }
+}
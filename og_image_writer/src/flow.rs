@@ -0,0 +1,105 @@
+use super::element::Element;
+use super::font::create_font;
+use super::layout::TextArea;
+use super::stats::Stopwatch;
+use super::style::{Style, TextOverflow};
+use super::writer::OGImageWriter;
+use crate::Error;
+use std::cell::RefCell;
+
+impl OGImageWriter {
+    /// Set [TextArea](super::TextArea) that flows through text frames in reading order, like columns of a magazine.
+    /// Each style of `frames` makes a text element, and text that is cut by `max_height` of a frame continues in the next frame.
+    /// Frames except the last one clip text regardless of `text_overflow`, and frames after the end of text are not set.
+    /// Return the rest of text that doesn't fit in the last frame.
+    pub fn set_textarea_flow(
+        &mut self,
+        mut textarea: TextArea,
+        frames: Vec<Style>,
+        font: Option<Vec<u8>>,
+    ) -> Result<Option<TextArea>, Error> {
+        let font = match font {
+            Some(data) => {
+                Some(create_font(data).map_err(|err| err.in_element(self.tree.0.len(), None))?)
+            }
+            None => None,
+        };
+
+        let len = frames.len();
+        for (i, style) in frames.into_iter().enumerate() {
+            let index = self.tree.0.len();
+            let style = if i + 1 < len {
+                Style {
+                    text_overflow: TextOverflow::Clip,
+                    ..style
+                }
+            } else {
+                style
+            };
+            self.check_element(textarea.char_count())
+                .map_err(|err| err.in_element(index, None))?;
+
+            // Text is transformed in advance, so that ranges of laid out lines point into the rest of text.
+            textarea.transform_text(&style);
+            let rest = textarea.clone();
+
+            let stopwatch = Stopwatch::start();
+            self.process_text(RefCell::new(textarea), style, font.clone())
+                .map_err(|err| err.in_element(index, None))?;
+            self.stats.layout_time += stopwatch.elapsed();
+
+            let end = match self.tree.0.last() {
+                Some(Element::Text(Some(text))) => text.fragments.last().map(|line| line.range.end),
+                _ => None,
+            };
+            textarea = rest;
+            textarea.remove(0..end.unwrap_or(0))?;
+            if textarea.char_count() == 0 {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(textarea))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::WindowStyle;
+
+    #[test]
+    fn test_set_textarea_flow() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 400,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text("one two three four five six seven eight nine ten");
+        let frame = Style {
+            font_size: 20.,
+            max_width: Some(100),
+            max_height: Some(15),
+            ..Style::default()
+        };
+        let rest = writer
+            .set_textarea_flow(textarea, vec![frame.clone(), frame], Some(font))
+            .unwrap();
+
+        let elements = writer.elements().unwrap();
+        assert_eq!(elements.len(), 2);
+        let texts: Vec<String> = elements
+            .iter()
+            .map(|elm| match elm {
+                Element::Text(Some(text)) => text.text().to_string(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(texts, ["one two three ", "four five six "]);
+        assert_eq!(rest.unwrap().as_string(), "seven eight nine ten");
+    }
+}
@@ -1,40 +1,77 @@
 use super::font_trait::Font;
 use super::Error;
 use ab_glyph::{
-    Font as AbFont, FontArc as AbFontArc, Glyph, GlyphId, OutlinedGlyph, ScaleFont as AbScaleFont,
+    Font as AbFont, FontArc as AbFontArc, FontRef, Glyph, GlyphId, Outline, OutlinedGlyph,
+    PxScaleFactor, ScaleFont as AbScaleFont,
 };
+use std::fmt;
+use std::sync::Arc;
 
 pub(super) struct FontMetrics {
     pub height: f32,
     pub width: f32,
 }
 
-#[derive(Debug)]
-pub(super) struct FontArc(AbFontArc);
+// Font and the data that is borrowed by it.
+// Fields are dropped in declaration order, so the font is dropped before the data.
+#[derive(Debug, Clone)]
+pub(super) struct FontArc {
+    font: AbFontArc,
+    _owner: Option<FontOwner>,
+}
+
+#[derive(Clone)]
+struct FontOwner {
+    _data: Arc<dyn AsRef<[u8]> + Send + Sync>,
+}
+
+impl fmt::Debug for FontOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FontOwner").finish()
+    }
+}
+
+impl FontArc {
+    pub(super) fn data(&self) -> &[u8] {
+        self.font.font_data()
+    }
+}
 
 impl Font for FontArc {
     fn glyph_id(&self, ch: char) -> GlyphId {
-        self.0.glyph_id(ch)
+        self.font.glyph_id(ch)
     }
 
     fn ascent(&self, scale: f32) -> f32 {
-        self.0.as_scaled(scale).ascent()
+        self.font.as_scaled(scale).ascent()
     }
 
     fn descent(&self, scale: f32) -> f32 {
-        self.0.as_scaled(scale).descent()
+        self.font.as_scaled(scale).descent()
+    }
+
+    fn line_gap(&self, scale: f32) -> f32 {
+        self.font.as_scaled(scale).line_gap()
     }
 
     fn h_advance(&self, glyph_id: GlyphId, scale: f32) -> f32 {
-        self.0.as_scaled(scale).h_advance(glyph_id)
+        self.font.as_scaled(scale).h_advance(glyph_id)
     }
 
     fn kern(&self, first: GlyphId, second: GlyphId, scale: f32) -> f32 {
-        self.0.as_scaled(scale).kern(first, second)
+        self.font.as_scaled(scale).kern(first, second)
     }
 
     fn outline_glyph(&self, glyph: Glyph, scale: f32) -> Option<OutlinedGlyph> {
-        self.0.as_scaled(scale).outline_glyph(glyph)
+        self.font.as_scaled(scale).outline_glyph(glyph)
+    }
+
+    fn outline(&self, glyph_id: GlyphId) -> Option<Outline> {
+        self.font.outline(glyph_id)
+    }
+
+    fn scale_factor(&self, scale: f32) -> PxScaleFactor {
+        self.font.as_scaled(scale).scale_factor()
     }
 }
 
@@ -42,7 +79,31 @@ pub(super) const WHITESPACE_EM: f32 = 0.2;
 
 pub(super) fn create_font(data: Vec<u8>) -> Result<FontArc, Error> {
     match AbFontArc::try_from_vec(data) {
-        Ok(font) => Ok(FontArc(font)),
+        Ok(font) => Ok(FontArc { font, _owner: None }),
+        Err(_) => Err(Error::InvalidFontBytes),
+    }
+}
+
+pub(super) fn create_font_from_static(data: &'static [u8]) -> Result<FontArc, Error> {
+    match AbFontArc::try_from_slice(data) {
+        Ok(font) => Ok(FontArc { font, _owner: None }),
+        Err(_) => Err(Error::InvalidFontBytes),
+    }
+}
+
+pub(super) fn create_font_from_owner<T>(owner: T) -> Result<FontArc, Error>
+where
+    T: AsRef<[u8]> + Send + Sync + 'static,
+{
+    let owner: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(owner);
+    let data = (*owner).as_ref();
+    // SAFETY: The owner is kept with the font and is never mutated, so the data outlives the font.
+    let data: &'static [u8] = unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+    match FontRef::try_from_slice(data) {
+        Ok(font) => Ok(FontArc {
+            font: AbFontArc::new(font),
+            _owner: Some(FontOwner { _data: owner }),
+        }),
         Err(_) => Err(Error::InvalidFontBytes),
     }
 }
@@ -58,6 +119,7 @@ pub(super) fn whitespace_width(size: f32) -> f32 {
 #[cfg(test)]
 pub(crate) mod test_utils {
     use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use super::*;
     use ab_glyph::{Outline, Point, PxScaleFactor, Rect};
@@ -67,6 +129,8 @@ pub(crate) mod test_utils {
     #[derive(Clone, Debug)]
     pub(crate) struct FontMock {
         glyph_table: Option<GlyphTable>,
+        // Number of advances that are measured, shared with clones.
+        advances: Arc<AtomicUsize>,
     }
 
     impl FontMock {
@@ -79,11 +143,19 @@ pub(crate) mod test_utils {
                     });
                     FontMock {
                         glyph_table: Some(glyph_table),
+                        advances: Arc::default(),
                     }
                 }
-                None => FontMock { glyph_table: None },
+                None => FontMock {
+                    glyph_table: None,
+                    advances: Arc::default(),
+                },
             }
         }
+
+        pub(crate) fn advances(&self) -> usize {
+            self.advances.load(Ordering::Relaxed)
+        }
     }
 
     impl Font for FontMock {
@@ -107,7 +179,12 @@ pub(crate) mod test_utils {
             -(scale / 2.)
         }
 
+        fn line_gap(&self, _scale: f32) -> f32 {
+            0.
+        }
+
         fn h_advance(&self, _glyph_id: GlyphId, scale: f32) -> f32 {
+            self.advances.fetch_add(1, Ordering::Relaxed);
             scale
         }
 
@@ -133,5 +210,16 @@ pub(crate) mod test_utils {
             );
             Some(og)
         }
+
+        fn outline(&self, _glyph_id: GlyphId) -> Option<Outline> {
+            None
+        }
+
+        fn scale_factor(&self, scale: f32) -> PxScaleFactor {
+            PxScaleFactor {
+                horizontal: scale,
+                vertical: scale,
+            }
+        }
     }
 }
@@ -1,8 +1,14 @@
 use crate::{
-    font::{create_font, match_font_family},
+    font::{
+        create_font, create_font_from_owner, create_font_from_static, match_font_family, FontArc,
+    },
+    font_dir::{self, FontFace, LazyFace},
     font_trait::Font,
     Error,
 };
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Debug, Clone, PartialEq)]
 pub(super) struct FontIndex(pub(super) usize);
@@ -12,13 +18,77 @@ pub(super) enum FontIndexStore {
     Global(FontIndex),
     Parent(FontIndex),
     Child(FontIndex),
+    // Index of emoji image in SplitText.
+    Emoji(FontIndex),
+    // Index of image of word rendered by ScriptRenderer in SplitText.
+    Script(FontIndex),
 }
 
-pub(super) struct FontStore(Vec<Box<dyn Font>>);
+// Bundled font is indexed after any pushed font, so its index doesn't change when fonts are pushed.
+const DEFAULT_FONT_INDEX: usize = usize::MAX;
+
+#[cfg_attr(not(feature = "default-font"), derive(Default))]
+pub(super) struct FontStore {
+    fonts: Vec<Box<dyn Font>>,
+    // Index of the first font that has the char, or `None` when no font has it.
+    fallback: RefCell<HashMap<char, Option<usize>>>,
+    // Fonts that are loaded by family, not used as fallback.
+    faces: Vec<LazyFace>,
+    #[cfg(feature = "default-font")]
+    default_font: FontArc,
+}
+
+#[cfg(feature = "default-font")]
+impl Default for FontStore {
+    fn default() -> Self {
+        FontStore {
+            fonts: vec![],
+            fallback: RefCell::default(),
+            faces: vec![],
+            default_font: crate::default_font::font(),
+        }
+    }
+}
 
 impl FontStore {
     pub(super) fn borrow_font(&self, idx: &FontIndex) -> &dyn Font {
-        &*self.0[idx.0]
+        #[cfg(feature = "default-font")]
+        if idx.0 == DEFAULT_FONT_INDEX {
+            return &self.default_font;
+        }
+        &*self.fonts[idx.0]
+    }
+
+    fn push(&mut self, font: Box<dyn Font>) {
+        self.fonts.push(font);
+        // Chars that were not found, or found only in the bundled font, may be found in the new font.
+        self.fallback
+            .get_mut()
+            .retain(|_, idx| matches!(idx, Some(idx) if *idx != DEFAULT_FONT_INDEX));
+    }
+
+    fn select(&self, ch: char) -> Option<usize> {
+        *self.fallback.borrow_mut().entry(ch).or_insert_with(|| {
+            self.fonts
+                .iter()
+                .position(|font| match_font_family(ch, &**font))
+                .or_else(|| self.select_default(ch))
+        })
+    }
+
+    // Runs are not selected with the bundled font, so that pushed fonts are tried for each char first.
+    #[cfg(feature = "default-font")]
+    fn select_default(&self, ch: char) -> Option<usize> {
+        if match_font_family(ch, &self.default_font) {
+            Some(DEFAULT_FONT_INDEX)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(feature = "default-font"))]
+    fn select_default(&self, _ch: char) -> Option<usize> {
+        None
     }
 }
 
@@ -28,7 +98,7 @@ mod font_context_store {
     use std::rc::Rc;
 
     thread_local! {
-        static FONT_CONTEXT_STORE: Rc<RefCell<FontStore>> = Rc::new(RefCell::new(FontStore(vec![])));
+        static FONT_CONTEXT_STORE: Rc<RefCell<FontStore>> = Rc::new(RefCell::new(FontStore::default()));
     }
 
     pub(super) fn get_mut() -> Rc<RefCell<FontStore>> {
@@ -37,15 +107,14 @@ mod font_context_store {
 
     pub fn clear() {
         FONT_CONTEXT_STORE.with(|f| {
-            let mut store = f.borrow_mut();
-            store.0.clear();
+            *f.borrow_mut() = FontStore::default();
         });
     }
 
     pub fn len() -> usize {
         FONT_CONTEXT_STORE.with(|f| {
             let store = f.borrow();
-            store.0.len()
+            store.fonts.len()
         })
     }
 
@@ -60,6 +129,60 @@ mod font_context_store {
     }
 }
 
+/// Typographic metrics of font scaled to font size in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TypographicMetrics {
+    /// Distance from baseline to the top of line.
+    pub ascent: f32,
+    /// Distance from baseline to the bottom of line. This is negative value.
+    pub descent: f32,
+    pub line_gap: f32,
+    /// Height of `H` above baseline. This is `None` when font does not have `H`.
+    pub cap_height: Option<f32>,
+}
+
+impl TypographicMetrics {
+    fn new(font: &dyn Font, size: f32) -> Self {
+        TypographicMetrics {
+            ascent: font.ascent(size),
+            descent: font.descent(size),
+            line_gap: font.line_gap(size),
+            cap_height: font.cap_height(size),
+        }
+    }
+}
+
+/// Get typographic metrics of font data at font size, like font passed to [`OGImageWriter::set_text()`](crate::writer::OGImageWriter::set_text).
+pub fn font_metrics(data: Vec<u8>, size: f32) -> Result<TypographicMetrics, Error> {
+    let font = create_font(data)?;
+    Ok(TypographicMetrics::new(&font, size))
+}
+
+/// Font that is parsed once and shared by writers without copying data.
+/// Cloning this is cheap, so large fonts like CJK fonts can be loaded once and passed to every writer.
+#[derive(Debug, Clone)]
+pub struct SharedFont(pub(super) FontArc);
+
+impl SharedFont {
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, Error> {
+        Ok(SharedFont(create_font(data)?))
+    }
+
+    /// Borrow font data, like data embedded by `include_bytes!`.
+    pub fn from_static(data: &'static [u8]) -> Result<Self, Error> {
+        Ok(SharedFont(create_font_from_static(data)?))
+    }
+
+    /// Borrow font data from its owner, like `Arc<[u8]>` or memory-mapped file such as `memmap2::Mmap`.
+    /// The owner is kept until every writer using the font is dropped.
+    pub fn from_owner<T>(owner: T) -> Result<Self, Error>
+    where
+        T: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        Ok(SharedFont(create_font_from_owner(owner)?))
+    }
+}
+
 // This strut do not have nothing.
 // But this struct provide operation for font_context_store local thread.
 // If you want to use font_context_store, you must call method from FontContext.
@@ -80,15 +203,22 @@ impl FontContext {
         let store = font_context_store::get_mut();
         let mut store = store.borrow_mut();
         let font = create_font(data)?;
-        store.0.push(Box::new(font));
+        store.push(Box::new(font));
         Ok(())
     }
 
+    /// Push [SharedFont] without copying font data.
+    pub fn push_shared(&mut self, font: &SharedFont) {
+        let store = font_context_store::get_mut();
+        let mut store = store.borrow_mut();
+        store.push(Box::new(font.0.clone()));
+    }
+
     #[cfg(test)]
     pub(crate) fn push_font(&mut self, font: Box<dyn Font>) {
         let store = font_context_store::get_mut();
         let mut store = store.borrow_mut();
-        store.0.push(font);
+        store.push(font);
     }
 
     pub fn clear(&self) {
@@ -104,17 +234,75 @@ impl FontContext {
         font_context_store::len() == 0
     }
 
+    /// Index fonts in `dir` by family and weight, reading only their `name` and `OS/2` tables.
+    /// Font data is read when the font is used by [font](Self::font) for the first time.
+    /// Only `.ttf` and `.otf` files directly in `dir` are indexed, and font collections are skipped.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<FontFace>, Error> {
+        let faces = font_dir::read_dir(dir.as_ref())?;
+        let indexed = faces.iter().map(|lazy| lazy.face.clone()).collect();
+        let store = font_context_store::get_mut();
+        store.borrow_mut().faces.extend(faces);
+        Ok(indexed)
+    }
+
+    /// Get font of `family` loaded by [load_dir](Self::load_dir), whose weight is the closest to `weight`.
+    /// Upright font is preferred to italic font. Return `None` when the family is not loaded.
+    pub fn font(&self, family: &str, weight: u16) -> Result<Option<SharedFont>, Error> {
+        let store = font_context_store::get_mut();
+        let mut store = store.borrow_mut();
+        font_dir::select(&mut store.faces, family, weight)
+            .map(|lazy| lazy.load())
+            .transpose()
+    }
+
+    /// Get typographic metrics of font at `index` in pushed order.
+    pub fn font_metrics(&self, index: usize, size: f32) -> Result<TypographicMetrics, Error> {
+        if index >= self.len() {
+            return Err(Error::NotFoundSpecifiedFontFamily);
+        }
+        Ok(self.with(&FontIndex(index), |font| {
+            TypographicMetrics::new(font, size)
+        }))
+    }
+
     pub(super) fn select_font_family(&self, ch: char) -> Result<FontIndex, Error> {
         let store = font_context_store::get_mut();
-        let font_list = &store.borrow().0;
-        for (i, font) in font_list.iter().enumerate() {
-            let has_font = match_font_family(ch, &**font);
-            if has_font {
-                return Ok(FontIndex(i));
+        let store = store.borrow();
+        store
+            .select(ch)
+            .map(FontIndex)
+            .ok_or(Error::NotFoundSpecifiedFontFamily)
+    }
+
+    /// Select fallback fonts for `chars` and rasterize their glyphs at `sizes` before the first render,
+    /// so that font data is loaded and the first request of a server does not pay for it.
+    /// Call this after pushing fonts.
+    pub fn prewarm(&self, chars: &str, sizes: &[f32]) {
+        let store = font_context_store::get_mut();
+        let store = store.borrow();
+        for ch in chars.chars() {
+            let font = match store.select(ch) {
+                Some(idx) => store.borrow_font(&FontIndex(idx)),
+                None => continue,
+            };
+            for &size in sizes {
+                let glyph = font.glyph_id(ch).with_scale(size);
+                if let Some(outlined) = font.outline_glyph(glyph, size) {
+                    outlined.draw(|_, _, _| {});
+                }
             }
         }
+    }
 
-        Err(Error::NotFoundSpecifiedFontFamily)
+    // Select first font that has all chars in run.
+    pub(super) fn select_font_family_for_run(&self, chars: &[char]) -> Option<FontIndex> {
+        let store = font_context_store::get_mut();
+        let store = store.borrow();
+        store
+            .fonts
+            .iter()
+            .position(|font| chars.iter().all(|&ch| match_font_family(ch, &**font)))
+            .map(FontIndex)
     }
 
     pub(super) fn with<F, T>(&self, idx: &FontIndex, f: F) -> T
@@ -124,3 +312,170 @@ impl FontContext {
         font_context_store::with(idx, f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{font_metrics, FontContext, SharedFont};
+    use crate::font_trait::Font;
+    use crate::style::{FallbackMetrics, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_font_metrics() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let metrics = font_metrics(font, 100.).unwrap();
+
+        assert!(metrics.descent < 0.);
+        assert!((metrics.ascent - metrics.descent - 100.).abs() < 1.);
+        let cap_height = metrics.cap_height.unwrap();
+        assert!(0. < cap_height && cap_height < metrics.ascent);
+    }
+
+    #[test]
+    fn test_shared_font() {
+        static FONT: &[u8] = include_bytes!("../../fonts/Roboto-Light.ttf");
+        fn render(set_text: impl FnOnce(&mut OGImageWriter)) -> Vec<u8> {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 200,
+                height: 100,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            set_text(&mut writer);
+            writer.paint().unwrap();
+            writer.into_vec().unwrap()
+        }
+
+        let expected = render(|writer| {
+            writer
+                .set_text("Hello", Style::default(), Some(FONT.to_vec()))
+                .unwrap()
+        });
+        let fonts = [
+            SharedFont::from_static(FONT).unwrap(),
+            SharedFont::from_owner(Arc::<[u8]>::from(FONT)).unwrap(),
+        ];
+        for font in fonts {
+            let actual = render(|writer| {
+                writer
+                    .set_text_with_font("Hello", Style::default(), &font)
+                    .unwrap()
+            });
+            assert!(actual == expected);
+        }
+
+        assert!(SharedFont::from_owner(vec![0u8; 4]).is_err());
+    }
+
+    #[test]
+    fn test_font_stack() {
+        let font_context = FontContext::new();
+        font_context.clear();
+        let fonts = [
+            SharedFont::from_static(include_bytes!("../../fonts/Roboto-Light.ttf")).unwrap(),
+            SharedFont::from_static(include_bytes!("../../fonts/Mplus1-Black.ttf")).unwrap(),
+        ];
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 200,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        // Japanese text is drawn with the second font without fonts in FontContext.
+        writer
+            .set_text_with_fonts("Hello こんにちは", Style::default(), &fonts)
+            .unwrap();
+        assert!(writer
+            .set_text_with_fonts("Hello こんにちは", Style::default(), &fonts[..1])
+            .is_err());
+        writer.paint().unwrap();
+    }
+
+    #[test]
+    fn test_fallback_metrics() {
+        let font_context = FontContext::new();
+        font_context.clear();
+        let fonts = [
+            SharedFont::from_static(include_bytes!("../../fonts/Roboto-Light.ttf")).unwrap(),
+            SharedFont::from_static(include_bytes!("../../fonts/Mplus1-Black.ttf")).unwrap(),
+        ];
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 300,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let primary = Style {
+            fallback_metrics: FallbackMetrics::Primary,
+            ..Style::default()
+        };
+        writer
+            .set_text_with_fonts("Hello", primary.clone(), &fonts)
+            .unwrap();
+        writer
+            .set_text_with_fonts("Hello こんにちは", primary, &fonts)
+            .unwrap();
+        writer
+            .set_text_with_fonts("Hello こんにちは", Style::default(), &fonts)
+            .unwrap();
+        let heights: Vec<u32> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| elm.border_box().height)
+            .collect();
+        assert_eq!(heights[0], heights[1]);
+        assert!(heights[1] < heights[2]);
+        writer.paint().unwrap();
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let mut font_context = FontContext::new();
+        font_context.clear();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../fonts");
+        let faces = font_context.load_dir(dir).unwrap();
+        assert_eq!(faces.len(), 3);
+        // Fonts that are loaded by family are not used as fallback.
+        assert!(font_context.is_empty());
+
+        let font = font_context.font("roboto", 700).unwrap().unwrap();
+        let metrics = font_metrics(
+            std::fs::read(format!("{}/Roboto-Light.ttf", dir)).unwrap(),
+            10.,
+        )
+        .unwrap();
+        assert_eq!(font.0.ascent(10.), metrics.ascent);
+        assert!(font_context.font("Missing", 400).unwrap().is_none());
+
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        writer
+            .set_text_with_font_family("Hello", Style::default(), "Roboto")
+            .unwrap();
+        font_context.clear();
+    }
+
+    #[test]
+    fn test_prewarm() {
+        let mut font_context = FontContext::new();
+        font_context.clear();
+        font_context
+            .push(Vec::from(
+                include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+            ))
+            .unwrap();
+        font_context.prewarm("aあ", &[16., 32.]);
+        assert_eq!(font_context.select_font_family('a').unwrap().0, 0);
+        assert!(font_context.select_font_family('あ').is_err());
+
+        // Chars that were not found are selected again after pushing font.
+        font_context
+            .push(Vec::from(
+                include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]
+            ))
+            .unwrap();
+        assert_eq!(font_context.select_font_family('あ').unwrap().0, 1);
+        font_context.clear();
+    }
+}
@@ -0,0 +1,187 @@
+// Index of font files in a directory, that is made by reading only `name` and `OS/2` tables.
+// Font data is read when the font is used for the first time.
+
+use crate::font_context::SharedFont;
+use crate::subset::{read_u16, read_u32};
+use crate::Error;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+const NAME_FAMILY: u16 = 1;
+const NAME_TYPOGRAPHIC_FAMILY: u16 = 16;
+
+/// Font file indexed by [`FontContext::load_dir()`](crate::font_context::FontContext::load_dir).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+    /// Weight from 100 to 900, like 400 for regular and 700 for bold.
+    pub weight: u16,
+    pub italic: bool,
+    pub path: PathBuf,
+}
+
+pub(crate) struct LazyFace {
+    pub(crate) face: FontFace,
+    font: Option<SharedFont>,
+}
+
+impl LazyFace {
+    pub(crate) fn load(&mut self) -> Result<SharedFont, Error> {
+        if let Some(font) = &self.font {
+            return Ok(font.clone());
+        }
+        let data = fs::read(&self.face.path).map_err(image::ImageError::IoError)?;
+        let font = SharedFont::from_vec(data)?;
+        self.font = Some(font.clone());
+        Ok(font)
+    }
+}
+
+// Index `.ttf` and `.otf` files in `dir` in order of file name. Subdirectories are not read.
+// Files that are not TrueType fonts like font collections are skipped.
+pub(crate) fn read_dir(dir: &Path) -> Result<Vec<LazyFace>, Error> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir).map_err(image::ImageError::IoError)? {
+        let path = entry.map_err(image::ImageError::IoError)?.path();
+        let is_font = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("ttf") || ext.eq_ignore_ascii_case("otf"));
+        if path.is_file() && is_font == Some(true) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| read_face(path).ok())
+        .map(|face| LazyFace { face, font: None })
+        .collect())
+}
+
+// Select the face of `family` whose weight is the closest to `weight`. Upright faces are preferred.
+pub(crate) fn select<'a>(
+    faces: &'a mut [LazyFace],
+    family: &str,
+    weight: u16,
+) -> Option<&'a mut LazyFace> {
+    faces
+        .iter_mut()
+        .filter(|lazy| lazy.face.family.eq_ignore_ascii_case(family))
+        .min_by_key(|lazy| {
+            let distance = (lazy.face.weight as i32 - weight as i32).abs();
+            (lazy.face.italic, distance)
+        })
+}
+
+fn read_face(path: PathBuf) -> Result<FontFace, Error> {
+    let mut file = File::open(&path).map_err(image::ImageError::IoError)?;
+    let header = read_at(&mut file, 0, 12)?;
+    match read_u32(&header, 0)? {
+        0x0001_0000 | 0x7472_7565 => {}
+        _ => return Err(Error::UnsupportedFontFormat),
+    }
+    let num_tables = read_u16(&header, 4)? as usize;
+    let records = read_at(&mut file, 12, num_tables * 16)?;
+
+    let (mut name, mut os2) = (None, None);
+    for record in records.chunks(16) {
+        let table = Some((read_u32(record, 8)?, read_u32(record, 12)?));
+        match &record[..4] {
+            b"name" => name = table,
+            b"OS/2" => os2 = table,
+            _ => {}
+        }
+    }
+
+    let (offset, length) = name.ok_or(Error::InvalidFontBytes)?;
+    let family = read_family(&read_at(&mut file, offset, length as usize)?)?;
+    // Weight and style of fonts without `OS/2` table are regular.
+    let (weight, italic) = match os2 {
+        Some((offset, _)) => {
+            let os2 = read_at(&mut file, offset, 64)?;
+            (read_u16(&os2, 4)?, read_u16(&os2, 62)? & 1 != 0)
+        }
+        None => (400, false),
+    };
+
+    Ok(FontFace {
+        family,
+        weight,
+        italic,
+        path,
+    })
+}
+
+fn read_at(file: &mut File, offset: u32, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0; len];
+    file.seek(SeekFrom::Start(offset as u64))
+        .and_then(|_| file.read_exact(&mut buf))
+        .map_err(image::ImageError::IoError)?;
+    Ok(buf)
+}
+
+// Read family name from `name` table. Typographic family is preferred, because family of
+// fonts that have many weights is split like `Roboto Light`. English names are preferred.
+// See https://learn.microsoft.com/en-us/typography/opentype/spec/name
+fn read_family(table: &[u8]) -> Result<String, Error> {
+    let count = read_u16(table, 2)? as usize;
+    let storage = read_u16(table, 4)? as usize;
+    let mut best: Option<((bool, bool), String)> = None;
+    for i in 0..count {
+        let record = 6 + i * 12;
+        let platform = read_u16(table, record)?;
+        let encoding = read_u16(table, record + 2)?;
+        let language = read_u16(table, record + 4)?;
+        let name_id = read_u16(table, record + 6)?;
+        if name_id != NAME_FAMILY && name_id != NAME_TYPOGRAPHIC_FAMILY {
+            continue;
+        }
+        let len = read_u16(table, record + 8)? as usize;
+        let offset = storage + read_u16(table, record + 10)? as usize;
+        let bytes = table
+            .get(offset..offset + len)
+            .ok_or(Error::InvalidFontBytes)?;
+        let name = match (platform, encoding) {
+            // Unicode or Windows Unicode names are UTF-16BE.
+            (0, _) | (3, 1) | (3, 10) => String::from_utf16_lossy(
+                &bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>(),
+            ),
+            // Mac Roman is read as Latin-1, which is the same for ASCII names.
+            (1, 0) => bytes.iter().map(|&byte| byte as char).collect(),
+            _ => continue,
+        };
+        let is_english = matches!((platform, language), (3, 0x0409) | (1, 0) | (0, _));
+        let rank = (name_id == NAME_TYPOGRAPHIC_FAMILY, is_english);
+        let is_better = match &best {
+            Some((best, _)) => rank > *best,
+            None => true,
+        };
+        if is_better {
+            best = Some((rank, name));
+        }
+    }
+    best.map(|(_, name)| name).ok_or(Error::InvalidFontBytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_face;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_read_face() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fonts");
+        let roboto = read_face(dir.join("Roboto-Light.ttf")).unwrap();
+        assert_eq!(roboto.family, "Roboto");
+        assert_eq!(roboto.weight, 300);
+        assert!(!roboto.italic);
+        let mplus = read_face(dir.join("Mplus1-Black.ttf")).unwrap();
+        assert_eq!(mplus.family, "M PLUS 1");
+        assert_eq!(mplus.weight, 900);
+    }
+}
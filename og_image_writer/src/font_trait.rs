@@ -4,15 +4,44 @@ use super::char::{is_newline_as_whitespace, CharFlags, RenderingCharIndices};
 use super::font::{whitespace_width, FontMetrics};
 use super::renderer::FontSetting;
 use crate::style::KernSetting;
-use ab_glyph::{point, Glyph, GlyphId, OutlinedGlyph, Rect};
+use ab_glyph::{point, Glyph, GlyphId, Outline, OutlinedGlyph, PxScaleFactor, Rect};
 
 pub(super) trait Font: Debug {
     fn glyph_id(&self, ch: char) -> GlyphId;
     fn ascent(&self, scale: f32) -> f32;
     fn descent(&self, scale: f32) -> f32;
+    fn line_gap(&self, scale: f32) -> f32;
     fn h_advance(&self, glyph_id: GlyphId, scale: f32) -> f32;
     fn kern(&self, first: GlyphId, second: GlyphId, scale: f32) -> f32;
     fn outline_glyph(&self, glyph: Glyph, scale: f32) -> Option<OutlinedGlyph>;
+    fn outline(&self, glyph_id: GlyphId) -> Option<Outline>;
+    fn scale_factor(&self, scale: f32) -> PxScaleFactor;
+
+    // Height of `H` above baseline, because cap height in OS/2 table is not exposed by ab_glyph.
+    fn cap_height(&self, scale: f32) -> Option<f32> {
+        let outline = self.outline(self.glyph_id('H'))?;
+        // Unscaled bounds of ab_glyph have the top of glyph in `min.y`.
+        Some(outline.bounds.min.y * self.scale_factor(scale).vertical)
+    }
+
+    // Advance of the widest digit that is used for tabular figures.
+    fn tabular_width(&self, scale: f32) -> f32 {
+        ('0'..='9')
+            .map(|ch| self.h_advance(self.glyph_id(ch), scale))
+            .fold(0., f32::max)
+    }
+
+    // Offset to center digit in the tabular width.
+    fn tabular_offset(&self, ch: char, rect: &Rect, setting: &FontSetting) -> i32 {
+        if !setting.tabular_nums || !ch.is_ascii_digit() {
+            return 0;
+        }
+        let width = match setting.kern_setting {
+            KernSetting::Normal => self.h_advance(self.glyph_id(ch), setting.size),
+            _ => rect.width(),
+        };
+        ((self.tabular_width(setting.size) - width) / 2.) as i32
+    }
 
     fn text_extents(&self, text: &str, setting: &FontSetting) -> FontMetrics {
         let mut chars = RenderingCharIndices::from_str(text);
@@ -42,7 +71,7 @@ pub(super) trait Font: Debug {
         if cur_char.is_whitespace() {
             return FontMetrics {
                 height,
-                width: whitespace_width(setting.size),
+                width: self.space_width(setting),
             };
         }
 
@@ -57,6 +86,14 @@ pub(super) trait Font: Debug {
         }
     }
 
+    fn space_width(&self, setting: &FontSetting) -> f32 {
+        if setting.preserve_spaces {
+            self.h_advance(self.glyph_id(' '), setting.size)
+        } else {
+            whitespace_width(setting.size)
+        }
+    }
+
     fn get_glyph_rect(&self, ch: char, setting: &FontSetting) -> Option<Rect> {
         let glyph_id = self.glyph_id(ch);
         let q_glyph: Glyph = glyph_id.with_scale_and_position(setting.size, point(0., 0.));
@@ -77,7 +114,11 @@ pub(super) trait Font: Debug {
         let glyph_id = self.glyph_id(cur_char);
 
         if cur_char.is_whitespace() || is_newline_as_whitespace(setting.is_pre, flags) {
-            return whitespace_width(setting.size) as i32 + setting.letter_spacing;
+            return self.space_width(setting) as i32 + setting.letter_spacing;
+        }
+
+        if setting.tabular_nums && cur_char.is_ascii_digit() {
+            return self.tabular_width(setting.size) as i32 + setting.letter_spacing;
         }
 
         let width = match setting.kern_setting {
@@ -0,0 +1,288 @@
+//! Format numbers for texts like prices and stats, with separators and currency placement of language.
+//!
+//! ```rust
+//! use og_image_writer::format::{compact, currency, number};
+//! use og_image_writer::style::LanguageTag;
+//!
+//! let de = LanguageTag::new("de-DE");
+//! assert_eq!(number(1234567.891, 2, None), "1,234,567.89");
+//! assert_eq!(number(1234567.891, 2, Some(&de)), "1.234.567,89");
+//! assert_eq!(compact(12400., None), "12.4k");
+//! assert_eq!(currency(9.5, "$", 2, None), "$9.50");
+//! assert_eq!(currency(9.5, "€", 2, Some(&de)), "9,50\u{A0}€");
+//! ```
+//!
+//! With `tera` feature, the functions are also registered as filters of templates by
+//! [`OGImageWriter::set_text_template()`](crate::writer::OGImageWriter::set_text_template).
+
+use crate::style::LanguageTag;
+
+// No-break space is laid out as space, so fonts don't need its glyph.
+const SPACE: &str = "\u{A0}";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Grouping {
+    // Groups of three digits like `1,234,567`.
+    Thousands,
+    // The first group has three digits and the others have two, like `12,34,567` in India.
+    Indian,
+}
+
+// Separators and currency placement of language.
+struct Locale {
+    group: &'static str,
+    decimal: &'static str,
+    grouping: Grouping,
+    symbol_after: bool,
+    symbol_space: bool,
+}
+
+impl Locale {
+    fn new(lang: Option<&LanguageTag>) -> Self {
+        let tag = lang
+            .map(|lang| lang.as_str().to_ascii_lowercase().replace('_', "-"))
+            .unwrap_or_default();
+        let language = tag.split('-').next().unwrap_or("");
+        let region = tag.split('-').nth(1).unwrap_or("");
+        let locale = |group, decimal, symbol_after, symbol_space| Locale {
+            group,
+            decimal,
+            grouping: Grouping::Thousands,
+            symbol_after,
+            symbol_space,
+        };
+        match (language, region) {
+            ("de", "ch") => locale("’", ".", false, true),
+            ("pt", "br") | ("nl", _) => locale(".", ",", false, true),
+            ("de" | "es" | "it" | "pt" | "da" | "el" | "id" | "ro" | "tr" | "vi", _) => {
+                locale(".", ",", true, true)
+            }
+            ("fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "hu", _) => {
+                locale(SPACE, ",", true, true)
+            }
+            ("hi" | "bn", _) | ("en", "in") => Locale {
+                grouping: Grouping::Indian,
+                ..locale(",", ".", false, false)
+            },
+            _ => locale(",", ".", false, false),
+        }
+    }
+}
+
+/// Format `value` with `decimals` digits after decimal point, and group digits with separator of `lang`.
+/// English separators like `1,234.5` are used when `lang` is `None`.
+pub fn number(value: f64, decimals: usize, lang: Option<&LanguageTag>) -> String {
+    format_number(value, decimals, &Locale::new(lang))
+}
+
+/// Format `value` in short form like `12.4k` or `3M`, and `1.2万` in Japanese and Chinese.
+/// Values less than 100 in the unit have a decimal digit.
+pub fn compact(value: f64, lang: Option<&LanguageTag>) -> String {
+    let units: &[(f64, &str)] = match lang.map(|lang| lang.language().to_ascii_lowercase()) {
+        Some(language) if language == "ja" => &[(1e12, "兆"), (1e8, "億"), (1e4, "万"), (1., "")],
+        Some(language) if language == "zh" => &[(1e12, "兆"), (1e8, "亿"), (1e4, "万"), (1., "")],
+        Some(language) if language == "ko" => &[(1e12, "조"), (1e8, "억"), (1e4, "만"), (1., "")],
+        _ => &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k"), (1., "")],
+    };
+    let locale = Locale::new(lang);
+    let abs = value.abs();
+    let mut i = units
+        .iter()
+        .position(|(unit, _)| abs >= *unit)
+        .unwrap_or(units.len() - 1);
+    loop {
+        let (unit, suffix) = units[i];
+        let scaled = abs / unit;
+        let factor = if scaled < 100. { 10. } else { 1. };
+        let rounded = (scaled * factor).round() / factor;
+        // `999.96k` is rounded to `1M` instead of `1000k`.
+        if i > 0 && rounded * unit >= units[i - 1].0 {
+            i -= 1;
+            continue;
+        }
+        let decimals = if rounded.fract() == 0. { 0 } else { 1 };
+        let sign = if value < 0. && rounded > 0. { "-" } else { "" };
+        return format!(
+            "{}{}{}",
+            sign,
+            format_number(rounded, decimals, &locale),
+            suffix
+        );
+    }
+}
+
+/// Format `value` as price with currency `symbol` like `$9.50` or `9,50 €`.
+/// Symbol is placed before or after the value and separated by no-break space as `lang` does.
+pub fn currency(value: f64, symbol: &str, decimals: usize, lang: Option<&LanguageTag>) -> String {
+    let locale = Locale::new(lang);
+    let amount = format_number(value.abs(), decimals, &locale);
+    let sign = if value < 0. && amount.chars().any(|ch| ch.is_ascii_digit() && ch != '0') {
+        "-"
+    } else {
+        ""
+    };
+    let space = if locale.symbol_space { SPACE } else { "" };
+    match locale.symbol_after {
+        true => format!("{}{}{}{}", sign, amount, space, symbol),
+        false => format!("{}{}{}{}", sign, symbol, space, amount),
+    }
+}
+
+fn format_number(value: f64, decimals: usize, locale: &Locale) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut groups = vec![];
+    let mut rest = integer;
+    let mut size = 3;
+    while rest.len() > size {
+        let (head, tail) = rest.split_at(rest.len() - size);
+        groups.push(tail);
+        rest = head;
+        if locale.grouping == Grouping::Indian {
+            size = 2;
+        }
+    }
+    groups.push(rest);
+    groups.reverse();
+
+    let is_zero = formatted.chars().all(|ch| matches!(ch, '0' | '.'));
+    let mut result = String::new();
+    if value < 0. && !is_zero {
+        result.push('-');
+    }
+    result.push_str(&groups.join(locale.group));
+    if let Some(fraction) = fraction {
+        result.push_str(locale.decimal);
+        result.push_str(fraction);
+    }
+    result
+}
+
+/// Register `number`, `compact` and `currency` filters to `tera`, like `{{ price | currency(symbol="€", lang="de") }}`.
+/// `number` takes `decimals` that is 0 by default, and `currency` takes `symbol` and `decimals` that is 2 by default.
+/// All filters take optional `lang`.
+#[cfg(feature = "tera")]
+pub fn register_filters(tera: &mut tera::Tera) {
+    use std::collections::HashMap;
+    use tera::Value;
+
+    type Args = HashMap<String, Value>;
+
+    fn input(name: &str, value: &Value, args: &Args) -> tera::Result<(f64, Option<LanguageTag>)> {
+        let value = value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|value| value.trim().parse().ok()))
+            .ok_or_else(|| {
+                tera::Error::msg(format!("Filter `{}` received non-number `{}`", name, value))
+            })?;
+        let lang = args
+            .get("lang")
+            .and_then(Value::as_str)
+            .map(LanguageTag::new);
+        Ok((value, lang))
+    }
+
+    fn decimals(args: &Args, default: u64) -> usize {
+        args.get("decimals")
+            .and_then(Value::as_u64)
+            .unwrap_or(default) as usize
+    }
+
+    tera.register_filter("number", |value: &Value, args: &Args| {
+        let (value, lang) = input("number", value, args)?;
+        Ok(Value::String(number(
+            value,
+            decimals(args, 0),
+            lang.as_ref(),
+        )))
+    });
+    tera.register_filter("compact", |value: &Value, args: &Args| {
+        let (value, lang) = input("compact", value, args)?;
+        Ok(Value::String(compact(value, lang.as_ref())))
+    });
+    tera.register_filter("currency", |value: &Value, args: &Args| {
+        let (value, lang) = input("currency", value, args)?;
+        let symbol = args
+            .get("symbol")
+            .and_then(Value::as_str)
+            .ok_or_else(|| tera::Error::msg("Filter `currency` expected `symbol`"))?;
+        Ok(Value::String(currency(
+            value,
+            symbol,
+            decimals(args, 2),
+            lang.as_ref(),
+        )))
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let lang = |tag| Some(LanguageTag::new(tag));
+        assert_eq!(number(0., 0, None), "0");
+        assert_eq!(number(-1234.5, 1, None), "-1,234.5");
+        assert_eq!(number(-0.001, 2, None), "0.00");
+        assert_eq!(
+            number(1234567., 0, lang("fr").as_ref()),
+            "1\u{A0}234\u{A0}567"
+        );
+        assert_eq!(number(1234.5, 2, lang("de-CH").as_ref()), "1’234.50");
+        assert_eq!(number(1234567., 0, lang("en-IN").as_ref()), "12,34,567");
+        assert_eq!(number(f64::NAN, 0, None), "NaN");
+    }
+
+    #[test]
+    fn test_compact() {
+        let ja = LanguageTag::new("ja");
+        assert_eq!(compact(950., None), "950");
+        assert_eq!(compact(4.56, None), "4.6");
+        assert_eq!(compact(12_400., None), "12.4k");
+        assert_eq!(compact(123_456., None), "123k");
+        assert_eq!(compact(999_960., None), "1M");
+        assert_eq!(compact(-2_000_000., None), "-2M");
+        assert_eq!(compact(12_000., Some(&ja)), "1.2万");
+        assert_eq!(compact(1.5e8, Some(&ja)), "1.5億");
+    }
+
+    #[test]
+    fn test_currency() {
+        let lang = |tag| Some(LanguageTag::new(tag));
+        assert_eq!(currency(1234.5, "$", 2, None), "$1,234.50");
+        assert_eq!(currency(-9.5, "$", 2, None), "-$9.50");
+        assert_eq!(currency(1200., "¥", 0, lang("ja").as_ref()), "¥1,200");
+        assert_eq!(currency(9.5, "€", 2, lang("fr-FR").as_ref()), "9,50\u{A0}€");
+        assert_eq!(currency(9.5, "€", 2, lang("nl").as_ref()), "€\u{A0}9,50");
+        assert_eq!(
+            currency(9.5, "R$", 2, lang("pt-BR").as_ref()),
+            "R$\u{A0}9,50"
+        );
+    }
+
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_register_filters() {
+        let mut tera = tera::Tera::default();
+        register_filters(&mut tera);
+        let mut context = tera::Context::new();
+        context.insert("stars", &12400);
+        context.insert("price", &9.5);
+        let text = tera
+            .render_str(
+                r#"{{ stars | compact }} stars, {{ price | currency(symbol="€", lang="de") }}"#,
+                &context,
+            )
+            .unwrap();
+        assert_eq!(text, "12.4k stars, 9,50\u{A0}€");
+        assert!(tera.render_str("{{ price | currency }}", &context).is_err());
+    }
+}
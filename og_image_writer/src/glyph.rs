@@ -1,7 +1,7 @@
 use super::font_context::FontIndexStore;
 use std::ops::Range;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Glyph {
     pub(super) range: Range<usize>,
     // If font_index is -1, font_index indicates font that element has.
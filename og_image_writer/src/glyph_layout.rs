@@ -0,0 +1,290 @@
+//! Positions of glyphs returned by [`OGImageWriter::glyph_layout()`](crate::writer::OGImageWriter::glyph_layout).
+//!
+//! The positions are collected from the same layout as the image, so texts can be animated on the web
+//! with tools like Lottie or CSS exactly as they appear in the image.
+
+use crate::char::{display_offsets, display_text, LINE_SEPARATOR};
+use crate::element::{Element, Fragment, Text};
+use crate::font_context::FontIndexStore;
+use crate::font_trait::Font;
+use crate::layout::GlyphRun;
+use crate::region::json_string;
+use crate::renderer::{for_each_advance, FontSetting};
+use crate::writer::{run_y, text_run_y, OGImageWriter};
+use crate::Error;
+use std::fs;
+use std::path::Path;
+
+/// Char or emoji cluster that is placed in the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphBox {
+    /// Index of text element in the order it was set to the writer.
+    pub element: usize,
+    /// Index of line in the text element.
+    pub line: usize,
+    /// Byte index in text of the element.
+    pub index: usize,
+    /// Drawn text, like `-` for soft hyphen at the end of line.
+    pub text: String,
+    /// Position of glyph origin on baseline.
+    pub x: f32,
+    pub y: f32,
+    pub advance: f32,
+    pub font_size: f32,
+}
+
+/// Glyphs of all text elements in the order of elements and lines, and size of the window.
+/// Line breaks are not included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphLayout {
+    pub width: u32,
+    pub height: u32,
+    pub glyphs: Vec<GlyphBox>,
+}
+
+impl GlyphLayout {
+    /// Serialize glyphs to JSON like `{"width":1200,"height":630,"glyphs":[{"element":0,"line":0,"index":0,"text":"H",...}]}`.
+    pub fn to_json(&self) -> String {
+        let glyphs: Vec<String> = self
+            .glyphs
+            .iter()
+            .map(|glyph| {
+                format!(
+                    r#"{{"element":{},"line":{},"index":{},"text":{},"x":{},"y":{},"advance":{},"font_size":{}}}"#,
+                    glyph.element,
+                    glyph.line,
+                    glyph.index,
+                    json_string(Some(&glyph.text)),
+                    glyph.x,
+                    glyph.y,
+                    glyph.advance,
+                    glyph.font_size,
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"width":{},"height":{},"glyphs":[{}]}}"#,
+            self.width,
+            self.height,
+            glyphs.join(",")
+        )
+    }
+}
+
+impl OGImageWriter {
+    /// Lay out elements and return positions and advances of glyphs, like [regions](Self::regions).
+    /// Texts in custom elements like containers and curved texts are not included.
+    pub fn glyph_layout(&mut self) -> Result<GlyphLayout, Error> {
+        self.process()?;
+        let len = self.tree.0.len();
+        let mut glyphs = vec![];
+        for (i, elm) in self.tree.0.iter().enumerate() {
+            if let Element::Text(Some(text)) = elm {
+                let element = self.element_index(i, len);
+                self.text_glyphs(element, text, &mut glyphs)?;
+            }
+        }
+        // Stable sort keeps the order of glyphs in each element.
+        glyphs.sort_by_key(|glyph| glyph.element);
+
+        Ok(GlyphLayout {
+            width: self.window.width,
+            height: self.window.height,
+            glyphs,
+        })
+    }
+
+    /// Write glyph layout as JSON sidecar file of the image. Call this before painting like [regions](Self::regions).
+    pub fn write_glyph_layout(&mut self, dest: &Path) -> Result<(), Error> {
+        fs::write(dest, self.glyph_layout()?.to_json()).map_err(image::ImageError::IoError)?;
+        Ok(())
+    }
+
+    // Collect glyphs of text in the same way as painting.
+    fn text_glyphs(
+        &self,
+        element: usize,
+        text_elm: &Text,
+        glyphs: &mut Vec<GlyphBox>,
+    ) -> Result<(), Error> {
+        for (line, fragment) in text_elm.fragments.iter().enumerate() {
+            let line_ascent = text_elm.line_ascent(fragment, &self.font_context);
+            let mut current_width = 0;
+            let advances = self.fragment_advances(text_elm, fragment)?;
+            let runs = text_elm.textarea.glyph_runs(fragment.range.clone())?;
+            for (run, advance) in runs.into_iter().zip(advances.iter()) {
+                let glyph = GlyphBox {
+                    element,
+                    line,
+                    index: run.range.start,
+                    text: String::new(),
+                    x: 0.,
+                    y: 0.,
+                    advance: 0.,
+                    font_size: run
+                        .split_text
+                        .style
+                        .as_ref()
+                        .unwrap_or(&text_elm.style)
+                        .font_size,
+                };
+                self.run_glyphs(
+                    text_elm,
+                    fragment,
+                    &run,
+                    line_ascent,
+                    current_width,
+                    glyph,
+                    glyphs,
+                )?;
+                current_width += advance;
+            }
+        }
+        Ok(())
+    }
+
+    // Collect glyphs of run. Fields of `glyph` except position and text are shared by the glyphs.
+    #[allow(clippy::too_many_arguments)]
+    fn run_glyphs(
+        &self,
+        text_elm: &Text,
+        fragment: &Fragment,
+        run: &GlyphRun,
+        line_ascent: f32,
+        current_width: u32,
+        glyph: GlyphBox,
+        glyphs: &mut Vec<GlyphBox>,
+    ) -> Result<(), Error> {
+        let style = run.split_text.style.as_ref().unwrap_or(&text_elm.style);
+        let text = &text_elm.text[run.range.clone()];
+
+        let mut push_text = |font: &dyn Font| {
+            let is_line_end = run.range.end == fragment.range.end;
+            let next_text = display_text(text, is_line_end);
+            let setting = FontSetting {
+                size: style.font_size,
+                letter_spacing: style.letter_spacing,
+                kern_setting: style.kern_setting,
+                is_pre: style.white_space.is_pre(),
+                preserve_spaces: style.white_space.preserves_spaces(),
+                tabular_nums: style.font_variant_numeric.is_tabular(),
+            };
+            let x = (fragment.rect.x + current_width) as i32;
+            let y = text_run_y(font, style, fragment, line_ascent) as f32
+                + font.ascent(style.font_size);
+
+            let mut offsets = display_offsets(text, is_line_end).into_iter();
+            for_each_advance(font, &setting, &next_text, |ch, offset, advance| {
+                let index = run.range.start + offsets.next().unwrap_or(0);
+                // Line breaks are laid out as spaces, but nothing is drawn.
+                if text_elm.text[index..].starts_with(['\n', '\r', LINE_SEPARATOR]) {
+                    return;
+                }
+                glyphs.push(GlyphBox {
+                    index,
+                    text: ch.to_string(),
+                    x: (x + offset) as f32,
+                    y,
+                    advance: advance as f32,
+                    ..glyph.clone()
+                });
+            });
+        };
+
+        let not_found = || Error::NotFoundSpecifiedFontFamily.at_char(run.range.start);
+        match &run.glyph.font_index_store {
+            FontIndexStore::Global(idx) => self.font_context.with(idx, push_text),
+            FontIndexStore::Parent(idx) => match text_elm
+                .textarea
+                .parent_font(idx, text_elm.font.as_ref().map(|font| font as &dyn Font))
+            {
+                Some(font) => push_text(font),
+                None => return Err(not_found()),
+            },
+            FontIndexStore::Child(idx) => match run.split_text.font(idx) {
+                Some(font) => push_text(font),
+                None => return Err(not_found()),
+            },
+            // Emoji image is a square of font size centered vertically, and its origin is the bottom left.
+            FontIndexStore::Emoji(_) => {
+                let size = style.font_size as u32;
+                let advance = (size as i32 + style.letter_spacing).max(0) as u32;
+                let top = fragment.rect.y + fragment.rect.height.saturating_sub(size) / 2;
+                glyphs.push(GlyphBox {
+                    text: text.to_string(),
+                    x: (fragment.rect.x + current_width) as f32,
+                    y: (top + size) as f32,
+                    advance: advance as f32,
+                    ..glyph
+                });
+            }
+            // Rendered word is a box of its image, and its origin is on baseline.
+            FontIndexStore::Script(idx) => {
+                let image = match run.split_text.script_images.get(idx.0) {
+                    Some(image) => image,
+                    None => return Err(Error::OutOfRangeText.at_char(run.range.start)),
+                };
+                let top = run_y(
+                    image.baseline as f32,
+                    image.image.height() as f32,
+                    style,
+                    fragment,
+                    line_ascent,
+                );
+                let advance = (image.image.width() as i32 + style.letter_spacing).max(0) as u32;
+                glyphs.push(GlyphBox {
+                    text: text.to_string(),
+                    x: (fragment.rect.x + current_width) as f32,
+                    y: (top + image.baseline) as f32,
+                    advance: advance as f32,
+                    ..glyph
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[test]
+    fn test_glyph_layout() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            max_width: Some(120),
+            ..Style::default()
+        };
+        writer.set_text("Hello World", style, Some(font)).unwrap();
+
+        let layout = writer.glyph_layout().unwrap();
+        let texts: String = layout
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.text.as_str())
+            .collect();
+        assert_eq!(texts, "Hello World");
+        let lines: Vec<usize> = layout.glyphs.iter().map(|glyph| glyph.line).collect();
+        assert_eq!(lines, [0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1]);
+        for pair in layout.glyphs[..5].windows(2) {
+            assert_eq!(pair[0].x + pair[0].advance, pair[1].x);
+            assert_eq!(pair[0].y, pair[1].y);
+        }
+        assert!(layout.glyphs[6].y > layout.glyphs[0].y);
+
+        let paths = writer.glyph_paths().unwrap();
+        assert_eq!(paths[0].x, layout.glyphs[0].x);
+        assert_eq!(paths[0].y, layout.glyphs[0].y);
+        assert!(layout.to_json().starts_with(
+            r#"{"width":300,"height":200,"glyphs":[{"element":0,"line":0,"index":0,"text":"H","#
+        ));
+    }
+}
@@ -0,0 +1,671 @@
+// Experimental GPU compositing enabled by `wgpu` feature.
+//
+// Draw calls of Context are recorded into GpuBatch while painting.
+// Glyphs are rasterized once into a coverage atlas, and images are uploaded as textures.
+// Then the batch is composited onto the canvas on GPU and read back when Context is flushed.
+// If GPU adapter is not available, Context falls back to CPU rendering.
+
+use super::font_trait::Font;
+use super::renderer::{for_each_glyph, FontSetting};
+use super::style::KernSetting;
+use crate::Error;
+use ab_glyph::GlyphId;
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::OnceLock;
+use wgpu::util::DeviceExt;
+
+const ATLAS_WIDTH: u32 = 1024;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+// position(2) + uv(2) + color(4)
+const VERTEX_LEN: usize = 8;
+const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+    wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+
+pub(super) struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    bind_group_layout: wgpu::BindGroupLayout,
+    image_pipeline: wgpu::RenderPipeline,
+    glyph_pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    max_texture_size: u32,
+}
+
+static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+
+impl Gpu {
+    // Device is shared with all Context.
+    pub(super) fn get() -> Option<&'static Gpu> {
+        GPU.get_or_init(|| pollster::block_on(Gpu::new())).as_ref()
+    }
+
+    async fn new() -> Option<Gpu> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                compatible_surface: None,
+            })
+            .await?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("og_image_writer"),
+                    features: wgpu::Features::empty(),
+                    limits: adapter.limits(),
+                },
+                None,
+            )
+            .await
+            .ok()?;
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("og_image_writer"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("og_image_writer"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(&wgpu::include_wgsl!("gpu.wgsl"));
+
+        let create_pipeline = |entry_point: &str| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: (VERTEX_LEN * 4) as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &ATTRIBUTES,
+                    }],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point,
+                    targets: &[wgpu::ColorTargetState {
+                        format: FORMAT,
+                        // Source over with straight alpha.
+                        blend: Some(wgpu::BlendState {
+                            color: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::SrcAlpha,
+                                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                            alpha: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::One,
+                                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }],
+                }),
+                multiview: None,
+            })
+        };
+
+        let image_pipeline = create_pipeline("fs_image");
+        let glyph_pipeline = create_pipeline("fs_glyph");
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let max_texture_size = device.limits().max_texture_dimension_2d;
+
+        Some(Gpu {
+            device,
+            queue,
+            bind_group_layout,
+            image_pipeline,
+            glyph_pipeline,
+            sampler,
+            max_texture_size,
+        })
+    }
+
+    fn create_texture(
+        &self,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        bytes_per_pixel: u32,
+        usage: wgpu::TextureUsages,
+        data: &[u8],
+    ) -> wgpu::Texture {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: usage | wgpu::TextureUsages::COPY_DST,
+        });
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(width * bytes_per_pixel),
+                rows_per_image: NonZeroU32::new(height),
+            },
+            size,
+        );
+        texture
+    }
+
+    fn create_bind_group(&self, texture: &wgpu::Texture) -> wgpu::BindGroup {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct AtlasRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+// Coverage of glyphs packed into rows.
+struct GlyphAtlas {
+    data: Vec<u8>,
+    height: u32,
+    cursor_x: u32,
+    cursor_y: u32,
+    row_height: u32,
+}
+
+impl GlyphAtlas {
+    fn new() -> Self {
+        GlyphAtlas {
+            data: vec![],
+            height: 0,
+            cursor_x: 0,
+            cursor_y: 0,
+            row_height: 0,
+        }
+    }
+
+    fn insert(&mut self, width: u32, height: u32, coverage: &[u8]) -> AtlasRect {
+        if self.cursor_x + width > ATLAS_WIDTH {
+            self.cursor_x = 0;
+            self.cursor_y += self.row_height;
+            self.row_height = 0;
+        }
+
+        let rect = AtlasRect {
+            x: self.cursor_x,
+            y: self.cursor_y,
+            width,
+            height,
+        };
+
+        if rect.y + height > self.height {
+            self.height = rect.y + height;
+            self.data.resize((ATLAS_WIDTH * self.height) as usize, 0);
+        }
+
+        for row in 0..height {
+            let src = (row * width) as usize;
+            let dst = ((rect.y + row) * ATLAS_WIDTH + rect.x) as usize;
+            self.data[dst..dst + width as usize]
+                .copy_from_slice(&coverage[src..src + width as usize]);
+        }
+
+        self.cursor_x += width;
+        self.row_height = self.row_height.max(height);
+
+        rect
+    }
+}
+
+struct Quad {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    // Normalized on flush
+    uv: AtlasRect,
+    color: [f32; 4],
+}
+
+enum DrawOp {
+    Image { buf: RgbaImage, x: u32, y: u32 },
+    Glyphs(Vec<Quad>),
+}
+
+pub(super) struct GpuBatch {
+    gpu: &'static Gpu,
+    ops: Vec<DrawOp>,
+    atlas: GlyphAtlas,
+}
+
+impl GpuBatch {
+    // Return None when GPU is not available or canvas is too large for GPU.
+    pub(super) fn new(width: u32, height: u32) -> Option<Self> {
+        let gpu = Gpu::get()?;
+        if width > gpu.max_texture_size || height > gpu.max_texture_size {
+            return None;
+        }
+        Some(GpuBatch {
+            gpu,
+            ops: vec![],
+            atlas: GlyphAtlas::new(),
+        })
+    }
+
+    pub(super) fn is_atlas_full(&self) -> bool {
+        self.atlas.height >= self.gpu.max_texture_size / 2
+    }
+
+    pub(super) fn draw_image(&mut self, buf: RgbaImage, x: u32, y: u32) {
+        self.ops.push(DrawOp::Image { buf, x, y });
+    }
+
+    pub(super) fn draw_text(
+        &mut self,
+        color: Rgba<u8>,
+        x: u32,
+        y: u32,
+        font: &dyn Font,
+        setting: &FontSetting,
+        text: &str,
+    ) {
+        let color = color.0.map(|c| c as f32 / 255.);
+        let ascent = font.ascent(setting.size);
+        // Font reference is only valid while drawing this text,
+        // so glyphs are shared within same text.
+        let mut cache: HashMap<GlyphId, AtlasRect> = HashMap::new();
+        let mut quads = vec![];
+        let atlas = &mut self.atlas;
+
+        // Position glyph in the same way as `draw_text_mut`.
+        for_each_glyph(font, setting, text, |q, current_x| {
+            let bb = q.px_bounds();
+            let width = bb.width().ceil() as u32;
+            let height = bb.height().ceil() as u32;
+            if width == 0 || height == 0 || width > ATLAS_WIDTH {
+                return;
+            }
+
+            let rect = *cache.entry(q.glyph().id).or_insert_with(|| {
+                let mut coverage = vec![0; (width * height) as usize];
+                q.draw(|gx, gy, gv| {
+                    if gx < width && gy < height {
+                        coverage[(gy * width + gx) as usize] = (gv * 255.).round() as u8;
+                    }
+                });
+                atlas.insert(width, height, &coverage)
+            });
+
+            let mut gx = x as i32 + current_x;
+            if let KernSetting::Normal = setting.kern_setting {
+                gx += bb.min.x as i32;
+            }
+            let gy = y as i32 + (bb.min.y + ascent) as i32;
+
+            quads.push(Quad {
+                x: gx as f32,
+                y: gy as f32,
+                width: width as f32,
+                height: height as f32,
+                uv: rect,
+                color,
+            });
+        });
+
+        if !quads.is_empty() {
+            self.ops.push(DrawOp::Glyphs(quads));
+        }
+    }
+
+    // Composite recorded draw calls onto the canvas.
+    pub(super) fn flush(&mut self, canvas: &mut RgbaImage) -> Result<(), Error> {
+        if self.ops.is_empty() {
+            return Ok(());
+        }
+
+        let gpu = self.gpu;
+        let (width, height) = canvas.dimensions();
+        let ndc = |x: f32, y: f32| [x / width as f32 * 2. - 1., 1. - y / height as f32 * 2.];
+
+        let atlas_height = self.atlas.height.max(1);
+        if self.atlas.data.is_empty() {
+            self.atlas.data.resize(ATLAS_WIDTH as usize, 0);
+        }
+        let atlas = gpu.create_texture(
+            ATLAS_WIDTH,
+            atlas_height,
+            wgpu::TextureFormat::R8Unorm,
+            1,
+            wgpu::TextureUsages::TEXTURE_BINDING,
+            &self.atlas.data,
+        );
+        let atlas_bind_group = gpu.create_bind_group(&atlas);
+
+        // Build vertices and textures for each draw call.
+        let mut vertices: Vec<f32> = vec![];
+        let mut draws = vec![];
+        let push_quad = |vertices: &mut Vec<f32>,
+                         x: f32,
+                         y: f32,
+                         w: f32,
+                         h: f32,
+                         uv: [f32; 4],
+                         color: [f32; 4]| {
+            let [u0, v0, u1, v1] = uv;
+            let [x0, y0] = ndc(x, y);
+            let [x1, y1] = ndc(x + w, y + h);
+            for (px, py, u, v) in [
+                (x0, y0, u0, v0),
+                (x1, y0, u1, v0),
+                (x0, y1, u0, v1),
+                (x0, y1, u0, v1),
+                (x1, y0, u1, v0),
+                (x1, y1, u1, v1),
+            ] {
+                vertices.extend_from_slice(&[px, py, u, v]);
+                vertices.extend_from_slice(&color);
+            }
+        };
+
+        for op in self.ops.drain(..) {
+            let start = (vertices.len() / VERTEX_LEN) as u32;
+            match op {
+                DrawOp::Image { buf, x, y } => {
+                    let (w, h) = buf.dimensions();
+                    let texture = gpu.create_texture(
+                        w,
+                        h,
+                        FORMAT,
+                        4,
+                        wgpu::TextureUsages::TEXTURE_BINDING,
+                        buf.as_raw(),
+                    );
+                    push_quad(
+                        &mut vertices,
+                        x as f32,
+                        y as f32,
+                        w as f32,
+                        h as f32,
+                        [0., 0., 1., 1.],
+                        [1.; 4],
+                    );
+                    let end = (vertices.len() / VERTEX_LEN) as u32;
+                    draws.push((true, Some(gpu.create_bind_group(&texture)), start..end));
+                }
+                DrawOp::Glyphs(quads) => {
+                    for quad in quads {
+                        let uv = [
+                            quad.uv.x as f32 / ATLAS_WIDTH as f32,
+                            quad.uv.y as f32 / atlas_height as f32,
+                            (quad.uv.x + quad.uv.width) as f32 / ATLAS_WIDTH as f32,
+                            (quad.uv.y + quad.uv.height) as f32 / atlas_height as f32,
+                        ];
+                        push_quad(
+                            &mut vertices,
+                            quad.x,
+                            quad.y,
+                            quad.width,
+                            quad.height,
+                            uv,
+                            quad.color,
+                        );
+                    }
+                    let end = (vertices.len() / VERTEX_LEN) as u32;
+                    draws.push((false, None, start..end));
+                }
+            }
+        }
+        self.atlas = GlyphAtlas::new();
+
+        let vertex_bytes: Vec<u8> = vertices.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &vertex_bytes,
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let target = gpu.create_texture(
+            width,
+            height,
+            FORMAT,
+            4,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            canvas.as_raw(),
+        );
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            for (is_image, bind_group, range) in &draws {
+                if *is_image {
+                    pass.set_pipeline(&gpu.image_pipeline);
+                } else {
+                    pass.set_pipeline(&gpu.glyph_pipeline);
+                }
+                pass.set_bind_group(0, bind_group.as_ref().unwrap_or(&atlas_bind_group), &[]);
+                pass.draw(range.clone(), 0..1);
+            }
+        }
+
+        // Rows of buffer must be aligned by COPY_BYTES_PER_ROW_ALIGNMENT.
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+        let output = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &target,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &output,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: NonZeroU32::new(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        gpu.queue.submit(Some(encoder.finish()));
+
+        let slice = output.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        gpu.device.poll(wgpu::Maintain::Wait);
+        if let Err(err) = pollster::block_on(mapping) {
+            return Err(Error::GpuError(err.to_string()));
+        }
+
+        {
+            let data = slice.get_mapped_range();
+            let canvas = &mut **canvas;
+            for row in 0..height as usize {
+                let src = row * padded_bytes_per_row as usize;
+                let dst = row * unpadded_bytes_per_row as usize;
+                canvas[dst..dst + unpadded_bytes_per_row as usize]
+                    .copy_from_slice(&data[src..src + unpadded_bytes_per_row as usize]);
+            }
+        }
+        output.unmap();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Gpu;
+    use crate::img::DecodedImage;
+    use crate::style::{Rgba, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::RgbaImage;
+
+    fn paint(is_gpu: bool) -> RgbaImage {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 200,
+            height: 100,
+            background_color: Some(Rgba([40, 40, 40, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        if !is_gpu {
+            writer.context.gpu = None;
+        }
+        writer
+            .set_text(
+                "Hello",
+                Style {
+                    font_size: 40.,
+                    color: Rgba([255, 200, 0, 255]),
+                    ..Style::default()
+                },
+                Some(font),
+            )
+            .unwrap();
+        let img = DecodedImage::from(RgbaImage::from_pixel(
+            20,
+            20,
+            image::Rgba([0, 128, 255, 128]),
+        ));
+        writer
+            .set_img_with_decoded(&img, 20, 20, Style::default())
+            .unwrap();
+        writer.paint().unwrap();
+        writer.into_rgba().unwrap()
+    }
+
+    #[test]
+    fn test_gpu_matches_cpu() {
+        // GPU adapter is not available in every environment.
+        if Gpu::get().is_none() {
+            return;
+        }
+        let (gpu, cpu) = (paint(true), paint(false));
+        assert_eq!(gpu.dimensions(), cpu.dimensions());
+        // Blending on GPU may round differently.
+        for ((x, y, a), b) in gpu.enumerate_pixels().zip(cpu.pixels()) {
+            assert!(
+                a.0.iter().zip(b.0).all(|(&a, b)| a.abs_diff(b) <= 2),
+                "unexpected difference at ({}, {}): {:?} != {:?}",
+                x,
+                y,
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_background_after_pending_draws() {
+        if Gpu::get().is_none() {
+            return;
+        }
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 10,
+            height: 10,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        // Draw call is pending in the batch until flush.
+        writer
+            .context
+            .fill_rect(0, 0, 10, 10, image::Rgba([255, 0, 0, 255]))
+            .unwrap();
+        writer
+            .context
+            .draw_background_color(image::Rgba([0, 0, 255, 255]))
+            .unwrap();
+
+        let image = writer.into_rgba().unwrap();
+        assert_eq!(image.get_pixel(5, 5).0, [0, 0, 255, 255]);
+    }
+}
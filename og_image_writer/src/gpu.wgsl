@@ -0,0 +1,36 @@
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] position: vec2<f32>,
+    [[location(1)]] uv: vec2<f32>,
+    [[location(2)]] color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = uv;
+    out.color = color;
+    return out;
+}
+
+[[group(0), binding(0)]]
+var source: texture_2d<f32>;
+[[group(0), binding(1)]]
+var source_sampler: sampler;
+
+// Image is drawn with straight alpha.
+[[stage(fragment)]]
+fn fs_image(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(source, source_sampler, input.uv);
+}
+
+// Glyph atlas has coverage in red channel.
+[[stage(fragment)]]
+fn fs_glyph(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let coverage = textureSample(source, source_sampler, input.uv).r;
+    return vec4<f32>(input.color.rgb, input.color.a * coverage);
+}
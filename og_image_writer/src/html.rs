@@ -0,0 +1,792 @@
+use crate::style::{
+    AlignItems, BorderRadius, Direction, FlexDirection, JustifyContent, Margin, MarginAuto,
+    Position, Rgba, Style, TextAlign, TextOverflow, TextTransform, WhiteSpace, WindowStyle,
+};
+use crate::writer::OGImageWriter;
+use crate::{Error, TextArea};
+use image::ImageError;
+use std::path::{Component, Path, PathBuf};
+
+// Elements that have no children and no closing tag.
+const VOID_ELEMENTS: [&str; 2] = ["img", "br"];
+
+#[derive(Debug)]
+enum Node {
+    Element(HtmlElement),
+    Text(String),
+}
+
+impl Node {
+    fn is_blank(&self) -> bool {
+        matches!(self, Node::Text(text) if text.trim().is_empty())
+    }
+}
+
+#[derive(Debug)]
+struct HtmlElement {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl HtmlElement {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Declarations of `style` attribute as pairs of lowercase property and value.
+    fn declarations(&self) -> Vec<(String, &str)> {
+        self.attr("style")
+            .unwrap_or("")
+            .split(';')
+            .filter_map(|declaration| {
+                let (property, value) = declaration.split_once(':')?;
+                Some((property.trim().to_ascii_lowercase(), value.trim()))
+            })
+            .collect()
+    }
+
+    // Size from `width` and `height` attributes, or the same CSS properties.
+    fn size(&self) -> Result<(Option<u32>, Option<u32>), Error> {
+        // Attributes are in pixels without unit.
+        let mut width = self.attr("width").map(|value| number(value, "width"));
+        let mut height = self.attr("height").map(|value| number(value, "height"));
+        for (property, value) in self.declarations() {
+            match property.as_str() {
+                "width" => width = Some(length(value, "width")),
+                "height" => height = Some(length(value, "height")),
+                _ => {}
+            }
+        }
+        Ok((
+            width.transpose()?.map(|width| width as u32),
+            height.transpose()?.map(|height| height as u32),
+        ))
+    }
+
+    // Style of the element. Text properties are inherited from `parent` like CSS.
+    fn style(&self, parent: &Style) -> Result<Style, Error> {
+        let mut style = Style {
+            color: parent.color,
+            font_size: parent.font_size,
+            line_height: parent.line_height,
+            letter_spacing: parent.letter_spacing,
+            text_align: parent.text_align,
+            direction: parent.direction,
+            text_transform: parent.text_transform,
+            white_space: parent.white_space,
+            ..Style::default()
+        };
+        for (property, value) in self.declarations() {
+            apply_style(&mut style, &property, value)?;
+        }
+        Ok(style)
+    }
+
+    fn window_style(&self) -> Result<WindowStyle, Error> {
+        let mut window = WindowStyle::default();
+        let (width, height) = self.size()?;
+        window.width = width.unwrap_or(window.width);
+        window.height = height.unwrap_or(window.height);
+        for (property, value) in self.declarations() {
+            apply_window_style(&mut window, &property, value)?;
+        }
+        Ok(window)
+    }
+}
+
+impl OGImageWriter {
+    /// Build writer from HTML with inline CSS, so that HTML templates can be ported as they are.
+    ///
+    /// Document must have one root `div`, that is the window. Its children are set in order.
+    /// - `div` is set as container, and its children are laid out in it.
+    /// - `p` and `span` are set as text. `span` and `br` in text are styled runs and line breaks.
+    /// - `img` is set as image. `src` is path of image file relative to `base_dir`.
+    ///   Absolute paths and paths out of `base_dir`, like `../secret.png`, are rejected, so that HTML can't read other files.
+    ///
+    /// Supported properties of `style` attribute are below, and the other properties are ignored.
+    /// - `div`: `width`, `height`, `background-color`, `flex-direction`, `justify-content`, `align-items`
+    /// - `img`: `width`, `height`, `border-radius`, `aspect-ratio`
+    /// - Text: `color`, `font-size`, `line-height`, `letter-spacing`, `text-align`, `direction`,
+    ///   `text-transform`, `white-space`, `text-overflow`, `max-width`, `max-height`
+    /// - All: `margin`, `position`, `top`, `right`, `bottom`, `left`, `flex-grow`, `flex-shrink`, `align-self`
+    ///
+    /// Lengths are in `px`, and colors are hex, `rgb()`, `rgba()`, `black`, `white` or `transparent`. Text properties are inherited.
+    /// All texts are drawn with `font`.
+    pub fn from_html(
+        html: &str,
+        font: Vec<u8>,
+        base_dir: impl AsRef<Path>,
+    ) -> Result<OGImageWriter, Error> {
+        let nodes = Parser { html, pos: 0 }.parse_nodes(None)?;
+        let mut roots = nodes.into_iter().filter(|node| !node.is_blank());
+        let root = match (roots.next(), roots.next()) {
+            (Some(Node::Element(root)), None) if root.name == "div" => root,
+            _ => return Err(invalid("document must have one root `div`")),
+        };
+        let style = root.style(&Style::default())?;
+        build_writer(&root, &style, &font, base_dir.as_ref())
+    }
+}
+
+fn build_writer(
+    div: &HtmlElement,
+    style: &Style,
+    font: &[u8],
+    base_dir: &Path,
+) -> Result<OGImageWriter, Error> {
+    let mut writer = OGImageWriter::new(div.window_style()?)?;
+    for child in &div.children {
+        match child {
+            Node::Element(element) => set_element(&mut writer, element, style, font, base_dir)?,
+            // Text directly in `div` is set like `p`.
+            Node::Text(_) => set_text(&mut writer, std::slice::from_ref(child), style, font)?,
+        }
+    }
+    Ok(writer)
+}
+
+fn set_element(
+    writer: &mut OGImageWriter,
+    element: &HtmlElement,
+    parent: &Style,
+    font: &[u8],
+    base_dir: &Path,
+) -> Result<(), Error> {
+    let style = element.style(parent)?;
+    match element.name.as_str() {
+        "div" => {
+            let container = build_writer(element, &style, font, base_dir)?;
+            writer.set_container(container, style)
+        }
+        "p" | "span" => set_text(writer, &element.children, &style, font),
+        "img" => {
+            let src = element
+                .attr("src")
+                .ok_or_else(|| invalid("`img` must have `src`"))?;
+            let path = resolve_src(base_dir, src)?;
+            let src = path
+                .to_str()
+                .ok_or_else(|| invalid(format!("invalid path of `img` src `{}`", src)))?;
+            let (width, height) = element.size()?;
+            // Missing side is computed from the other side when `aspect-ratio` is set.
+            let computed = style.aspect_ratio.map(|_| 0);
+            match (width.or(computed), height.or(computed)) {
+                (Some(width), Some(height)) => writer.set_img(src, width, height, style),
+                _ => Err(invalid("`img` must have `width` and `height`")),
+            }
+        }
+        name => Err(invalid(format!("unsupported element `{}`", name))),
+    }
+}
+
+fn set_text(
+    writer: &mut OGImageWriter,
+    nodes: &[Node],
+    style: &Style,
+    font: &[u8],
+) -> Result<(), Error> {
+    let mut runs = vec![];
+    collect_runs(nodes, style, &mut runs)?;
+
+    // Spaces around text are removed like HTML.
+    if let Some((text, _)) = runs.first_mut() {
+        *text = text.trim_start().to_string();
+    }
+    if let Some((text, _)) = runs.last_mut() {
+        *text = text.trim_end().to_string();
+    }
+    runs.retain(|(text, _)| !text.is_empty());
+
+    match runs.len() {
+        0 => Ok(()),
+        1 => writer.set_text(&runs[0].0, runs[0].1.clone(), Some(font.to_vec())),
+        _ => {
+            let mut textarea = TextArea::new();
+            for (text, style) in runs {
+                textarea.push(&text, style, None)?;
+            }
+            writer.set_textarea(textarea, style.clone(), Some(font.to_vec()))
+        }
+    }
+}
+
+// Collect texts with their styles. Spaces are collapsed unless `white-space` preserves them.
+fn collect_runs(
+    nodes: &[Node],
+    style: &Style,
+    runs: &mut Vec<(String, Style)>,
+) -> Result<(), Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                let text = match style.white_space {
+                    WhiteSpace::Normal => {
+                        let ends_with_space = match runs.last() {
+                            Some((text, _)) => text.ends_with([' ', '\u{2028}']),
+                            None => true,
+                        };
+                        let collapsed = collapse_spaces(text);
+                        match ends_with_space {
+                            true => collapsed.trim_start().to_string(),
+                            false => collapsed,
+                        }
+                    }
+                    _ => text.clone(),
+                };
+                runs.push((text, style.clone()));
+            }
+            Node::Element(element) => match element.name.as_str() {
+                "span" => collect_runs(&element.children, &element.style(style)?, runs)?,
+                // Line separator is a forced line break.
+                "br" => runs.push(('\u{2028}'.to_string(), style.clone())),
+                name => return Err(invalid(format!("unsupported element `{}` in text", name))),
+            },
+        }
+    }
+    Ok(())
+}
+
+fn collapse_spaces(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut is_space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !is_space {
+                collapsed.push(' ');
+            }
+            is_space = true;
+        } else {
+            collapsed.push(c);
+            is_space = false;
+        }
+    }
+    collapsed
+}
+
+fn apply_style(style: &mut Style, property: &str, value: &str) -> Result<(), Error> {
+    match property {
+        "margin" => {
+            let sides = value
+                .split_ascii_whitespace()
+                .map(|side| margin(side, property))
+                .collect::<Result<Vec<_>, _>>()?;
+            let [top, right, bottom, left] = match sides.as_slice() {
+                [all] => [*all; 4],
+                [vertical, horizontal] => [*vertical, *horizontal, *vertical, *horizontal],
+                [top, horizontal, bottom] => [*top, *horizontal, *bottom, *horizontal],
+                [top, right, bottom, left] => [*top, *right, *bottom, *left],
+                _ => return Err(invalid_value(property, value)),
+            };
+            style.margin = Margin(top.0, right.0, bottom.0, left.0);
+            style.margin_auto = MarginAuto(top.1, right.1, bottom.1, left.1);
+        }
+        "margin-top" => (style.margin.0, style.margin_auto.0) = margin(value, property)?,
+        "margin-right" => (style.margin.1, style.margin_auto.1) = margin(value, property)?,
+        "margin-bottom" => (style.margin.2, style.margin_auto.2) = margin(value, property)?,
+        "margin-left" => (style.margin.3, style.margin_auto.3) = margin(value, property)?,
+        "color" => style.color = color(value, property)?,
+        "font-size" => style.font_size = length(value, property)?,
+        "line-height" => style.line_height = number(value, property)?,
+        "letter-spacing" => style.letter_spacing = length(value, property)? as i32,
+        "text-align" => {
+            style.text_align = match value {
+                "start" => TextAlign::Start,
+                "center" => TextAlign::Center,
+                "end" => TextAlign::End,
+                "left" => TextAlign::Left,
+                "right" => TextAlign::Right,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "direction" => {
+            style.direction = match value {
+                "ltr" => Direction::Ltr,
+                "rtl" => Direction::Rtl,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "text-transform" => {
+            style.text_transform = match value {
+                "none" => TextTransform::None,
+                "uppercase" => TextTransform::Uppercase,
+                "lowercase" => TextTransform::Lowercase,
+                "capitalize" => TextTransform::Capitalize,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "white-space" => {
+            style.white_space = match value {
+                "normal" => WhiteSpace::Normal,
+                "pre-line" => WhiteSpace::PreLine,
+                "pre" => WhiteSpace::Pre,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "text-overflow" => {
+            style.text_overflow = match value {
+                "clip" => TextOverflow::Clip,
+                "ellipsis" => TextOverflow::Ellipsis,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "max-width" => style.max_width = Some(length(value, property)? as u32),
+        "max-height" => style.max_height = Some(length(value, property)? as u32),
+        "position" => {
+            style.position = match value {
+                "static" | "relative" => Position::Static,
+                "absolute" => Position::Absolute,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "top" => style.top = Some(length(value, property)? as i32),
+        "right" => style.right = Some(length(value, property)? as i32),
+        "bottom" => style.bottom = Some(length(value, property)? as i32),
+        "left" => style.left = Some(length(value, property)? as i32),
+        "border-radius" => {
+            let corners = value
+                .split_ascii_whitespace()
+                .map(|corner| length(corner, property).map(|corner| corner as u32))
+                .collect::<Result<Vec<_>, _>>()?;
+            style.border_radius = match corners.as_slice() {
+                [all] => BorderRadius(*all, *all, *all, *all),
+                [a, b] => BorderRadius(*a, *b, *a, *b),
+                [a, b, c] => BorderRadius(*a, *b, *c, *b),
+                [a, b, c, d] => BorderRadius(*a, *b, *c, *d),
+                _ => return Err(invalid_value(property, value)),
+            };
+        }
+        "aspect-ratio" => {
+            let ratio = match value.split_once('/') {
+                Some((width, height)) => number(width, property)? / number(height, property)?,
+                None => number(value, property)?,
+            };
+            style.aspect_ratio = Some(ratio);
+        }
+        "flex-grow" => style.flex_grow = number(value, property)?,
+        "flex-shrink" => style.flex_shrink = number(value, property)?,
+        "align-self" => {
+            style.align_self = match value {
+                "auto" => None,
+                _ => Some(align(value, property)?),
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn apply_window_style(window: &mut WindowStyle, property: &str, value: &str) -> Result<(), Error> {
+    match property {
+        "background-color" => window.background_color = Some(color(value, property)?),
+        "flex-direction" => {
+            window.flex_direction = match value {
+                "column" => FlexDirection::Column,
+                "row" => FlexDirection::Row,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "justify-content" => {
+            window.justify_content = match value {
+                "flex-start" | "start" => JustifyContent::Start,
+                "center" => JustifyContent::Center,
+                "flex-end" | "end" => JustifyContent::End,
+                _ => return Err(invalid_value(property, value)),
+            }
+        }
+        "align-items" => window.align_items = align(value, property)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn align(value: &str, property: &str) -> Result<AlignItems, Error> {
+    match value {
+        "flex-start" | "start" => Ok(AlignItems::Start),
+        "center" => Ok(AlignItems::Center),
+        "flex-end" | "end" => Ok(AlignItems::End),
+        "baseline" => Ok(AlignItems::Baseline),
+        _ => Err(invalid_value(property, value)),
+    }
+}
+
+// Length in `px`. Unit can be omitted for zero.
+fn length(value: &str, property: &str) -> Result<f32, Error> {
+    match value.strip_suffix("px") {
+        Some(value) => number(value, property),
+        None if value == "0" => Ok(0.),
+        None => Err(invalid_value(property, value)),
+    }
+}
+
+// Length of margin, and whether it is `auto`.
+fn margin(value: &str, property: &str) -> Result<(i32, bool), Error> {
+    match value {
+        "auto" => Ok((0, true)),
+        _ => Ok((length(value, property)? as i32, false)),
+    }
+}
+
+fn number(value: &str, property: &str) -> Result<f32, Error> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid_value(property, value))
+}
+
+fn color(value: &str, property: &str) -> Result<Rgba, Error> {
+    let err = || invalid_value(property, value);
+    match value {
+        "black" => return Ok(Rgba([0, 0, 0, 255])),
+        "white" => return Ok(Rgba([255, 255, 255, 255])),
+        "transparent" => return Ok(Rgba([0, 0, 0, 0])),
+        _ => {}
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(err)?;
+        let channels: Vec<u8> = match digits.len() {
+            3 | 4 => digits.iter().map(|digit| digit * 17).collect(),
+            6 | 8 => digits
+                .chunks(2)
+                .map(|pair| pair[0] * 16 + pair[1])
+                .collect(),
+            _ => return Err(err()),
+        };
+        let alpha = channels.get(3).copied().unwrap_or(255);
+        return Ok(Rgba([channels[0], channels[1], channels[2], alpha]));
+    }
+    let args = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|args| args.strip_suffix(')'))
+        .ok_or_else(err)?;
+    let args = args
+        .split(',')
+        .map(|arg| number(arg, property))
+        .collect::<Result<Vec<_>, _>>()?;
+    match args.as_slice() {
+        [r, g, b] => Ok(Rgba([*r as u8, *g as u8, *b as u8, 255])),
+        [r, g, b, a] => Ok(Rgba([*r as u8, *g as u8, *b as u8, (a * 255.) as u8])),
+        _ => Err(err()),
+    }
+}
+
+// Path of `src` in `base_dir`. Symlinks are resolved, so that they can't point out of `base_dir` either.
+fn resolve_src(base_dir: &Path, src: &str) -> Result<PathBuf, Error> {
+    let is_relative = Path::new(src)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_relative {
+        return Err(invalid(format!(
+            "`img` src `{}` must be relative path in base directory",
+            src
+        )));
+    }
+    let base_dir = base_dir.canonicalize().map_err(ImageError::IoError)?;
+    let path = base_dir
+        .join(src)
+        .canonicalize()
+        .map_err(ImageError::IoError)?;
+    if !path.starts_with(&base_dir) {
+        return Err(invalid(format!(
+            "`img` src `{}` is out of base directory",
+            src
+        )));
+    }
+    Ok(path)
+}
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidHtml(message.into())
+}
+
+fn invalid_value(property: &str, value: &str) -> Error {
+    invalid(format!("invalid value `{}` of `{}`", value, property))
+}
+
+struct Parser<'a> {
+    html: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.html[self.pos..]
+    }
+
+    // Parse nodes until the closing tag of `parent`.
+    fn parse_nodes(&mut self, parent: Option<&str>) -> Result<Vec<Node>, Error> {
+        let mut nodes = vec![];
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return match parent {
+                    Some(name) => Err(invalid(format!("`{}` is not closed", name))),
+                    None => Ok(nodes),
+                };
+            }
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let end = comment
+                    .find("-->")
+                    .ok_or_else(|| invalid("comment is not closed"))?;
+                self.pos += "<!--".len() + end + "-->".len();
+            } else if let Some(tag) = rest.strip_prefix("</") {
+                let end = tag
+                    .find('>')
+                    .ok_or_else(|| invalid("closing tag is not closed"))?;
+                let name = tag[..end].trim().to_ascii_lowercase();
+                self.pos += "</".len() + end + 1;
+                return match parent {
+                    Some(parent) if parent == name => Ok(nodes),
+                    _ => Err(invalid(format!("unexpected closing tag `{}`", name))),
+                };
+            } else if rest.starts_with('<') {
+                nodes.push(Node::Element(self.parse_element()?));
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                nodes.push(Node::Text(decode_entities(&rest[..end])));
+                self.pos += end;
+            }
+        }
+    }
+
+    fn parse_element(&mut self) -> Result<HtmlElement, Error> {
+        self.pos += 1;
+        let name = self
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            return Err(invalid("tag must have name"));
+        }
+
+        let mut attrs = vec![];
+        let is_self_closing = loop {
+            self.take_while(|c| c.is_whitespace());
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                break true;
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                break false;
+            }
+            let key = self
+                .take_while(|c| !c.is_whitespace() && !matches!(c, '=' | '>' | '/'))
+                .to_ascii_lowercase();
+            if key.is_empty() {
+                return Err(invalid(format!("`{}` is not closed", name)));
+            }
+            self.take_while(|c| c.is_whitespace());
+            let value = match self.rest().strip_prefix('=') {
+                Some(_) => {
+                    self.pos += 1;
+                    self.take_while(|c| c.is_whitespace());
+                    self.parse_attr_value()?
+                }
+                None => String::new(),
+            };
+            attrs.push((key, value));
+        };
+
+        let children = if is_self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            vec![]
+        } else {
+            self.parse_nodes(Some(&name))?
+        };
+        Ok(HtmlElement {
+            name,
+            attrs,
+            children,
+        })
+    }
+
+    fn parse_attr_value(&mut self) -> Result<String, Error> {
+        let rest = self.rest();
+        match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..]
+                    .find(quote)
+                    .ok_or_else(|| invalid("attribute value is not closed"))?;
+                self.pos += end + 2;
+                Ok(decode_entities(&rest[1..end + 1]))
+            }
+            _ => Ok(decode_entities(
+                self.take_while(|c| !c.is_whitespace() && c != '>'),
+            )),
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest
+            .find(';')
+            .map(|end| (&rest[1..end], end))
+            .and_then(|(name, end)| decode_entity(name).map(|c| (c, end)));
+        match entity {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::Element;
+
+    const FONT: &[u8] = include_bytes!("../../fonts/Roboto-Light.ttf");
+
+    #[test]
+    fn test_from_html() {
+        let html = r#"
+            <div style="width: 600px; height: 300px; background-color: #46285a; justify-content: center">
+                <!-- Title and image -->
+                <p style="color: rgb(255, 255, 255); font-size: 40px; margin: 0 auto">
+                    Hello   <span style="color: #ff0">HTML</span>&amp;CSS<br>
+                    Card
+                </p>
+                <img src="assets/thumbnail_circle.png" width="60" height="60" style="margin-left: 20px" />
+                <div style="width: 100px; height: 40px; position: absolute; bottom: 10px; right: 10px">
+                    Footer
+                </div>
+            </div>
+        "#;
+        let mut writer = OGImageWriter::from_html(html, FONT.to_vec(), "..").unwrap();
+
+        let elements = writer.elements().unwrap();
+        assert_eq!(elements.len(), 3);
+        let Element::Text(Some(text)) = elements[0] else {
+            unreachable!()
+        };
+        assert_eq!(text.text(), "Hello HTML&CSS\u{2028}Card");
+        let style = text.style();
+        assert_eq!(style.font_size, 40.);
+        assert!(matches!(
+            style.margin_auto,
+            MarginAuto(false, true, false, true)
+        ));
+        assert!(matches!(elements[1], Element::Img(Some(_))));
+        assert!(matches!(
+            elements[2].style().unwrap().position,
+            Position::Absolute
+        ));
+
+        writer.paint().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_html() {
+        let error = |html: &str| {
+            let err = OGImageWriter::from_html(html, FONT.to_vec(), "..")
+                .err()
+                .unwrap();
+            err.to_string()
+        };
+        assert_eq!(
+            error("<p>text</p>"),
+            "invalid html: document must have one root `div`"
+        );
+        assert_eq!(
+            error("<div><p>text</div>"),
+            "invalid html: unexpected closing tag `div`"
+        );
+        assert_eq!(
+            error(r#"<div><p style="font-size: large">text</p></div>"#),
+            "invalid html: invalid value `large` of `font-size`"
+        );
+        assert_eq!(
+            error("<div><table></table></div>"),
+            "invalid html: unsupported element `table`"
+        );
+    }
+
+    #[test]
+    fn test_img_src_in_base_dir() {
+        let from_html = |src: &str| {
+            let html = format!(r#"<div><img src="{}" width="60" height="60" /></div>"#, src);
+            OGImageWriter::from_html(&html, FONT.to_vec(), "../assets")
+        };
+        assert!(from_html("thumbnail_circle.png").is_ok());
+        assert!(from_html("./thumbnail_circle.png").is_ok());
+
+        // Files out of base directory are rejected before they are read.
+        for src in [
+            "../fonts/Roboto-Light.ttf",
+            "images/../../fonts/Roboto-Light.ttf",
+            "/etc/passwd",
+        ] {
+            let err = from_html(src).err().unwrap();
+            assert!(
+                matches!(err.inner(), Error::InvalidHtml(_)),
+                "{} must be rejected: {}",
+                src,
+                err
+            );
+        }
+
+        // Symlink in base directory can't point out of it either.
+        #[cfg(unix)]
+        {
+            let dir =
+                std::env::temp_dir().join(format!("og_image_writer_html_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let font = Path::new("../fonts/Roboto-Light.ttf")
+                .canonicalize()
+                .unwrap();
+            std::os::unix::fs::symlink(font, dir.join("font.png")).unwrap();
+            let html = r#"<div><img src="font.png" width="60" height="60" /></div>"#;
+            let err = OGImageWriter::from_html(html, FONT.to_vec(), &dir)
+                .err()
+                .unwrap();
+            assert!(matches!(err.inner(), Error::InvalidHtml(_)));
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
@@ -1,15 +1,25 @@
 use crate::Error;
 
-use super::style::BorderRadius;
+use super::style::{BorderRadius, ImageCrop, ImageFrame, ImageRepeat, ImageSlice, Style};
+use image::codecs::gif::GifDecoder;
+use image::error::{ImageFormatHint, ParameterError, ParameterErrorKind};
 use image::{
-    load_from_memory_with_format, open, DynamicImage, ImageBuffer, ImageError, ImageFormat, Rgba,
+    imageops, load_from_memory_with_format, open, AnimationDecoder, DynamicImage, Frame,
+    GenericImageView, ImageBuffer, ImageError, ImageFormat, ImageResult, Rgba,
 };
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
 pub enum ImageInputFormat {
     Png,
     Jpeg,
+    /// Frame of animated GIF is chosen by `image_frame` of style.
+    Gif,
     // WebP,
     // Avif,
 }
@@ -19,25 +29,100 @@ impl ImageInputFormat {
         match self {
             ImageInputFormat::Png => ImageFormat::Png,
             ImageInputFormat::Jpeg => ImageFormat::Jpeg,
+            ImageInputFormat::Gif => ImageFormat::Gif,
             // ImageInputFormat::WebP => ImageFormat::WebP,
             // ImageInputFormat::Avif => ImageFormat::Avif,
         }
     }
+
+    // Guess format from magic bytes, for data that comes without format like assets.
+    pub(super) fn guess(data: &[u8]) -> Result<Self, ImageError> {
+        match image::guess_format(data)? {
+            ImageFormat::Png => Ok(ImageInputFormat::Png),
+            ImageFormat::Jpeg => Ok(ImageInputFormat::Jpeg),
+            ImageFormat::Gif => Ok(ImageInputFormat::Gif),
+            format => Err(ImageError::Unsupported(
+                ImageFormatHint::Exact(format).into(),
+            )),
+        }
+    }
+}
+
+/// Image that is decoded in advance and shared by renders, like a logo that appears on every card.
+/// Cloning this is cheap.
+#[derive(Debug, Clone)]
+pub struct DecodedImage(Arc<DynamicImage>);
+
+impl DecodedImage {
+    /// Decode image data. The first frame is decoded for GIF.
+    pub fn from_data(data: &[u8], format: ImageInputFormat) -> Result<Self, Error> {
+        let rgba = match format {
+            ImageInputFormat::Gif => decode_gif_frame(Cursor::new(data), ImageFrame::First)?,
+            _ => load_from_memory_with_format(data, format.as_image_format())?.into_rgba8(),
+        };
+        Ok(DecodedImage::from(rgba))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    // Pixels are borrowed unless the image is not RGBA.
+    fn rgba(&self) -> Cow<'_, ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        match &*self.0 {
+            DynamicImage::ImageRgba8(rgba) => Cow::Borrowed(rgba),
+            image => Cow::Owned(image.to_rgba8()),
+        }
+    }
+}
+
+impl From<ImageBuffer<Rgba<u8>, Vec<u8>>> for DecodedImage {
+    fn from(rgba: ImageBuffer<Rgba<u8>, Vec<u8>>) -> Self {
+        DecodedImage(Arc::new(DynamicImage::ImageRgba8(rgba)))
+    }
+}
+
+/// Image is converted to RGBA here, so it is not converted for each render.
+impl From<DynamicImage> for DecodedImage {
+    fn from(image: DynamicImage) -> Self {
+        DecodedImage::from(image.into_rgba8())
+    }
+}
+
+/// Image is shared without copying. Images other than RGBA are converted for each render.
+impl From<Arc<DynamicImage>> for DecodedImage {
+    fn from(image: Arc<DynamicImage>) -> Self {
+        DecodedImage(image)
+    }
 }
 
 pub(super) struct Size {
     pub(super) height: u32,
     pub(super) width: u32,
+    // Ratio of resized width to source width.
+    pub(super) scale: f32,
 }
 
 pub(super) struct ImageInfo(pub(super) ImageBuffer<Rgba<u8>, Vec<u8>>, pub(super) Size);
 
-pub(super) fn open_and_resize(src: &str, w: u32, h: u32) -> Result<ImageInfo, Error> {
-    let rgba = open(src)?.into_rgba8();
-    let buffer = DynamicImage::ImageRgba8(rgba).thumbnail(w, h).into_rgba8();
-    let height = buffer.height();
-    let width = buffer.width();
-    Ok(ImageInfo(buffer, Size { height, width }))
+pub(super) fn open_and_resize(
+    src: &str,
+    w: u32,
+    h: u32,
+    style: &Style,
+) -> Result<ImageInfo, Error> {
+    let rgba = match ImageFormat::from_path(src) {
+        Ok(ImageFormat::Gif) => {
+            let file = File::open(src).map_err(ImageError::IoError)?;
+            decode_gif_frame(BufReader::new(file), style.image_frame)?
+        }
+        _ => open(src)?.into_rgba8(),
+    };
+    resize(&rgba, w, h, style)
 }
 
 pub(super) fn open_and_resize_with_data(
@@ -45,22 +130,301 @@ pub(super) fn open_and_resize_with_data(
     w: u32,
     h: u32,
     format: ImageInputFormat,
-) -> Result<ImageInfo, ImageError> {
-    let rgba = load_from_memory_with_format(data, format.as_image_format())?.into_rgba8();
-    let buffer = DynamicImage::ImageRgba8(rgba).thumbnail(w, h).into_rgba8();
-    let height = buffer.height();
-    let width = buffer.width();
-    Ok(ImageInfo(buffer, Size { height, width }))
+    style: &Style,
+) -> Result<ImageInfo, Error> {
+    let rgba = match format {
+        ImageInputFormat::Gif => decode_gif_frame(Cursor::new(data), style.image_frame)?,
+        _ => load_from_memory_with_format(data, format.as_image_format())?.into_rgba8(),
+    };
+    resize(&rgba, w, h, style)
+}
+
+pub(super) fn resize_decoded(
+    image: &DecodedImage,
+    w: u32,
+    h: u32,
+    style: &Style,
+) -> Result<ImageInfo, Error> {
+    resize(&image.rgba(), w, h, style)
+}
+
+// Decode `frame` of animated GIF. Frames after the chosen frame are not decoded when possible.
+fn decode_gif_frame<R: Read>(
+    reader: R,
+    frame: ImageFrame,
+) -> ImageResult<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let mut frames = GifDecoder::new(reader)?.into_frames();
+    let frame = match frame {
+        ImageFrame::First => frames.next().transpose()?,
+        // Index beyond the last frame is clamped.
+        ImageFrame::Index(index) => frames
+            .take(index + 1)
+            .collect::<ImageResult<Vec<_>>>()?
+            .pop(),
+        ImageFrame::Middle => {
+            let mut frames = frames.collect_frames()?;
+            let index = frames.len() / 2;
+            (index < frames.len()).then(|| frames.swap_remove(index))
+        }
+        ImageFrame::Last => frames.collect_frames()?.pop(),
+    };
+    frame.map(Frame::into_buffer).ok_or_else(|| {
+        ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::NoMoreData))
+    })
+}
+
+// Rectangle of source image that is shown by `crop`, and whether it is resized to exactly `w` and `h`.
+pub(super) fn crop_rect(
+    (width, height): (u32, u32),
+    w: u32,
+    h: u32,
+    crop: ImageCrop,
+) -> ((u32, u32, u32, u32), bool) {
+    match crop {
+        ImageCrop::Rect {
+            x,
+            y,
+            width: crop_width,
+            height: crop_height,
+        } => {
+            let (x, y) = (x.min(width - 1), y.min(height - 1));
+            (
+                (
+                    x,
+                    y,
+                    crop_width.clamp(1, width - x),
+                    crop_height.clamp(1, height - y),
+                ),
+                false,
+            )
+        }
+        ImageCrop::Focus { x, y, zoom } => {
+            let scale = (w as f32 / width as f32).max(h as f32 / height as f32) * zoom.max(1.);
+            let crop_width = ((w as f32 / scale).round() as u32).clamp(1, width);
+            let crop_height = ((h as f32 / scale).round() as u32).clamp(1, height);
+            // Center the focal point, but keep the rectangle inside of image.
+            let left = (x * width as f32 - crop_width as f32 / 2.)
+                .round()
+                .clamp(0., (width - crop_width) as f32) as u32;
+            let top = (y * height as f32 - crop_height as f32 / 2.)
+                .round()
+                .clamp(0., (height - crop_height) as f32) as u32;
+            ((left, top, crop_width, crop_height), true)
+        }
+    }
+}
+
+// Stretch image to `w` and `h` without scaling corners that are split by `slice`.
+fn nine_slice(
+    rgba: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    w: u32,
+    h: u32,
+    slice: ImageSlice,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (width, height) = rgba.dimensions();
+    // Insets are shrunk when they don't fit in the source or the element.
+    let fit = |start: u32, end: u32, max: u32| {
+        let sum = start + end;
+        if sum <= max || sum == 0 {
+            (start, end)
+        } else {
+            let start = (start as u64 * max as u64 / sum as u64) as u32;
+            (start, max - start)
+        }
+    };
+    let (top, bottom) = fit(slice.0, slice.2, height.min(h));
+    let (left, right) = fit(slice.3, slice.1, width.min(w));
+
+    // Start and length of 3 parts in source and destination.
+    let parts = |start: u32, end: u32, src: u32, dst: u32| {
+        [
+            (0, start, 0, start),
+            (start, src - start - end, start, dst - start - end),
+            (src - end, end, dst - end, end),
+        ]
+    };
+    let mut buffer = ImageBuffer::new(w, h);
+    for (sy, sh, dy, dh) in parts(top, bottom, height, h) {
+        for (sx, sw, dx, dw) in parts(left, right, width, w) {
+            if sw == 0 || sh == 0 || dw == 0 || dh == 0 {
+                continue;
+            }
+            let part = imageops::crop_imm(rgba, sx, sy, sw, sh).to_image();
+            let part = if (sw, sh) == (dw, dh) {
+                part
+            } else {
+                imageops::resize(&part, dw, dh, imageops::FilterType::Triangle)
+            };
+            imageops::replace(&mut buffer, &part, dx, dy);
+        }
+    }
+    buffer
+}
+
+// Tile image from the top left of `w` and `h`.
+fn repeat(
+    rgba: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    w: u32,
+    h: u32,
+    repeat: ImageRepeat,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (width, height) = rgba.dimensions();
+    let (columns, rows) = match repeat {
+        ImageRepeat::NoRepeat => (1, 1),
+        ImageRepeat::RepeatX => (w.div_ceil(width), 1),
+        ImageRepeat::RepeatY => (1, h.div_ceil(height)),
+        ImageRepeat::Repeat => (w.div_ceil(width), h.div_ceil(height)),
+    };
+    let mut buffer = ImageBuffer::new(w, h);
+    for row in 0..rows {
+        for column in 0..columns {
+            // Tiles are cut off at the edge.
+            imageops::replace(&mut buffer, rgba, column * width, row * height);
+        }
+    }
+    buffer
+}
+
+fn resize(
+    rgba: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    w: u32,
+    h: u32,
+    style: &Style,
+) -> Result<ImageInfo, Error> {
+    check_size("source image", rgba.width(), rgba.height())?;
+    check_size("image", w, h)?;
+
+    if let Some(slice) = style.image_slice {
+        // Corners keep their size, so the image is not upscaled.
+        return Ok(ImageInfo(
+            nine_slice(rgba, w, h, slice),
+            Size {
+                height: h,
+                width: w,
+                scale: 1.,
+            },
+        ));
+    }
+
+    if style.image_repeat != ImageRepeat::NoRepeat {
+        // Tiles keep the original size.
+        return Ok(ImageInfo(
+            repeat(rgba, w, h, style.image_repeat),
+            Size {
+                height: h,
+                width: w,
+                scale: 1.,
+            },
+        ));
+    }
+
+    let cropped;
+    let mut rgba = rgba;
+    let mut is_exact = false;
+    if let Some(crop) = style.crop {
+        let ((x, y, width, height), exact) = crop_rect(rgba.dimensions(), w, h, crop);
+        cropped = imageops::crop_imm(rgba, x, y, width, height).to_image();
+        rgba = &cropped;
+        is_exact = exact;
+    }
+
+    let (width, height) = if is_exact {
+        (w, h)
+    } else {
+        fit_size(rgba.dimensions(), w, h)
+    };
+    let buffer = imageops::thumbnail(rgba, width, height);
+    let scale = width as f32 / rgba.width() as f32;
+    Ok(ImageInfo(
+        buffer,
+        Size {
+            height,
+            width,
+            scale,
+        },
+    ))
+}
+
+// Image element and output image must have at least one pixel.
+pub(super) fn check_size(target: &'static str, width: u32, height: u32) -> Result<(), Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidDimensions {
+            target,
+            width,
+            height,
+        });
+    }
+    Ok(())
+}
+
+// Largest size in `w` and `h` that keeps aspect ratio, like `DynamicImage::thumbnail()`.
+fn fit_size((width, height): (u32, u32), w: u32, h: u32) -> (u32, u32) {
+    let (width, height, w, h) = (width as u64, height as u64, w as u64, h as u64);
+    if w * height <= width * h {
+        (w as u32, (height * w / width).max(1) as u32)
+    } else {
+        ((width * h / height).max(1) as u32, h as u32)
+    }
+}
+
+// Blend pixels onto opaque background color.
+pub(super) fn flatten(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    background: Rgba<u8>,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+        let Rgba([r, g, b, a]) = *img.get_pixel(x, y);
+        let blend = |fg: u8, bg: u8| {
+            ((fg as u32 * a as u32 + bg as u32 * (255 - a as u32) + 127) / 255) as u8
+        };
+        Rgba([
+            blend(r, background[0]),
+            blend(g, background[1]),
+            blend(b, background[2]),
+            255,
+        ])
+    })
+}
+
+// Bounding box of pixels where `is_content` returns true.
+pub(super) fn content_bounds(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    is_content: impl Fn(&Rgba<u8>) -> bool,
+) -> Option<(u32, u32, u32, u32)> {
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in img.enumerate_pixels() {
+        if is_content(pixel) {
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+    }
+    if left > right {
+        return None;
+    }
+    Some((left, top, right - left + 1, bottom - top + 1))
 }
 
 // See https://users.rust-lang.org/t/how-to-trim-image-to-circle-image-without-jaggy/70374
 // Thanks @steffahn for sending some ideas.
 pub(super) fn round(img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, radius: &mut BorderRadius) {
     let (width, height) = img.dimensions();
-    assert!(radius.0 + radius.1 <= width);
-    assert!(radius.3 + radius.2 <= width);
-    assert!(radius.0 + radius.3 <= height);
-    assert!(radius.1 + radius.2 <= height);
+    // Radii are scaled down when adjacent corners overlap, like CSS.
+    let scale = [
+        (radius.0 + radius.1, width),
+        (radius.3 + radius.2, width),
+        (radius.0 + radius.3, height),
+        (radius.1 + radius.2, height),
+    ]
+    .iter()
+    .filter(|(sum, _)| *sum > 0)
+    .map(|&(sum, side)| side as f64 / sum as f64)
+    .fold(1., f64::min);
+    if scale < 1. {
+        let fit = |r: u32| (r as f64 * scale) as u32;
+        *radius = BorderRadius(fit(radius.0), fit(radius.1), fit(radius.2), fit(radius.3));
+    }
 
     // top left
     border_radius(img, radius.0, |x, y| (x - 1, y - 1));
@@ -178,3 +542,195 @@ fn border_radius(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crop_rect() {
+        // Focal point at the right side of wide image.
+        let focus = ImageCrop::Focus {
+            x: 0.75,
+            y: 0.5,
+            zoom: 1.,
+        };
+        assert_eq!(
+            crop_rect((400, 100), 100, 100, focus),
+            ((250, 0, 100, 100), true)
+        );
+
+        // Rectangle is kept inside of image.
+        let focus = ImageCrop::Focus {
+            x: 1.,
+            y: 0.,
+            zoom: 2.,
+        };
+        assert_eq!(
+            crop_rect((400, 100), 100, 100, focus),
+            ((350, 0, 50, 50), true)
+        );
+
+        let rect = ImageCrop::Rect {
+            x: 300,
+            y: 50,
+            width: 200,
+            height: 20,
+        };
+        assert_eq!(
+            crop_rect((400, 100), 100, 100, rect),
+            ((300, 50, 100, 20), false)
+        );
+
+        let style = Style {
+            crop: Some(focus),
+            ..Style::default()
+        };
+        let ImageInfo(buf, size) = resize(&ImageBuffer::new(400, 100), 100, 100, &style).unwrap();
+        assert_eq!(buf.dimensions(), (100, 100));
+        assert_eq!(size.scale, 2.);
+    }
+
+    #[test]
+    fn test_nine_slice() {
+        // 6x6 image that has red corners of 2x2, and blue edges and center.
+        let mut image = ImageBuffer::from_pixel(6, 6, Rgba([0, 0, 255, 255]));
+        for (x, y) in [(0, 0), (4, 0), (0, 4), (4, 4)] {
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                image.put_pixel(x + dx, y + dy, Rgba([255, 0, 0, 255]));
+            }
+        }
+
+        let buffer = nine_slice(&image, 20, 10, ImageSlice(2, 2, 2, 2));
+        assert_eq!(buffer.dimensions(), (20, 10));
+        let is_red = |x, y| buffer.get_pixel(x, y).0 == [255, 0, 0, 255];
+        // Corners keep their size.
+        assert!(is_red(0, 0) && is_red(1, 1) && is_red(18, 8) && is_red(19, 9));
+        assert!(!is_red(2, 0) && !is_red(17, 9) && !is_red(0, 2) && !is_red(19, 7));
+        assert_eq!(buffer.get_pixel(10, 5).0, [0, 0, 255, 255]);
+
+        // Insets are shrunk to fit in the element.
+        assert_eq!(
+            nine_slice(&image, 3, 3, ImageSlice(2, 2, 2, 2)).dimensions(),
+            (3, 3)
+        );
+    }
+
+    #[test]
+    fn test_repeat() {
+        let image = ImageBuffer::from_pixel(4, 3, Rgba([255, 0, 0, 255]));
+        let is_filled =
+            |buffer: &ImageBuffer<Rgba<u8>, Vec<u8>>, x, y| buffer.get_pixel(x, y).0[3] > 0;
+
+        let buffer = repeat(&image, 10, 10, ImageRepeat::Repeat);
+        assert!(buffer.pixels().all(|p| p.0[3] > 0));
+
+        let buffer = repeat(&image, 10, 10, ImageRepeat::RepeatX);
+        assert!(is_filled(&buffer, 9, 2) && !is_filled(&buffer, 0, 3));
+
+        let buffer = repeat(&image, 10, 10, ImageRepeat::RepeatY);
+        assert!(is_filled(&buffer, 3, 9) && !is_filled(&buffer, 4, 0));
+    }
+
+    #[test]
+    fn test_decode_gif_frame() {
+        use image::codecs::gif::GifEncoder;
+
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+        let mut data = vec![];
+        GifEncoder::new(&mut data)
+            .encode_frames(
+                colors
+                    .iter()
+                    .map(|&color| Frame::new(ImageBuffer::from_pixel(2, 2, Rgba(color)))),
+            )
+            .unwrap();
+
+        let color = |frame| {
+            decode_gif_frame(Cursor::new(&data), frame)
+                .unwrap()
+                .get_pixel(0, 0)
+                .0
+        };
+        assert_eq!(color(ImageFrame::First), colors[0]);
+        assert_eq!(color(ImageFrame::Middle), colors[1]);
+        assert_eq!(color(ImageFrame::Last), colors[2]);
+        assert_eq!(color(ImageFrame::Index(1)), colors[1]);
+        assert_eq!(color(ImageFrame::Index(10)), colors[2]);
+    }
+
+    #[test]
+    fn test_content_bounds() {
+        let mut image = ImageBuffer::new(10, 8);
+        assert_eq!(content_bounds(&image, |p| p.0[3] > 0), None);
+
+        image.put_pixel(2, 5, Rgba([0, 0, 0, 255]));
+        image.put_pixel(6, 3, Rgba([0, 0, 0, 1]));
+        assert_eq!(content_bounds(&image, |p| p.0[3] > 0), Some((2, 3, 5, 3)));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut image = ImageBuffer::new(2, 1);
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+        image.put_pixel(1, 0, Rgba([0, 0, 255, 128]));
+
+        let flat = flatten(&image, Rgba([255, 255, 255, 0]));
+        assert_eq!(flat.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(flat.get_pixel(1, 0), &Rgba([127, 127, 255, 255]));
+    }
+
+    #[test]
+    fn test_decoded_image() {
+        let data = include_bytes!("../../assets/thumbnail_circle.png");
+        let style = Style::default();
+        let decoded = DecodedImage::from_data(data, ImageInputFormat::Png).unwrap();
+        let ImageInfo(expected, _) =
+            open_and_resize_with_data(data, 100, 100, ImageInputFormat::Png, &style).unwrap();
+        let ImageInfo(actual, size) = resize_decoded(&decoded, 100, 100, &style).unwrap();
+        assert!(actual == expected);
+        assert_eq!((size.width, size.height), expected.dimensions());
+
+        let rgb = DynamicImage::new_rgb8(40, 20);
+        let decoded = DecodedImage::from(Arc::new(rgb));
+        let ImageInfo(actual, _) = resize_decoded(&decoded, 20, 20, &style).unwrap();
+        assert_eq!(actual.dimensions(), (20, 10));
+    }
+
+    #[test]
+    fn test_round_with_large_radius() {
+        let mut image = ImageBuffer::from_pixel(10, 6, Rgba([255, 0, 0, 255]));
+        let mut radius = BorderRadius(10, 10, 0, 0);
+        round(&mut image, &mut radius);
+        // Radii are scaled to the width of image.
+        assert_eq!((radius.0, radius.1, radius.2, radius.3), (5, 5, 0, 0));
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(image.get_pixel(5, 5)[3], 255);
+
+        let mut empty = ImageBuffer::new(0, 0);
+        round(&mut empty, &mut BorderRadius(4, 4, 4, 4));
+    }
+
+    #[test]
+    fn test_resize_empty_image() {
+        let style = Style::default();
+        let error = resize(&ImageBuffer::new(0, 10), 10, 10, &style).err();
+        assert!(matches!(
+            error,
+            Some(Error::InvalidDimensions {
+                target: "source image",
+                width: 0,
+                height: 10,
+            })
+        ));
+        let error = resize(&ImageBuffer::new(10, 10), 10, 0, &style).err();
+        assert!(matches!(
+            error,
+            Some(Error::InvalidDimensions {
+                target: "image",
+                width: 10,
+                height: 0,
+            })
+        ));
+    }
+}
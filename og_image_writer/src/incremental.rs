@@ -0,0 +1,225 @@
+use super::element::{Element, Rect};
+use image::{imageops, RgbaImage};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write};
+use std::hash::Hasher;
+
+// Background and elements of the previous paint, so that only changed elements are painted again.
+pub(super) struct Incremental {
+    pub(super) background: RgbaImage,
+    painted: Vec<Painted>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Painted {
+    // Custom element has no fingerprint, because it can not be compared.
+    fingerprint: Option<u64>,
+    bounds: Rect,
+}
+
+impl Incremental {
+    pub(super) fn new(background: RgbaImage) -> Self {
+        Incremental {
+            background,
+            painted: vec![],
+        }
+    }
+
+    // Return the rect that must be painted again, and remember elements for the next paint.
+    // Whole window is dirty when elements are added or removed.
+    pub(super) fn update(&mut self, elements: &[Element]) -> Option<Rect> {
+        let (width, height) = self.background.dimensions();
+        let painted: Vec<Painted> = elements
+            .iter()
+            .map(|elm| Painted {
+                fingerprint: fingerprint(elm),
+                bounds: bounds(elm, width, height),
+            })
+            .collect();
+
+        let dirty = if painted.len() != self.painted.len() {
+            (!painted.is_empty() || !self.painted.is_empty())
+                .then(|| Rect::new(0, 0, width, height))
+        } else {
+            painted
+                .iter()
+                .zip(&self.painted)
+                .filter(|(new, old)| new.fingerprint.is_none() || new != old)
+                .flat_map(|(new, old)| [&new.bounds, &old.bounds])
+                .filter(|rect| rect.width > 0 && rect.height > 0)
+                .fold(None, |dirty: Option<Rect>, rect| match dirty {
+                    Some(dirty) => Some(union(&dirty, rect)),
+                    None => Some(Rect::new(rect.x, rect.y, rect.width, rect.height)),
+                })
+        };
+        self.painted = painted;
+        dirty
+    }
+}
+
+// Copy the rect of `src` to `dest`.
+pub(super) fn copy_rect(dest: &mut RgbaImage, src: &RgbaImage, rect: &Rect) {
+    let part = imageops::crop_imm(src, rect.x, rect.y, rect.width, rect.height).to_image();
+    imageops::replace(dest, &part, rect.x, rect.y);
+}
+
+pub(super) fn intersects(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+fn union(a: &Rect, b: &Rect) -> Rect {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    Rect::new(x, y, right - x, bottom - y)
+}
+
+// Area where the element may draw, clamped to the window.
+pub(super) fn bounds(elm: &Element, width: u32, height: u32) -> Rect {
+    let rect = match elm {
+        Element::Img(Some(img)) => {
+            Rect::new(img.rect.x, img.rect.y, img.rect.width, img.rect.height)
+        }
+        Element::Custom(Some(custom)) => Rect::new(
+            custom.rect.x,
+            custom.rect.y,
+            custom.rect.width,
+            custom.rect.height,
+        ),
+        // Glyphs can overflow line boxes, like italic overhang and ruby,
+        // so bounds are inflated by half of font size. Bar of text is drawn in the inset of lines.
+        Element::Text(Some(text)) => {
+            let pad = (text.style.font_size / 2.).ceil() as u32;
+            text.fragments
+                .iter()
+                .map(|fragment| {
+                    let rect = &fragment.rect;
+                    let x = rect.x.saturating_sub(fragment.inset + pad);
+                    Rect::new(
+                        x,
+                        rect.y.saturating_sub(pad),
+                        rect.x + rect.width + pad - x,
+                        rect.height + pad * 2,
+                    )
+                })
+                .reduce(|a, b| union(&a, &b))
+                .unwrap_or_default()
+        }
+        _ => Rect::default(),
+    };
+    let x = rect.x.min(width);
+    let y = rect.y.min(height);
+    Rect::new(x, y, rect.width.min(width - x), rect.height.min(height - y))
+}
+
+fn fingerprint(elm: &Element) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match elm {
+        Element::Img(Some(img)) => {
+            write!(HashWriter(&mut hasher), "{:?}{:?}", img.rect, img.style).ok()?;
+            hasher.write(img.buf.as_raw());
+        }
+        Element::Text(Some(text)) => {
+            write!(HashWriter(&mut hasher), "{:?}", text).ok()?;
+            // Font data is large, so it is hashed with faster CRC.
+            if let Some(font) = &text.font {
+                hasher.write_u32(crc32fast::hash(font.data()));
+            }
+        }
+        _ => return None,
+    }
+    Some(hasher.finish())
+}
+
+// Hash formatted value without allocating string.
+struct HashWriter<'a>(&'a mut DefaultHasher);
+
+impl<'a> Write for HashWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::Img;
+    use crate::style::{AlignItems, JustifyContent, Rgba as StyleRgba, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::Rgba;
+
+    fn img(x: u32, color: u8) -> Element {
+        let buf = RgbaImage::from_pixel(10, 10, Rgba([color, 0, 0, 255]));
+        Element::Img(Some(Img::new(
+            buf,
+            Rect::new(x, 0, 10, 10),
+            Style::default(),
+        )))
+    }
+
+    #[test]
+    fn test_update() {
+        let mut incremental = Incremental::new(RgbaImage::new(100, 50));
+        assert_eq!(incremental.update(&[]), None);
+        assert_eq!(
+            incremental.update(&[img(0, 0), img(50, 0)]),
+            Some(Rect::new(0, 0, 100, 50))
+        );
+        assert_eq!(incremental.update(&[img(0, 0), img(50, 0)]), None);
+        assert_eq!(
+            incremental.update(&[img(0, 0), img(50, 255)]),
+            Some(Rect::new(50, 0, 10, 10))
+        );
+        assert_eq!(
+            incremental.update(&[img(0, 0), img(60, 255)]),
+            Some(Rect::new(50, 0, 20, 10))
+        );
+        assert_eq!(
+            incremental.update(&[img(0, 0)]),
+            Some(Rect::new(0, 0, 100, 50))
+        );
+    }
+
+    #[test]
+    fn test_incremental_paint() {
+        fn set_texts(writer: &mut OGImageWriter, title: &str) {
+            let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+            for text in ["Heading", title] {
+                let style = Style {
+                    font_size: 40.,
+                    color: StyleRgba([255, 255, 255, 255]),
+                    ..Style::default()
+                };
+                writer.set_text(text, style, Some(font.clone())).unwrap();
+            }
+        }
+        fn writer() -> OGImageWriter {
+            OGImageWriter::new(WindowStyle {
+                width: 400,
+                height: 200,
+                background_color: Some(StyleRgba([70, 40, 90, 255])),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..WindowStyle::default()
+            })
+            .unwrap()
+        }
+
+        let mut incremental = writer();
+        incremental.set_incremental(true).unwrap();
+        set_texts(&mut incremental, "First");
+        incremental.paint().unwrap();
+        set_texts(&mut incremental, "Second");
+        assert_eq!(incremental.paint().unwrap().lines, 2);
+        set_texts(&mut incremental, "Second");
+        assert_eq!(incremental.paint().unwrap().lines, 0);
+
+        let mut full = writer();
+        set_texts(&mut full, "Second");
+        full.paint().unwrap();
+
+        assert!(incremental.into_rgba().unwrap() == full.into_rgba().unwrap());
+    }
+}
@@ -1,40 +1,242 @@
 mod container;
+mod custom;
 mod img;
+mod spacer;
 mod split_text;
 mod text;
 mod textarea;
 
 pub(super) use split_text::SplitText;
-pub use textarea::TextArea;
+pub(super) use textarea::{ruby_size, GlyphRun};
+pub use textarea::{TextArea, TextSpan};
 
-use super::element::Element;
-use super::style::{AlignItems, FlexDirection, JustifyContent, Margin, TextAlign};
+use super::element::{Custom, Element, Img};
+use super::style::{AlignItems, FlexDirection, JustifyContent, Margin, Style, TextAlign};
 use super::writer::OGImageWriter;
 use super::Error;
 
 impl OGImageWriter {
-    pub(super) fn process(&mut self) {
+    pub(super) fn process(&mut self) -> Result<(), Error> {
+        // Position of elements is relative to the current value, so layout must run only once.
+        if self.is_laid_out {
+            return Ok(());
+        }
+        // Anchors are resolved before elements are moved, so that unknown id leaves the tree as it was.
+        let anchors = self.resolve_anchors()?;
+        self.is_laid_out = true;
+        self.clamped.clear();
+
         if !matches!(self.window.justify_content, JustifyContent::End) {
             self.tree.0.reverse();
         }
 
+        let space = self.process_flex();
+
         let mut current_y = self.calculate_logical_block() as i32;
         let mut current_x = self.calculate_logical_inline() as i32;
 
+        // Elements are placed so that their baselines are on this position.
+        let row_baseline = match &self.window.flex_direction {
+            FlexDirection::Row => self
+                .tree
+                .0
+                .iter()
+                .filter(|elm| match elm.style() {
+                    Some(style) => {
+                        !elm.is_absolute() && matches!(self.align(style), AlignItems::Baseline)
+                    }
+                    None => false,
+                })
+                .map(|elm| elm.baseline())
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        // Bottom edge of the previous element in column, so that raised text doesn't overlap it.
+        let mut previous_bottom = 0;
+
         let mut tree = OGImageWriter::create_tree();
+        let is_end = matches!(self.window.justify_content, JustifyContent::End);
         while let Some(mut elm) = self.tree.0.pop() {
+            self.is_clamped = false;
             if elm.is_absolute() {
                 self.process_absolute(&mut elm);
             } else {
+                // Elements are placed from the bottom in `End`, so the space after the element comes first.
+                let (before, after) = match space.share(&elm) {
+                    (before, after) if is_end => (-after, -before),
+                    shared => shared,
+                };
                 match self.window.flex_direction {
-                    FlexDirection::Column => self.process_column_flexbox(&mut elm, &mut current_y),
-                    FlexDirection::Row => self.process_row_flexbox(&mut elm, &mut current_x),
+                    FlexDirection::Column => {
+                        current_y += before;
+                        self.process_column_flexbox(&mut elm, &mut current_y, &mut previous_bottom);
+                        current_y += after;
+                    }
+                    FlexDirection::Row => {
+                        current_x += before;
+                        self.process_row_flexbox(&mut elm, &mut current_x, row_baseline);
+                        current_x += after;
+                    }
                 }
             }
 
+            if self.is_clamped {
+                self.clamped.push(tree.0.len());
+            }
             tree.0.push(elm);
         }
         self.tree.0.append(&mut tree.0);
+
+        self.process_anchored(&anchors);
+        Ok(())
+    }
+
+    // Index of anchor element for each element in the order they were set.
+    // Id of style is found first, and then id of named text.
+    fn resolve_anchors(&self) -> Result<Vec<Option<usize>>, Error> {
+        let find = |id: &str| {
+            self.tree
+                .0
+                .iter()
+                .position(|elm| elm.style().and_then(|style| style.id.as_deref()) == Some(id))
+                .or_else(|| self.named.get(id).map(|named| named.index))
+                .ok_or_else(|| Error::NotFoundElementId(id.to_string()))
+        };
+        self.tree
+            .0
+            .iter()
+            .map(
+                |elm| match elm.style().and_then(|style| style.anchor.as_ref()) {
+                    Some(anchor) => find(&anchor.to).map(Some),
+                    None => Ok(None),
+                },
+            )
+            .collect()
+    }
+
+    // Anchored elements are moved after the other elements are placed, because anchor element may be set later.
+    fn process_anchored(&mut self, anchors: &[Option<usize>]) {
+        let len = self.tree.0.len();
+        // Elements are moved in the order they were set, so an element can be anchored to an anchored element.
+        for (index, to) in anchors.iter().enumerate() {
+            let to = match to {
+                Some(to) if *to != index => *to,
+                _ => continue,
+            };
+            let position = self.element_index(index, len);
+            let (edge, offset) = match self.tree.0[position]
+                .style()
+                .and_then(|style| style.anchor.as_ref())
+            {
+                Some(anchor) => (anchor.edge, anchor.offset),
+                None => continue,
+            };
+            let target = self.tree.0[self.element_index(to, len)].border_box();
+            let current = self.tree.0[position].border_box();
+
+            let (x, y) = edge.align(&target, current.width, current.height);
+            let (x, y) = (x + offset.0, y + offset.1);
+            // Anchored position replaces the position that is placed by layout.
+            self.clamped.retain(|&clamped| clamped != position);
+            if !self.tree.0[position].translate(x - current.x as i32, y - current.y as i32) {
+                self.clamped.push(position);
+            }
+        }
+    }
+
+    // Free space is shared by `flex_grow` or auto margins, and overflow is cut from texts by `flex_shrink`.
+    // Content size is updated, so that justified position is computed with flexed elements.
+    fn process_flex(&mut self) -> FreeSpace {
+        let is_row = matches!(self.window.flex_direction, FlexDirection::Row);
+        let in_flow = || self.tree.0.iter().filter(|elm| !elm.is_absolute());
+        let (content, window) = if is_row {
+            (self.content.width, self.window.width)
+        } else {
+            // Texts take extra half line in column unless they are centered.
+            let extra: u32 = match self.window.justify_content {
+                JustifyContent::Center => 0,
+                _ => in_flow()
+                    .map(|elm| match elm {
+                        Element::Text(Some(text)) => text.metrics.max_line_height as u32 / 2,
+                        _ => 0,
+                    })
+                    .sum(),
+            };
+            (self.content.height + extra, self.window.height)
+        };
+
+        let total_grow: f32 = in_flow()
+            .filter_map(|elm| elm.style())
+            .map(|style| style.flex_grow.max(0.))
+            .sum();
+        let auto_margins: u32 = in_flow()
+            .filter_map(|elm| elm.style())
+            .map(|style| {
+                let (start, end) = style.margin_auto.main_axis(is_row);
+                start as u32 + end as u32
+            })
+            .sum();
+        // Auto margins take free space only when no element grows, like CSS.
+        if content < window && (total_grow > 0. || auto_margins > 0) {
+            let is_auto = total_grow <= 0.;
+            let space = FreeSpace {
+                free: window - content,
+                total: if is_auto {
+                    auto_margins as f32
+                } else {
+                    total_grow
+                },
+                is_row,
+                is_auto,
+            };
+            let shared: i32 = in_flow()
+                .map(|elm| {
+                    let (before, after) = space.share(elm);
+                    before + after
+                })
+                .sum();
+            if is_row {
+                self.content.width += shared as u32;
+            } else {
+                self.content.height += shared as u32;
+            }
+            return space;
+        }
+
+        let total_shrink: f32 = in_flow()
+            .filter_map(|elm| match elm {
+                Element::Text(Some(text)) => Some(text.style.flex_shrink.max(0.)),
+                _ => None,
+            })
+            .sum();
+        // Lines can't be narrowed, so texts shrink only in column.
+        if content > window && total_shrink > 0. && !is_row {
+            let overflow = (content - window) as f32;
+            let mut shrunk = 0;
+            for elm in self.tree.0.iter_mut().filter(|elm| !elm.is_absolute()) {
+                if let Element::Text(Some(text)) = elm {
+                    if text.style.flex_shrink > 0. {
+                        let cut = (overflow * text.style.flex_shrink / total_shrink).ceil() as u32;
+                        shrunk += text.shrink(cut);
+                    }
+                }
+            }
+            self.content.height -= shrunk;
+        }
+        FreeSpace::default()
+    }
+
+    // Alignment of the element on the cross axis. Auto margins and `align_self` override `align_items`.
+    fn align(&self, style: &Style) -> AlignItems {
+        let is_row = matches!(self.window.flex_direction, FlexDirection::Row);
+        match style.margin_auto.cross_axis(is_row) {
+            (true, true) => AlignItems::Center,
+            (true, false) => AlignItems::End,
+            (false, true) => AlignItems::Start,
+            (false, false) => style.align_self.unwrap_or(self.window.align_items),
+        }
     }
 
     fn calculate_logical_block(&self) -> u32 {
@@ -75,43 +277,60 @@ impl OGImageWriter {
         }
     }
 
-    fn process_column_flexbox(&mut self, elm: &mut Element, current_y: &mut i32) {
+    fn process_column_flexbox(
+        &mut self,
+        elm: &mut Element,
+        current_y: &mut i32,
+        previous_bottom: &mut i32,
+    ) {
         let window_width = self.window.width as i32;
         let is_end = matches!(self.window.justify_content, JustifyContent::End);
         match elm {
-            Element::Img(Some(img)) => {
-                let Margin(margin_top, margin_right, margin_bottom, margin_left) = img.style.margin;
+            Element::Img(Some(Img { rect, style, .. }))
+            | Element::Custom(Some(Custom { rect, style, .. })) => {
+                let Margin(margin_top, margin_right, margin_bottom, margin_left) = style.margin;
 
-                let logical_inline = match &self.window.align_items {
-                    AlignItems::Start => margin_left,
+                let logical_inline = match self.align(style) {
+                    AlignItems::Start | AlignItems::Baseline => margin_left,
                     AlignItems::Center => {
-                        window_width / 2 - img.rect.width as i32 / 2 + margin_left - margin_right
+                        window_width / 2 - rect.width as i32 / 2 + margin_left - margin_right
                     }
-                    AlignItems::End => window_width - img.rect.width as i32 - margin_right,
+                    AlignItems::End => window_width - rect.width as i32 - margin_right,
                 };
 
-                img.rect.x = logical_inline as u32;
+                rect.x = self.place(0, logical_inline);
 
                 if is_end {
-                    img.rect.y += (*current_y - img.rect.height as i32 - margin_bottom) as u32;
-                    *current_y -= img.rect.height as i32 + margin_top + margin_bottom;
+                    rect.y = self.place(rect.y, *current_y - rect.height as i32 - margin_bottom);
+                    *current_y -= rect.height as i32 + margin_top + margin_bottom;
                 } else {
-                    img.rect.y += (*current_y + margin_top) as u32;
-                    *current_y += img.rect.height as i32 + margin_top + margin_bottom;
+                    rect.y = self.place(rect.y, *current_y + margin_top);
+                    *current_y += rect.height as i32 + margin_top + margin_bottom;
                 }
+                *previous_bottom = (rect.y + rect.height) as i32;
             }
             Element::Text(Some(text)) => {
                 let Margin(margin_top, margin_right, margin_bottom, margin_left) =
                     text.style.margin;
 
                 let line_metrics = &text.metrics;
+                let align = self.align(&text.style);
 
                 // Because ab_glyph draw text that include line_height.
-                let mut system_line_height = line_metrics.max_line_height as u32 / 2;
+                let system_line_height = line_metrics.max_line_height as u32 / 2;
 
+                // Centered text is raised by `system_line_height`, but not over the previous element.
+                let raised = match self.window.justify_content {
+                    JustifyContent::Center => (*current_y + margin_top - *previous_bottom)
+                        .clamp(0, system_line_height as i32)
+                        as u32,
+                    _ => 0,
+                };
+
+                let text_align = text.style.text_align.resolve(text.is_rtl);
                 for fragment in &mut text.fragments {
-                    let logical_inline = match &self.window.align_items {
-                        AlignItems::Start => margin_left,
+                    let logical_inline = match align {
+                        AlignItems::Start | AlignItems::Baseline => margin_left,
                         AlignItems::Center => {
                             window_width / 2 - line_metrics.max_line_width as i32 / 2 + margin_left
                                 - margin_right
@@ -121,37 +340,44 @@ impl OGImageWriter {
                         }
                     };
 
-                    let content_box_inline = match text.style.text_align {
-                        TextAlign::Start => 0,
+                    let shift = match text_align {
+                        // `Start` and `End` are resolved to `Left` or `Right`.
+                        // Lines are aligned to decimal point on layout of text.
+                        TextAlign::Start | TextAlign::Left | TextAlign::Decimal => 0,
                         TextAlign::Center => {
-                            line_metrics.max_line_width as i32 / 2 - fragment.rect.width as i32 / 2
+                            line_metrics.column_end(fragment.column) as i32 / 2
+                                - (fragment.rect.x + fragment.rect.width) as i32 / 2
                         }
-                        TextAlign::End => {
-                            line_metrics.max_line_width as i32 - fragment.rect.width as i32
+                        TextAlign::End | TextAlign::Right => {
+                            line_metrics.column_end(fragment.column) as i32
+                                - (fragment.rect.x + fragment.rect.width) as i32
                         }
-                    } + logical_inline;
+                    };
 
-                    fragment.rect.x += content_box_inline as u32;
+                    // Line that is wider than its column is shifted to the start of the window at most.
+                    fragment.rect.x = self.place(fragment.rect.x, shift + logical_inline);
+                    fragment.inset = offset(fragment.inset, shift);
                     if is_end {
-                        fragment.rect.y +=
-                            (*current_y - line_metrics.total_height as i32 - margin_bottom) as u32
-                                - system_line_height;
+                        fragment.rect.y = self.place(
+                            fragment.rect.y,
+                            *current_y
+                                - line_metrics.total_height as i32
+                                - margin_bottom
+                                - system_line_height as i32,
+                        );
                     } else {
-                        fragment.rect.y += (*current_y + margin_top) as u32;
-                    }
-
-                    if matches!(self.window.justify_content, JustifyContent::Center) {
-                        if fragment.rect.y >= system_line_height {
-                            fragment.rect.y -= system_line_height;
-                        } else {
-                            fragment.rect.y = 0;
-                            system_line_height = 0;
-                        }
+                        fragment.rect.y =
+                            self.place(fragment.rect.y, *current_y + margin_top - raised as i32);
                     }
                 }
+                *previous_bottom =
+                    *current_y + margin_top + line_metrics.total_height as i32 - raised as i32;
 
                 if is_end {
-                    *current_y -= line_metrics.total_height as i32 + margin_top + margin_bottom;
+                    *current_y -= line_metrics.total_height as i32
+                        + margin_top
+                        + margin_bottom
+                        + system_line_height as i32;
                 } else {
                     *current_y += line_metrics.total_height as i32 + margin_top + margin_bottom;
                     if matches!(self.window.justify_content, JustifyContent::Start) {
@@ -163,29 +389,31 @@ impl OGImageWriter {
         }
     }
 
-    fn process_row_flexbox(&mut self, elm: &mut Element, current_x: &mut i32) {
+    fn process_row_flexbox(&mut self, elm: &mut Element, current_x: &mut i32, row_baseline: i32) {
         let window_height = self.window.height as i32;
         let is_end = matches!(self.window.justify_content, JustifyContent::End);
         match elm {
-            Element::Img(Some(img)) => {
-                let Margin(margin_top, margin_right, margin_bottom, margin_left) = img.style.margin;
+            Element::Img(Some(Img { rect, style, .. }))
+            | Element::Custom(Some(Custom { rect, style, .. })) => {
+                let Margin(margin_top, margin_right, margin_bottom, margin_left) = style.margin;
 
-                let logical_block = match &self.window.align_items {
+                let logical_block = match self.align(style) {
                     AlignItems::Start => margin_top,
                     AlignItems::Center => {
-                        window_height / 2 - img.rect.height as i32 / 2 + margin_top - margin_bottom
+                        window_height / 2 - rect.height as i32 / 2 + margin_top - margin_bottom
                     }
-                    AlignItems::End => window_height - img.rect.height as i32 - margin_bottom,
+                    AlignItems::End => window_height - rect.height as i32 - margin_bottom,
+                    AlignItems::Baseline => row_baseline - rect.height as i32,
                 };
 
-                img.rect.y = logical_block as u32;
+                rect.y = self.place(0, logical_block);
 
                 if is_end {
-                    img.rect.x += (*current_x - img.rect.width as i32 - margin_right) as u32;
-                    *current_x -= img.rect.width as i32 + margin_left + margin_right;
+                    rect.x = self.place(rect.x, *current_x - rect.width as i32 - margin_right);
+                    *current_x -= rect.width as i32 + margin_left + margin_right;
                 } else {
-                    img.rect.x += (*current_x + margin_left) as u32;
-                    *current_x += img.rect.width as i32 + margin_left + margin_right;
+                    rect.x = self.place(rect.x, *current_x + margin_left);
+                    *current_x += rect.width as i32 + margin_left + margin_right;
                 }
             }
             Element::Text(Some(text)) => {
@@ -193,12 +421,14 @@ impl OGImageWriter {
                     text.style.margin;
 
                 let line_metrics = &text.metrics;
+                let align = self.align(&text.style);
 
                 // Because ab_glyph draw text that include line_height.
                 let mut system_line_height = line_metrics.max_line_height as u32 / 2;
 
+                let text_align = text.style.text_align.resolve(text.is_rtl);
                 for fragment in &mut text.fragments {
-                    let logical_block = match &self.window.align_items {
+                    let logical_block = match align {
                         AlignItems::Start => margin_top,
                         AlignItems::Center => {
                             window_height / 2 - line_metrics.total_height as i32 / 2 + margin_top
@@ -210,28 +440,35 @@ impl OGImageWriter {
                                 - system_line_height as i32
                                 - margin_bottom
                         }
+                        AlignItems::Baseline => row_baseline - line_metrics.baseline as i32,
                     };
 
-                    fragment.rect.y += logical_block as u32;
+                    fragment.rect.y = self.place(fragment.rect.y, logical_block);
 
-                    fragment.rect.x += match text.style.text_align {
-                        TextAlign::Start => 0,
+                    let shift = match text_align {
+                        TextAlign::Start | TextAlign::Left | TextAlign::Decimal => 0,
                         TextAlign::Center => {
-                            line_metrics.max_line_width as i32 / 2 - fragment.rect.width as i32 / 2
+                            line_metrics.column_end(fragment.column) as i32 / 2
+                                - (fragment.rect.x + fragment.rect.width) as i32 / 2
                         }
-                        TextAlign::End => {
-                            line_metrics.max_line_width as i32 - fragment.rect.width as i32
+                        TextAlign::End | TextAlign::Right => {
+                            line_metrics.column_end(fragment.column) as i32
+                                - (fragment.rect.x + fragment.rect.width) as i32
                         }
-                    } as u32;
+                    };
+                    fragment.rect.x = self.place(fragment.rect.x, shift);
+                    fragment.inset = offset(fragment.inset, shift);
 
                     if is_end {
-                        fragment.rect.x +=
-                            (*current_x - line_metrics.max_line_width as i32 - margin_right) as u32;
+                        fragment.rect.x = self.place(
+                            fragment.rect.x,
+                            *current_x - line_metrics.max_line_width as i32 - margin_right,
+                        );
                     } else {
-                        fragment.rect.x += (*current_x + margin_left) as u32;
+                        fragment.rect.x = self.place(fragment.rect.x, *current_x + margin_left);
                     }
 
-                    if matches!(self.window.align_items, AlignItems::Center) {
+                    if matches!(align, AlignItems::Center) {
                         if fragment.rect.y >= system_line_height {
                             fragment.rect.y -= system_line_height;
                         } else {
@@ -253,23 +490,26 @@ impl OGImageWriter {
 
     fn process_absolute(&mut self, elm: &mut Element) {
         match elm {
-            Element::Img(Some(img)) => {
-                let Margin(margin_top, margin_right, margin_bottom, margin_left) = img.style.margin;
+            Element::Img(Some(Img { rect, style, .. }))
+            | Element::Custom(Some(Custom { rect, style, .. })) => {
+                let Margin(margin_top, margin_right, margin_bottom, margin_left) = style.margin;
 
-                img.rect.x += match (img.style.left, img.style.right) {
+                let x = match (style.left, style.right) {
                     (Some(left), _) => left as i32 + margin_left,
                     (None, Some(right)) => {
-                        self.window.width as i32 - img.rect.width as i32 - right - margin_right
+                        self.window.width as i32 - rect.width as i32 - right - margin_right
                     }
                     (None, None) => margin_left,
-                } as u32;
-                img.rect.y += match (img.style.top, img.style.bottom) {
+                };
+                let y = match (style.top, style.bottom) {
                     (Some(top), _) => top + margin_top,
                     (None, Some(bottom)) => {
-                        self.window.height as i32 - img.rect.height as i32 - bottom - margin_bottom
+                        self.window.height as i32 - rect.height as i32 - bottom - margin_bottom
                     }
                     (None, None) => margin_top,
-                } as u32;
+                };
+                rect.x = self.place(rect.x, x);
+                rect.y = self.place(rect.y, y);
             }
             Element::Text(Some(text)) => {
                 let Margin(margin_top, margin_right, margin_bottom, margin_left) =
@@ -277,44 +517,643 @@ impl OGImageWriter {
 
                 let line_metrics = &text.metrics;
 
-                for fragment in &mut text.fragments {
-                    fragment.rect.x += match (text.style.left, text.style.right) {
-                        (Some(left), _) => left + margin_left,
-                        (None, Some(right)) => {
-                            self.window.width as i32
-                                - line_metrics.max_line_width as i32
-                                - right
-                                - margin_right
-                        }
-                        (None, None) => margin_left,
-                    } as u32;
-                    fragment.rect.y += match (text.style.top, text.style.bottom) {
-                        (Some(top), _) => top + margin_top,
-                        (None, Some(bottom)) => {
-                            let system_line_height = line_metrics.max_line_height as u32 / 2;
+                let x = match (text.style.left, text.style.right) {
+                    (Some(left), _) => left + margin_left,
+                    (None, Some(right)) => {
+                        self.window.width as i32
+                            - line_metrics.max_line_width as i32
+                            - right
+                            - margin_right
+                    }
+                    (None, None) => margin_left,
+                };
+                let y = match (text.style.top, text.style.bottom) {
+                    (Some(top), _) => top + margin_top,
+                    (None, Some(bottom)) => {
+                        let system_line_height = line_metrics.max_line_height as u32 / 2;
 
-                            self.window.height as i32
-                                - system_line_height as i32
-                                - line_metrics.total_height as i32
-                                - bottom
-                                - margin_bottom
-                        }
-                        (None, None) => margin_top,
-                    } as u32;
+                        self.window.height as i32
+                            - system_line_height as i32
+                            - line_metrics.total_height as i32
+                            - bottom
+                            - margin_bottom
+                    }
+                    (None, None) => margin_top,
+                };
+                for fragment in &mut text.fragments {
+                    fragment.rect.x = self.place(fragment.rect.x, x);
+                    fragment.rect.y = self.place(fragment.rect.y, y);
                 }
             }
             _ => {}
         }
     }
 
+    // Position of element moved by `delta`. Element that is moved over the start of the window is placed at the start,
+    // and it is recorded so that validation can report it.
+    fn place(&mut self, position: u32, delta: i32) -> u32 {
+        self.is_clamped |= (position as i32 + delta) < 0;
+        offset(position, delta)
+    }
+
     pub(super) fn process_background(&mut self) -> Result<(), Error> {
         let window = &self.window;
-        let background_color = match &window.background_color {
-            None => return Ok(()),
-            Some(color) => color,
+        let background_color = match (&window.background_token, &window.background_color) {
+            (Some(token), _) => self.theme.color(*token),
+            (None, Some(color)) => *color,
+            (None, None) => return Ok(()),
         };
 
         self.context
             .draw_background_color(background_color.as_image_rgba())
     }
 }
+
+// Position moved by `delta`, that is clamped at 0.
+fn offset(position: u32, delta: i32) -> u32 {
+    (position as i32 + delta).max(0) as u32
+}
+
+// Free space of the main axis that is shared by `flex_grow`, or by auto margins.
+#[derive(Default)]
+struct FreeSpace {
+    free: u32,
+    total: f32,
+    is_row: bool,
+    is_auto: bool,
+}
+
+impl FreeSpace {
+    // Space that is placed before and after the element.
+    fn share(&self, elm: &Element) -> (i32, i32) {
+        let style = match elm.style() {
+            Some(style) if self.total > 0. => style,
+            _ => return (0, 0),
+        };
+        let share = |weight: f32| (self.free as f32 * weight / self.total) as i32;
+        if self.is_auto {
+            let (start, end) = style.margin_auto.main_axis(self.is_row);
+            (share(start as u32 as f32), share(end as u32 as f32))
+        } else {
+            (0, share(style.flex_grow.max(0.)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::collage::{Collage, CollageLayout};
+    use crate::element::{CustomElement, Element};
+    use crate::img::{DecodedImage, ImageInputFormat};
+    use crate::painter::Painter;
+    use crate::style::{
+        AlignItems, Anchor, AnchorEdge, FlexDirection, JustifyContent, MarginAuto, Rgba, Spacer,
+        Style, WindowStyle,
+    };
+    use crate::writer::OGImageWriter;
+    use crate::Error;
+    use image::RgbaImage;
+
+    struct Square(u32);
+
+    impl CustomElement for Square {
+        fn measure(&self, _max_width: u32, _max_height: u32) -> (u32, u32) {
+            (self.0, self.0)
+        }
+
+        fn paint(
+            &self,
+            painter: &mut dyn Painter,
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+        ) -> Result<(), Error> {
+            painter.fill_rect(x, y, width, height, image::Rgba([0, 0, 255, 255]))
+        }
+    }
+
+    // Start and end of the element on the main axis.
+    fn span(elm: &Element, is_row: bool) -> (u32, u32) {
+        let rects = match elm {
+            Element::Img(Some(img)) => vec![img.rect()],
+            Element::Custom(Some(custom)) => vec![custom.rect()],
+            Element::Text(Some(text)) => text.lines().iter().map(|line| line.rect()).collect(),
+            _ => unreachable!(),
+        };
+        rects
+            .iter()
+            .map(|rect| match is_row {
+                true => (rect.x(), rect.x() + rect.width()),
+                false => (rect.y(), rect.y() + rect.height()),
+            })
+            .fold((u32::MAX, 0), |(start, end), (s, e)| {
+                (start.min(s), end.max(e))
+            })
+    }
+
+    fn set_elements(writer: &mut OGImageWriter) {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        for i in 0..2 {
+            writer
+                .set_text(&format!("Text {}", i), Style::default(), Some(font.clone()))
+                .unwrap();
+            writer
+                .set_img_with_decoded(&img, 30, 30, Style::default())
+                .unwrap();
+            writer
+                .set_custom(Box::new(Square(20)), Style::default())
+                .unwrap();
+            let container = OGImageWriter::new(WindowStyle {
+                width: 40,
+                height: 40,
+                background_color: Some(Rgba([255, 0, 0, 255])),
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer.set_container(container, Style::default()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_many_elements_flow_in_order() {
+        for flex_direction in [FlexDirection::Column, FlexDirection::Row] {
+            for justify_content in [
+                JustifyContent::Start,
+                JustifyContent::Center,
+                JustifyContent::End,
+            ] {
+                let is_row = matches!(flex_direction, FlexDirection::Row);
+                let mut writer = OGImageWriter::new(WindowStyle {
+                    width: 1200,
+                    height: 1200,
+                    flex_direction,
+                    justify_content,
+                    ..WindowStyle::default()
+                })
+                .unwrap();
+                set_elements(&mut writer);
+
+                let spans: Vec<(u32, u32)> = writer
+                    .elements()
+                    .unwrap()
+                    .iter()
+                    .map(|elm| span(elm, is_row))
+                    .collect();
+                assert_eq!(spans.len(), 8);
+                for pair in spans.windows(2) {
+                    assert!(pair[0].1 <= pair[1].0, "{:?}", spans);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_optional_elements_take_no_space() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let paint = |subtitle: Option<&str>, logo: Option<&[u8]>| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 200,
+                justify_content: JustifyContent::Center,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer
+                .set_img_with_data_optional(logo, 20, 20, ImageInputFormat::Png, Style::default())
+                .unwrap();
+            writer
+                .set_text("Title", Style::default(), Some(font.clone()))
+                .unwrap();
+            writer
+                .set_text_optional(subtitle, Style::default(), Some(font.clone()))
+                .unwrap();
+            assert_eq!(writer.elements().unwrap().len(), 1);
+            writer.paint().unwrap();
+            writer.into_rgba().unwrap()
+        };
+        assert!(paint(None, None) == paint(Some(""), None));
+
+        let mut expected = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        expected
+            .set_text("Title", Style::default(), Some(font.clone()))
+            .unwrap();
+        expected.paint().unwrap();
+        assert!(paint(None, None) == expected.into_rgba().unwrap());
+    }
+
+    #[test]
+    fn test_anchor() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let anchored = |to: &str, edge| Style {
+            anchor: Some(Anchor {
+                to: to.to_string(),
+                edge,
+                offset: (5, -5),
+            }),
+            ..Style::default()
+        };
+        writer
+            .set_img_with_decoded(&img, 20, 20, anchored("title", AnchorEdge::TopRight))
+            .unwrap();
+        writer
+            .set_text_with_id("title", "Title", Style::default(), Some(font))
+            .unwrap();
+        writer
+            .set_img_with_decoded(&img, 10, 10, anchored("title", AnchorEdge::Center))
+            .unwrap();
+
+        let rects: Vec<(u32, u32, u32, u32)> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| {
+                let rect = elm.border_box();
+                (rect.x, rect.y, rect.width, rect.height)
+            })
+            .collect();
+        // Anchored elements are out of the flow, so only the text is placed by flexbox.
+        let (x, y, width, height) = rects[1];
+        assert_eq!(rects[0], (x + width - 20 + 5, y - 5, 20, 20));
+        assert_eq!(
+            rects[2],
+            (x + (width - 10) / 2 + 5, y + (height - 10) / 2 - 5, 10, 10)
+        );
+    }
+
+    #[test]
+    fn test_anchor_to_image() {
+        let img = DecodedImage::from(RgbaImage::new(20, 10));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let badge = OGImageWriter::new(WindowStyle {
+            width: 30,
+            height: 12,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        // Badge is set before the cover, and pinned to the corner of the cover by id of its style.
+        writer
+            .set_container(
+                badge,
+                Style {
+                    anchor: Some(Anchor {
+                        to: "cover".to_string(),
+                        edge: AnchorEdge::TopRight,
+                        offset: (6, -4),
+                    }),
+                    ..Style::default()
+                },
+            )
+            .unwrap();
+        writer
+            .set_img_with_decoded(
+                &img,
+                100,
+                50,
+                Style {
+                    id: Some("cover".to_string()),
+                    ..Style::default()
+                },
+            )
+            .unwrap();
+
+        let elements = writer.elements().unwrap();
+        let cover = elements[1].border_box();
+        let badge = elements[0].border_box();
+        assert_eq!((cover.x, cover.y), (100, 75));
+        assert_eq!(
+            (badge.x, badge.y, badge.width, badge.height),
+            (cover.x + cover.width - 30 + 6, cover.y - 4, 30, 12)
+        );
+    }
+
+    #[test]
+    fn test_anchor_to_unknown_id() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        let style = Style {
+            anchor: Some(Anchor {
+                to: "missing".to_string(),
+                edge: AnchorEdge::TopLeft,
+                offset: (0, 0),
+            }),
+            ..Style::default()
+        };
+        writer.set_img_with_decoded(&img, 10, 10, style).unwrap();
+
+        assert!(matches!(
+            writer.paint(),
+            Err(Error::NotFoundElementId(id)) if id == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        let img = DecodedImage::from(RgbaImage::new(160, 90));
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        let style = Style {
+            aspect_ratio: Some(16. / 9.),
+            ..Style::default()
+        };
+        writer
+            .set_img_with_decoded(&img, 320, 0, style.clone())
+            .unwrap();
+        // Width is computed from height when width is 0.
+        writer
+            .set_img_with_decoded(&img, 0, 90, style.clone())
+            .unwrap();
+        writer
+            .set_custom(Box::new(Square(32)), style.clone())
+            .unwrap();
+        writer
+            .set_collage(
+                Collage::new(CollageLayout::Grid { columns: 1 }, 64, 0),
+                style.clone(),
+            )
+            .unwrap();
+        let container = OGImageWriter::new(WindowStyle {
+            width: 32,
+            height: 10,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let square = Style {
+            aspect_ratio: Some(1.),
+            ..Style::default()
+        };
+        writer.set_container(container, square).unwrap();
+
+        let sizes: Vec<(u32, u32)> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| {
+                let rect = elm.border_box();
+                (rect.width, rect.height)
+            })
+            .collect();
+        assert_eq!(sizes, [(320, 180), (160, 90), (32, 18), (64, 36), (32, 32)]);
+
+        for ratio in [0., -1., f32::NAN, f32::INFINITY] {
+            let style = Style {
+                aspect_ratio: Some(ratio),
+                ..Style::default()
+            };
+            let is_invalid = |result: Result<(), Error>| {
+                matches!(result.unwrap_err().inner(), Error::InvalidAspectRatio(_))
+            };
+            assert!(is_invalid(writer.set_img_with_decoded(
+                &img,
+                320,
+                0,
+                style.clone()
+            )));
+            assert!(is_invalid(writer.set_custom(Box::new(Square(32)), style)));
+        }
+    }
+
+    #[test]
+    fn test_flex_grow() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        for justify_content in [
+            JustifyContent::Start,
+            JustifyContent::Center,
+            JustifyContent::End,
+        ] {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 200,
+                justify_content,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            let style = Style {
+                flex_grow: 1.,
+                ..Style::default()
+            };
+            writer.set_img_with_decoded(&img, 40, 40, style).unwrap();
+            writer
+                .set_img_with_decoded(&img, 40, 40, Style::default())
+                .unwrap();
+
+            let spans: Vec<(u32, u32)> = writer
+                .elements()
+                .unwrap()
+                .iter()
+                .map(|elm| span(elm, false))
+                .collect();
+            // Footer is pushed to the bottom by the grown title.
+            assert_eq!(spans, vec![(0, 40), (160, 200)]);
+        }
+    }
+
+    #[test]
+    fn test_flex_shrink() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 200,
+            height: 150,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let style = Style {
+            flex_shrink: 1.,
+            ..Style::default()
+        };
+        writer
+            .set_text(&"Long title ".repeat(20), style, Some(font))
+            .unwrap();
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+
+        let elements = writer.elements().unwrap();
+        let Element::Text(Some(text)) = elements[0] else {
+            unreachable!()
+        };
+        assert!(text.is_overflow());
+        assert!(span(elements[0], false).1 <= span(elements[1], false).0);
+        assert!(span(elements[1], false).1 <= 150);
+    }
+
+    #[test]
+    fn test_margin_auto() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let rects = |styles: Vec<Style>| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 200,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            for style in styles {
+                writer.set_img_with_decoded(&img, 40, 40, style).unwrap();
+            }
+            writer
+                .elements()
+                .unwrap()
+                .iter()
+                .map(|elm| {
+                    let rect = elm.border_box();
+                    (rect.x, rect.y)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let centered = Style {
+            margin_auto: MarginAuto(true, true, true, true),
+            ..Style::default()
+        };
+        assert_eq!(rects(vec![centered]), vec![(130, 80)]);
+
+        // Footer is pushed to the end, and the first image is centered horizontally.
+        let footer = Style {
+            margin_auto: MarginAuto(true, false, false, false),
+            ..Style::default()
+        };
+        let title = Style {
+            margin_auto: MarginAuto(false, true, false, true),
+            ..Style::default()
+        };
+        assert_eq!(
+            rects(vec![title, Style::default(), footer]),
+            vec![(130, 0), (0, 40), (0, 160)]
+        );
+    }
+
+    #[test]
+    fn test_align_self() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+        let style = Style {
+            align_self: Some(AlignItems::Start),
+            ..Style::default()
+        };
+        writer.set_img_with_decoded(&img, 40, 40, style).unwrap();
+
+        let rects: Vec<(u32, u32)> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .map(|elm| {
+                let rect = elm.border_box();
+                (rect.x, rect.y)
+            })
+            .collect();
+        assert_eq!(rects, vec![(0, 80), (40, 0)]);
+    }
+
+    #[test]
+    fn test_spacer() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+        writer.set_spacer(Spacer::Flex(1.)).unwrap();
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+        writer.set_spacer(Spacer::Fixed(20)).unwrap();
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+
+        let spans: Vec<(u32, u32)> = writer
+            .elements()
+            .unwrap()
+            .iter()
+            .step_by(2)
+            .map(|elm| span(elm, false))
+            .collect();
+        assert_eq!(spans, vec![(0, 40), (100, 140), (160, 200)]);
+    }
+
+    #[test]
+    fn test_empty_window() {
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        for justify_content in [JustifyContent::Center, JustifyContent::End] {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                justify_content,
+                align_items: AlignItems::End,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer
+                .set_img_with_decoded(&img, 40, 40, Style::default())
+                .unwrap();
+            writer
+                .set_text("Hello", Style::default(), Some(font.clone()))
+                .unwrap();
+            writer
+                .set_text("", Style::default(), Some(font.clone()))
+                .unwrap();
+
+            // Elements that are placed over the start of the window are moved to the start.
+            let spans: Vec<(u32, u32)> = writer
+                .elements()
+                .unwrap()
+                .iter()
+                .take(2)
+                .map(|elm| span(elm, true))
+                .collect();
+            assert!(spans.iter().all(|&(start, _)| start == 0));
+
+            writer.paint().unwrap();
+            assert_eq!(writer.into_rgba().unwrap().dimensions(), (0, 0));
+        }
+
+        let writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            writer.encode(crate::ImageOutputFormat::Png),
+            Err(Error::InvalidDimensions {
+                width: 100,
+                height: 0,
+                ..
+            })
+        ));
+    }
+}
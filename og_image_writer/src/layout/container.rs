@@ -1,30 +1,107 @@
-use crate::element::{Element, Img, Rect};
+use crate::element::CustomElement;
 use crate::img::round;
-use crate::style::Style;
+use crate::painter::Painter;
+use crate::stack::Stack;
+use crate::style::{BorderRadius, Style};
 use crate::writer::OGImageWriter;
 use crate::Error;
+use image::RgbaImage;
+use std::cell::RefCell;
 
-impl OGImageWriter {
-    pub(crate) fn process_container(
-        &mut self,
-        writer: &mut OGImageWriter,
-        style: Style,
+// Child writers that are laid out with the size of the first window, and painted when the parent is painted.
+struct Container {
+    stack: RefCell<Stack>,
+    border_radius: BorderRadius,
+    // Painted child is kept, because the parent may paint the same layout several times like strips.
+    image: RefCell<Option<RgbaImage>>,
+}
+
+impl CustomElement for Container {
+    fn measure(&self, _max_width: u32, _max_height: u32) -> (u32, u32) {
+        self.stack.borrow().size()
+    }
+
+    fn paint(
+        &self,
+        painter: &mut dyn Painter,
+        x: u32,
+        y: u32,
+        _width: u32,
+        _height: u32,
     ) -> Result<(), Error> {
-        let mut image = match writer.context.image.take() {
-            Some(image) => image,
-            None => return Err(Error::NotFoundContainerImage),
+        let mut image = self.image.borrow_mut();
+        if image.is_none() {
+            let mut painted = self.stack.borrow_mut().paint()?;
+            // TODO: support border for image
+            let mut radius = self.border_radius;
+            round(&mut painted, &mut radius);
+            *image = Some(painted);
+        }
+        match &*image {
+            Some(image) => painter.draw_image(image, x, y),
+            None => Err(Error::NotFoundContainerImage),
+        }
+    }
+
+    fn texts(&self) -> Vec<String> {
+        self.stack.borrow_mut().texts()
+    }
+
+    fn missing_chars(&self) -> Vec<char> {
+        self.stack.borrow_mut().missing_chars()
+    }
+}
+
+impl OGImageWriter {
+    pub(crate) fn process_container(&mut self, stack: Stack, style: Style) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
+        let container = Container {
+            stack: RefCell::new(stack),
+            border_radius: style.border_radius,
+            image: RefCell::new(None),
         };
+        self.process_custom(Box::new(container), style)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::element::Element;
+    use crate::style::{AlignItems, JustifyContent, Rgba, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
 
-        // TODO: support border for image
-        round(&mut image, &mut style.border_radius.clone());
+    #[test]
+    fn test_container_is_painted_with_parent() {
+        let child = OGImageWriter::new(WindowStyle {
+            width: 20,
+            height: 10,
+            background_color: Some(Rgba([255, 0, 0, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let mut parent = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 50,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        parent.set_container(child, Style::default()).unwrap();
 
-        let img = Element::Img(Some(Img::new(
-            image,
-            Rect::new(0, 0, writer.window.width, writer.window.height),
-            style,
-        )));
+        // Container is laid out with the size of its window before it is painted.
+        match parent.elements().unwrap()[0] {
+            Element::Custom(Some(custom)) => {
+                let rect = custom.rect();
+                assert_eq!((rect.x(), rect.y()), (40, 20));
+                assert_eq!((rect.width(), rect.height()), (20, 10));
+            }
+            elm => panic!("unexpected element: {:?}", elm),
+        }
 
-        self.process_img(img, writer.window.width, writer.window.height);
-        Ok(())
+        parent.paint().unwrap();
+        let image = parent.into_rgba().unwrap();
+        assert_eq!(image.get_pixel(50, 25).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 0, 0]);
     }
 }
@@ -0,0 +1,28 @@
+use crate::element::{Custom, CustomElement, Element, Rect};
+use crate::style::Style;
+use crate::writer::OGImageWriter;
+use crate::Error;
+
+impl OGImageWriter {
+    pub(crate) fn process_custom(
+        &mut self,
+        element: Box<dyn CustomElement>,
+        style: Style,
+    ) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
+        let (width, height) = element.measure(self.window.width, self.window.height);
+        let (width, height) = style.constrained_size(width, height)?;
+        self.limits.check_element_size(width, height)?;
+
+        let custom = Element::Custom(Some(Custom::new(
+            element,
+            Rect::new(0, 0, width, height),
+            style,
+        )));
+
+        // Custom element is laid out in the same way as image.
+        self.process_img(custom, width, height);
+
+        Ok(())
+    }
+}
@@ -1,23 +1,27 @@
-use image::ImageError;
-
+use crate::collage::Collage;
 use crate::element::{Element, Img, Rect};
-use crate::img::{open_and_resize, open_and_resize_with_data, round, ImageInfo, ImageInputFormat};
+use crate::img::{
+    check_size, open_and_resize, open_and_resize_with_data, resize_decoded, round, DecodedImage,
+    ImageInfo, ImageInputFormat,
+};
 use crate::style::{FlexDirection, Margin, Style};
 use crate::writer::OGImageWriter;
 use crate::Error;
 use std::str;
 
 impl OGImageWriter {
-    pub(super) fn process_img(&mut self, img: Element, width: u32, height: u32) {
+    pub(crate) fn process_img(&mut self, img: Element, width: u32, height: u32) {
         let Margin(margin_top, margin_right, margin_bottom, margin_left) = img.margin();
 
         if !img.is_absolute() {
+            // Negative margin can shrink the element, but not the content.
             match self.window.flex_direction {
                 FlexDirection::Column => {
-                    self.content.height += (height as i32 + margin_top + margin_bottom) as u32;
+                    self.content.height +=
+                        (height as i32 + margin_top + margin_bottom).max(0) as u32;
                 }
                 FlexDirection::Row => {
-                    self.content.width += (width as i32 + margin_left + margin_right) as u32;
+                    self.content.width += (width as i32 + margin_left + margin_right).max(0) as u32;
                 }
             }
         }
@@ -32,16 +36,18 @@ impl OGImageWriter {
         height: u32,
         style: Style,
     ) -> Result<(), Error> {
-        let ImageInfo(mut buf, size) = open_and_resize(src, width, height)?;
+        let style = self.theme.resolve(style);
+        let (width, height) = style.constrained_size(width, height)?;
+        self.limits.check_element_size(width, height)?;
+        let ImageInfo(mut buf, size) = open_and_resize(src, width, height, &style)?;
 
         // TODO: support border for image
         round(&mut buf, &mut style.border_radius.clone());
 
-        let img = Element::Img(Some(Img::new(
-            buf,
-            Rect::new(0, 0, size.width, size.height),
-            style,
-        )));
+        let img = Element::Img(Some(Img {
+            scale: size.scale,
+            ..Img::new(buf, Rect::new(0, 0, size.width, size.height), style)
+        }));
 
         self.process_img(img, size.width, size.height);
 
@@ -55,19 +61,71 @@ impl OGImageWriter {
         height: u32,
         format: ImageInputFormat,
         style: Style,
-    ) -> Result<(), ImageError> {
-        let ImageInfo(mut buf, size) = open_and_resize_with_data(data, width, height, format)?;
+    ) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
+        let (width, height) = style.constrained_size(width, height)?;
+        self.limits.check_element_size(width, height)?;
+        let ImageInfo(mut buf, size) =
+            open_and_resize_with_data(data, width, height, format, &style)?;
 
         // TODO: support border for image
         round(&mut buf, &mut style.border_radius.clone());
 
+        let img = Element::Img(Some(Img {
+            scale: size.scale,
+            ..Img::new(buf, Rect::new(0, 0, size.width, size.height), style)
+        }));
+
+        self.process_img(img, size.width, size.height);
+
+        Ok(())
+    }
+
+    pub(crate) fn process_img_with_decoded(
+        &mut self,
+        image: &DecodedImage,
+        width: u32,
+        height: u32,
+        style: Style,
+    ) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
+        let (width, height) = style.constrained_size(width, height)?;
+        self.limits.check_element_size(width, height)?;
+        let ImageInfo(mut buf, size) = resize_decoded(image, width, height, &style)?;
+
+        round(&mut buf, &mut style.border_radius.clone());
+
+        let img = Element::Img(Some(Img {
+            scale: size.scale,
+            ..Img::new(buf, Rect::new(0, 0, size.width, size.height), style)
+        }));
+
+        self.process_img(img, size.width, size.height);
+
+        Ok(())
+    }
+
+    pub(crate) fn process_collage(
+        &mut self,
+        mut collage: Collage,
+        style: Style,
+    ) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
+        (collage.width, collage.height) = style.constrained_size(collage.width, collage.height)?;
+        check_size("collage", collage.width, collage.height)?;
+        self.limits
+            .check_element_size(collage.width, collage.height)?;
+        let mut buf = collage.compose()?;
+
+        round(&mut buf, &mut style.border_radius.clone());
+
         let img = Element::Img(Some(Img::new(
             buf,
-            Rect::new(0, 0, size.width, size.height),
+            Rect::new(0, 0, collage.width, collage.height),
             style,
         )));
 
-        self.process_img(img, size.width, size.height);
+        self.process_img(img, collage.width, collage.height);
 
         Ok(())
     }
@@ -0,0 +1,59 @@
+use crate::element::{Custom, CustomElement, Element, Rect};
+use crate::painter::Painter;
+use crate::style::{FlexDirection, Spacer, Style};
+use crate::writer::OGImageWriter;
+use crate::Error;
+
+// Element that only takes space.
+struct SpacerElement(u32, u32);
+
+impl CustomElement for SpacerElement {
+    fn measure(&self, _max_width: u32, _max_height: u32) -> (u32, u32) {
+        (self.0, self.1)
+    }
+
+    fn paint(
+        &self,
+        _painter: &mut dyn Painter,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl OGImageWriter {
+    pub(crate) fn process_spacer(&mut self, spacer: Spacer) -> Result<(), Error> {
+        // Flexible spacer has no size of its own, and grows into free space.
+        let (size, style) = match spacer {
+            Spacer::Fixed(size) => (size, Style::default()),
+            Spacer::Flex(grow) => (
+                0,
+                Style {
+                    flex_grow: grow,
+                    ..Style::default()
+                },
+            ),
+        };
+        let (width, height) = match self.window.flex_direction {
+            FlexDirection::Column => (0, size),
+            FlexDirection::Row => (size, 0),
+        };
+        // Spacer spans the window across `flex_direction`.
+        match self.window.flex_direction {
+            FlexDirection::Column => self.limits.check_element_size(self.window.width, size)?,
+            FlexDirection::Row => self.limits.check_element_size(size, self.window.height)?,
+        }
+
+        let spacer = Element::Custom(Some(Custom::new(
+            Box::new(SpacerElement(width, height)),
+            Rect::new(0, 0, width, height),
+            style,
+        )));
+        self.process_img(spacer, width, height);
+
+        Ok(())
+    }
+}
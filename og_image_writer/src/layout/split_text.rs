@@ -1,42 +1,58 @@
 use std::fmt::Debug;
 use std::ops::Range;
+use std::sync::Arc;
 
-use crate::char::RenderingCharIndices;
+use crate::char::{layout_char, RenderingCharIndices};
+use crate::emoji_image::{emoji_cluster_len, EmojiResolver};
 use crate::font::match_font_family;
 use crate::font_context::{FontContext, FontIndex, FontIndexStore};
 use crate::font_trait::Font;
 use crate::glyph::Glyph;
+use crate::script::itemize;
+use crate::script_image::{needs_shaping, word_len, ScriptImage, ScriptRenderer};
 use crate::style::Style;
 use crate::Error;
+use image::RgbaImage;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct SplitText {
     pub(crate) text: String,
     pub(crate) style: Option<Style>,
-    pub(crate) font: Option<Box<dyn Font>>,
+    // Fonts in priority order like CSS font stack. Fonts are shared with texts that are split from this text.
+    pub(crate) fonts: Vec<Arc<dyn Font>>,
     // Fast path for glyphs.
     pub(crate) range: Range<usize>,
     pub(crate) glyphs: Vec<Glyph>,
+    // Images of emoji resolved by EmojiResolver.
+    pub(crate) emoji: Vec<RgbaImage>,
+    // Images of words rendered by ScriptRenderer.
+    pub(crate) script_images: Vec<ScriptImage>,
+    // Ruby annotation drawn above this text.
+    pub(crate) ruby: Option<String>,
 }
 
 impl SplitText {
     // Set bundled glyphs with text range that has same font.
     // When all text have same font then glyphs length is 1.
     // Glyph has text range bundled with same font.
+    // Chars are drawn with the first font that has them in child fonts, parent font and its fallback fonts,
+    // and fonts of FontContext.
+    // Words in script runs that need shaping or that fonts don't have are drawn with images of ScriptRenderer.
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn set_glyphs(
         &mut self,
+        parent_style: &Style,
         parent_font: &Option<impl Font>,
+        parent_fallbacks: &[Arc<dyn Font>],
         current_range_start: &mut usize,
         font_context: &FontContext,
+        emoji_resolver: Option<&dyn EmojiResolver>,
+        script_renderer: Option<&dyn ScriptRenderer>,
     ) -> Result<(), Error> {
         let mut glyphs = vec![];
-        // TODO: Handle parent font as Vec
-        let parent_font_index = 0;
 
         let text = &self.text;
-        let child_font = &self.font;
-        // TODO: Handle child font as Vec
-        let child_font_index = 0;
+        let child_fonts = &self.fonts;
 
         let mut current_range_end = *current_range_start;
 
@@ -44,23 +60,94 @@ impl SplitText {
         let mut font_index_store: Option<FontIndexStore> = None;
         let mut prev_font_index_store: Option<FontIndexStore> = None;
 
-        for (_, _, ch, _) in RenderingCharIndices::from_str(text) {
-            let has_parent_font = match parent_font {
-                Some(parent_font) => match_font_family(ch, parent_font),
-                None => false,
-            };
-            let has_child_font = match child_font {
-                Some(font) => match_font_family(ch, &**font),
-                None => false,
-            };
+        // Chars in emoji cluster until this position share the emoji image.
+        let mut emoji_end = 0;
+        let mut emoji = vec![];
+
+        // Chars in word until this position share the image of ScriptRenderer.
+        let font_size = self.style.as_ref().unwrap_or(parent_style).font_size;
+        let mut script_end = 0;
+        let mut script_images = vec![];
+        let mut is_rendered_run = false;
+
+        // Font is selected for each script run, so that chars in a word use same font.
+        // When no font has all chars in the run, font is selected for each char.
+        let mut runs = itemize(text).into_iter();
+        let mut run_end = 0;
+        let mut run_font_index_store = None;
 
-            if has_child_font {
-                font_index_store = Some(FontIndexStore::Child(FontIndex(child_font_index)));
-            } else if has_parent_font {
-                font_index_store = Some(FontIndexStore::Parent(FontIndex(parent_font_index)));
+        for (_, i, ch, len) in RenderingCharIndices::from_str(text) {
+            if i >= run_end {
+                if let Some(run) = runs.next() {
+                    run_end = run.end;
+                    let chars: Vec<char> = RenderingCharIndices::from_str(&text[run])
+                        .map(|(_, _, ch, _)| layout_char(ch))
+                        .collect();
+                    run_font_index_store = select_font_for_run(
+                        &chars,
+                        parent_font,
+                        parent_fallbacks,
+                        child_fonts,
+                        font_context,
+                    );
+                    is_rendered_run = script_renderer.is_some()
+                        && (needs_shaping(&text[i..run_end])
+                            || run_font_index_store.is_none()
+                                && chars.iter().any(|&ch| {
+                                    !child_fonts
+                                        .iter()
+                                        .any(|font| match_font_family(ch, &**font))
+                                        && parent_position(parent_font, parent_fallbacks, |font| {
+                                            match_font_family(ch, font)
+                                        })
+                                        .is_none()
+                                        && font_context.select_font_family(ch).is_err()
+                                }));
+                }
+            }
+
+            if is_rendered_run && i >= script_end && !ch.is_whitespace() {
+                let len = word_len(&text[i..run_end]);
+                let image = script_renderer
+                    .and_then(|renderer| renderer.render(&text[i..i + len], font_size));
+                if let Some(image) = image {
+                    script_end = i + len;
+                    script_images.push(image);
+                }
+            }
+
+            if i >= emoji_end {
+                let image = emoji_resolver.and_then(|resolver| {
+                    let len = emoji_cluster_len(&text[i..])?;
+                    resolver
+                        .resolve(&text[i..i + len])
+                        .map(|image| (len, image))
+                });
+                if let Some((len, image)) = image {
+                    emoji_end = i + len;
+                    emoji.push(image);
+                }
+            }
+
+            let font_ch = layout_char(ch);
+            let has_ch = |font: &dyn Font| match_font_family(font_ch, font);
+            let child_font_index = child_fonts.iter().position(|font| has_ch(&**font));
+            let parent_font_index = parent_position(parent_font, parent_fallbacks, has_ch);
+
+            if i < emoji_end {
+                font_index_store = Some(FontIndexStore::Emoji(FontIndex(emoji.len() - 1)));
+            } else if i < script_end {
+                font_index_store = Some(FontIndexStore::Script(FontIndex(script_images.len() - 1)));
+            } else if run_font_index_store.is_some() {
+                font_index_store = run_font_index_store.clone();
+            } else if let Some(idx) = child_font_index {
+                font_index_store = Some(FontIndexStore::Child(FontIndex(idx)));
+            } else if let Some(idx) = parent_font_index {
+                font_index_store = Some(FontIndexStore::Parent(FontIndex(idx)));
             } else {
-                font_index_store =
-                    Some(FontIndexStore::Global(font_context.select_font_family(ch)?));
+                font_index_store = Some(FontIndexStore::Global(
+                    font_context.select_font_family(font_ch)?,
+                ));
             }
 
             let is_equal_font_index_store = match (&font_index_store, &prev_font_index_store) {
@@ -82,13 +169,15 @@ impl SplitText {
                 *current_range_start = current_range_end;
             }
 
-            current_range_end += ch.to_string().len();
+            current_range_end += len;
         }
 
         let font_index_store = match prev_font_index_store.take() {
             Some(store) => store,
             None => {
                 self.glyphs.append(&mut glyphs);
+                self.emoji.append(&mut emoji);
+                self.script_images.append(&mut script_images);
                 *current_range_start = current_range_end;
                 return Ok(());
             }
@@ -101,17 +190,60 @@ impl SplitText {
         *current_range_start = current_range_end;
 
         self.glyphs.append(&mut glyphs);
+        self.emoji.append(&mut emoji);
+        self.script_images.append(&mut script_images);
 
         Ok(())
     }
 
+    pub(crate) fn font(&self, idx: &FontIndex) -> Option<&dyn Font> {
+        self.fonts.get(idx.0).map(|font| &**font)
+    }
+
+    // Glyphs are sorted by range, so the glyph of `range` is found by binary search.
     pub(crate) fn get_glyphs_from_char_range(&self, range: Range<usize>) -> Option<&Glyph> {
-        for glyph in &self.glyphs {
-            if glyph.range.start <= range.start && range.end <= glyph.range.end {
-                return Some(glyph);
-            }
-        }
-        None
+        let first = self
+            .glyphs
+            .partition_point(|glyph| glyph.range.end < range.start);
+        self.glyphs[first..]
+            .iter()
+            .take_while(|glyph| glyph.range.start <= range.start)
+            .find(|glyph| range.end <= glyph.range.end)
+    }
+}
+
+fn select_font_for_run(
+    chars: &[char],
+    parent_font: &Option<impl Font>,
+    parent_fallbacks: &[Arc<dyn Font>],
+    child_fonts: &[Arc<dyn Font>],
+    font_context: &FontContext,
+) -> Option<FontIndexStore> {
+    let has_all = |font: &dyn Font| chars.iter().all(|&ch| match_font_family(ch, font));
+    if let Some(idx) = child_fonts.iter().position(|font| has_all(&**font)) {
+        return Some(FontIndexStore::Child(FontIndex(idx)));
+    }
+    if let Some(idx) = parent_position(parent_font, parent_fallbacks, has_all) {
+        return Some(FontIndexStore::Parent(FontIndex(idx)));
+    }
+    font_context
+        .select_font_family_for_run(chars)
+        .map(FontIndexStore::Global)
+}
+
+// Index of the first font that satisfies `has` in parent font and its fallback fonts.
+// Parent font is index 0, and fallback fonts follow it.
+fn parent_position(
+    parent_font: &Option<impl Font>,
+    parent_fallbacks: &[Arc<dyn Font>],
+    has: impl Fn(&dyn Font) -> bool,
+) -> Option<usize> {
+    match parent_font {
+        Some(font) if has(font) => Some(0),
+        _ => parent_fallbacks
+            .iter()
+            .position(|font| has(&**font))
+            .map(|idx| idx + 1),
     }
 }
 
@@ -120,9 +252,12 @@ mod test {
     use crate::{
         font::test_utils::FontMock,
         font_context::{FontContext, FontIndex, FontIndexStore},
+        font_trait::Font,
+        style::Style,
     };
 
     use super::SplitText;
+    use std::sync::Arc;
 
     #[test]
     fn test_set_glyphs() {
@@ -135,9 +270,12 @@ mod test {
         let mut split_text = SplitText {
             text: text.to_string(),
             style: None,
-            font: Some(Box::new(child_font)),
+            fonts: vec![Arc::new(child_font)],
             range: 0..text.len(),
             glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
         };
 
         let mut current_range_start = 5;
@@ -147,7 +285,15 @@ mod test {
         font_context.push_font(Box::new(global_mark_font));
 
         split_text
-            .set_glyphs(&Some(parent_font), &mut current_range_start, &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(parent_font),
+                &[],
+                &mut current_range_start,
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         assert!(!split_text.glyphs.is_empty());
@@ -180,4 +326,100 @@ mod test {
             FontIndexStore::Global(FontIndex(1))
         );
     }
+
+    #[test]
+    fn test_set_glyphs_with_script_run() {
+        let text = "Web(開発)";
+
+        // Closing paren is drawn with same font as CJK run.
+        let child_font = FontMock::new(Some("Web()"));
+        let global_font = FontMock::new(Some("開発()"));
+        let mut split_text = SplitText {
+            text: text.to_string(),
+            style: None,
+            fonts: vec![Arc::new(child_font)],
+            range: 0..text.len(),
+            glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
+        };
+
+        let mut current_range_start = 0;
+
+        let mut font_context = FontContext::new();
+        font_context.push_font(Box::new(global_font));
+
+        split_text
+            .set_glyphs(
+                &Style::default(),
+                &None::<FontMock>,
+                &[],
+                &mut current_range_start,
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let ranges: Vec<_> = split_text
+            .glyphs
+            .iter()
+            .map(|glyph| (glyph.range.clone(), glyph.font_index_store.clone()))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0..4, FontIndexStore::Child(FontIndex(0))),
+                (4..11, FontIndexStore::Global(FontIndex(0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_glyphs_with_font_stack() {
+        let text = "abcdghef";
+        let mut split_text = SplitText {
+            text: text.to_string(),
+            style: None,
+            fonts: vec![
+                Arc::new(FontMock::new(Some("ab"))),
+                Arc::new(FontMock::new(Some("abcd"))),
+            ],
+            range: 0..text.len(),
+            glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
+        };
+
+        let mut current_range_start = 0;
+        let parent_fallbacks: Vec<Arc<dyn Font>> = vec![Arc::new(FontMock::new(Some("efgh")))];
+        split_text
+            .set_glyphs(
+                &Style::default(),
+                &Some(FontMock::new(Some("gh"))),
+                &parent_fallbacks,
+                &mut current_range_start,
+                &FontContext::new(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        let ranges: Vec<_> = split_text
+            .glyphs
+            .iter()
+            .map(|glyph| (glyph.range.clone(), glyph.font_index_store.clone()))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0..2, FontIndexStore::Child(FontIndex(0))),
+                (2..4, FontIndexStore::Child(FontIndex(1))),
+                (4..6, FontIndexStore::Parent(FontIndex(0))),
+                (6..8, FontIndexStore::Parent(FontIndex(1))),
+            ]
+        );
+    }
 }
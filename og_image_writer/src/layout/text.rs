@@ -1,17 +1,32 @@
 use super::split_text::SplitText;
 use super::textarea::TextArea;
-use crate::char::{CharFlags, RevRenderingCharIndices};
+use crate::char::{display_text, layout_char, RenderingCharIndices, RevRenderingCharIndices};
 use crate::element::{Element, Fragment, LineMetrics, Rect, Text};
 use crate::font::{match_font_family, whitespace_width, FontArc, FontMetrics};
+use crate::font_context::FontIndexStore;
 use crate::font_trait::Font;
 use crate::line_breaker::LineBreaker;
 use crate::renderer::FontSetting;
-use crate::style::{FlexDirection, Margin, Position, Style, TextOverflow};
+use crate::script::is_rtl;
+use crate::style::{
+    Direction, FlexDirection, Margin, Position, Style, TextAlign, TextOverflow, TextWrap,
+};
 use crate::writer::OGImageWriter;
 use crate::Error;
 use std::cell::RefCell;
+use std::ops::Range;
 use std::str;
 
+// Lines of text that are placed relative to the text box.
+pub(crate) struct TextLayout {
+    // Text that ellipsis is applied to.
+    pub(crate) text: String,
+    pub(crate) lines: Vec<Fragment>,
+    pub(crate) metrics: LineMetrics,
+    pub(crate) is_overflow: bool,
+    pub(crate) is_rtl: bool,
+}
+
 impl OGImageWriter {
     pub(crate) fn process_text(
         &mut self,
@@ -21,6 +36,7 @@ impl OGImageWriter {
         // Parent font that effect child element
         font: Option<FontArc>,
     ) -> Result<(), Error> {
+        let style = self.theme.resolve(style);
         let window_width = self.window.width as f32;
 
         let Margin(margin_top, margin_right, margin_bottom, margin_left) = style.margin;
@@ -40,112 +56,338 @@ impl OGImageWriter {
         } - left
             - right;
 
-        let text = textarea.borrow().as_string();
-
-        textarea
-            .borrow_mut()
-            .set_glyphs(&font, &self.font_context)?;
-
-        let mut line_breaker = LineBreaker::new(&text);
-        line_breaker.break_text(
-            text_area_width as f32,
+        let text_layout = self.layout_text(
+            &mut textarea.borrow_mut(),
             &style,
             &font,
-            &textarea.borrow(),
+            text_area_width as f32,
+        )?;
+        let total_height = text_layout.metrics.total_height;
+        let max_line_width = text_layout.metrics.max_line_width;
+
+        let text_elm = Element::Text(Some(Text {
+            is_overflow: text_layout.is_overflow,
+            is_rtl: text_layout.is_rtl,
+            ..Text::new(
+                text_layout.text,
+                text_layout.lines,
+                text_layout.metrics,
+                style,
+                font,
+                textarea.into_inner(),
+            )
+        }));
+
+        // TODO: refactor
+        if !text_elm.is_absolute() {
+            match self.window.flex_direction {
+                FlexDirection::Column => {
+                    self.content.height +=
+                        (total_height as i32 + margin_top + margin_bottom).max(0) as u32;
+                }
+                FlexDirection::Row => {
+                    self.content.width +=
+                        (max_line_width as i32 + margin_left + margin_right).max(0) as u32;
+                }
+            }
+        }
+
+        self.tree.0.push(text_elm);
+
+        Ok(())
+    }
+
+    // Break text into lines in `width`, and place lines without painting.
+    pub(crate) fn layout_text(
+        &self,
+        textarea: &mut TextArea,
+        style: &Style,
+        font: &Option<FontArc>,
+        width: f32,
+    ) -> Result<TextLayout, Error> {
+        // Direction is resolved before bidi controls are stripped, so that isolated text doesn't decide it.
+        let is_rtl = match style.direction {
+            Direction::Auto => is_rtl(&textarea.as_string()),
+            Direction::Ltr => false,
+            Direction::Rtl => true,
+        };
+        textarea.transform_text(style);
+        textarea.scale_emoji_only(style);
+        let text = textarea.as_string();
+
+        textarea.set_glyphs(
+            style,
+            font,
             &self.font_context,
+            self.emoji_resolver.as_deref(),
+            self.script_renderer.as_deref(),
         )?;
 
+        // Lines are broken with width of column, and flow into balanced columns.
+        let columns = style.columns.max(1) as usize;
+        let column_gap = if columns > 1 {
+            style.column_gap as f32
+        } else {
+            0.
+        };
+        let column_width = (width - column_gap * (columns - 1) as f32) / columns as f32;
+
+        let mut line_breaker = LineBreaker::with_buffers(&text, self.line_buffers.take());
+        line_breaker.break_text(column_width, style, font, textarea, &self.font_context)?;
+        match style.text_wrap {
+            TextWrap::Wrap => {}
+            TextWrap::Balance => line_breaker.balance(column_width, style, textarea)?,
+            TextWrap::Pretty => line_breaker.avoid_orphan(column_width, style, textarea)?,
+        }
+
         let max_line_height = line_breaker.max_line_height;
-        let max_line_width = line_breaker.max_line_width;
+        let (max_line_width, column_width) = if columns > 1 {
+            (width, column_width)
+        } else {
+            (line_breaker.max_line_width, line_breaker.max_line_width)
+        };
 
-        let mut lines: Vec<Fragment> = vec![];
+        let mut lines: Vec<Fragment> = Vec::with_capacity(line_breaker.lines.len());
 
-        // Calculate line position
+        // Calculate row position
         let mut total_height = 0.;
         let line_height = max_line_height * style.line_height / 2. - max_line_height / 2.;
         let lines_len = line_breaker.lines.len();
-        let mut is_overflow = false;
-        for (i, line) in line_breaker.lines.into_iter().enumerate() {
-            let is_first_line = i == 0;
-            let next_height = if is_first_line {
-                total_height + max_line_height
+        // Ruby is drawn in space reserved above each row.
+        let ruby_height = textarea.ruby_height(style);
+        let mut row_positions = Vec::with_capacity(lines_len.div_ceil(columns));
+        for row in 0..lines_len.div_ceil(columns) {
+            let is_first_row = row == 0;
+            let next_height = if is_first_row {
+                total_height + ruby_height + max_line_height
             } else {
-                total_height + max_line_height + line_height
+                total_height + ruby_height + max_line_height + line_height
             };
 
             match style.max_height {
-                Some(max_height) if next_height > max_height as f32 => {
-                    is_overflow = true;
-                    break;
-                }
+                Some(max_height) if next_height > max_height as f32 => break,
                 _ => {}
             }
 
-            if lines_len == 1 {
-                total_height = next_height;
-                lines.push(Fragment::new(
-                    line.range,
-                    Rect::new(0, 0, line.width as u32, line.height as u32),
-                ));
-                break;
-            }
-
-            let pos_y = total_height;
-            let pos_y = if !is_first_line {
-                pos_y + line_height
+            let pos_y = if is_first_row {
+                total_height + ruby_height
             } else {
-                pos_y
+                total_height + line_height + ruby_height
             };
-
+            row_positions.push(pos_y);
             total_height = next_height;
-            lines.push(Fragment::new(
+        }
+
+        let rows = row_positions.len();
+        let is_overflow = rows * columns < lines_len;
+        for (i, line) in line_breaker
+            .lines
+            .drain(..)
+            .take(rows * columns)
+            .enumerate()
+        {
+            let column = i / rows;
+            let pos_x = column as f32 * (column_width + column_gap);
+            let mut fragment = Fragment::new(
                 line.range,
-                Rect::new(0, pos_y as u32, line.width as u32, line.height as u32),
-            ));
+                Rect::new(
+                    pos_x as u32 + style.line_indent(i == 0),
+                    row_positions[i % rows] as u32,
+                    line.width as u32,
+                    line.height as u32,
+                ),
+            );
+            fragment.column = column;
+            fragment.inset = style.line_indent(i == 0);
+            lines.push(fragment);
         }
+        self.line_buffers.replace(line_breaker.into_buffers());
+
+        let text = match lines.last() {
+            Some(last) if is_overflow => {
+                let end = last.range.end;
+                self.set_ellipsis(&text[0..end], &mut lines, style, font, textarea)?
+            }
+            // Not even one line fits in max height.
+            None if is_overflow => String::new(),
+            _ => text.to_string(),
+        };
 
-        let text = if is_overflow {
-            self.set_ellipsis(
-                &text[0..lines.last().unwrap().range.end],
-                &mut lines,
-                &style,
-                &font,
-                &mut textarea.borrow_mut(),
-            )?
+        let (max_line_width, column_width) = if let TextAlign::Decimal = style.text_align {
+            let right = self.align_decimal(&text, &mut lines, style, font, textarea)?;
+            if columns > 1 {
+                (max_line_width, column_width)
+            } else {
+                (max_line_width.max(right), column_width.max(right))
+            }
         } else {
-            text.to_string()
+            (max_line_width, column_width)
         };
 
-        let text_elm = Element::Text(Some(Text::new(
+        for line in lines.iter_mut() {
+            line.ascent =
+                Some(textarea.line_ascent(line.range.clone(), style, font, &self.font_context));
+            line.advances =
+                Some(self.run_advances(&text, line.range.clone(), style, font, textarea)?);
+        }
+        let baseline = match lines.first() {
+            Some(line) => line.rect.y as f32 + line.ascent.unwrap_or(0.),
+            None => 0.,
+        };
+
+        Ok(TextLayout {
             text,
             lines,
-            LineMetrics::new(total_height as u32, max_line_height, max_line_width),
-            style,
-            font,
-            textarea.into_inner(),
-        )));
+            metrics: LineMetrics {
+                column_width,
+                column_gap,
+                baseline,
+                ..LineMetrics::new(total_height as u32, max_line_height, max_line_width)
+            },
+            is_overflow,
+            is_rtl,
+        })
+    }
 
-        // TODO: refactor
-        if !text_elm.is_absolute() {
-            match self.window.flex_direction {
-                FlexDirection::Column => {
-                    self.content.height +=
-                        (total_height as i32 + margin_top + margin_bottom) as u32;
-                }
-                FlexDirection::Row => {
-                    self.content.width +=
-                        (max_line_width as i32 + margin_left + margin_right) as u32;
+    // Shift lines so that decimal points of lines in the same column are at the same position.
+    // Return the right end of the shifted lines.
+    fn align_decimal(
+        &self,
+        text: &str,
+        lines: &mut [Fragment],
+        style: &Style,
+        font: &Option<FontArc>,
+        textarea: &TextArea,
+    ) -> Result<f32, Error> {
+        let mut points = Vec::with_capacity(lines.len());
+        for line in lines.iter() {
+            let line_text = text[line.range.clone()].trim_end();
+            // Width after decimal point.
+            let tail = match decimal_point(line_text) {
+                Some(idx) => self.text_range_width(
+                    text,
+                    line.range.start + idx..line.range.start + line_text.len(),
+                    style,
+                    font,
+                    textarea,
+                )?,
+                None => 0.,
+            };
+            points.push((line.column, (line.rect.x + line.rect.width) as f32 - tail));
+        }
+
+        let mut right: f32 = 0.;
+        for (line, (column, point)) in lines.iter_mut().zip(&points) {
+            let target = points
+                .iter()
+                .filter(|(c, _)| c == column)
+                .map(|(_, p)| *p)
+                .fold(0., f32::max);
+            let shift = (target - point) as u32;
+            line.rect.x += shift;
+            line.inset += shift;
+            right = right.max((line.rect.x + line.rect.width) as f32);
+        }
+        Ok(right)
+    }
+
+    // Advance of each glyph run in the line of `range`, in the order of `TextArea::glyph_runs`.
+    // Runs are measured in the same way as painting, so that painting doesn't measure texts again.
+    pub(crate) fn run_advances(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        style: &Style,
+        font: &Option<FontArc>,
+        textarea: &TextArea,
+    ) -> Result<Vec<u32>, Error> {
+        let runs = textarea.glyph_runs(range.clone())?;
+        let mut advances = Vec::with_capacity(runs.len());
+        for run in runs {
+            let style = run.split_text.style.as_ref().unwrap_or(style);
+            let is_line_end = run.range.end == range.end;
+            let measure = |font: &dyn Font| {
+                let setting = FontSetting {
+                    size: style.font_size,
+                    letter_spacing: style.letter_spacing,
+                    kern_setting: style.kern_setting,
+                    is_pre: style.white_space.is_pre(),
+                    preserve_spaces: style.white_space.preserves_spaces(),
+                    tabular_nums: style.font_variant_numeric.is_tabular(),
+                };
+                let run_text = display_text(&text[run.range.clone()], is_line_end);
+                self.context.measure_text(font, &setting, &run_text).width as u32
+            };
+            let not_found = || Error::NotFoundSpecifiedFontFamily.at_char(run.range.start);
+            let advance = match &run.glyph.font_index_store {
+                FontIndexStore::Global(idx) => self.font_context.with(idx, measure),
+                FontIndexStore::Parent(idx) => textarea
+                    .parent_font(idx, font.as_ref().map(|font| font as &dyn Font))
+                    .map(measure)
+                    .ok_or_else(not_found)?,
+                FontIndexStore::Child(idx) => run
+                    .split_text
+                    .font(idx)
+                    .map(measure)
+                    .ok_or_else(not_found)?,
+                // Emoji image is a square of font size.
+                FontIndexStore::Emoji(_) => {
+                    (style.font_size as u32 as i32 + style.letter_spacing).max(0) as u32
                 }
-            }
+                FontIndexStore::Script(idx) => match run.split_text.script_images.get(idx.0) {
+                    Some(image) => {
+                        (image.image.width() as i32 + style.letter_spacing).max(0) as u32
+                    }
+                    None => return Err(Error::OutOfRangeText.at_char(run.range.start)),
+                },
+            };
+            advances.push(advance);
         }
+        Ok(advances)
+    }
 
-        self.tree.0.push(text_elm);
+    // Width of text in `range` that is measured in the same way as line breaking.
+    fn text_range_width(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        style: &Style,
+        font: &Option<FontArc>,
+        textarea: &TextArea,
+    ) -> Result<f32, Error> {
+        let setting = FontSetting {
+            size: style.font_size,
+            letter_spacing: style.letter_spacing,
+            kern_setting: style.kern_setting,
+            is_pre: style.white_space.is_pre(),
+            preserve_spaces: style.white_space.preserves_spaces(),
+            tabular_nums: style.font_variant_numeric.is_tabular(),
+        };
 
-        Ok(())
+        let mut width = 0.;
+        let mut chars = RenderingCharIndices::from_str(&text[..range.end]);
+        while let Some((flags, i, ch, len)) = chars.next() {
+            let peek_char = chars.peek_char();
+            if i < range.start {
+                continue;
+            }
+            let ch = layout_char(ch);
+            let extents = textarea.char_extents(
+                ch,
+                peek_char,
+                &flags,
+                font.as_ref().map(|font| font as &dyn Font),
+                i..i + len,
+                &self.font_context,
+                &setting,
+            )?;
+            width += extents.width;
+        }
+        Ok(width)
     }
 
     fn set_ellipsis(
-        &mut self,
+        &self,
         text: &str,
         fragments: &mut Vec<Fragment>,
         style: &Style,
@@ -193,6 +435,8 @@ impl OGImageWriter {
             letter_spacing: style.letter_spacing,
             kern_setting: style.kern_setting,
             is_pre: style.white_space.is_pre(),
+            preserve_spaces: style.white_space.preserves_spaces(),
+            tabular_nums: style.font_variant_numeric.is_tabular(),
         };
 
         let ellipsis_width = match font {
@@ -211,39 +455,19 @@ impl OGImageWriter {
         let mut chars = RevRenderingCharIndices::from_str(text);
         while let Some((flags, i, ch, len)) = chars.next() {
             let peek_char = chars.peek_char();
-            let is_newline = matches!(flags, Some(CharFlags::Newline));
+            let is_newline = flags.is_some();
             let (split_text, _) = textarea.get_glyphs_from_char_range(i..i + len);
-            let extents = match font {
-                Some(font) if match_font_family(ch, font) => {
-                    rev_char_extents(is_newline, style.font_size, split_text, || {
-                        textarea.char_extents(
-                            ch,
-                            peek_char,
-                            &flags,
-                            font,
-                            i..i + len,
-                            &self.font_context,
-                            &setting,
-                        )
-                    })?
-                }
-                _ => {
-                    let idx = self.font_context.select_font_family(ch)?;
-                    self.font_context.with(&idx, |font| {
-                        rev_char_extents(is_newline, style.font_size, split_text, || {
-                            textarea.char_extents(
-                                ch,
-                                peek_char,
-                                &flags,
-                                font,
-                                i..i + len,
-                                &self.font_context,
-                                &setting,
-                            )
-                        })
-                    })?
-                }
-            };
+            let extents = rev_char_extents(is_newline, style.font_size, split_text, || {
+                textarea.char_extents(
+                    ch,
+                    peek_char,
+                    &flags,
+                    font.as_ref().map(|font| font as &dyn Font),
+                    i..i + len,
+                    &self.font_context,
+                    &setting,
+                )
+            })?;
 
             total_char_width += extents.width;
             if total_char_width >= ellipsis_width {
@@ -253,6 +477,8 @@ impl OGImageWriter {
         }
 
         if let Some(fragment) = fragments.last_mut() {
+            // Ellipsis that is wider than the last line replaces only the last line.
+            let split_index = split_index.max(fragment.range.start);
             // shape TextArea with ellipsis
             while let Some(mut split_text) = textarea.0.pop() {
                 if split_text.range.start <= split_index && split_index <= split_text.range.end {
@@ -285,3 +511,67 @@ impl OGImageWriter {
         Ok(text.to_string())
     }
 }
+
+// Byte index of decimal point in line, that is the last `.` followed by digit.
+fn decimal_point(text: &str) -> Option<usize> {
+    text.char_indices()
+        .rev()
+        .find(|&(i, ch)| ch == '.' && text[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::element::Element;
+    use crate::style::{Style, TextOverflow, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    fn lines(writer: &mut OGImageWriter) -> Vec<String> {
+        match writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => text
+                .fragments
+                .iter()
+                .map(|line| text.text()[line.range.clone()].to_string())
+                .collect(),
+            elm => panic!("unexpected element: {:?}", elm),
+        }
+    }
+
+    #[test]
+    fn test_text_overflow() {
+        let font = Vec::from(include_bytes!("../../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+
+        // No line is laid out when even one line doesn't fit in max height.
+        let style = Style {
+            font_size: 20.,
+            max_height: Some(1),
+            text_overflow: TextOverflow::Ellipsis,
+            ..Style::default()
+        };
+        writer
+            .set_text("Hello World", style, Some(font.clone()))
+            .unwrap();
+        assert!(lines(&mut writer).is_empty());
+        writer.paint().unwrap();
+
+        // Ellipsis that is wider than the last line doesn't remove the previous lines.
+        let style = Style {
+            font_size: 20.,
+            max_width: Some(30),
+            max_height: Some(35),
+            text_overflow: TextOverflow::Content("WWWWWWWW".to_string()),
+            ..Style::default()
+        };
+        writer
+            .set_text("i i i i i i i i i i", style, Some(font))
+            .unwrap();
+        assert_eq!(lines(&mut writer), ["i i i i ", "WWWWWWWW"]);
+        writer.paint().unwrap();
+    }
+}
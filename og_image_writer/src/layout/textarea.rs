@@ -1,38 +1,115 @@
-use crate::char::CharFlags;
-use crate::font::{create_font, FontArc, FontMetrics};
-use crate::font_context::{FontContext, FontIndexStore};
+use crate::casing::transform_text;
+use crate::char::{expand_tabs, has_no_glyph, layout_char, strip_bidi_controls, CharFlags};
+use crate::emoji_image::{is_emoji_only, EmojiResolver};
+use crate::font::{create_font, match_font_family, FontArc, FontMetrics};
+use crate::font_context::{FontContext, FontIndex, FontIndexStore, SharedFont};
 use crate::font_trait::Font;
 use crate::glyph::Glyph;
 use crate::renderer::FontSetting;
-use crate::style::Style;
+use crate::script_image::ScriptRenderer;
+use crate::style::{FallbackMetrics, Style, StyleOverride};
 use crate::Error;
-use std::{ops::Range, str};
+use std::{borrow::Cow, ops::Range, str, sync::Arc};
 
 use super::split_text::SplitText;
 
+// Ruby font size relative to base text.
+const RUBY_SCALE: f32 = 0.5;
+// Ruby line height relative to ruby font size.
+const RUBY_LINE_HEIGHT: f32 = 1.2;
+
+pub(crate) fn ruby_size(style: &Style) -> f32 {
+    style.font_size * RUBY_SCALE
+}
+
+// Emoji shortcodes like `:rocket:` are expanded when `emoji-shortcodes` feature is enabled.
+#[cfg(feature = "emoji-shortcodes")]
+fn expand_shortcodes(text: &str) -> Cow<'_, str> {
+    crate::emoji::expand_shortcodes(text)
+}
+
+#[cfg(not(feature = "emoji-shortcodes"))]
+fn expand_shortcodes(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+/// Text with its style in [`TextArea`].
+#[derive(Debug)]
+pub struct TextSpan<'a> {
+    pub text: &'a str,
+    /// Byte range in the whole text.
+    pub range: Range<usize>,
+    /// `None` when parent style is used.
+    pub style: Option<&'a Style>,
+    pub ruby: Option<&'a str>,
+}
+
+// Text in a line that is drawn with the same split text and font.
+pub(crate) struct GlyphRun<'a> {
+    pub(crate) range: Range<usize>,
+    pub(crate) split_text: &'a SplitText,
+    pub(crate) glyph: &'a Glyph,
+}
+
 /// TextArea is box to store each text with style.
 /// For example you can set style to text one by one.
-#[derive(Debug, Default)]
-pub struct TextArea(pub(super) Vec<SplitText>);
+#[derive(Debug, Default, Clone)]
+pub struct TextArea(
+    pub(super) Vec<SplitText>,
+    // Fallback fonts of parent font, that are set by `OGImageWriter::set_textarea_with_fonts()`.
+    pub(crate) Vec<Arc<dyn Font>>,
+);
 
 impl TextArea {
     pub fn new() -> TextArea {
-        TextArea(vec![])
+        TextArea(vec![], vec![])
+    }
+
+    pub(crate) fn char_count(&self) -> usize {
+        self.0.iter().map(|split| split.text.chars().count()).sum()
     }
 
     /// Push text with style.
+    /// `\n` in text always breaks line regardless of `white_space`.
+    /// Emoji shortcodes like `:rocket:` are expanded when `emoji-shortcodes` feature is enabled.
     pub fn push(&mut self, text: &str, style: Style, font: Option<Vec<u8>>) -> Result<(), Error> {
-        let last_range_end = match self.0.iter().last() {
-            Some(split) => split.range.end,
-            None => 0,
-        };
+        let text: &str = &expand_shortcodes(text);
 
-        let font: Option<Box<dyn Font>> = match font {
+        let fonts: Vec<Arc<dyn Font>> = match font {
             Some(font) => match create_font(font) {
-                Ok(font) => Some(Box::new(font)),
+                Ok(font) => vec![Arc::new(font)],
                 Err(_) => return Err(Error::InvalidFontBytes),
             },
-            None => None,
+            None => vec![],
+        };
+        self.push_split(text, style, fonts);
+
+        Ok(())
+    }
+
+    /// Same as [`TextArea::push()`], but font data of [SharedFont] is not copied.
+    pub fn push_with_font(&mut self, text: &str, style: Style, font: &SharedFont) {
+        let text: &str = &expand_shortcodes(text);
+
+        self.push_split(text, style, vec![Arc::new(font.0.clone())]);
+    }
+
+    /// Same as [`TextArea::push_with_font()`], but chars are drawn with the first font in `fonts` that has them,
+    /// like CSS font stack. Parent fonts and fonts in [FontContext] are used after `fonts`.
+    pub fn push_with_fonts(&mut self, text: &str, style: Style, fonts: &[SharedFont]) {
+        let text: &str = &expand_shortcodes(text);
+
+        let fonts = fonts
+            .iter()
+            .map(|font| Arc::new(font.0.clone()) as Arc<dyn Font>)
+            .collect();
+        self.push_split(text, style, fonts);
+    }
+
+    fn push_split(&mut self, text: &str, style: Style, fonts: Vec<Arc<dyn Font>>) {
+        let last_range_end = match self.0.iter().last() {
+            Some(split) => split.range.end,
+            None => 0,
         };
 
         let mut string = String::new();
@@ -41,20 +118,23 @@ impl TextArea {
         let split_text = SplitText {
             text: string,
             style: Some(style),
-            font,
+            fonts,
             range: last_range_end..last_range_end + text.len(),
             glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
         };
 
         self.0.push(split_text);
-
-        Ok(())
     }
 
     /// Push text without style.
     /// Style is override with parent style.
     /// Parent style is set with [`OGImageWriter::set_textarea()`](crate::writer::OGImageWriter::set_textarea).
     pub fn push_text(&mut self, text: &str) {
+        let text: &str = &expand_shortcodes(text);
+
         let last_range_end = match self.0.iter().last() {
             Some(split) => split.range.end,
             None => 0,
@@ -66,14 +146,248 @@ impl TextArea {
         let split_text = SplitText {
             text: string,
             style: None,
-            font: None,
+            fonts: vec![],
             range: last_range_end..last_range_end + text.len(),
             glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
         };
 
         self.0.push(split_text);
     }
 
+    /// Push text with style that is overridden for each char by `char_style`.
+    /// `char_style` is called with index of char in text, and chars with same override are pushed together.
+    /// This is useful for effects like gradient by char.
+    pub fn push_with_char_styles<F>(
+        &mut self,
+        text: &str,
+        style: Style,
+        font: Option<Vec<u8>>,
+        mut char_style: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(usize, char) -> StyleOverride,
+    {
+        let text: &str = &expand_shortcodes(text);
+
+        let fonts: Vec<Arc<dyn Font>> = match font {
+            Some(font) => match create_font(font) {
+                Ok(font) => vec![Arc::new(font)],
+                Err(_) => return Err(Error::InvalidFontBytes),
+            },
+            None => vec![],
+        };
+
+        let mut runs: Vec<(String, StyleOverride)> = vec![];
+        for (i, ch) in text.chars().enumerate() {
+            let style_override = char_style(i, ch);
+            match runs.last_mut() {
+                Some((run, last)) if *last == style_override => run.push(ch),
+                _ => runs.push((ch.to_string(), style_override)),
+            }
+        }
+
+        for (run, style_override) in runs {
+            let last_range_end = match self.0.iter().last() {
+                Some(split) => split.range.end,
+                None => 0,
+            };
+            self.0.push(SplitText {
+                range: last_range_end..last_range_end + run.len(),
+                text: run,
+                style: Some(style_override.apply(&style)),
+                fonts: fonts.clone(),
+                glyphs: vec![],
+                emoji: vec![],
+                script_images: vec![],
+                ruby: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Push text with style, and break line after it like `\n`.
+    /// This is useful for multi-paragraph text.
+    pub fn push_line(
+        &mut self,
+        text: &str,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.push(&format!("{}\n", text), style, font)
+    }
+
+    /// Push text without style, and break line after it like `\n`.
+    pub fn push_line_text(&mut self, text: &str) {
+        self.push_text(&format!("{}\n", text));
+    }
+
+    /// Push base text with ruby annotation like furigana.
+    /// Ruby is drawn above base text with half size of base text,
+    /// and base text is not broken into lines.
+    pub fn push_ruby(
+        &mut self,
+        base: &str,
+        ruby: &str,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        self.push(base, style, font)?;
+        self.set_last_ruby(ruby);
+        Ok(())
+    }
+
+    /// Push base text with ruby annotation without style.
+    /// Style is override with parent style.
+    pub fn push_ruby_text(&mut self, base: &str, ruby: &str) {
+        self.push_text(base);
+        self.set_last_ruby(ruby);
+    }
+
+    fn set_last_ruby(&mut self, ruby: &str) {
+        if let Some(split_text) = self.0.last_mut() {
+            split_text.ruby = Some(ruby.to_string());
+        }
+    }
+
+    /// Insert text with style at byte `index` of the whole text.
+    pub fn insert(
+        &mut self,
+        index: usize,
+        text: &str,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let mut inserted = TextArea::new();
+        inserted.push(text, style, font)?;
+        self.insert_split_texts(index, inserted)
+    }
+
+    /// Insert text without style at byte `index` of the whole text.
+    /// Style is override with parent style.
+    pub fn insert_text(&mut self, index: usize, text: &str) -> Result<(), Error> {
+        let mut inserted = TextArea::new();
+        inserted.push_text(text);
+        self.insert_split_texts(index, inserted)
+    }
+
+    /// Remove text in byte `range` of the whole text.
+    pub fn remove(&mut self, range: Range<usize>) -> Result<(), Error> {
+        let (start, end) = self.split_range(range)?;
+        self.0.drain(start..end);
+        self.update_ranges();
+        Ok(())
+    }
+
+    /// Set style to text in byte `range` of the whole text.
+    /// Font of the text is kept.
+    pub fn set_style(&mut self, range: Range<usize>, style: Style) -> Result<(), Error> {
+        let (start, end) = self.split_range(range)?;
+        for split_text in &mut self.0[start..end] {
+            split_text.style = Some(style.clone());
+        }
+        Ok(())
+    }
+
+    /// Iterate over texts that are pushed or split by editing.
+    pub fn spans(&self) -> impl Iterator<Item = TextSpan<'_>> {
+        self.0.iter().map(|split_text| TextSpan {
+            text: &split_text.text,
+            range: split_text.range.clone(),
+            style: split_text.style.as_ref(),
+            ruby: split_text.ruby.as_deref(),
+        })
+    }
+
+    fn insert_split_texts(&mut self, index: usize, inserted: TextArea) -> Result<(), Error> {
+        let position = self.split_at(index)?;
+        self.0.splice(position..position, inserted.0);
+        self.update_ranges();
+        Ok(())
+    }
+
+    fn split_range(&mut self, range: Range<usize>) -> Result<(usize, usize), Error> {
+        if range.start > range.end {
+            return Err(Error::OutOfRangeText);
+        }
+        let start = self.split_at(range.start)?;
+        let end = self.split_at(range.end)?;
+        Ok((start, end))
+    }
+
+    // Split text at byte `index`, and return position of text that starts at `index`.
+    // Ruby annotation is removed when its base text is split.
+    fn split_at(&mut self, index: usize) -> Result<usize, Error> {
+        for (position, split_text) in self.0.iter_mut().enumerate() {
+            if index == split_text.range.start {
+                return Ok(position);
+            }
+            if index >= split_text.range.end {
+                continue;
+            }
+
+            let offset = index - split_text.range.start;
+            if !split_text.text.is_char_boundary(offset) {
+                return Err(Error::OutOfRangeText);
+            }
+            let next = SplitText {
+                text: split_text.text.split_off(offset),
+                style: split_text.style.clone(),
+                fonts: split_text.fonts.clone(),
+                range: index..split_text.range.end,
+                glyphs: vec![],
+                emoji: vec![],
+                script_images: vec![],
+                ruby: None,
+            };
+            split_text.range.end = index;
+            split_text.ruby = None;
+            self.0.insert(position + 1, next);
+            return Ok(position + 1);
+        }
+
+        match self.0.last() {
+            Some(split_text) if index == split_text.range.end => Ok(self.0.len()),
+            None if index == 0 => Ok(0),
+            _ => Err(Error::OutOfRangeText),
+        }
+    }
+
+    fn update_ranges(&mut self) {
+        let mut range_start = 0;
+        for split_text in self.0.iter_mut() {
+            split_text.range = range_start..range_start + split_text.text.len();
+            range_start = split_text.range.end;
+        }
+    }
+
+    // Whether text can not be broken before char at `idx` because it is inside of ruby base.
+    pub(crate) fn is_in_ruby_base(&self, idx: usize) -> bool {
+        self.0.iter().any(|split_text| {
+            split_text.ruby.is_some() && split_text.range.start < idx && idx < split_text.range.end
+        })
+    }
+
+    pub(crate) fn is_ruby_base_start(&self, idx: usize) -> bool {
+        self.0
+            .iter()
+            .any(|split_text| split_text.ruby.is_some() && split_text.range.start == idx)
+    }
+
+    // Height reserved above each line for ruby.
+    pub(crate) fn ruby_height(&self, parent_style: &Style) -> f32 {
+        self.0
+            .iter()
+            .filter(|split_text| split_text.ruby.is_some())
+            .map(|split_text| {
+                ruby_size(split_text.style.as_ref().unwrap_or(parent_style)) * RUBY_LINE_HEIGHT
+            })
+            .fold(0., f32::max)
+    }
+
     pub(super) fn push_text_with_glyphs(
         &mut self,
         text: &str,
@@ -91,21 +405,78 @@ impl TextArea {
         let mut split_text = SplitText {
             text: string,
             style: None,
-            font: None,
+            fonts: vec![],
             range: last_range_end..last_range_end + text.len(),
             glyphs: vec![],
+            emoji: vec![],
+            script_images: vec![],
+            ruby: None,
         };
 
         let mut current_range_start = last_range_end;
 
-        split_text.set_glyphs(font, &mut current_range_start, font_context)?;
+        split_text.set_glyphs(
+            &Style::default(),
+            font,
+            &self.1,
+            &mut current_range_start,
+            font_context,
+            None,
+            None,
+        )?;
 
         self.0.push(split_text);
 
         Ok(())
     }
 
-    pub(super) fn as_string(&self) -> String {
+    // Apply `text_transform` of each text, strip bidi controls and expand tabs in preformatted text,
+    // and recalculate ranges because case mapping can change text length.
+    pub(crate) fn transform_text(&mut self, parent_style: &Style) {
+        let mut is_word_start = true;
+        // Position in the current line that is used to find next tab stop.
+        let mut column = 0;
+        for split_text in self.0.iter_mut() {
+            let style = split_text.style.as_ref().unwrap_or(parent_style);
+            if let Cow::Owned(text) = transform_text(
+                &split_text.text,
+                style.text_transform,
+                style.lang.as_ref(),
+                is_word_start,
+            ) {
+                split_text.text = text;
+            }
+            if let Cow::Owned(text) = strip_bidi_controls(&split_text.text) {
+                split_text.text = text;
+            }
+            let tab_size = if style.white_space.preserves_spaces() {
+                Some(style.tab_size as usize)
+            } else {
+                None
+            };
+            if let Cow::Owned(text) = expand_tabs(&split_text.text, tab_size, &mut column) {
+                split_text.text = text;
+            }
+            if let Some(ch) = split_text.text.chars().last() {
+                is_word_start = ch.is_whitespace();
+            }
+        }
+        self.update_ranges();
+    }
+
+    // Scale font size of all texts by `emoji_only_scale` when the whole text has only emoji.
+    pub(crate) fn scale_emoji_only(&mut self, parent_style: &Style) {
+        let scale = parent_style.emoji_only_scale;
+        if scale == 1. || !is_emoji_only(&self.as_string()) {
+            return;
+        }
+        for split_text in self.0.iter_mut() {
+            let style = split_text.style.get_or_insert_with(|| parent_style.clone());
+            style.font_size *= scale;
+        }
+    }
+
+    pub(crate) fn as_string(&self) -> String {
         let mut text = String::new();
         for split_text in &self.0 {
             text.push_str(&split_text.text);
@@ -113,27 +484,249 @@ impl TextArea {
         text
     }
 
+    // Split texts and their glyphs are sorted by range, so the split text of `range` is found by binary search.
     pub(crate) fn get_glyphs_from_char_range(
         &self,
         range: Range<usize>,
     ) -> (Option<&SplitText>, Option<&Glyph>) {
+        let first = self
+            .0
+            .partition_point(|split_text| split_text.range.end < range.start);
+        self.0[first..]
+            .iter()
+            .take_while(|split_text| split_text.range.start <= range.start)
+            .find_map(|split_text| {
+                let glyph = split_text.get_glyphs_from_char_range(range.clone())?;
+                Some((Some(split_text), Some(glyph)))
+            })
+            .unwrap_or((None, None))
+    }
+
+    // Glyphs in the order of text with their split texts, that are walked along chars without collecting runs.
+    pub(crate) fn glyphs(&self) -> impl Iterator<Item = (&SplitText, &Glyph)> {
+        self.0.iter().flat_map(|split_text| {
+            split_text
+                .glyphs
+                .iter()
+                .map(move |glyph| (split_text, glyph))
+        })
+    }
+
+    // Runs of text in `range` in order. Split texts and glyphs are sorted by range,
+    // so glyphs are walked from the first glyph of `range` that is found by binary search.
+    pub(crate) fn glyph_runs(&self, range: Range<usize>) -> Result<Vec<GlyphRun<'_>>, Error> {
+        let mut runs: Vec<GlyphRun> = vec![];
+        let mut end = range.start;
+        let first = self
+            .0
+            .partition_point(|split_text| split_text.range.end <= range.start);
+        for split_text in &self.0[first..] {
+            if split_text.range.start >= range.end {
+                break;
+            }
+            let first = split_text
+                .glyphs
+                .partition_point(|glyph| glyph.range.end <= range.start);
+            for glyph in &split_text.glyphs[first..] {
+                if glyph.range.start >= range.end {
+                    break;
+                }
+                let start = glyph.range.start.max(range.start);
+                let glyph_end = glyph.range.end.min(range.end);
+                if start >= glyph_end {
+                    continue;
+                }
+                if start != end {
+                    return Err(Error::OutOfRangeText.at_char(end));
+                }
+                end = glyph_end;
+                match runs.last_mut() {
+                    Some(last)
+                        if std::ptr::eq(last.split_text, split_text)
+                            && last.glyph.font_index_store == glyph.font_index_store =>
+                    {
+                        last.range.end = end
+                    }
+                    _ => runs.push(GlyphRun {
+                        range: start..end,
+                        split_text,
+                        glyph,
+                    }),
+                }
+            }
+        }
+        if end < range.end {
+            return Err(Error::OutOfRangeText.at_char(end));
+        }
+        Ok(runs)
+    }
+
+    // Font at `idx` of parent font stack, where parent font is followed by its fallback fonts.
+    pub(crate) fn parent_font<'a>(
+        &'a self,
+        idx: &FontIndex,
+        parent_font: Option<&'a dyn Font>,
+    ) -> Option<&'a dyn Font> {
+        match idx.0 {
+            0 => parent_font,
+            i => self.1.get(i - 1).map(|font| &**font),
+        }
+    }
+
+    // Chars that the fonts of their glyphs don't have, and chars of ruby that no font has.
+    pub(crate) fn missing_chars(
+        &self,
+        parent_font: Option<&dyn Font>,
+        font_context: &FontContext,
+    ) -> Vec<char> {
+        let mut chars = vec![];
+        for split_text in &self.0 {
+            for glyph in &split_text.glyphs {
+                let has_ch = |ch: char| match &glyph.font_index_store {
+                    FontIndexStore::Global(idx) => {
+                        font_context.with(idx, |font| match_font_family(ch, font))
+                    }
+                    FontIndexStore::Parent(idx) => self
+                        .parent_font(idx, parent_font)
+                        .is_some_and(|font| match_font_family(ch, font)),
+                    FontIndexStore::Child(idx) => split_text
+                        .font(idx)
+                        .is_some_and(|font| match_font_family(ch, font)),
+                    FontIndexStore::Emoji(_) | FontIndexStore::Script(_) => true,
+                };
+                let start = glyph.range.start - split_text.range.start;
+                let end = glyph.range.end - split_text.range.start;
+                let text = split_text.text.get(start..end).unwrap_or_default();
+                chars.extend(
+                    text.chars()
+                        .filter(|&ch| !has_no_glyph(ch) && !has_ch(layout_char(ch))),
+                );
+            }
+
+            if let Some(ruby) = &split_text.ruby {
+                let fonts: Vec<&dyn Font> = split_text
+                    .fonts
+                    .iter()
+                    .map(|font| &**font)
+                    .chain(parent_font)
+                    .chain(self.1.iter().map(|font| &**font))
+                    .collect();
+                chars.extend(ruby.chars().filter(|&ch| {
+                    !has_no_glyph(ch)
+                        && !fonts.iter().any(|&font| match_font_family(ch, font))
+                        && font_context.select_font_family(ch).is_err()
+                }));
+            }
+        }
+        chars
+    }
+
+    // The largest ascent of texts in `range`, that is used as baseline of line.
+    pub(crate) fn line_ascent(
+        &self,
+        range: Range<usize>,
+        parent_style: &Style,
+        parent_font: &Option<FontArc>,
+        font_context: &FontContext,
+    ) -> f32 {
+        let parent_font = parent_font.as_ref().map(|font| font as &dyn Font);
+        let mut line_ascent: f32 = 0.;
         for split_text in &self.0 {
-            let glyph = split_text.get_glyphs_from_char_range(range.clone());
-            if glyph.is_some() {
-                return (Some(split_text), glyph);
+            let style = split_text.style.as_ref().unwrap_or(parent_style);
+            let size = style.font_size;
+            for glyph in &split_text.glyphs {
+                if glyph.range.end <= range.start || range.end <= glyph.range.start {
+                    continue;
+                }
+                let ascent = match &glyph.font_index_store {
+                    FontIndexStore::Global(idx) => {
+                        Some(font_context.with(idx, |font| font.ascent(size)))
+                    }
+                    FontIndexStore::Parent(idx) => self
+                        .parent_font(idx, parent_font)
+                        .map(|font| font.ascent(size)),
+                    FontIndexStore::Child(idx) => {
+                        split_text.font(idx).map(|font| font.ascent(size))
+                    }
+                    FontIndexStore::Emoji(_) => None,
+                    FontIndexStore::Script(idx) => split_text
+                        .script_images
+                        .get(idx.0)
+                        .map(|image| image.baseline as f32),
+                };
+                let ascent = match (&glyph.font_index_store, style.fallback_metrics) {
+                    (FontIndexStore::Emoji(_) | FontIndexStore::Script(_), _)
+                    | (_, FallbackMetrics::Font) => ascent,
+                    (_, FallbackMetrics::Primary) => self
+                        .primary_metrics(split_text, parent_font, font_context, size)
+                        .map(|(ascent, _)| ascent)
+                        .or(ascent),
+                };
+                line_ascent = line_ascent.max(ascent.unwrap_or(0.));
             }
         }
-        (None, None)
+        line_ascent
+    }
+
+    // Ascent and height of the primary font of split text, that is used as metrics of its fallback fonts.
+    // The primary font is its first font, parent font or the first font in FontContext in this order.
+    fn primary_metrics(
+        &self,
+        split_text: &SplitText,
+        parent_font: Option<&dyn Font>,
+        font_context: &FontContext,
+        size: f32,
+    ) -> Option<(f32, f32)> {
+        let metrics = |font: &dyn Font| {
+            let ascent = font.ascent(size);
+            (ascent, ascent + font.descent(size))
+        };
+        match split_text.font(&FontIndex(0)).or(parent_font) {
+            Some(font) => Some(metrics(font)),
+            None if !font_context.is_empty() => Some(font_context.with(&FontIndex(0), metrics)),
+            None => None,
+        }
+    }
+
+    // Height of char in `glyph` of `split_text`, that is replaced with height of the primary font when `fallback_metrics` is `Primary`.
+    pub(crate) fn char_height(
+        &self,
+        split_text: &SplitText,
+        glyph: &Glyph,
+        height: f32,
+        parent_style: &Style,
+        parent_font: Option<&dyn Font>,
+        font_context: &FontContext,
+    ) -> f32 {
+        let style = split_text.style.as_ref().unwrap_or(parent_style);
+        match (&glyph.font_index_store, style.fallback_metrics) {
+            (FontIndexStore::Emoji(_) | FontIndexStore::Script(_), _)
+            | (_, FallbackMetrics::Font) => height,
+            (_, FallbackMetrics::Primary) => self
+                .primary_metrics(split_text, parent_font, font_context, style.font_size)
+                .map_or(height, |(_, height)| height),
+        }
     }
 
     pub(crate) fn set_glyphs(
         &mut self,
+        parent_style: &Style,
         parent_font: &Option<impl Font>,
         font_context: &FontContext,
+        emoji_resolver: Option<&dyn EmojiResolver>,
+        script_renderer: Option<&dyn ScriptRenderer>,
     ) -> Result<(), Error> {
         let mut current_range_start = 0;
         for split_text in self.0.iter_mut() {
-            split_text.set_glyphs(parent_font, &mut current_range_start, font_context)?;
+            split_text.set_glyphs(
+                parent_style,
+                parent_font,
+                &self.1,
+                &mut current_range_start,
+                font_context,
+                emoji_resolver,
+                script_renderer,
+            )?;
         }
         Ok(())
     }
@@ -144,38 +737,215 @@ impl TextArea {
         cur_char: char,
         next_char: Option<char>,
         flags: &Option<CharFlags>,
-        parent_font: &dyn Font,
+        parent_font: Option<&dyn Font>,
         range: Range<usize>,
         font_context: &FontContext,
         setting: &FontSetting,
     ) -> Result<FontMetrics, Error> {
-        let extents = match self.get_glyphs_from_char_range(range) {
-            (Some(split_text), Some(glyph)) => {
-                let setting = match &split_text.style {
-                    Some(style) => FontSetting {
-                        size: style.font_size,
-                        letter_spacing: style.letter_spacing,
-                        kern_setting: style.kern_setting,
-                        is_pre: style.white_space.is_pre(),
-                    },
-                    None => setting.clone(),
-                };
-                match &glyph.font_index_store {
-                    FontIndexStore::Global(idx) => font_context.with(idx, |font| {
-                        font.char_extents(cur_char, next_char, flags, &setting)
-                    }),
-                    FontIndexStore::Parent(_) => {
-                        parent_font.char_extents(cur_char, next_char, flags, &setting)
-                    }
-                    FontIndexStore::Child(_) => match &split_text.font {
-                        Some(font) => font.char_extents(cur_char, next_char, flags, &setting),
-                        None => return Err(Error::NotFoundSpecifiedFontFamily),
+        match self.get_glyphs_from_char_range(range.clone()) {
+            (Some(split_text), Some(glyph)) => self.glyph_char_extents(
+                split_text,
+                glyph,
+                cur_char,
+                next_char,
+                flags,
+                parent_font,
+                range,
+                font_context,
+                setting,
+            ),
+            _ => Err(Error::OutOfRangeText),
+        }
+    }
+
+    // Same as `char_extents`, but with split text and glyph that have the char.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn glyph_char_extents(
+        &self,
+        split_text: &SplitText,
+        glyph: &Glyph,
+        cur_char: char,
+        next_char: Option<char>,
+        flags: &Option<CharFlags>,
+        parent_font: Option<&dyn Font>,
+        range: Range<usize>,
+        font_context: &FontContext,
+        setting: &FontSetting,
+    ) -> Result<FontMetrics, Error> {
+        let setting = match &split_text.style {
+            Some(style) => FontSetting {
+                size: style.font_size,
+                letter_spacing: style.letter_spacing,
+                kern_setting: style.kern_setting,
+                is_pre: style.white_space.is_pre(),
+                preserve_spaces: style.white_space.preserves_spaces(),
+                tabular_nums: style.font_variant_numeric.is_tabular(),
+            },
+            None => setting.clone(),
+        };
+        let extents = match &glyph.font_index_store {
+            FontIndexStore::Global(idx) => font_context.with(idx, |font| {
+                font.char_extents(cur_char, next_char, flags, &setting)
+            }),
+            FontIndexStore::Parent(idx) => match self.parent_font(idx, parent_font) {
+                Some(font) => font.char_extents(cur_char, next_char, flags, &setting),
+                None => return Err(Error::NotFoundSpecifiedFontFamily),
+            },
+            FontIndexStore::Child(idx) => match split_text.font(idx) {
+                Some(font) => font.char_extents(cur_char, next_char, flags, &setting),
+                None => return Err(Error::NotFoundSpecifiedFontFamily),
+            },
+            // Emoji cluster is laid out as a square of font size at its first char.
+            FontIndexStore::Emoji(_) => FontMetrics {
+                height: setting.size,
+                width: if range.start == glyph.range.start {
+                    setting.size + setting.letter_spacing as f32
+                } else {
+                    0.
+                },
+            },
+            // Rendered word is laid out with the size of its image at its first char.
+            FontIndexStore::Script(idx) => match split_text.script_images.get(idx.0) {
+                Some(image) => FontMetrics {
+                    height: image.image.height() as f32,
+                    width: if range.start == glyph.range.start {
+                        image.image.width() as f32 + setting.letter_spacing as f32
+                    } else {
+                        0.
                     },
-                }
-            }
-            _ => return Err(Error::OutOfRangeText),
+                },
+                None => return Err(Error::OutOfRangeText),
+            },
         };
 
         Ok(extents)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::TextArea;
+    use crate::font_context::{FontIndex, FontIndexStore};
+    use crate::glyph::Glyph;
+    use crate::style::{Style, StyleOverride};
+
+    fn spans(textarea: &TextArea) -> Vec<(&str, bool)> {
+        textarea
+            .spans()
+            .map(|span| (span.text, span.style.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn test_edit_textarea() {
+        let mut textarea = TextArea::new();
+        textarea.push_text("Hello World");
+
+        textarea.insert(5, ",", Style::default(), None).unwrap();
+        assert_eq!(
+            spans(&textarea),
+            vec![("Hello", false), (",", true), (" World", false)]
+        );
+
+        textarea.set_style(7..12, Style::default()).unwrap();
+        assert_eq!(
+            spans(&textarea),
+            vec![("Hello", false), (",", true), (" ", false), ("World", true)]
+        );
+
+        textarea.remove(4..7).unwrap();
+        textarea
+            .insert_text(textarea.as_string().len(), "!")
+            .unwrap();
+        assert_eq!(
+            spans(&textarea),
+            vec![("Hell", false), ("World", true), ("!", false)]
+        );
+        assert_eq!(textarea.spans().last().unwrap().range, 9..10);
+
+        assert!(textarea.remove(8..11).is_err());
+    }
+
+    #[test]
+    fn test_push_with_char_styles() {
+        let mut textarea = TextArea::new();
+        textarea
+            .push_with_char_styles("aab c", Style::default(), None, |i, ch| StyleOverride {
+                font_size: if ch == 'a' { Some(50.) } else { None },
+                baseline_shift: Some(i as i32 / 3),
+                ..StyleOverride::default()
+            })
+            .unwrap();
+
+        let spans: Vec<(&str, f32, i32)> = textarea
+            .spans()
+            .map(|span| {
+                let style = span.style.unwrap();
+                (span.text, style.font_size, style.baseline_shift)
+            })
+            .collect();
+        assert_eq!(spans, vec![("aa", 50., 0), ("b", 30., 0), (" c", 30., 1)]);
+    }
+
+    #[test]
+    fn test_edit_textarea_at_char_boundary() {
+        let mut textarea = TextArea::new();
+        textarea.push_text("日本");
+        assert!(textarea.insert_text(1, "x").is_err());
+        textarea.insert_text(3, "x").unwrap();
+        assert_eq!(textarea.as_string(), "日x本");
+    }
+
+    #[test]
+    fn test_glyph_runs() {
+        let mut textarea = TextArea::new();
+        textarea.push_text("ab");
+        textarea.push_text("cd");
+        textarea.0[0].glyphs = vec![
+            Glyph::new(0..1, FontIndexStore::Parent(FontIndex(0))),
+            Glyph::new(1..2, FontIndexStore::Parent(FontIndex(0))),
+        ];
+        textarea.0[1].glyphs = vec![Glyph::new(2..4, FontIndexStore::Parent(FontIndex(0)))];
+
+        let runs: Vec<_> = textarea
+            .glyph_runs(1..4)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.range)
+            .collect();
+        assert_eq!(runs, vec![1..2, 2..4]);
+
+        textarea.0[1].glyphs.clear();
+        assert!(textarea.glyph_runs(0..4).is_err());
+    }
+
+    #[test]
+    fn test_glyph_runs_in_split_texts() {
+        let mut textarea = TextArea::new();
+        for i in 0..100 {
+            textarea.push_text("ab");
+            // Empty text has no glyphs.
+            textarea.push_text("");
+            let start = i * 2;
+            textarea.0[i * 2].glyphs = vec![
+                Glyph::new(start..start + 1, FontIndexStore::Parent(FontIndex(0))),
+                Glyph::new(start + 1..start + 2, FontIndexStore::Parent(FontIndex(1))),
+            ];
+        }
+
+        // Glyphs of range are found in the middle of text.
+        let runs: Vec<_> = textarea
+            .glyph_runs(101..104)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.range)
+            .collect();
+        assert_eq!(runs, vec![101..102, 102..103, 103..104]);
+        assert!(textarea.glyph_runs(150..150).unwrap().is_empty());
+
+        let (split_text, glyph) = textarea.get_glyphs_from_char_range(151..152);
+        assert_eq!(split_text.unwrap().range, 150..152);
+        assert_eq!(glyph.unwrap().range, 151..152);
+        assert!(textarea.get_glyphs_from_char_range(200..201).1.is_none());
+    }
+}
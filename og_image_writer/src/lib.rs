@@ -122,21 +122,79 @@
 //! For more examples, see [keiya01/og_image_writer/examples](https://github.com/keiya01/og_image_writer/tree/main/examples).
 //!
 
+pub mod alt_text;
+pub mod asset;
+pub mod batch;
+pub mod cache;
+pub mod cancel;
+pub mod collage;
+pub mod coverage;
+#[cfg(feature = "default-font")]
+pub mod default_font;
 pub mod element;
+#[cfg(feature = "emoji-shortcodes")]
+pub mod emoji;
+pub mod emoji_image;
 pub mod font_context;
+pub mod format;
+pub mod glyph_layout;
 pub mod img;
+pub mod limits;
+#[cfg(feature = "markdown")]
+pub mod markdown;
+pub mod measure;
+pub mod painter;
+pub mod region;
+pub mod script_image;
+pub mod stack;
+pub mod stats;
 pub mod style;
+pub mod subset;
+pub mod templates;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod text_path;
+pub mod theme;
+pub mod validate;
 pub mod writer;
-pub use context::ImageOutputFormat;
-pub use error::Error;
-pub use layout::TextArea;
+pub use animation::AnimationFormat;
+pub use context::{ImageOutputFormat, OutputChannels};
+pub use error::{ElementContext, Error};
+pub use font_dir::FontFace;
+pub use layout::{TextArea, TextSpan};
+pub use palette::PaletteOptions;
+pub use progressive::ProgressiveFormat;
 
+mod animation;
+mod blend;
+mod casing;
 mod char;
 mod context;
 mod error;
+#[cfg(feature = "figma")]
+mod figma;
+mod fill;
+mod flow;
 mod font;
+mod font_dir;
 mod font_trait;
 mod glyph;
+#[cfg(feature = "wgpu")]
+mod gpu;
+#[cfg(feature = "html")]
+mod html;
+mod incremental;
 mod layout;
 mod line_breaker;
+mod macros;
+mod named;
+mod palette;
+mod progressive;
 mod renderer;
+mod script;
+mod segment;
+mod strip;
+mod svg;
+#[cfg(feature = "tera")]
+mod template;
+mod text_effect;
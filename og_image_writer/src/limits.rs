@@ -0,0 +1,281 @@
+//! Limits for rendering untrusted input.
+//!
+//! Set [Limits] with [`OGImageWriter::with_limits()`](crate::writer::OGImageWriter::with_limits)
+//! when window size, images or texts come from users.
+//! Each limit is checked before memory is allocated, and [Error::LimitExceeded] is returned when it is exceeded.
+
+use crate::img::ImageInputFormat;
+use crate::Error;
+use image::io::Reader;
+use std::{fmt, fs, io::Cursor};
+
+/// Resource checked by [Limits].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resource {
+    /// Width times height of window. Size of each element is checked with it too.
+    WindowPixels,
+    /// Width times height of input image before resizing, and of image after resizing.
+    ImagePixels,
+    /// Byte length of input image.
+    ImageBytes,
+    /// Number of characters of a text element.
+    TextLength,
+    /// Number of elements set to a writer.
+    Elements,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Resource::WindowPixels => "window pixels",
+            Resource::ImagePixels => "image pixels",
+            Resource::ImageBytes => "image bytes",
+            Resource::TextLength => "text length",
+            Resource::Elements => "elements",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `None` means unlimited. All limits are unlimited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    pub max_window_pixels: Option<u64>,
+    pub max_image_pixels: Option<u64>,
+    pub max_image_bytes: Option<u64>,
+    pub max_text_length: Option<usize>,
+    pub max_elements: Option<usize>,
+}
+
+impl Limits {
+    /// Limits that are enough for common Open Graphic Image.
+    pub fn recommended() -> Self {
+        Limits {
+            // 4096x4096
+            max_window_pixels: Some(16_777_216),
+            max_image_pixels: Some(16_777_216),
+            max_image_bytes: Some(10 * 1024 * 1024),
+            max_text_length: Some(2000),
+            max_elements: Some(100),
+        }
+    }
+
+    pub(crate) fn check_window(&self, width: u32, height: u32) -> Result<(), Error> {
+        check(
+            Resource::WindowPixels,
+            width as u64 * height as u64,
+            self.max_window_pixels,
+        )
+    }
+
+    // `len` is number of characters.
+    pub(crate) fn check_text(&self, len: usize) -> Result<(), Error> {
+        check(
+            Resource::TextLength,
+            len as u64,
+            self.max_text_length.map(|limit| limit as u64),
+        )
+    }
+
+    // `count` is number of elements including new element.
+    pub(crate) fn check_elements(&self, count: usize) -> Result<(), Error> {
+        check(
+            Resource::Elements,
+            count as u64,
+            self.max_elements.map(|limit| limit as u64),
+        )
+    }
+
+    // Check size of image data without decoding pixels.
+    pub(crate) fn check_image_data(
+        &self,
+        data: &[u8],
+        format: ImageInputFormat,
+    ) -> Result<(), Error> {
+        check(
+            Resource::ImageBytes,
+            data.len() as u64,
+            self.max_image_bytes,
+        )?;
+        if self.max_image_pixels.is_some() {
+            let (width, height) = Reader::with_format(Cursor::new(data), format.as_image_format())
+                .into_dimensions()?;
+            self.check_image_pixels(width, height)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_image_src(&self, src: &str) -> Result<(), Error> {
+        if self.max_image_bytes.is_some() {
+            let len = fs::metadata(src).map_err(image::ImageError::IoError)?.len();
+            check(Resource::ImageBytes, len, self.max_image_bytes)?;
+        }
+        if self.max_image_pixels.is_some() {
+            let (width, height) = image::image_dimensions(src)?;
+            self.check_image_pixels(width, height)?;
+        }
+        Ok(())
+    }
+
+    // Check size of element that is allocated or painted, like image after resizing.
+    pub(crate) fn check_element_size(&self, width: u32, height: u32) -> Result<(), Error> {
+        self.check_image_pixels(width, height)?;
+        self.check_window(width, height)
+    }
+
+    pub(crate) fn check_image_pixels(&self, width: u32, height: u32) -> Result<(), Error> {
+        check(
+            Resource::ImagePixels,
+            width as u64 * height as u64,
+            self.max_image_pixels,
+        )
+    }
+}
+
+fn check(resource: Resource, value: u64, limit: Option<u64>) -> Result<(), Error> {
+    match limit {
+        Some(limit) if value > limit => Err(Error::LimitExceeded {
+            resource,
+            value,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::collage::{Collage, CollageLayout};
+    use crate::img::DecodedImage;
+    use crate::style::{Spacer, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_limits() {
+        let limits = Limits {
+            max_window_pixels: Some(100 * 100),
+            max_text_length: Some(5),
+            max_elements: Some(1),
+            max_image_pixels: Some(50 * 50),
+            ..Limits::default()
+        };
+
+        let window = WindowStyle {
+            width: 200,
+            height: 100,
+            ..WindowStyle::default()
+        };
+        assert!(matches!(
+            OGImageWriter::with_limits(window, limits),
+            Err(Error::LimitExceeded {
+                resource: Resource::WindowPixels,
+                value: 20000,
+                limit: 10000,
+            })
+        ));
+
+        let mut writer = OGImageWriter::with_limits(
+            WindowStyle {
+                width: 100,
+                height: 100,
+                ..WindowStyle::default()
+            },
+            limits,
+        )
+        .unwrap();
+
+        let err = writer
+            .set_img_with_data(
+                include_bytes!("../../assets/thumbnail_circle.png"),
+                10,
+                10,
+                ImageInputFormat::Png,
+                Style::default(),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::LimitExceeded {
+                resource: Resource::ImagePixels,
+                ..
+            }
+        ));
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let err = writer
+            .set_text("Too long", Style::default(), Some(font.clone()))
+            .unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::LimitExceeded {
+                resource: Resource::TextLength,
+                value: 8,
+                limit: 5,
+            }
+        ));
+
+        writer
+            .set_text("Hello", Style::default(), Some(font.clone()))
+            .unwrap();
+        let err = writer
+            .set_text("Hello", Style::default(), Some(font))
+            .unwrap_err();
+        assert!(matches!(
+            err.inner(),
+            Error::LimitExceeded {
+                resource: Resource::Elements,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_limits_of_resized_size() {
+        let limits = Limits::recommended();
+        let mut writer = OGImageWriter::with_limits(
+            WindowStyle {
+                width: 100,
+                height: 100,
+                ..WindowStyle::default()
+            },
+            limits,
+        )
+        .unwrap();
+        let image = DecodedImage::from(RgbaImage::new(2, 2));
+        let is_over = |result: Result<(), Error>| {
+            matches!(
+                result.unwrap_err().inner(),
+                Error::LimitExceeded {
+                    resource: Resource::ImagePixels,
+                    ..
+                }
+            )
+        };
+
+        // Small image is checked with the size after resizing, before it is allocated.
+        assert!(is_over(writer.set_img_with_decoded(
+            &image,
+            30000,
+            30000,
+            Style::default()
+        )));
+        let style = Style {
+            aspect_ratio: Some(0.0001),
+            ..Style::default()
+        };
+        assert!(is_over(
+            writer.set_img_with_decoded(&image, 1000, 10, style)
+        ));
+        assert!(is_over(writer.set_spacer(Spacer::Fixed(u32::MAX))));
+        assert!(is_over(writer.set_collage(
+            Collage::new(CollageLayout::Grid { columns: 2 }, 30000, 30000),
+            Style::default()
+        )));
+
+        writer
+            .set_img_with_decoded(&image, 100, 100, Style::default())
+            .unwrap();
+    }
+}
@@ -1,12 +1,14 @@
-use super::char::{CharFlags, RenderingCharIndices};
+use super::char::{layout_char, RenderingCharIndices, NO_BREAK_SPACE, SOFT_HYPHEN};
 use super::layout::TextArea;
-use crate::font::{match_font_family, whitespace_width, FontMetrics};
+use crate::font::{whitespace_width, FontMetrics};
 use crate::font_context::FontContext;
 use crate::font_trait::Font;
 use crate::renderer::FontSetting;
+use crate::segment::complex_word_breaks;
 use crate::style::{Style, WordBreak};
 use crate::Error;
 use std::ops::Range;
+use std::rc::Rc;
 
 pub(super) struct Line {
     pub(super) range: Range<usize>,
@@ -26,25 +28,124 @@ impl Line {
     }
 }
 
+// Size of char that is measured once for each text, because lines are broken again by `balance` and `avoid_orphan`.
+#[derive(Debug, Clone, Copy)]
+struct CharMetrics {
+    width: f32,
+    height: f32,
+    whitespace_width: f32,
+    preserve_spaces: bool,
+}
+
+// Balancing is skipped for long text, because it is intended for headings.
+const MAX_BALANCED_LINES: usize = 6;
+
 pub(super) struct LineBreaker<'a> {
     pub(super) title: &'a str,
     pub(super) lines: Vec<Line>,
     pub(super) max_line_height: f32,
     pub(super) max_line_width: f32,
+    // Index and width of line that is broken with narrower width than the others.
+    narrow_line: Option<(usize, f32)>,
+    // Word boundaries in scripts like Thai that are not separated by whitespace.
+    // They are shared with breakers that break the same text again, because segmentation is expensive.
+    word_breaks: Rc<Vec<usize>>,
+    // Metrics of chars in the order of RenderingCharIndices, that are measured by `break_text`
+    // and shared with breakers that break the same text again.
+    metrics: Rc<Vec<CharMetrics>>,
+    // Lines of candidates that are not chosen, that are reused by the next candidates.
+    spare: Vec<Vec<Line>>,
+}
+
+// Buffers of `LineBreaker` that are kept by the writer between renders,
+// so that texts are broken again without allocating them.
+#[derive(Default)]
+pub(crate) struct LineBuffers {
+    lines: Vec<Line>,
+    word_breaks: Rc<Vec<usize>>,
+    metrics: Rc<Vec<CharMetrics>>,
+    spare: Vec<Vec<Line>>,
 }
 
 // TODO: support truncate text when overflow specified height.
 impl<'a> LineBreaker<'a> {
+    #[cfg(test)]
     pub(super) fn new(title: &'a str) -> Self {
+        LineBreaker::with_buffers(title, LineBuffers::default())
+    }
+
+    pub(super) fn with_buffers(title: &'a str, buffers: LineBuffers) -> Self {
+        let LineBuffers {
+            mut lines,
+            mut word_breaks,
+            metrics,
+            spare,
+        } = buffers;
+        lines.clear();
+        *Rc::make_mut(&mut word_breaks) = complex_word_breaks(title);
         LineBreaker {
             title,
-            lines: vec![],
+            lines,
             max_line_height: 0.,
             max_line_width: 0.,
+            narrow_line: None,
+            word_breaks,
+            metrics,
+            spare,
         }
     }
 
-    // TODO: support hyphenation
+    // Give buffers back after lines are taken, so that the next text is broken with them.
+    pub(super) fn into_buffers(self) -> LineBuffers {
+        LineBuffers {
+            lines: self.lines,
+            word_breaks: self.word_breaks,
+            metrics: self.metrics,
+            spare: self.spare,
+        }
+    }
+
+    // Breaker of the same text that breaks it again with other width.
+    fn candidate(&mut self) -> Self {
+        let lines = match self.spare.pop() {
+            Some(lines) => lines,
+            None => Vec::with_capacity(self.lines.len()),
+        };
+        LineBreaker {
+            title: self.title,
+            lines,
+            max_line_height: 0.,
+            max_line_width: 0.,
+            narrow_line: None,
+            word_breaks: self.word_breaks.clone(),
+            metrics: self.metrics.clone(),
+            spare: vec![],
+        }
+    }
+
+    // Keep lines of candidate that is not chosen for the next candidates.
+    fn recycle(&mut self, candidate: LineBreaker) {
+        let mut lines = candidate.lines;
+        lines.clear();
+        self.spare.push(lines);
+    }
+
+    // Take lines of candidate, and keep the previous lines for the next candidates.
+    fn choose(&mut self, mut candidate: LineBreaker<'a>) {
+        std::mem::swap(&mut self.lines, &mut candidate.lines);
+        self.max_line_height = candidate.max_line_height;
+        self.max_line_width = candidate.max_line_width;
+        self.recycle(candidate);
+    }
+
+    // Remove lines but keep their buffer, so that text can be broken again without allocation.
+    fn reset(&mut self) {
+        self.lines.clear();
+        self.max_line_height = 0.;
+        self.max_line_width = 0.;
+    }
+
+    // Chars are measured again, so the same breaker can break text again after its style or width is changed.
     pub(super) fn break_text(
         &mut self,
         width: f32,
@@ -53,119 +154,106 @@ impl<'a> LineBreaker<'a> {
         textarea: &TextArea,
         font_context: &FontContext,
     ) -> Result<(), Error> {
+        self.reset();
+        self.narrow_line = None;
+        // Metrics are measured into the buffer of the previous text.
+        let metrics = Rc::make_mut(&mut self.metrics);
+        metrics.clear();
+        measure_chars(self.title, metrics, style, font, textarea, font_context)?;
+        self.break_lines(width, style, textarea)
+    }
+
+    // Break text with metrics that are measured by `break_text`.
+    // TODO: support hyphenation
+    fn break_lines(&mut self, width: f32, style: &Style, textarea: &TextArea) -> Result<(), Error> {
         let mut last_whitespace_idx = 0;
+        // Width removed from line when line is broken at last_whitespace_idx.
+        // Whitespace is removed, and hyphen is added for soft hyphen.
+        let mut last_break_width = 0.;
         // Space between whitespace and whitespace
         let mut word_width = 0.;
         let mut range = 0..0;
         let mut line_height = 0.;
         let mut line_width = 0.;
-        let mut chars = RenderingCharIndices::from_str(self.title);
-        while let Some((flags, i, ch, ch_len)) = chars.next() {
-            let setting = match textarea.get_glyphs_from_char_range(i..i + ch_len) {
-                (Some(split_text), _) => {
-                    let style = split_text.style.as_ref().unwrap_or(style);
-                    FontSetting {
-                        size: style.font_size,
-                        letter_spacing: style.letter_spacing,
-                        kern_setting: style.kern_setting,
-                        is_pre: style.white_space.is_pre(),
-                    }
-                }
-                _ => FontSetting {
-                    size: style.font_size,
-                    letter_spacing: style.letter_spacing,
-                    kern_setting: style.kern_setting,
-                    is_pre: style.white_space.is_pre(),
-                },
-            };
-            let whitespace_width = whitespace_width(setting.size);
-
-            let peek_char = chars.peek_char();
-
-            let extents = match font {
-                Some(font) if match_font_family(ch, font) => textarea.char_extents(
-                    ch,
-                    peek_char,
-                    &flags,
-                    font,
-                    i..i + ch_len,
-                    font_context,
-                    &setting,
-                )?,
-                _ => {
-                    let idx = font_context.select_font_family('.')?;
-                    font_context.with(&idx, |font| {
-                        textarea.char_extents(
-                            ch,
-                            peek_char,
-                            &flags,
-                            font,
-                            i..i + ch_len,
-                            font_context,
-                            &setting,
-                        )
-                    })?
-                }
-            };
+        // Metrics are shared, so lines can be pushed while they are read.
+        let char_metrics = self.metrics.clone();
+        let chars = RenderingCharIndices::from_str(self.title);
+        for ((flags, i, ch, ch_len), &metrics) in chars.zip(char_metrics.iter()) {
+            let is_ruby_break =
+                matches!(style.word_break, WordBreak::BreakAll) && textarea.is_ruby_base_start(i);
+            if i > range.start && (is_ruby_break || self.word_breaks.binary_search(&i).is_ok()) {
+                last_whitespace_idx = i;
+                last_break_width = 0.;
+                word_width = 0.;
+            }
 
-            let ch_width = extents.width;
-            let is_newline = matches!(flags, Some(CharFlags::Newline));
+            let ch_width = if ch == SOFT_HYPHEN { 0. } else { metrics.width };
+            let is_line_break = flags.as_ref().is_some_and(|flags| flags.is_line_break());
 
-            if setting.is_pre && is_newline {
+            if is_line_break {
                 let start = range.end + ch_len;
-                self.lines.push(Line::new(
+                self.push_line(
                     range.start..range.end + ch_len,
                     line_width,
                     line_height,
-                ));
-                self.set_max_line_size(FontMetrics {
-                    height: line_height,
-                    width: line_width,
-                });
+                    style,
+                );
                 range = start..start;
                 line_width = 0.;
                 line_height = 0.;
             }
 
-            if width <= line_width + ch_width {
+            let line_limit = match self.narrow_line {
+                Some((idx, narrow_width)) if idx == self.lines.len() => narrow_width,
+                _ => width,
+            };
+            let available_width = line_limit - style.line_indent(self.lines.is_empty()) as f32;
+            // Preformatted text is broken only at newlines.
+            if available_width <= line_width + ch_width && !style.white_space.preserves_spaces() {
                 match style.word_break {
-                    WordBreak::Normal => {
+                    // Ruby base is broken before its first char like a word.
+                    // Line has at least one char even if the char is wider than the line.
+                    WordBreak::BreakAll if !range.is_empty() && !textarea.is_in_ruby_base(i) => {
+                        let start = range.end;
+                        self.push_line(range, line_width, line_height, style);
+                        range = start..start;
+                        line_width = 0.;
+                        line_height = 0.;
+                    }
+                    // Text overflows when line can not be broken.
+                    _ if last_whitespace_idx <= range.start => {}
+                    _ => {
                         let end = range.end;
-                        line_width -= word_width + whitespace_width;
-                        // TODO: support overflow text when text can not be broken.
-                        self.lines.push(Line::new(
+                        line_width -= word_width + last_break_width;
+                        self.push_line(
                             range.start..last_whitespace_idx,
                             line_width,
                             line_height,
-                        ));
-                        self.set_max_line_size(FontMetrics {
-                            height: line_height,
-                            width: line_width,
-                        });
+                            style,
+                        );
                         range = last_whitespace_idx..end;
                         line_width = word_width;
                         line_height = 0.;
                     }
-                    WordBreak::BreakAll => {
-                        let start = range.end;
-                        self.lines.push(Line::new(range, line_width, line_height));
-                        self.set_max_line_size(FontMetrics {
-                            height: line_height,
-                            width: line_width,
-                        });
-                        range = start..start;
-                        line_width = 0.;
-                        line_height = 0.;
-                    }
                 }
             }
-
-            if setting.is_pre && is_newline {
+            if is_line_break {
                 word_width = 0.;
-            } else if ch.is_whitespace() {
+            } else if ch.is_whitespace() && ch != NO_BREAK_SPACE {
+                let whitespace_width = if metrics.preserve_spaces {
+                    metrics.width
+                } else {
+                    metrics.whitespace_width
+                };
                 range.end = i + ch_len;
                 line_width += whitespace_width;
                 last_whitespace_idx = i + ch_len;
+                last_break_width = whitespace_width;
+                word_width = 0.;
+            } else if ch == SOFT_HYPHEN {
+                range.end = i + ch_len;
+                last_whitespace_idx = i + ch_len;
+                last_break_width = -metrics.width;
                 word_width = 0.;
             } else {
                 range.end = i + ch_len;
@@ -173,24 +261,107 @@ impl<'a> LineBreaker<'a> {
                 word_width += ch_width;
             }
 
-            line_height = if extents.height > line_height {
-                extents.height
+            line_height = if metrics.height > line_height {
+                metrics.height
             } else {
                 line_height
             };
         }
 
         if !range.is_empty() {
-            self.lines.push(Line::new(range, line_width, line_height));
-            self.set_max_line_size(FontMetrics {
-                height: line_height,
-                width: line_width,
-            });
+            self.push_line(range, line_width, line_height, style);
         }
 
         Ok(())
     }
 
+    // Break text again with the narrowest width that keeps the number of lines,
+    // so that short headings do not leave a word alone on the last line.
+    pub(super) fn balance(
+        &mut self,
+        width: f32,
+        style: &Style,
+        textarea: &TextArea,
+    ) -> Result<(), Error> {
+        let line_count = self.lines.len();
+        if !(2..=MAX_BALANCED_LINES).contains(&line_count) {
+            return Ok(());
+        }
+
+        let mut balanced: Option<LineBreaker<'a>> = None;
+        let mut line_breaker = self.candidate();
+        let (mut low, mut high) = (0., width);
+        while high - low > 1. {
+            let mid = (low + high) / 2.;
+            line_breaker.reset();
+            line_breaker.break_lines(mid, style, textarea)?;
+            if line_breaker.lines.len() <= line_count && line_breaker.max_line_width <= mid {
+                high = mid;
+                // Lines of the previous result are reused for the next width.
+                line_breaker = match balanced.replace(line_breaker) {
+                    Some(prev) => prev,
+                    None => self.candidate(),
+                };
+            } else {
+                low = mid;
+            }
+        }
+
+        self.recycle(line_breaker);
+        if let Some(balanced) = balanced {
+            self.choose(balanced);
+        }
+
+        Ok(())
+    }
+
+    // Move last word of previous line to last line when last line has only one word.
+    pub(super) fn avoid_orphan(
+        &mut self,
+        width: f32,
+        style: &Style,
+        textarea: &TextArea,
+    ) -> Result<(), Error> {
+        let line_count = self.lines.len();
+        if line_count < 2 || self.word_count(line_count - 1) != 1 {
+            return Ok(());
+        }
+
+        // Previous line is broken before its last word.
+        let prev_idx = line_count - 2;
+        let mut line_breaker = self.candidate();
+        line_breaker.narrow_line = Some((prev_idx, self.lines[prev_idx].width - 1.));
+        line_breaker.break_lines(width, style, textarea)?;
+
+        if line_breaker.lines.len() == line_count
+            && line_breaker.word_count(prev_idx) > 0
+            && line_breaker.word_count(line_count - 1) > 1
+        {
+            self.choose(line_breaker);
+        } else {
+            self.recycle(line_breaker);
+        }
+
+        Ok(())
+    }
+
+    fn word_count(&self, line_idx: usize) -> usize {
+        self.title[self.lines[line_idx].range.clone()]
+            .split(|ch: char| ch.is_whitespace() && ch != NO_BREAK_SPACE)
+            .filter(|word| !word.is_empty())
+            .count()
+    }
+
+    // Push line, and include indent of the line in max line width.
+    fn push_line(&mut self, range: Range<usize>, width: f32, height: f32, style: &Style) {
+        let indent = style.line_indent(self.lines.is_empty()) as f32;
+        self.lines.push(Line::new(range, width, height));
+        self.set_max_line_size(FontMetrics {
+            height,
+            width: width + indent,
+        });
+    }
+
     // Calculate line size
     pub fn set_max_line_size(&mut self, metrics: FontMetrics) {
         let max_line_height = self.max_line_height;
@@ -210,12 +381,102 @@ impl<'a> LineBreaker<'a> {
     }
 }
 
+// Measure chars with their glyphs. Glyphs are walked along chars instead of being looked up for each char.
+fn measure_chars(
+    title: &str,
+    metrics: &mut Vec<CharMetrics>,
+    style: &Style,
+    font: &Option<impl Font>,
+    textarea: &TextArea,
+    font_context: &FontContext,
+) -> Result<(), Error> {
+    let parent_font = font.as_ref().map(|font| font as &dyn Font);
+    let mut glyphs = textarea.glyphs().peekable();
+    let mut chars = RenderingCharIndices::from_str(title);
+    while let Some((flags, i, ch, ch_len)) = chars.next() {
+        while glyphs.next_if(|(_, glyph)| glyph.range.end <= i).is_some() {}
+        let (split_text, glyph) = match glyphs.peek() {
+            Some(&(split_text, glyph)) if glyph.range.start <= i => (split_text, glyph),
+            _ => return Err(Error::OutOfRangeText.at_char(i)),
+        };
+        let split_style = split_text.style.as_ref().unwrap_or(style);
+        let setting = FontSetting {
+            size: split_style.font_size,
+            letter_spacing: split_style.letter_spacing,
+            kern_setting: split_style.kern_setting,
+            is_pre: split_style.white_space.is_pre(),
+            preserve_spaces: split_style.white_space.preserves_spaces(),
+            tabular_nums: split_style.font_variant_numeric.is_tabular(),
+        };
+
+        // Soft hyphen is measured as hyphen that is rendered when line is broken.
+        let extents = textarea.glyph_char_extents(
+            split_text,
+            glyph,
+            layout_char(ch),
+            chars.peek_char(),
+            &flags,
+            parent_font,
+            i..i + ch_len,
+            font_context,
+            &setting,
+        )?;
+        let height = textarea.char_height(
+            split_text,
+            glyph,
+            extents.height,
+            style,
+            parent_font,
+            font_context,
+        );
+        metrics.push(CharMetrics {
+            width: extents.width,
+            height,
+            whitespace_width: whitespace_width(setting.size),
+            preserve_spaces: setting.preserve_spaces,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::font::test_utils::FontMock;
     use crate::layout::TextArea;
-    use crate::style::WhiteSpace;
+    use crate::style::{TextWrap, WhiteSpace};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Allocator that counts allocations of each thread, so that tests running in parallel don't affect the count.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
 
     #[test]
     fn test_break_test_with_whitespace() {
@@ -233,7 +494,13 @@ mod tests {
         let font_context = FontContext::new();
 
         textarea
-            .set_glyphs(&Some(font.clone()), &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         let mut line_breaker = LineBreaker::new(text);
@@ -264,6 +531,496 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_break_text_with_newline() {
+        let cases: [(WhiteSpace, &[&str]); 2] = [
+            (WhiteSpace::Normal, &["Title\n", "Hello\n", "World"]),
+            (WhiteSpace::PreLine, &["Title\n", "Hello\n", "World"]),
+        ];
+
+        for (white_space, expects) in cases {
+            let font = FontMock::new(None);
+
+            let mut textarea = TextArea::new();
+            textarea.push_text("Title\n");
+            textarea.push_line_text("Hello");
+            textarea.push_text("World");
+            let text = "Title\nHello\nWorld";
+
+            let font_context = FontContext::new();
+
+            textarea
+                .set_glyphs(
+                    &Style::default(),
+                    &Some(font.clone()),
+                    &font_context,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let mut line_breaker = LineBreaker::new(text);
+            line_breaker
+                .break_text(
+                    1000.,
+                    &Style {
+                        font_size: 10.,
+                        white_space,
+                        ..Style::default()
+                    },
+                    &Some(font),
+                    &textarea,
+                    &font_context,
+                )
+                .unwrap();
+
+            let lines: Vec<&str> = line_breaker
+                .lines
+                .iter()
+                .map(|line| &text[line.range.clone()])
+                .collect();
+            assert_eq!(&lines, expects);
+        }
+    }
+
+    #[test]
+    fn test_break_text_with_soft_hyphen_and_no_break_space() {
+        let cases: [(&str, &[&str], f32); 2] = [
+            ("Hello World\u{A0}kms", &["Hello ", "World\u{A0}kms"], 82.),
+            // Hyphen is added to width of broken line.
+            ("Open Graph\u{AD}ical", &["Open Graph\u{AD}", "ical"], 102.),
+        ];
+
+        for (text, expects, max_line_width) in cases {
+            let font = FontMock::new(None);
+
+            let mut textarea = TextArea::new();
+            textarea.push_text(text);
+
+            let font_context = FontContext::new();
+
+            textarea
+                .set_glyphs(
+                    &Style::default(),
+                    &Some(font.clone()),
+                    &font_context,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let mut line_breaker = LineBreaker::new(text);
+            line_breaker
+                .break_text(
+                    130.,
+                    &Style {
+                        font_size: 10.,
+                        word_break: WordBreak::Normal,
+                        ..Style::default()
+                    },
+                    &Some(font),
+                    &textarea,
+                    &font_context,
+                )
+                .unwrap();
+
+            let lines: Vec<&str> = line_breaker
+                .lines
+                .iter()
+                .map(|line| &text[line.range.clone()])
+                .collect();
+            assert_eq!(&lines, expects);
+            assert_eq!(line_breaker.max_line_width, max_line_width);
+        }
+    }
+
+    #[test]
+    fn test_break_text_with_text_indent() {
+        let text = "Hello World, Hello World";
+        let cases: [(i32, &[&str]); 2] = [
+            (40, &["Hello ", "World, Hello ", "World"]),
+            // Hanging indent
+            (-40, &["Hello World, ", "Hello ", "World"]),
+        ];
+
+        for (text_indent, expects) in cases {
+            let font = FontMock::new(None);
+
+            let mut textarea = TextArea::new();
+            textarea.push_text(text);
+
+            let font_context = FontContext::new();
+
+            textarea
+                .set_glyphs(
+                    &Style::default(),
+                    &Some(font.clone()),
+                    &font_context,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let mut line_breaker = LineBreaker::new(text);
+            line_breaker
+                .break_text(
+                    130.,
+                    &Style {
+                        font_size: 10.,
+                        text_indent,
+                        ..Style::default()
+                    },
+                    &Some(font),
+                    &textarea,
+                    &font_context,
+                )
+                .unwrap();
+
+            let lines: Vec<&str> = line_breaker
+                .lines
+                .iter()
+                .map(|line| &text[line.range.clone()])
+                .collect();
+            assert_eq!(&lines, expects);
+        }
+    }
+
+    #[test]
+    fn test_break_text_with_long_word() {
+        // Word that is longer than width overflows instead of being broken.
+        let text = "Hi Supercalifragilistic hi";
+        let font = FontMock::new(None);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+
+        let font_context = FontContext::new();
+
+        textarea
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(
+                50.,
+                &Style {
+                    font_size: 10.,
+                    ..Style::default()
+                },
+                &Some(font),
+                &textarea,
+                &font_context,
+            )
+            .unwrap();
+
+        let lines: Vec<&str> = line_breaker
+            .lines
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["Hi ", "Supercalifragilistic ", "hi"]);
+    }
+
+    #[test]
+    fn test_break_all_with_narrow_width() {
+        // Each char is put on its own line when it is wider than the line.
+        let text = "abc";
+        let font = FontMock::new(None);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+
+        let font_context = FontContext::new();
+
+        textarea
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(
+                0.,
+                &Style {
+                    font_size: 10.,
+                    word_break: WordBreak::BreakAll,
+                    ..Style::default()
+                },
+                &Some(font),
+                &textarea,
+                &font_context,
+            )
+            .unwrap();
+
+        let lines: Vec<&str> = line_breaker
+            .lines
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_balance() {
+        let text = "Open Graphic Image Writer for you";
+        let style = Style {
+            font_size: 10.,
+            ..Style::default()
+        };
+        let font = FontMock::new(None);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+
+        let font_context = FontContext::new();
+
+        textarea
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(150., &style, &Some(font.clone()), &textarea, &font_context)
+            .unwrap();
+        let lines: Vec<&str> = line_breaker
+            .lines
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["Open Graphic ", "Image Writer for ", "you"]);
+
+        line_breaker.balance(150., &style, &textarea).unwrap();
+        let lines: Vec<&str> = line_breaker
+            .lines
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["Open Graphic ", "Image Writer ", "for you"]);
+    }
+
+    #[test]
+    fn test_measure_chars_once() {
+        let text = "Open Graphic Image Writer for you";
+        let style = Style {
+            font_size: 10.,
+            ..Style::default()
+        };
+        let font = FontMock::new(None);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+
+        let font_context = FontContext::new();
+
+        textarea
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let before = font.advances();
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(150., &style, &Some(font.clone()), &textarea, &font_context)
+            .unwrap();
+        let measured = font.advances() - before;
+        // Each char except whitespace is measured once, so measurement is linear in length of text.
+        assert_eq!(
+            measured,
+            text.chars().filter(|ch| !ch.is_whitespace()).count()
+        );
+
+        // Text is broken again many times by balancing, but chars are not measured again.
+        line_breaker.balance(150., &style, &textarea).unwrap();
+        line_breaker.avoid_orphan(150., &style, &textarea).unwrap();
+        assert_eq!(font.advances() - before, measured);
+    }
+
+    #[test]
+    fn test_break_again() {
+        let text = "Open Graphic Image Writer for you";
+        let font = Some(FontMock::new(None));
+        let font_context = FontContext::new();
+        let new_textarea = |styled: Option<Style>| {
+            let mut textarea = TextArea::new();
+            textarea.push_text("Open Graphic ");
+            match styled {
+                Some(style) => textarea.push("Image Writer", style, None).unwrap(),
+                None => textarea.push_text("Image Writer"),
+            }
+            textarea.push_text(" for you");
+            textarea
+                .set_glyphs(&Style::default(), &font, &font_context, None, None)
+                .unwrap();
+            textarea
+        };
+        let lines = |line_breaker: &LineBreaker| {
+            let lines: Vec<(Range<usize>, f32, f32)> = line_breaker
+                .lines
+                .iter()
+                .map(|line| (line.range.clone(), line.width, line.height))
+                .collect();
+            (
+                lines,
+                line_breaker.max_line_width,
+                line_breaker.max_line_height,
+            )
+        };
+
+        let style = Style {
+            font_size: 10.,
+            ..Style::default()
+        };
+        let textarea = new_textarea(None);
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(150., &style, &font, &textarea, &font_context)
+            .unwrap();
+        line_breaker.balance(150., &style, &textarea).unwrap();
+
+        // Text is broken again with other width, style and text in textarea.
+        let larger = Style {
+            font_size: 20.,
+            ..Style::default()
+        };
+        let cases = [
+            (80., &style, new_textarea(None)),
+            (200., &larger, new_textarea(None)),
+            (150., &style, new_textarea(Some(larger.clone()))),
+        ];
+        for (width, style, textarea) in cases {
+            line_breaker
+                .break_text(width, style, &font, &textarea, &font_context)
+                .unwrap();
+            let mut fresh = LineBreaker::new(text);
+            fresh
+                .break_text(width, style, &font, &textarea, &font_context)
+                .unwrap();
+            assert_eq!(lines(&line_breaker), lines(&fresh));
+
+            line_breaker.avoid_orphan(width, style, &textarea).unwrap();
+            fresh.avoid_orphan(width, style, &textarea).unwrap();
+            assert_eq!(lines(&line_breaker), lines(&fresh));
+        }
+    }
+
+    #[test]
+    fn test_avoid_orphan() {
+        let cases: [(&str, &[&str]); 3] = [
+            (
+                "Open Graphic Image Writer for you",
+                &["Open Graphic ", "Image Writer ", "for you"],
+            ),
+            ("Open Graphic Image", &["Open ", "Graphic Image"]),
+            // Last line is not changed when previous line has only one word.
+            ("Supercalifragilistic hi", &["Supercalifragilistic ", "hi"]),
+        ];
+
+        for (text, expects) in cases {
+            let style = Style {
+                font_size: 10.,
+                ..Style::default()
+            };
+            let font = FontMock::new(None);
+
+            let mut textarea = TextArea::new();
+            textarea.push_text(text);
+
+            let font_context = FontContext::new();
+
+            textarea
+                .set_glyphs(
+                    &Style::default(),
+                    &Some(font.clone()),
+                    &font_context,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let mut line_breaker = LineBreaker::new(text);
+            line_breaker
+                .break_text(150., &style, &Some(font.clone()), &textarea, &font_context)
+                .unwrap();
+            line_breaker.avoid_orphan(150., &style, &textarea).unwrap();
+
+            let lines: Vec<&str> = line_breaker
+                .lines
+                .iter()
+                .map(|line| &text[line.range.clone()])
+                .collect();
+            assert_eq!(&lines, expects);
+        }
+    }
+
+    #[test]
+    fn test_break_all_with_ruby() {
+        // Ruby base "cd" is not broken.
+        let text = "abcde";
+        let font = FontMock::new(None);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text("ab");
+        textarea.push_ruby_text("cd", "x");
+        textarea.push_text("e");
+
+        let font_context = FontContext::new();
+
+        textarea
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
+            .unwrap();
+
+        let mut line_breaker = LineBreaker::new(text);
+        line_breaker
+            .break_text(
+                30.,
+                &Style {
+                    font_size: 10.,
+                    word_break: WordBreak::BreakAll,
+                    ..Style::default()
+                },
+                &Some(font),
+                &textarea,
+                &font_context,
+            )
+            .unwrap();
+
+        let lines: Vec<&str> = line_breaker
+            .lines
+            .iter()
+            .map(|line| &text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["ab", "cd", "e"]);
+    }
+
     #[test]
     fn test_break_test_with_pre_line() {
         let width = 130u32;
@@ -279,7 +1036,13 @@ mod tests {
         let font_context = FontContext::new();
 
         textarea
-            .set_glyphs(&Some(font.clone()), &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         let mut line_breaker = LineBreaker::new(text);
@@ -312,7 +1075,7 @@ mod tests {
     }
 
     #[test]
-    fn test_break_with_newline_as_whitespace() {
+    fn test_break_with_newline_in_normal_white_space() {
         let width = 130u32;
         let _height = 50u32;
 
@@ -326,7 +1089,13 @@ mod tests {
         let font_context = FontContext::new();
 
         textarea
-            .set_glyphs(&Some(font.clone()), &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         let mut line_breaker = LineBreaker::new(text);
@@ -345,8 +1114,9 @@ mod tests {
             )
             .unwrap();
 
-        let expects = ["Hello World,\n", "Hello\nWorld"];
+        let expects = ["Hello World,\n", "Hello\n", "World"];
 
+        assert_eq!(line_breaker.lines.len(), expects.len());
         for (i, line) in line_breaker.lines.iter().enumerate() {
             if expects[i] != &text[line.range.clone()] {
                 panic!(
@@ -383,7 +1153,13 @@ mod tests {
         let font_context = FontContext::new();
 
         textarea
-            .set_glyphs(&Some(font.clone()), &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         let mut line_breaker = LineBreaker::new(text);
@@ -424,7 +1200,13 @@ mod tests {
         let font_context = FontContext::new();
 
         textarea
-            .set_glyphs(&Some(font.clone()), &font_context)
+            .set_glyphs(
+                &Style::default(),
+                &Some(font.clone()),
+                &font_context,
+                None,
+                None,
+            )
             .unwrap();
 
         let mut line_breaker = LineBreaker::new(text);
@@ -454,4 +1236,50 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_reuse_buffers() {
+        let text = "Open Graphic Image Writer for you";
+        let font = Some(FontMock::new(None));
+        let font_context = FontContext::new();
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+        textarea
+            .set_glyphs(&Style::default(), &font, &font_context, None, None)
+            .unwrap();
+        let style = Style {
+            font_size: 10.,
+            ..Style::default()
+        };
+
+        let render = |buffers: LineBuffers, text_wrap: TextWrap| {
+            let mut line_breaker = LineBreaker::with_buffers(text, buffers);
+            line_breaker
+                .break_text(150., &style, &font, &textarea, &font_context)
+                .unwrap();
+            match text_wrap {
+                TextWrap::Wrap => {}
+                TextWrap::Balance => line_breaker.balance(150., &style, &textarea).unwrap(),
+                TextWrap::Pretty => line_breaker.avoid_orphan(150., &style, &textarea).unwrap(),
+            }
+            let lines = line_breaker.lines.len();
+            (lines, line_breaker.into_buffers())
+        };
+
+        for text_wrap in [TextWrap::Wrap, TextWrap::Balance, TextWrap::Pretty] {
+            let before = allocations();
+            let (lines, buffers) = render(LineBuffers::default(), text_wrap);
+            assert!(lines > 1);
+            assert!(allocations() > before);
+
+            // Candidates of balancing take lines of each other, so buffers grow until the second render.
+            let (_, buffers) = render(buffers, text_wrap);
+
+            // Text of the next render is broken with buffers of the previous render.
+            let before = allocations();
+            let (next_lines, _) = render(buffers, text_wrap);
+            assert_eq!(allocations(), before);
+            assert_eq!(next_lines, lines);
+        }
+    }
 }
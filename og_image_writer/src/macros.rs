@@ -0,0 +1,98 @@
+/// Build [OGImageWriter](crate::writer::OGImageWriter) declaratively.
+///
+/// The macro is expanded to `set_*` calls and returns `Result<OGImageWriter, Error>`.
+///
+/// - `window { .. }` takes fields of [WindowStyle](crate::style::WindowStyle).
+/// - `text(text) { .. }` and `text(text, font) { .. }` take fields of [Style](crate::style::Style).
+/// - `img(src, width, height) { .. }` takes fields of [Style](crate::style::Style).
+/// - `column(..) { .. }` and `row(..) { .. }` create container with fields of [WindowStyle](crate::style::WindowStyle).
+///   Fields of [Style](crate::style::Style) for the container can follow after `;`.
+///
+/// ```rust
+/// use og_image_writer::{og_image, style::*};
+///
+/// fn main() -> anyhow::Result<()> {
+///     let font = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+///
+///     let mut writer = og_image! {
+///         window {
+///             width: 1024,
+///             height: 512,
+///             background_color: Some(Rgba([70, 40, 90, 255])),
+///             align_items: AlignItems::Center,
+///             justify_content: JustifyContent::Center,
+///         }
+///         text("Open Graphic Image Writer", font.clone()) {
+///             font_size: 80.,
+///             color: Rgba([255, 255, 255, 255]),
+///         }
+///         row(width: 600, height: 100; margin: Margin(20, 0, 0, 0)) {
+///             text("for Web Developer", font) {
+///                 font_size: 40.,
+///                 color: Rgba([255, 0, 255, 255]),
+///             }
+///         }
+///     }?;
+///
+///     writer.paint()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! og_image {
+    (window { $($field:ident : $value:expr),* $(,)? } $($children:tt)*) => {
+        (|| -> ::std::result::Result<$crate::writer::OGImageWriter, $crate::Error> {
+            #[allow(unused_mut)]
+            let mut writer = $crate::writer::OGImageWriter::new($crate::style::WindowStyle {
+                $($field: $value,)*
+                ..$crate::style::WindowStyle::default()
+            })?;
+            $crate::og_image!(@children writer; $($children)*);
+            Ok(writer)
+        })()
+    };
+
+    (@children $writer:ident;) => {};
+    (@children $writer:ident; text($text:expr, $font:expr) { $($field:ident : $value:expr),* $(,)? } $($rest:tt)*) => {
+        $writer.set_text($text, $crate::og_image!(@style $($field: $value),*), Some($font))?;
+        $crate::og_image!(@children $writer; $($rest)*);
+    };
+    (@children $writer:ident; text($text:expr) { $($field:ident : $value:expr),* $(,)? } $($rest:tt)*) => {
+        $writer.set_text($text, $crate::og_image!(@style $($field: $value),*), None)?;
+        $crate::og_image!(@children $writer; $($rest)*);
+    };
+    (@children $writer:ident; img($src:expr, $width:expr, $height:expr) { $($field:ident : $value:expr),* $(,)? } $($rest:tt)*) => {
+        $writer.set_img($src, $width, $height, $crate::og_image!(@style $($field: $value),*))?;
+        $crate::og_image!(@children $writer; $($rest)*);
+    };
+    (@children $writer:ident; column($($args:tt)*) { $($inner:tt)* } $($rest:tt)*) => {
+        $crate::og_image!(@container $writer; Column; ($($args)*) { $($inner)* });
+        $crate::og_image!(@children $writer; $($rest)*);
+    };
+    (@children $writer:ident; row($($args:tt)*) { $($inner:tt)* } $($rest:tt)*) => {
+        $crate::og_image!(@container $writer; Row; ($($args)*) { $($inner)* });
+        $crate::og_image!(@children $writer; $($rest)*);
+    };
+
+    (@container $writer:ident; $direction:ident; ($($field:ident : $value:expr),* $(,)? $(; $($style_field:ident : $style_value:expr),* $(,)?)?) { $($inner:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut container = $crate::writer::OGImageWriter::new($crate::style::WindowStyle {
+            $($field: $value,)*
+            flex_direction: $crate::style::FlexDirection::$direction,
+            ..$crate::style::WindowStyle::default()
+        })?;
+        $crate::og_image!(@children container; $($inner)*);
+        $writer.set_container(
+            container,
+            $crate::og_image!(@style $($($style_field: $style_value),*)?),
+        )?;
+    }};
+
+    (@style $($field:ident : $value:expr),*) => {
+        $crate::style::Style {
+            $($field: $value,)*
+            ..$crate::style::Style::default()
+        }
+    };
+}
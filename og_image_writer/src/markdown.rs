@@ -0,0 +1,470 @@
+//! Render Markdown text like a post excerpt.
+//!
+//! Headings, paragraphs, lists, blockquotes and code blocks are set as text elements in order.
+//! Inline emphasis, strong emphasis, code and links are styled runs of the text.
+//!
+//! ```rust
+//! use og_image_writer::markdown::MarkdownTheme;
+//! use og_image_writer::style::WindowStyle;
+//! use og_image_writer::writer::OGImageWriter;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let mut writer = OGImageWriter::new(WindowStyle::default())?;
+//!     let theme = MarkdownTheme {
+//!         font: Some(Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8])),
+//!         ..MarkdownTheme::default()
+//!     };
+//!     writer.set_markdown("# Release notes\n\n- **Faster** layout\n- `set_markdown`", &theme)?;
+//!     writer.paint()?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::style::{Margin, Style, TextBar, Token, WhiteSpace};
+use crate::writer::OGImageWriter;
+use crate::{Error, TextArea};
+
+/// Styles of Markdown elements. Colors are tokens, so they are resolved with theme of the writer.
+#[derive(Debug, Clone)]
+pub struct MarkdownTheme {
+    /// Style of paragraphs. The other elements are based on this style.
+    pub text: Style,
+    /// Font size of headings from `#` to `######` relative to `font_size` of `text`.
+    pub heading_scale: [f32; 6],
+    pub heading: Token,
+    pub emphasis: Token,
+    pub strong: Token,
+    pub code: Token,
+    pub quote: Token,
+    /// Bar along the left of blockquotes.
+    pub quote_bar: Option<TextBar>,
+    /// Font of text. Font of writer is used when this is `None`.
+    pub font: Option<Vec<u8>>,
+    /// Fonts of inline elements like italic or bold font. `font` is used when they are `None`.
+    pub emphasis_font: Option<Vec<u8>>,
+    pub strong_font: Option<Vec<u8>>,
+    pub code_font: Option<Vec<u8>>,
+    /// Space between blocks. Half of it is used between list items.
+    pub block_gap: i32,
+    /// Indent of each level of list and blockquote.
+    pub indent: i32,
+    pub bullet: String,
+}
+
+impl Default for MarkdownTheme {
+    fn default() -> Self {
+        MarkdownTheme {
+            text: Style {
+                font_size: 28.,
+                line_height: 1.4,
+                color_token: Some(Token::Text),
+                ..Style::default()
+            },
+            heading_scale: [2., 1.6, 1.3, 1.15, 1., 0.9],
+            heading: Token::Text,
+            emphasis: Token::Accent,
+            strong: Token::Primary,
+            code: Token::Secondary,
+            quote: Token::Muted,
+            quote_bar: None,
+            font: None,
+            emphasis_font: None,
+            strong_font: None,
+            code_font: None,
+            block_gap: 16,
+            indent: 32,
+            bullet: "•".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    // Level of nesting, marker and text.
+    ListItem(usize, String, String),
+    Quote(String),
+    Code(String),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Inline {
+    emphasis: bool,
+    strong: bool,
+    code: bool,
+}
+
+impl OGImageWriter {
+    /// Set Markdown text as elements styled by `theme`. See [markdown](crate::markdown) module.
+    pub fn set_markdown(&mut self, markdown: &str, theme: &MarkdownTheme) -> Result<(), Error> {
+        let blocks = parse_blocks(markdown);
+        for (i, block) in blocks.iter().enumerate() {
+            let mut style = theme.text.clone();
+            let (text, indent, gap) = match block {
+                Block::Heading(level, text) => {
+                    style.font_size *= theme.heading_scale[level - 1];
+                    style.color_token = Some(theme.heading);
+                    (text.clone(), 0, theme.block_gap)
+                }
+                Block::Paragraph(text) => (text.clone(), 0, theme.block_gap),
+                Block::ListItem(level, marker, text) => {
+                    let gap = match blocks.get(i + 1) {
+                        Some(Block::ListItem(..)) => theme.block_gap / 2,
+                        _ => theme.block_gap,
+                    };
+                    let marker = match marker.as_str() {
+                        "-" | "*" | "+" => &theme.bullet,
+                        marker => marker,
+                    };
+                    (format!("{} {}", marker, text), level + 1, gap)
+                }
+                Block::Quote(text) => {
+                    style.color_token = Some(theme.quote);
+                    style.text_bar = theme.quote_bar;
+                    (text.clone(), 1, theme.block_gap)
+                }
+                Block::Code(code) => {
+                    style.color_token = Some(theme.code);
+                    style.white_space = WhiteSpace::Pre;
+                    (code.clone(), 0, theme.block_gap)
+                }
+            };
+            // Gap is not added after the last block.
+            let gap = if i + 1 == blocks.len() { 0 } else { gap };
+            let Margin(top, right, bottom, left) = style.margin;
+            style.margin = Margin(
+                top,
+                right,
+                bottom + gap,
+                left + indent as i32 * theme.indent,
+            );
+
+            // Code block is not parsed as inline elements.
+            if let Block::Code(_) = block {
+                let font = theme.code_font.clone().or_else(|| theme.font.clone());
+                self.set_text(&text, style, font)?;
+                continue;
+            }
+
+            let runs = parse_inline(&text);
+            match runs.as_slice() {
+                [] => {}
+                [(text, inline)] if *inline == Inline::default() => {
+                    self.set_text(text, style, theme.font.clone())?
+                }
+                _ => {
+                    let mut textarea = TextArea::new();
+                    for (text, inline) in runs {
+                        // Runs without their own font are drawn with font of the text.
+                        let (color_token, font) = match inline {
+                            Inline { code: true, .. } => (Some(theme.code), &theme.code_font),
+                            Inline { strong: true, .. } => (Some(theme.strong), &theme.strong_font),
+                            Inline { emphasis: true, .. } => {
+                                (Some(theme.emphasis), &theme.emphasis_font)
+                            }
+                            _ => (style.color_token, &None),
+                        };
+                        let style = Style {
+                            color_token,
+                            ..style.clone()
+                        };
+                        textarea.push(&text, style, font.clone())?;
+                    }
+                    self.set_textarea(textarea, style, theme.font.clone())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    // Lines of paragraph or blockquote that is not finished yet.
+    let mut lines: Vec<&str> = vec![];
+    let mut is_quote = false;
+    let mut code: Option<Vec<&str>> = None;
+
+    let flush = |blocks: &mut Vec<Block>, lines: &mut Vec<&str>, is_quote: bool| {
+        if lines.is_empty() {
+            return;
+        }
+        let text = join_lines(lines);
+        lines.clear();
+        blocks.push(match is_quote {
+            true => Block::Quote(text),
+            false => Block::Paragraph(text),
+        });
+    };
+
+    for line in markdown.lines() {
+        if let Some(code_lines) = &mut code {
+            if line.trim_start().starts_with("```") {
+                blocks.push(Block::Code(code_lines.join("\n")));
+                code = None;
+            } else {
+                code_lines.push(line);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            flush(&mut blocks, &mut lines, is_quote);
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            flush(&mut blocks, &mut lines, is_quote);
+            code = Some(vec![]);
+            continue;
+        }
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            if !is_quote {
+                flush(&mut blocks, &mut lines, is_quote);
+            }
+            is_quote = true;
+            lines.push(quote.strip_prefix(' ').unwrap_or(quote));
+            continue;
+        }
+        if let Some((level, text)) = heading(trimmed) {
+            flush(&mut blocks, &mut lines, is_quote);
+            blocks.push(Block::Heading(level, text.to_string()));
+            continue;
+        }
+        if let Some((marker, text)) = list_item(trimmed) {
+            flush(&mut blocks, &mut lines, is_quote);
+            // Two spaces of indent are one level of nesting.
+            let level = (line.len() - line.trim_start().len()) / 2;
+            blocks.push(Block::ListItem(level, marker.to_string(), text.to_string()));
+            continue;
+        }
+        if is_quote {
+            flush(&mut blocks, &mut lines, is_quote);
+        }
+        is_quote = false;
+        lines.push(line);
+    }
+    if let Some(code_lines) = code {
+        blocks.push(Block::Code(code_lines.join("\n")));
+    }
+    flush(&mut blocks, &mut lines, is_quote);
+    blocks
+}
+
+// Lines are joined with space, and line that ends with two spaces breaks line.
+fn join_lines(lines: &[&str]) -> String {
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            // Line separator is a forced line break.
+            text.push(match lines[i - 1].ends_with("  ") {
+                true => '\u{2028}',
+                false => ' ',
+            });
+        }
+        text.push_str(line.trim());
+    }
+    text
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match &line[level..] {
+        text if (1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')) => {
+            Some((level, text.trim().trim_end_matches('#').trim_end()))
+        }
+        _ => None,
+    }
+}
+
+fn list_item(line: &str) -> Option<(&str, &str)> {
+    let (marker, text) = line.split_once(' ')?;
+    let is_bullet = matches!(marker, "-" | "*" | "+");
+    let is_ordered = marker.len() > 1
+        && marker.ends_with(['.', ')'])
+        && marker[..marker.len() - 1]
+            .chars()
+            .all(|c| c.is_ascii_digit());
+    match is_bullet || is_ordered {
+        true => Some((marker, text.trim())),
+        false => None,
+    }
+}
+
+// Split text into runs of inline elements. Markers that are not closed are kept as text.
+fn parse_inline(text: &str) -> Vec<(String, Inline)> {
+    let mut runs: Vec<(String, Inline)> = vec![];
+    let mut inline = Inline::default();
+    let mut current = String::new();
+    let push = |runs: &mut Vec<(String, Inline)>, current: &mut String, inline: Inline| {
+        if !current.is_empty() {
+            runs.push((std::mem::take(current), inline));
+        }
+    };
+
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        match c {
+            '\\' if after.starts_with(|c: char| c.is_ascii_punctuation()) => {
+                let escaped = after.chars().next().unwrap();
+                current.push(escaped);
+                rest = &after[escaped.len_utf8()..];
+            }
+            '`' => match after.find('`') {
+                Some(end) => {
+                    push(&mut runs, &mut current, inline);
+                    let code = Inline {
+                        code: true,
+                        ..inline
+                    };
+                    runs.push((after[..end].to_string(), code));
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    current.push(c);
+                    rest = after;
+                }
+            },
+            '*' | '_' if after.starts_with(c) => {
+                let marker = &rest[..2];
+                let after = &rest[2..];
+                if inline.strong || after.contains(marker) {
+                    push(&mut runs, &mut current, inline);
+                    inline.strong = !inline.strong;
+                } else {
+                    current.push_str(marker);
+                }
+                rest = after;
+            }
+            '*' | '_' => {
+                // `_` in words like `snake_case` is not a marker.
+                let is_intraword = c == '_'
+                    && current.ends_with(|c: char| c.is_alphanumeric())
+                    && after.starts_with(|c: char| c.is_alphanumeric());
+                if !is_intraword && (inline.emphasis || after.contains(c)) {
+                    push(&mut runs, &mut current, inline);
+                    inline.emphasis = !inline.emphasis;
+                } else {
+                    current.push(c);
+                }
+                rest = after;
+            }
+            // Only label of link is rendered.
+            '[' => match link(rest) {
+                Some((label, len)) => {
+                    current.push_str(label);
+                    rest = &rest[len..];
+                }
+                None => {
+                    current.push(c);
+                    rest = after;
+                }
+            },
+            _ => {
+                current.push(c);
+                rest = after;
+            }
+        }
+    }
+    push(&mut runs, &mut current, inline);
+    runs
+}
+
+// Label of `[label](url)` and length of the link.
+fn link(text: &str) -> Option<(&str, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = text[label_end..].find(')')? + label_end;
+    Some((&text[1..label_end], url_end + 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::Element;
+    use crate::style::{Rgba, WindowStyle};
+
+    #[test]
+    fn test_parse_blocks() {
+        let markdown = "# Title #\n\nFirst line\nsecond line  \nbreak\n\n- one\n  1. nested\n\n> quote\n> more\n\n```\nlet a = 1;\n```";
+        assert_eq!(
+            parse_blocks(markdown),
+            vec![
+                Block::Heading(1, "Title".to_string()),
+                Block::Paragraph("First line second line\u{2028}break".to_string()),
+                Block::ListItem(0, "-".to_string(), "one".to_string()),
+                Block::ListItem(1, "1.".to_string(), "nested".to_string()),
+                Block::Quote("quote more".to_string()),
+                Block::Code("let a = 1;".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        let strong = Inline {
+            strong: true,
+            ..Inline::default()
+        };
+        let emphasis = Inline {
+            emphasis: true,
+            ..Inline::default()
+        };
+        let code = Inline {
+            code: true,
+            ..Inline::default()
+        };
+        assert_eq!(
+            parse_inline("**bold** and *it* `a*b` [link](https://example.com) snake_case 2 * 3"),
+            vec![
+                ("bold".to_string(), strong),
+                (" and ".to_string(), Inline::default()),
+                ("it".to_string(), emphasis),
+                (" ".to_string(), Inline::default()),
+                ("a*b".to_string(), code),
+                (" link snake_case 2 * 3".to_string(), Inline::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_markdown() {
+        let mut writer = OGImageWriter::new(WindowStyle::default()).unwrap();
+        let theme = MarkdownTheme {
+            font: Some(Vec::from(
+                include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+            )),
+            quote_bar: Some(TextBar {
+                color: Rgba([128, 128, 128, 255]),
+                width: 4,
+                gap: 12,
+            }),
+            ..MarkdownTheme::default()
+        };
+        writer
+            .set_markdown("## Hello\n\n- **one**\n- two\n\n> quote", &theme)
+            .unwrap();
+
+        let elements = writer.elements().unwrap();
+        assert_eq!(elements.len(), 4);
+        let texts: Vec<&str> = elements
+            .iter()
+            .map(|elm| match elm {
+                Element::Text(Some(text)) => text.text(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(texts, vec!["Hello", "• one", "• two", "quote"]);
+
+        let heading = elements[0].style().unwrap();
+        assert_eq!(heading.font_size, 28. * 1.6);
+        assert_eq!(heading.margin.2, 16);
+        let item = elements[1].style().unwrap();
+        assert_eq!((item.margin.2, item.margin.3), (8, 32));
+        assert_eq!(elements[3].style().unwrap().margin.2, 0);
+        assert_eq!(elements[3].style().unwrap().line_indent(false), 16);
+
+        writer.paint().unwrap();
+    }
+}
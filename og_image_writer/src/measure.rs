@@ -0,0 +1,123 @@
+//! Result of measuring text returned by [`OGImageWriter::measure_text()`](crate::writer::OGImageWriter::measure_text).
+
+use std::ops::Range;
+
+/// Box of a line relative to the top left of the text, before `text_align` is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    /// Byte range of the line in [`TextMeasurement::text`].
+    pub range: Range<usize>,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Size and lines of text that is laid out without painting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMeasurement {
+    /// Text after `text_transform`, and ellipsis when text overflows.
+    pub text: String,
+    /// Width of the widest line, or the whole width of columns.
+    pub width: u32,
+    pub height: u32,
+    pub lines: Vec<LineBox>,
+    /// Whether text is cut by `max_height`.
+    pub is_overflow: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::{
+        FontVariantNumeric, KernSetting, Style, TextAlign, WhiteSpace, WindowStyle,
+    };
+    use crate::writer::OGImageWriter;
+    use crate::TextArea;
+
+    #[test]
+    fn test_measure_text() {
+        let writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text("Hello World, Hello World");
+
+        let measured = writer
+            .measure_text(&textarea, &Style::default(), Some(font.clone()), 400)
+            .unwrap();
+        assert_eq!(measured.lines.len(), 1);
+
+        let narrow = writer
+            .measure_text(&textarea, &Style::default(), Some(font), 150)
+            .unwrap();
+        let lines: Vec<&str> = narrow
+            .lines
+            .iter()
+            .map(|line| &narrow.text[line.range.clone()])
+            .collect();
+        assert_eq!(lines, vec!["Hello World, ", "Hello World"]);
+        assert!(narrow.width <= 150);
+        assert!(narrow.height > measured.height);
+        assert!(!narrow.is_overflow);
+
+        // TextArea is not consumed by measuring.
+        assert_eq!(textarea.spans().count(), 1);
+    }
+
+    #[test]
+    fn test_tabular_nums_and_decimal_align() {
+        let writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+        // Digits are measured with their outlines, so they have different widths.
+        let proportional = Style {
+            kern_setting: KernSetting::Optical,
+            ..Style::default()
+        };
+        let style = Style {
+            font_variant_numeric: FontVariantNumeric::TabularNums,
+            ..proportional.clone()
+        };
+
+        let width = |text: &str, style: &Style| {
+            let mut textarea = TextArea::new();
+            textarea.push_text(text);
+            writer
+                .measure_text(&textarea, style, Some(font.clone()), 400)
+                .unwrap()
+                .width
+        };
+        assert_ne!(width("111", &proportional), width("000", &proportional));
+        assert_eq!(width("111", &style), width("000", &style));
+        let digit = width("1000", &style) - width("100", &style);
+
+        let mut textarea = TextArea::new();
+        textarea.push_text("3.14\n12.5\n100");
+        let measured = writer
+            .measure_text(
+                &textarea,
+                &Style {
+                    white_space: WhiteSpace::PreLine,
+                    text_align: TextAlign::Decimal,
+                    ..style.clone()
+                },
+                Some(font),
+                400,
+            )
+            .unwrap();
+        let lines = &measured.lines;
+        assert_eq!(lines.len(), 3);
+        // Decimal points are aligned, and integer is aligned at its end.
+        assert_eq!(lines[0].x, lines[1].x + digit);
+        assert_eq!(lines[2].x + lines[2].width, lines[0].x + digit);
+    }
+}
@@ -0,0 +1,192 @@
+use super::element::Element;
+use super::font::{create_font, FontArc};
+use super::layout::TextArea;
+use super::stats::Stopwatch;
+use super::style::{FlexDirection, Margin, Style};
+use super::writer::OGImageWriter;
+use crate::Error;
+use std::cell::RefCell;
+
+// Source of named text, so that the text can be laid out again when it is updated.
+pub(super) struct NamedText {
+    pub(super) index: usize,
+    text: String,
+    style: Style,
+    font: Option<FontArc>,
+}
+
+impl OGImageWriter {
+    /// Same as [set_text](Self::set_text), but the text can be updated by `id` until painting.
+    /// This is useful to build a template once and fill in texts for each page.
+    pub fn set_text_with_id(
+        &mut self,
+        id: &str,
+        text: &str,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        let font = font
+            .map(create_font)
+            .transpose()
+            .map_err(|err| err.in_element(index, Some(text)))?;
+        self.set_text_with(text, style.clone(), || Ok(font.clone()))?;
+
+        self.named.insert(
+            id.to_string(),
+            NamedText {
+                index,
+                text: text.to_string(),
+                style,
+                font,
+            },
+        );
+        Ok(())
+    }
+
+    /// Replace content of the text that is set by [set_text_with_id](Self::set_text_with_id).
+    pub fn update_text(&mut self, id: &str, text: &str) -> Result<(), Error> {
+        self.update_named(id, |named| named.text = text.to_string())
+    }
+
+    /// Replace style of the text that is set by [set_text_with_id](Self::set_text_with_id).
+    pub fn update_text_style(&mut self, id: &str, style: Style) -> Result<(), Error> {
+        self.update_named(id, |named| named.style = style)
+    }
+
+    // Lay out the named text again, and put it in place of the previous element.
+    fn update_named(&mut self, id: &str, update: impl FnOnce(&mut NamedText)) -> Result<(), Error> {
+        // Positions are fixed after layout, so texts can't be updated until painting.
+        if self.is_laid_out {
+            return Err(Error::AlreadyLaidOut);
+        }
+        let mut named = match self.named.remove(id) {
+            Some(named) => named,
+            None => return Err(Error::NotFoundElementId(id.to_string())),
+        };
+        update(&mut named);
+
+        let index = named.index;
+        let previous = self.tree.0.remove(index);
+        self.resize_content(&previous, false);
+
+        let text = &named.text;
+        let result = self.limits.check_text(text.chars().count()).and_then(|_| {
+            let mut textarea = TextArea::new();
+            textarea.push_text(text);
+            let stopwatch = Stopwatch::start();
+            let result = self.process_text(
+                RefCell::new(textarea),
+                named.style.clone(),
+                named.font.clone(),
+            );
+            self.stats.layout_time += stopwatch.elapsed();
+            result
+        });
+        let elm = match result {
+            Ok(()) => self.tree.0.pop().unwrap_or(previous),
+            Err(err) => {
+                self.resize_content(&previous, true);
+                self.tree.0.insert(index, previous);
+                return Err(err.in_element(index, Some(text)));
+            }
+        };
+        self.tree.0.insert(index, elm);
+        self.named.insert(id.to_string(), named);
+        Ok(())
+    }
+
+    // Add or remove size of the element to the content of window.
+    fn resize_content(&mut self, elm: &Element, is_added: bool) {
+        if elm.is_absolute() {
+            return;
+        }
+        let Margin(margin_top, margin_right, margin_bottom, margin_left) = elm.margin();
+        let (width, height) = match elm {
+            Element::Img(Some(img)) => (img.rect.width, img.rect.height),
+            Element::Custom(Some(custom)) => (custom.rect.width, custom.rect.height),
+            Element::Text(Some(text)) => (
+                text.metrics.max_line_width as u32,
+                text.metrics.total_height,
+            ),
+            _ => return,
+        };
+        let (content, size) = match self.window.flex_direction {
+            FlexDirection::Column => (
+                &mut self.content.height,
+                (height as i32 + margin_top + margin_bottom).max(0) as u32,
+            ),
+            FlexDirection::Row => (
+                &mut self.content.width,
+                (width as i32 + margin_left + margin_right).max(0) as u32,
+            ),
+        };
+        *content = match is_added {
+            true => *content + size,
+            false => content.saturating_sub(size),
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::{AlignItems, JustifyContent, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use crate::Error;
+
+    fn writer() -> OGImageWriter {
+        OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 200,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        })
+        .unwrap()
+    }
+
+    fn font() -> Option<Vec<u8>> {
+        Some(Vec::from(
+            include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+        ))
+    }
+
+    #[test]
+    fn test_update_text() {
+        let style = Style {
+            font_size: 30.,
+            ..Style::default()
+        };
+        let mut updated = writer();
+        updated
+            .set_text_with_id("title", "Draft", style.clone(), font())
+            .unwrap();
+        updated.set_text("Footer", style.clone(), font()).unwrap();
+        updated
+            .update_text_style(
+                "title",
+                Style {
+                    font_size: 40.,
+                    ..style.clone()
+                },
+            )
+            .unwrap();
+        updated.update_text("title", "Published").unwrap();
+        assert!(matches!(
+            updated.update_text("subtitle", "Missing"),
+            Err(Error::NotFoundElementId(_))
+        ));
+        updated.paint().unwrap();
+
+        let mut expected = writer();
+        let title_style = Style {
+            font_size: 40.,
+            ..style.clone()
+        };
+        expected.set_text("Published", title_style, font()).unwrap();
+        expected.set_text("Footer", style, font()).unwrap();
+        expected.paint().unwrap();
+
+        assert!(updated.into_rgba().unwrap() == expected.into_rgba().unwrap());
+    }
+}
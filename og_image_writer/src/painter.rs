@@ -0,0 +1,657 @@
+//! Painter is the drawing backend of [OGImageWriter](crate::writer::OGImageWriter).
+//!
+//! By default, elements are rasterized into [RgbaImage](image::RgbaImage).
+//! You can replace it with your own rasterizer or recording painter
+//! by [`OGImageWriter::set_painter()`](crate::writer::OGImageWriter::set_painter).
+
+use super::blend::overlay;
+use super::font_trait::Font;
+use super::renderer::{draw_text_mut, for_each_glyph, FontSetting};
+use super::style::KernSetting;
+use crate::Error;
+use ab_glyph::{point, Outline, OutlineCurve, OutlinedGlyph, Point, PxScaleFactor};
+use ab_glyph_rasterizer::Rasterizer;
+use image::{Rgba, RgbaImage};
+use imageproc::pixelops::weighted_sum;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Width and height of text.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TextExtents {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Command of glyph outline in pixel coordinates of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// Control point and end point.
+    QuadTo(f32, f32, f32, f32),
+    /// Two control points and end point.
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// Outline of glyph that is placed in the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphPath {
+    pub glyph_id: u16,
+    /// Position of glyph origin on baseline.
+    pub x: f32,
+    pub y: f32,
+    pub color: Rgba<u8>,
+    pub commands: Vec<PathCommand>,
+}
+
+/// Text that is drawn with single font and style.
+pub struct TextRun<'a> {
+    pub(super) text: &'a str,
+    pub(super) font: &'a dyn Font,
+    pub(super) setting: &'a FontSetting,
+    pub(super) color: Rgba<u8>,
+    pub(super) x: u32,
+    pub(super) y: u32,
+}
+
+impl<'a> TextRun<'a> {
+    pub(super) fn new(
+        text: &'a str,
+        font: &'a dyn Font,
+        setting: &'a FontSetting,
+        color: Rgba<u8>,
+        x: u32,
+        y: u32,
+    ) -> Self {
+        TextRun {
+            text,
+            font,
+            setting,
+            color,
+            x,
+            y,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.text
+    }
+
+    pub fn color(&self) -> Rgba<u8> {
+        self.color
+    }
+
+    /// Left position of text.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// Top position of text.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn font_size(&self) -> f32 {
+        self.setting.size
+    }
+
+    pub fn ascent(&self) -> f32 {
+        self.font.ascent(self.setting.size)
+    }
+
+    pub fn descent(&self) -> f32 {
+        self.font.descent(self.setting.size)
+    }
+
+    /// Width and height calculated by font metrics.
+    pub fn extents(&self) -> TextExtents {
+        let metrics = self.font.text_extents(self.text, self.setting);
+        TextExtents {
+            width: metrics.width,
+            height: metrics.height,
+        }
+    }
+
+    /// Call `f` with each outlined glyph and the position of its pixel bounds.
+    /// Glyphs are positioned in the same way as default painter.
+    pub fn for_each_glyph<F>(&self, mut f: F)
+    where
+        F: FnMut(&OutlinedGlyph, i32, i32),
+    {
+        let ascent = self.ascent();
+        for_each_glyph(self.font, self.setting, self.text, |q, current_x| {
+            let bb = q.px_bounds();
+            let mut x = self.x as i32 + current_x;
+            if let KernSetting::Normal = self.setting.kern_setting {
+                x += bb.min.x as i32;
+            }
+            let y = self.y as i32 + (bb.min.y + ascent) as i32;
+            f(q, x, y);
+        });
+    }
+
+    /// Unscaled outline of glyph. This is useful for vector painter.
+    pub fn outline(&self, glyph: &OutlinedGlyph) -> Option<Outline> {
+        self.font.outline(glyph.glyph().id)
+    }
+
+    /// Scale factor to convert unscaled outline to pixel.
+    pub fn scale_factor(&self) -> PxScaleFactor {
+        self.font.scale_factor(self.setting.size)
+    }
+
+    /// Outlines of glyphs in pixel coordinates. Glyphs without outline like spaces are skipped.
+    pub fn glyph_paths(&self) -> Vec<GlyphPath> {
+        let mut paths = vec![];
+        let ascent = self.ascent();
+        let scale_factor = self.scale_factor();
+
+        self.for_each_glyph(|q, x, _| {
+            let outline = match self.outline(q) {
+                Some(outline) => outline,
+                None => return,
+            };
+
+            // Glyph position is given by its pixel bounds, so move it back to the glyph origin.
+            let origin_x = x as f32 - q.px_bounds().min.x;
+            let origin_y = self.y as f32 + ascent;
+
+            let to_px = |p: Point| {
+                (
+                    origin_x + p.x * scale_factor.horizontal,
+                    origin_y - p.y * scale_factor.vertical,
+                )
+            };
+
+            paths.push(GlyphPath {
+                glyph_id: q.glyph().id.0,
+                x: origin_x,
+                y: origin_y,
+                color: self.color,
+                commands: outline_commands(&outline, to_px),
+            });
+        });
+
+        paths
+    }
+}
+// Convert unscaled outline to path commands. `to_px` maps a point of outline to pixel coordinates.
+pub(super) fn outline_commands<F>(outline: &Outline, to_px: F) -> Vec<PathCommand>
+where
+    F: Fn(Point) -> (f32, f32),
+{
+    let mut commands = vec![];
+    let mut last: Option<Point> = None;
+    for curve in &outline.curves {
+        let start = match curve {
+            OutlineCurve::Line(p0, _)
+            | OutlineCurve::Quad(p0, _, _)
+            | OutlineCurve::Cubic(p0, _, _, _) => *p0,
+        };
+        if last != Some(start) {
+            if last.is_some() {
+                commands.push(PathCommand::Close);
+            }
+            let (x0, y0) = to_px(start);
+            commands.push(PathCommand::MoveTo(x0, y0));
+        }
+
+        last = Some(match curve {
+            OutlineCurve::Line(_, p1) => {
+                let (x1, y1) = to_px(*p1);
+                commands.push(PathCommand::LineTo(x1, y1));
+                *p1
+            }
+            OutlineCurve::Quad(_, p1, p2) => {
+                let (x1, y1) = to_px(*p1);
+                let (x2, y2) = to_px(*p2);
+                commands.push(PathCommand::QuadTo(x1, y1, x2, y2));
+                *p2
+            }
+            OutlineCurve::Cubic(_, p1, p2, p3) => {
+                let (x1, y1) = to_px(*p1);
+                let (x2, y2) = to_px(*p2);
+                let (x3, y3) = to_px(*p3);
+                commands.push(PathCommand::CubicTo(x1, y1, x2, y2, x3, y3));
+                *p3
+            }
+        });
+    }
+    if last.is_some() {
+        commands.push(PathCommand::Close);
+    }
+    commands
+}
+
+// Coverage of path that is rasterized in the pixel bounds of path.
+pub(super) struct PathCoverage {
+    pub(super) x: i32,
+    pub(super) y: i32,
+    pub(super) width: u32,
+    pub(super) height: u32,
+    rasterizer: Rasterizer,
+}
+
+impl PathCoverage {
+    pub(super) fn new(commands: &[PathCommand]) -> Option<Self> {
+        let mut min = (f32::MAX, f32::MAX);
+        let mut max = (f32::MIN, f32::MIN);
+        let mut extend = |x: f32, y: f32| {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        };
+        for command in commands {
+            match *command {
+                PathCommand::MoveTo(x, y) | PathCommand::LineTo(x, y) => extend(x, y),
+                PathCommand::QuadTo(x1, y1, x, y) => {
+                    extend(x1, y1);
+                    extend(x, y);
+                }
+                PathCommand::CubicTo(x1, y1, x2, y2, x, y) => {
+                    extend(x1, y1);
+                    extend(x2, y2);
+                    extend(x, y);
+                }
+                PathCommand::Close => {}
+            }
+        }
+        if min.0 > max.0 || min.1 > max.1 {
+            return None;
+        }
+
+        let (x, y) = (min.0.floor(), min.1.floor());
+        let width = (max.0 - x).ceil() as u32 + 1;
+        let height = (max.1 - y).ceil() as u32 + 1;
+        let mut rasterizer = Rasterizer::new(width as usize, height as usize);
+
+        // Points are relative to the top left of bounds.
+        let p = |px: f32, py: f32| point(px - x, py - y);
+        let mut start = point(0., 0.);
+        let mut current = start;
+        for command in commands {
+            match *command {
+                PathCommand::MoveTo(px, py) => {
+                    start = p(px, py);
+                    current = start;
+                }
+                PathCommand::LineTo(px, py) => {
+                    let next = p(px, py);
+                    rasterizer.draw_line(current, next);
+                    current = next;
+                }
+                PathCommand::QuadTo(x1, y1, px, py) => {
+                    let next = p(px, py);
+                    rasterizer.draw_quad(current, p(x1, y1), next);
+                    current = next;
+                }
+                PathCommand::CubicTo(x1, y1, x2, y2, px, py) => {
+                    let next = p(px, py);
+                    rasterizer.draw_cubic(current, p(x1, y1), p(x2, y2), next);
+                    current = next;
+                }
+                PathCommand::Close => {
+                    if current != start {
+                        rasterizer.draw_line(current, start);
+                    }
+                    current = start;
+                }
+            }
+        }
+
+        Some(PathCoverage {
+            x: x as i32,
+            y: y as i32,
+            width,
+            height,
+            rasterizer,
+        })
+    }
+
+    // Call `f` with position in the image and coverage of each pixel that is covered by path.
+    pub(super) fn for_each_pixel<F: FnMut(i32, i32, f32)>(&self, mut f: F) {
+        self.rasterizer.for_each_pixel_2d(|px, py, coverage| {
+            if coverage > 0. {
+                f(self.x + px as i32, self.y + py as i32, coverage.min(1.));
+            }
+        });
+    }
+}
+
+/// Painter receives draw calls of elements in painting order.
+pub trait Painter {
+    /// Fill rectangle with color. Pixels in the rectangle are replaced with color.
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error>;
+
+    /// Draw image at position.
+    fn draw_image(&mut self, image: &RgbaImage, x: u32, y: u32) -> Result<(), Error>;
+
+    /// Draw text at position of [TextRun].
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error>;
+
+    /// Fill outline of glyph that is placed individually like text along a path.
+    /// By default, the outline is rasterized and drawn by [draw_image](Self::draw_image).
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        let coverage = match PathCoverage::new(&path.commands) {
+            Some(coverage) => coverage,
+            None => return Ok(()),
+        };
+
+        // Image can not be drawn at negative position, so pixels outside of the top left are cut off.
+        let (x, y) = (coverage.x.max(0), coverage.y.max(0));
+        let width = (coverage.x + coverage.width as i32 - x).max(0) as u32;
+        let height = (coverage.y + coverage.height as i32 - y).max(0) as u32;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let mut image = RgbaImage::new(width, height);
+        let [r, g, b, a] = path.color.0;
+        coverage.for_each_pixel(|px, py, value| {
+            if px >= x && py >= y {
+                let alpha = (a as f32 * value).round() as u8;
+                image.put_pixel((px - x) as u32, (py - y) as u32, Rgba([r, g, b, alpha]));
+            }
+        });
+
+        self.draw_image(&image, x as u32, y as u32)
+    }
+
+    /// Measure text that is drawn by [draw_text](Self::draw_text).
+    /// This is used to advance position of next text in the same line.
+    fn measure(&self, run: &TextRun) -> TextExtents {
+        run.extents()
+    }
+}
+
+/// Default painter that rasterizes elements into image.
+impl Painter for RgbaImage {
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        let x_end = x.saturating_add(width).min(self.width());
+        let y_end = y.saturating_add(height).min(self.height());
+        for py in y..y_end {
+            for px in x..x_end {
+                self.put_pixel(px, py, color);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_image(&mut self, image: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        overlay(self, image, x, y);
+        Ok(())
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        draw_text_mut(
+            self,
+            run.color,
+            run.x,
+            run.y as i32,
+            run.font,
+            run.setting,
+            run.text,
+        );
+        Ok(())
+    }
+
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        fill_glyph_path(self, path, 0);
+        Ok(())
+    }
+}
+
+// Fill glyph path into image whose first row is row `top` of the window.
+pub(super) fn fill_glyph_path(image: &mut RgbaImage, path: &GlyphPath, top: i32) {
+    let coverage = match PathCoverage::new(&path.commands) {
+        Some(coverage) => coverage,
+        None => return,
+    };
+    let (width, height) = (image.width() as i32, image.height() as i32);
+    coverage.for_each_pixel(|x, y, value| {
+        let y = y - top;
+        if x >= 0 && x < width && y >= 0 && y < height {
+            let pixel = image.get_pixel_mut(x as u32, y as u32);
+            *pixel = weighted_sum(*pixel, path.color, 1.0 - value, value);
+        }
+    });
+}
+
+// Painter that records glyph paths of texts and ignores the other draw calls.
+pub(super) struct GlyphPathRecorder(pub(super) Rc<RefCell<Vec<GlyphPath>>>);
+
+impl Painter for GlyphPathRecorder {
+    fn fill_rect(
+        &mut self,
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+        _color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn draw_image(&mut self, _image: &RgbaImage, _x: u32, _y: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        self.0.borrow_mut().append(&mut run.glyph_paths());
+        Ok(())
+    }
+
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        self.0.borrow_mut().push(path.clone());
+        Ok(())
+    }
+}
+
+// Painter that draws into image shared with the writer, like offscreen layer.
+pub(super) struct SharedImage(pub(super) Rc<RefCell<RgbaImage>>);
+
+impl Painter for SharedImage {
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        self.0.borrow_mut().fill_rect(x, y, width, height, color)
+    }
+
+    fn draw_image(&mut self, image: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        self.0.borrow_mut().draw_image(image, x, y)
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        self.0.borrow_mut().draw_text(run)
+    }
+
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        self.0.borrow_mut().draw_glyph_path(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::layout::TextArea;
+    use crate::style::{Rgba as StyleRgba, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[derive(Default)]
+    struct RecordingPainter(Rc<RefCell<Vec<String>>>);
+
+    impl Painter for RecordingPainter {
+        fn fill_rect(
+            &mut self,
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+            _color: Rgba<u8>,
+        ) -> Result<(), Error> {
+            self.0
+                .borrow_mut()
+                .push(format!("rect {} {} {} {}", x, y, width, height));
+            Ok(())
+        }
+
+        fn draw_image(&mut self, _image: &RgbaImage, _x: u32, _y: u32) -> Result<(), Error> {
+            self.0.borrow_mut().push("image".to_string());
+            Ok(())
+        }
+
+        fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+            self.0.borrow_mut().push(format!("text {}", run.text()));
+            Ok(())
+        }
+
+        fn measure(&self, run: &TextRun) -> TextExtents {
+            self.0.borrow_mut().push(format!("measure {}", run.text()));
+            run.extents()
+        }
+    }
+
+    #[test]
+    fn test_set_painter() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 50,
+            background_color: Some(StyleRgba([255, 0, 0, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+
+        let painter = RecordingPainter::default();
+        let calls = painter.0.clone();
+        writer.set_painter(Box::new(painter)).unwrap();
+
+        writer
+            .set_text(
+                "Hello",
+                Style::default(),
+                Some(Vec::from(
+                    include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+                )),
+            )
+            .unwrap();
+        writer.paint().unwrap();
+
+        // Text is measured when it is laid out, and it is not measured again when it is painted.
+        assert_eq!(
+            *calls.borrow(),
+            vec!["rect 0 0 100 50", "measure Hello", "text Hello"]
+        );
+
+        // Text is not painted to image while painter is set.
+        assert!(writer.take_painter().is_some());
+        let image = writer.into_rgba().unwrap();
+        assert!(image.pixels().all(|p| p == &Rgba([255, 0, 0, 255])));
+    }
+
+    #[test]
+    fn test_paint_without_measuring() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let painter = RecordingPainter::default();
+        let calls = painter.0.clone();
+        writer.set_painter(Box::new(painter)).unwrap();
+
+        let mut textarea = TextArea::new();
+        textarea.push_text("Hello ");
+        textarea
+            .push(
+                "World",
+                Style {
+                    font_size: 40.,
+                    ..Style::default()
+                },
+                None,
+            )
+            .unwrap();
+        writer
+            .set_textarea(
+                textarea,
+                Style::default(),
+                Some(Vec::from(
+                    include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+                )),
+            )
+            .unwrap();
+        let measures = || {
+            calls
+                .borrow()
+                .iter()
+                .filter(|call| call.starts_with("measure"))
+                .count()
+        };
+        // Each run is measured once in layout.
+        assert_eq!(measures(), 2);
+
+        writer.glyph_layout().unwrap();
+        writer.paint().unwrap();
+        assert_eq!(measures(), 2);
+        assert_eq!(
+            calls
+                .borrow()
+                .iter()
+                .filter(|call| call.starts_with("text"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_glyph_paths() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 200,
+            height: 50,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_text(
+                "Hi o",
+                Style::default(),
+                Some(Vec::from(
+                    include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]
+                )),
+            )
+            .unwrap();
+
+        let paths = writer.glyph_paths().unwrap();
+        // Space does not have outline.
+        assert_eq!(paths.len(), 3);
+        assert!(paths.windows(2).all(|p| p[0].x < p[1].x));
+        for path in &paths {
+            assert!(matches!(
+                path.commands.first(),
+                Some(PathCommand::MoveTo(..))
+            ));
+            assert_eq!(path.commands.last(), Some(&PathCommand::Close));
+        }
+    }
+}
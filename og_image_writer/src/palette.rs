@@ -0,0 +1,122 @@
+use crate::animation::{write_chunk, PngChunks, PNG_SIGNATURE};
+use crate::Error;
+use color_quant::NeuQuant;
+use image::codecs::png::PngEncoder;
+use image::imageops::{dither, index_colors};
+use image::{ColorType, RgbaImage};
+use wasm_bindgen::prelude::*;
+
+// Sampling factor of NeuQuant. 1 is the best quality and 30 is the fastest.
+const SAMPLE_FACTOR: i32 = 10;
+
+/// Options to quantize image to indexed palette PNG by [`OGImageWriter::encode_palette()`](crate::writer::OGImageWriter::encode_palette).
+/// Palette PNG is much smaller than full color PNG, and it works well for flat colors of typical cards.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct PaletteOptions {
+    /// Number of colors in palette. This is clamped to `2..=256`.
+    pub colors: usize,
+    /// Diffuse quantization error with Floyd-Steinberg dithering to reduce banding of gradients.
+    pub dither: bool,
+}
+
+impl Default for PaletteOptions {
+    fn default() -> Self {
+        PaletteOptions {
+            colors: 256,
+            dither: true,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl PaletteOptions {
+    pub fn new() -> PaletteOptions {
+        PaletteOptions::default()
+    }
+}
+
+// Indexed color is not supported by png encoder that is used in image crate.
+// So indices are encoded as grayscale PNG, and color type is rewritten to indexed color with palette.
+pub(super) fn encode_palette(image: &RgbaImage, options: PaletteOptions) -> Result<Vec<u8>, Error> {
+    let quant = NeuQuant::new(SAMPLE_FACTOR, options.colors.clamp(2, 256), image.as_raw());
+    let indices = if options.dither {
+        let mut image = image.clone();
+        dither(&mut image, &quant);
+        index_colors(&image, &quant)
+    } else {
+        index_colors(image, &quant)
+    };
+
+    let (width, height) = image.dimensions();
+    let mut png = vec![];
+    PngEncoder::new(&mut png).encode(indices.as_raw(), width, height, ColorType::L8)?;
+
+    let colors = quant.color_map_rgba();
+    let mut buf = PNG_SIGNATURE.to_vec();
+    for (chunk_type, data) in PngChunks::new(&png[PNG_SIGNATURE.len()..]) {
+        match &chunk_type {
+            b"IHDR" => {
+                let mut ihdr = data.to_vec();
+                // Color type of indexed color.
+                ihdr[9] = 3;
+                write_chunk(&mut buf, b"IHDR", &ihdr);
+
+                let plte: Vec<u8> = colors
+                    .chunks(4)
+                    .flat_map(|color| color[..3].to_vec())
+                    .collect();
+                write_chunk(&mut buf, b"PLTE", &plte);
+
+                // Alpha of each palette entry. Trailing opaque entries can be omitted.
+                let mut trns: Vec<u8> = colors.chunks(4).map(|color| color[3]).collect();
+                while trns.last() == Some(&255) {
+                    trns.pop();
+                }
+                if !trns.is_empty() {
+                    write_chunk(&mut buf, b"tRNS", &trns);
+                }
+            }
+            b"IDAT" => write_chunk(&mut buf, b"IDAT", data),
+            _ => {}
+        }
+    }
+    write_chunk(&mut buf, b"IEND", &[]);
+
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_encode_palette() {
+        let mut image =
+            RgbaImage::from_fn(64, 64, |x, y| Rgba([x as u8 * 4, y as u8 * 4, 128, 255]));
+        image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+
+        let options = PaletteOptions {
+            colors: 16,
+            dither: true,
+        };
+        let buf = encode_palette(&image, options).unwrap();
+
+        let chunks: Vec<_> = PngChunks::new(&buf[PNG_SIGNATURE.len()..]).collect();
+        let chunk_types: Vec<&[u8; 4]> = chunks.iter().map(|(chunk_type, _)| chunk_type).collect();
+        assert_eq!(chunk_types[..3], [b"IHDR", b"PLTE", b"tRNS"]);
+        assert_eq!(chunks[1].1.len(), 16 * 3);
+
+        let decoded = image::load_from_memory_with_format(&buf, image::ImageFormat::Png)
+            .unwrap()
+            .into_rgba8();
+        assert_eq!(decoded.dimensions(), (64, 64));
+        assert_eq!(decoded.get_pixel(0, 0).0[3], 0);
+        assert_eq!(decoded.get_pixel(32, 32).0[3], 255);
+        let mut colors: Vec<_> = decoded.pixels().map(|p| p.0).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        assert!(colors.len() <= 16);
+    }
+}
@@ -0,0 +1,525 @@
+use crate::animation::{write_chunk, PNG_SIGNATURE};
+use deflate::deflate_bytes_zlib;
+use image::RgbaImage;
+use std::f32::consts::PI;
+
+/// Format that is displayed progressively while it is downloaded, for link previews on slow networks.
+/// This is encoded by [`OGImageWriter::encode_progressive()`](crate::writer::OGImageWriter::encode_progressive).
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressiveFormat {
+    /// PNG that is interlaced with Adam7.
+    Png,
+    /// Progressive JPEG with quality in `1..=100`. Alpha channel is dropped.
+    Jpeg(u8),
+}
+
+pub(super) fn encode_progressive(image: &RgbaImage, format: ProgressiveFormat) -> Vec<u8> {
+    match format {
+        ProgressiveFormat::Png => encode_interlaced_png(image),
+        ProgressiveFormat::Jpeg(quality) => encode_progressive_jpeg(image, quality),
+    }
+}
+
+// Start column, start row, column step and row step of 7 passes of Adam7.
+const ADAM7: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+pub(super) const PNG_FILTER_PAETH: u8 = 4;
+
+// Interlacing is not supported by png encoder that is used in image crate.
+// So scanlines of each pass are filtered and compressed here.
+// See https://www.w3.org/TR/png/#8Interlace
+fn encode_interlaced_png(image: &RgbaImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+
+    let mut raw = vec![];
+    for &(x0, y0, dx, dy) in &ADAM7 {
+        if x0 >= width || y0 >= height {
+            continue;
+        }
+        // Each pass is filtered as independent image, so the first row of pass has no previous row.
+        let mut previous = vec![];
+        for y in (y0..height).step_by(dy as usize) {
+            let row: Vec<u8> = (x0..width)
+                .step_by(dx as usize)
+                .flat_map(|x| image.get_pixel(x, y).0)
+                .collect();
+            if previous.is_empty() {
+                previous = vec![0; row.len()];
+            }
+            raw.push(PNG_FILTER_PAETH);
+            raw.extend(paeth_filter(&row, &previous));
+            previous = row;
+        }
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // Bit depth, color type of RGBA, compression method, filter method and Adam7 interlace method.
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 1]);
+
+    let mut buf = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut buf, b"IHDR", &ihdr);
+    write_chunk(&mut buf, b"IDAT", &deflate_bytes_zlib(&raw));
+    write_chunk(&mut buf, b"IEND", &[]);
+    buf
+}
+
+pub(super) fn paeth_filter<'a>(row: &'a [u8], previous: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+    // Bytes per pixel of RGBA.
+    const BPP: usize = 4;
+    (0..row.len()).map(move |i| {
+        let (a, c) = if i >= BPP {
+            (row[i - BPP], previous[i - BPP])
+        } else {
+            (0, 0)
+        };
+        row[i].wrapping_sub(paeth(a, previous[i], c))
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Tables of JPEG are taken from Annex K of ITU-T T.81, which are also used by jpeg encoder of image crate.
+
+#[rustfmt::skip]
+const STD_LUMA_QTABLE: [u8; 64] = [
+    16, 11, 10, 16,  24,  40,  51,  61,
+    12, 12, 14, 19,  26,  58,  60,  55,
+    14, 13, 16, 24,  40,  57,  69,  56,
+    14, 17, 22, 29,  51,  87,  80,  62,
+    18, 22, 37, 56,  68, 109, 103,  77,
+    24, 35, 55, 64,  81, 104, 113,  92,
+    49, 64, 78, 87, 103, 121, 120, 101,
+    72, 92, 95, 98, 112, 100, 103,  99,
+];
+
+#[rustfmt::skip]
+const STD_CHROMA_QTABLE: [u8; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99,
+    18, 21, 26, 66, 99, 99, 99, 99,
+    24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+const STD_LUMA_DC_CODE_LENGTHS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const STD_CHROMA_DC_CODE_LENGTHS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const STD_DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const STD_LUMA_AC_CODE_LENGTHS: [u8; 16] = [
+    0x00, 0x02, 0x01, 0x03, 0x03, 0x02, 0x04, 0x03, 0x05, 0x05, 0x04, 0x04, 0x00, 0x00, 0x01, 0x7D,
+];
+
+const STD_LUMA_AC_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08, 0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+    0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5,
+    0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+const STD_CHROMA_AC_CODE_LENGTHS: [u8; 16] = [
+    0x00, 0x02, 0x01, 0x02, 0x04, 0x04, 0x03, 0x04, 0x07, 0x05, 0x04, 0x04, 0x00, 0x01, 0x02, 0x77,
+];
+
+const STD_CHROMA_AC_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0,
+    0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5,
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3,
+    0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+// Index of coefficient in block for each position of zigzag order.
+#[rustfmt::skip]
+const UNZIGZAG: [usize; 64] = [
+     0,  1,  8, 16,  9,  2,  3, 10,
+    17, 24, 32, 25, 18, 11,  4,  5,
+    12, 19, 26, 33, 40, 48, 41, 34,
+    27, 20, 13,  6,  7, 14, 21, 28,
+    35, 42, 49, 56, 57, 50, 43, 36,
+    29, 22, 15, 23, 30, 37, 44, 51,
+    58, 59, 52, 45, 38, 31, 39, 46,
+    53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const APP0: u8 = 0xE0;
+const DQT: u8 = 0xDB;
+// Progressive DCT with Huffman coding.
+const SOF2: u8 = 0xC2;
+const DHT: u8 = 0xC4;
+const SOS: u8 = 0xDA;
+
+// Spectral bands of AC scans. Low frequencies are sent first, so the preview gets sharp progressively.
+const AC_BANDS: [(usize, usize); 2] = [(1, 5), (6, 63)];
+
+// Code and its length of each symbol.
+type HuffmanTable = [(u16, u8); 256];
+
+// Progressive JPEG is not supported by jpeg encoder that is used in image crate.
+// So coefficients are encoded with spectral selection here.
+// DC coefficients of all components are sent first, and then bands of AC coefficients are sent for each component.
+// See Annex G of ITU-T T.81.
+fn encode_progressive_jpeg(image: &RgbaImage, quality: u8) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let qtables = [
+        scale_qtable(&STD_LUMA_QTABLE, quality),
+        scale_qtable(&STD_CHROMA_QTABLE, quality),
+    ];
+    let blocks = ycbcr_blocks(image, &qtables);
+
+    let mut buf = vec![];
+    write_segment(&mut buf, SOI, None);
+    write_segment(
+        &mut buf,
+        APP0,
+        Some(&[b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0]),
+    );
+    for (id, qtable) in qtables.iter().enumerate() {
+        let mut dqt = vec![id as u8];
+        dqt.extend(UNZIGZAG.iter().map(|&i| qtable[i] as u8));
+        write_segment(&mut buf, DQT, Some(&dqt));
+    }
+
+    let mut sof = vec![8];
+    sof.extend_from_slice(&(height as u16).to_be_bytes());
+    sof.extend_from_slice(&(width as u16).to_be_bytes());
+    sof.push(3);
+    for component in 0..3u8 {
+        // Id, no subsampling and quantization table.
+        sof.extend_from_slice(&[component + 1, 0x11, component.min(1)]);
+    }
+    write_segment(&mut buf, SOF2, Some(&sof));
+
+    let tables: [(u8, &[u8; 16], &[u8]); 4] = [
+        (0x00, &STD_LUMA_DC_CODE_LENGTHS, &STD_DC_VALUES),
+        (0x01, &STD_CHROMA_DC_CODE_LENGTHS, &STD_DC_VALUES),
+        (0x10, &STD_LUMA_AC_CODE_LENGTHS, &STD_LUMA_AC_VALUES),
+        (0x11, &STD_CHROMA_AC_CODE_LENGTHS, &STD_CHROMA_AC_VALUES),
+    ];
+    for (class_and_id, lengths, values) in &tables {
+        let mut dht = vec![*class_and_id];
+        dht.extend_from_slice(*lengths);
+        dht.extend_from_slice(values);
+        write_segment(&mut buf, DHT, Some(&dht));
+    }
+    let dc_tables = [
+        huffman_table(&STD_LUMA_DC_CODE_LENGTHS, &STD_DC_VALUES),
+        huffman_table(&STD_CHROMA_DC_CODE_LENGTHS, &STD_DC_VALUES),
+    ];
+    let ac_tables = [
+        huffman_table(&STD_LUMA_AC_CODE_LENGTHS, &STD_LUMA_AC_VALUES),
+        huffman_table(&STD_CHROMA_AC_CODE_LENGTHS, &STD_CHROMA_AC_VALUES),
+    ];
+
+    // DC scan of interleaved components.
+    write_segment(
+        &mut buf,
+        SOS,
+        Some(&[3, 1, 0x00, 2, 0x10, 3, 0x10, 0, 0, 0]),
+    );
+    let mut writer = BitWriter::new(&mut buf);
+    let mut predictions = [0; 3];
+    let [y_blocks, cb_blocks, cr_blocks] = &blocks;
+    for ((y, cb), cr) in y_blocks.iter().zip(cb_blocks).zip(cr_blocks) {
+        for (component, block) in [y, cb, cr].iter().enumerate() {
+            let prediction = &mut predictions[component];
+            let dc = block[0] as i32;
+            let (size, bits) = magnitude(dc - *prediction);
+            writer.write_symbol(&dc_tables[component.min(1)], size);
+            writer.write(bits, size);
+            *prediction = dc;
+        }
+    }
+    writer.flush();
+
+    // AC scans of each component.
+    for &(start, end) in &AC_BANDS {
+        for (component, component_blocks) in blocks.iter().enumerate() {
+            let table = &ac_tables[component.min(1)];
+            write_segment(
+                &mut buf,
+                SOS,
+                Some(&[
+                    1,
+                    component as u8 + 1,
+                    (component.min(1) as u8) << 4 | component.min(1) as u8,
+                    start as u8,
+                    end as u8,
+                    0,
+                ]),
+            );
+            let mut writer = BitWriter::new(&mut buf);
+            for block in component_blocks {
+                let mut run = 0;
+                for &coefficient in &block[start..=end] {
+                    if coefficient == 0 {
+                        run += 1;
+                        continue;
+                    }
+                    while run >= 16 {
+                        // ZRL
+                        writer.write_symbol(table, 0xF0);
+                        run -= 16;
+                    }
+                    let (size, bits) = magnitude(coefficient as i32);
+                    writer.write_symbol(table, run << 4 | size);
+                    writer.write(bits, size);
+                    run = 0;
+                }
+                if run > 0 {
+                    // EOB that ends only this block.
+                    writer.write_symbol(table, 0x00);
+                }
+            }
+            writer.flush();
+        }
+    }
+
+    write_segment(&mut buf, EOI, None);
+    buf
+}
+
+fn write_segment(buf: &mut Vec<u8>, marker: u8, data: Option<&[u8]>) {
+    buf.extend_from_slice(&[0xFF, marker]);
+    if let Some(data) = data {
+        buf.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+        buf.extend_from_slice(data);
+    }
+}
+
+// Scale quantization table by quality in the same way as libjpeg.
+fn scale_qtable(table: &[u8; 64], quality: u8) -> [u16; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    let mut scaled = [0; 64];
+    for (scaled, &value) in scaled.iter_mut().zip(table) {
+        *scaled = ((value as u32 * scale + 50) / 100).clamp(1, 255) as u16;
+    }
+    scaled
+}
+
+// Quantized DCT coefficients in zigzag order of Y, Cb and Cr blocks.
+// Pixels on the right and bottom edges are repeated to fill the last blocks.
+fn ycbcr_blocks(image: &RgbaImage, qtables: &[[u16; 64]; 2]) -> [Vec<[i16; 64]>; 3] {
+    let (width, height) = image.dimensions();
+    let mut cos = [[0f32; 8]; 8];
+    for (u, row) in cos.iter_mut().enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = ((2 * x + 1) as f32 * u as f32 * PI / 16.).cos();
+        }
+    }
+
+    let mut blocks = [vec![], vec![], vec![]];
+    for by in 0..height.div_ceil(8) {
+        for bx in 0..width.div_ceil(8) {
+            let mut samples = [[0f32; 64]; 3];
+            let [y_samples, cb_samples, cr_samples] = &mut samples;
+            let pixels = y_samples.iter_mut().zip(cb_samples).zip(cr_samples);
+            for (i, ((y_sample, cb_sample), cr_sample)) in pixels.enumerate() {
+                let x = (bx * 8 + i as u32 % 8).min(width - 1);
+                let y = (by * 8 + i as u32 / 8).min(height - 1);
+                let [r, g, b, _] = image.get_pixel(x, y).0;
+                let (r, g, b) = (r as f32, g as f32, b as f32);
+                // Samples are shifted to be centered at 0.
+                *y_sample = 0.299 * r + 0.587 * g + 0.114 * b - 128.;
+                *cb_sample = -0.168_736 * r - 0.331_264 * g + 0.5 * b;
+                *cr_sample = 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+            }
+            for (component, samples) in samples.iter().enumerate() {
+                let qtable = &qtables[component.min(1)];
+                let coefficients = fdct(samples, &cos);
+                let mut block = [0i16; 64];
+                for (value, &i) in block.iter_mut().zip(UNZIGZAG.iter()) {
+                    *value = (coefficients[i] / qtable[i] as f32)
+                        .round()
+                        .clamp(-1023., 1023.) as i16;
+                }
+                blocks[component].push(block);
+            }
+        }
+    }
+    blocks
+}
+
+// Separable 2D DCT-II of 8x8 block.
+fn fdct(samples: &[f32; 64], cos: &[[f32; 8]; 8]) -> [f32; 64] {
+    let mut rows = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..8).map(|x| samples[y * 8 + x] * cos[u][x]).sum();
+        }
+    }
+    let c = |u: usize| if u == 0 { 1. / 2f32.sqrt() } else { 1. };
+    let mut coefficients = [0f32; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            let sum: f32 = (0..8).map(|y| rows[y * 8 + u] * cos[v][y]).sum();
+            coefficients[v * 8 + u] = c(u) * c(v) * sum / 4.;
+        }
+    }
+    coefficients
+}
+
+// Huffman codes generated from code lengths and values, as described in Annex C of ITU-T T.81.
+fn huffman_table(lengths: &[u8; 16], values: &[u8]) -> HuffmanTable {
+    let mut table = [(0, 0); 256];
+    let mut code = 0u16;
+    let mut values = values.iter();
+    for (i, &count) in lengths.iter().enumerate() {
+        for value in values.by_ref().take(count as usize) {
+            table[*value as usize] = (code, i as u8 + 1);
+            code += 1;
+        }
+        code <<= 1;
+    }
+    table
+}
+
+// Size category of value and its additional bits.
+fn magnitude(value: i32) -> (u8, u16) {
+    let size = 32 - value.unsigned_abs().leading_zeros();
+    let bits = if value < 0 {
+        value - 1 + (1 << size)
+    } else {
+        value
+    };
+    (size as u8, bits as u16)
+}
+
+struct BitWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    bits: u32,
+    len: u8,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(buf: &'a mut Vec<u8>) -> Self {
+        BitWriter {
+            buf,
+            bits: 0,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, bits: u16, len: u8) {
+        if len == 0 {
+            return;
+        }
+        self.bits = self.bits << len | (bits as u32 & ((1 << len) - 1));
+        self.len += len;
+        while self.len >= 8 {
+            let byte = (self.bits >> (self.len - 8)) as u8;
+            self.buf.push(byte);
+            // 0xFF in entropy coded data is stuffed with 0x00 so that it is not read as marker.
+            if byte == 0xFF {
+                self.buf.push(0);
+            }
+            self.len -= 8;
+        }
+        self.bits &= (1 << self.len) - 1;
+    }
+
+    fn write_symbol(&mut self, table: &HuffmanTable, symbol: u8) {
+        let (code, len) = table[symbol as usize];
+        self.write(code, len);
+    }
+
+    // Pad the last byte with 1 bits.
+    fn flush(&mut self) {
+        if self.len > 0 {
+            let padding = 8 - self.len;
+            self.write((1 << padding) - 1, padding);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageFormat, Rgba};
+
+    fn gradient() -> RgbaImage {
+        RgbaImage::from_fn(37, 21, |x, y| {
+            Rgba([x as u8 * 6, y as u8 * 10, 200, if x == 0 { 0 } else { 255 }])
+        })
+    }
+
+    #[test]
+    fn test_interlaced_png() {
+        let image = gradient();
+        let buf = encode_progressive(&image, ProgressiveFormat::Png);
+        // Interlace method of IHDR.
+        assert_eq!(buf[PNG_SIGNATURE.len() + 8 + 12], 1);
+
+        let decoded = image::load_from_memory_with_format(&buf, ImageFormat::Png)
+            .unwrap()
+            .into_rgba8();
+        assert_eq!(decoded, image);
+    }
+
+    #[test]
+    fn test_progressive_jpeg() {
+        let image = gradient();
+        let buf = encode_progressive(&image, ProgressiveFormat::Jpeg(95));
+        assert!(buf.windows(2).any(|w| w == [0xFF, SOF2]));
+        // DC scan and 2 bands of AC scans for 3 components.
+        assert_eq!(buf.windows(2).filter(|w| w == &[0xFF, SOS]).count(), 7);
+
+        let decoded = image::load_from_memory_with_format(&buf, ImageFormat::Jpeg)
+            .unwrap()
+            .into_rgb8();
+        assert_eq!(decoded.dimensions(), image.dimensions());
+        for (x, y, pixel) in decoded.enumerate_pixels() {
+            let expected = image.get_pixel(x, y).0;
+            for c in 0..3 {
+                let diff = (pixel.0[c] as i32 - expected[c] as i32).abs();
+                assert!(diff <= 12, "({}, {}) {:?} {:?}", x, y, pixel, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Regions of laid out elements returned by [`OGImageWriter::regions()`](crate::writer::OGImageWriter::regions).
+//!
+//! Regions can be exported as JSON sidecar of the image, to make hotspots of interactive previews
+//! or to check positions of elements in visual QA.
+
+use crate::element::Element;
+
+/// Kind of element of [Region].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionKind {
+    Text,
+    Image,
+    /// Custom element, including containers.
+    Custom,
+}
+
+impl RegionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegionKind::Text => "text",
+            RegionKind::Image => "image",
+            RegionKind::Custom => "custom",
+        }
+    }
+}
+
+/// Box of element in the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// Index of element in the order it was set to the writer.
+    pub index: usize,
+    pub kind: RegionKind,
+    /// `tag` of style of the element.
+    pub tag: Option<String>,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Text of text element.
+    pub text: Option<String>,
+}
+
+/// Regions of all elements that have size, and size of the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionMap {
+    pub width: u32,
+    pub height: u32,
+    pub regions: Vec<Region>,
+}
+
+impl RegionMap {
+    pub(crate) fn new(width: u32, height: u32, elements: &[&Element]) -> Self {
+        let regions = elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, elm)| {
+                let (kind, text) = match elm {
+                    Element::Text(Some(text)) => (RegionKind::Text, Some(text.text().to_string())),
+                    Element::Img(Some(_)) => (RegionKind::Image, None),
+                    Element::Custom(Some(_)) => (RegionKind::Custom, None),
+                    _ => return None,
+                };
+                let rect = elm.border_box();
+                // Elements without size like spacers are not regions.
+                if rect.width == 0 || rect.height == 0 {
+                    return None;
+                }
+                Some(Region {
+                    index,
+                    kind,
+                    tag: elm.style().and_then(|style| style.tag.clone()),
+                    x: rect.x,
+                    y: rect.y,
+                    width: rect.width,
+                    height: rect.height,
+                    text,
+                })
+            })
+            .collect();
+        RegionMap {
+            width,
+            height,
+            regions,
+        }
+    }
+
+    /// Serialize regions to JSON like `{"width":1200,"height":630,"regions":[{"index":0,"kind":"text",...}]}`.
+    /// `tag` and `text` are `null` when they are not set.
+    pub fn to_json(&self) -> String {
+        let regions: Vec<String> = self
+            .regions
+            .iter()
+            .map(|region| {
+                format!(
+                    r#"{{"index":{},"kind":"{}","tag":{},"x":{},"y":{},"width":{},"height":{},"text":{}}}"#,
+                    region.index,
+                    region.kind.as_str(),
+                    json_string(region.tag.as_deref()),
+                    region.x,
+                    region.y,
+                    region.width,
+                    region.height,
+                    json_string(region.text.as_deref()),
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"width":{},"height":{},"regions":[{}]}}"#,
+            self.width,
+            self.height,
+            regions.join(",")
+        )
+    }
+}
+
+pub(crate) fn json_string(value: Option<&str>) -> String {
+    let value = match value {
+        Some(value) => value,
+        None => return "null".to_string(),
+    };
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::img::DecodedImage;
+    use crate::style::{Spacer, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::RgbaImage;
+
+    #[test]
+    fn test_regions() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let style = Style {
+            tag: Some("title".to_string()),
+            ..Style::default()
+        };
+        writer.set_text("Say \"hi\"", style, Some(font)).unwrap();
+        writer.set_spacer(Spacer::Fixed(10)).unwrap();
+        let img = DecodedImage::from(RgbaImage::new(10, 10));
+        writer
+            .set_img_with_decoded(&img, 40, 40, Style::default())
+            .unwrap();
+
+        let map = writer.regions().unwrap();
+        assert_eq!(map.regions.len(), 2);
+        assert_eq!(map.regions[1].index, 2);
+        let json = map.to_json();
+        assert!(json.starts_with(
+            r#"{"width":300,"height":200,"regions":[{"index":0,"kind":"text","tag":"title","#
+        ));
+        assert!(json.contains(r#""text":"Say \"hi\""}"#));
+        assert!(json.ends_with(r#""width":40,"height":40,"text":null}]}"#));
+    }
+}
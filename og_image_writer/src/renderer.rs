@@ -1,13 +1,9 @@
+use super::blend::mix_row;
 use super::char::RenderingCharIndices;
-use super::font::whitespace_width;
 use super::font_trait::Font;
 use super::style::KernSetting;
-use ab_glyph::{point, Glyph};
-use conv::ValueInto;
-use image::Pixel;
-use imageproc::definitions::Clamp;
-use imageproc::drawing::Canvas;
-use imageproc::pixelops::weighted_sum;
+use ab_glyph::{point, Glyph, OutlinedGlyph};
+use image::{Rgba, RgbaImage};
 use std::f32;
 use std::i32;
 
@@ -17,6 +13,10 @@ pub struct FontSetting {
     pub size: f32,
     pub kern_setting: KernSetting,
     pub is_pre: bool,
+    // Whitespace is measured with advance of space in font instead of fixed width.
+    pub preserve_spaces: bool,
+    // Digits are laid out with the same width.
+    pub tabular_nums: bool,
 }
 
 impl Default for FontSetting {
@@ -26,26 +26,20 @@ impl Default for FontSetting {
             letter_spacing: 0,
             kern_setting: KernSetting::Normal,
             is_pre: false,
+            preserve_spaces: false,
+            tabular_nums: false,
         }
     }
 }
 
-/// Draws colored text on an image in place. `scale` is augmented font scaling on both the x and y axis (in pixels). Note that this function *does not* support newlines, you must do this manually
-pub(super) fn draw_text_mut<'a, C>(
-    canvas: &'a mut C,
-    color: C::Pixel,
-    x: u32,
-    y: u32,
-    font: &'a dyn Font,
-    setting: &FontSetting,
-    text: &'a str,
-) where
-    C: Canvas,
-    <C::Pixel as Pixel>::Subpixel: ValueInto<f32> + Clamp<f32>,
+// Call `f` with each outlined glyph in text and the x offset from the start of text.
+pub(super) fn for_each_glyph<F>(font: &dyn Font, setting: &FontSetting, text: &str, mut f: F)
+where
+    F: FnMut(&OutlinedGlyph, i32),
 {
     let mut current_x = 0;
     let mut chars = RenderingCharIndices::from_str(text);
-    let whitespace = whitespace_width(setting.size) as i32;
+    let whitespace = font.space_width(setting) as i32;
     while let Some((flags, _, ch, _)) = chars.next() {
         let peek_char = chars.peek_char();
 
@@ -59,30 +53,104 @@ pub(super) fn draw_text_mut<'a, C>(
         let glyph_id = font.glyph_id(ch);
         let q_glyph: Glyph = glyph_id.with_scale_and_position(setting.size, point(0., 0.));
         if let Some(q) = font.outline_glyph(q_glyph, setting.size) {
+            f(
+                &q,
+                current_x + font.tabular_offset(ch, &q.px_bounds(), setting),
+            );
+
             let bb = q.px_bounds();
-            q.draw(|gx, gy, gv| {
-                let mut gx = gx as i32 + current_x;
-                if let KernSetting::Normal = setting.kern_setting {
-                    gx += bb.min.x as i32;
-                }
+            current_x += font.calculate_text_width(ch, peek_char, &flags, &bb, setting);
+        }
+    }
+}
 
-                let y_bearing = (bb.min.y + font.ascent(setting.size)) as i32;
-                let gy = gy as i32 + y_bearing;
+// Call `f` with each char in text, its x offset from the start of text and its advance.
+// Chars are placed in the same way as `for_each_glyph`, and chars without outline don't advance.
+pub(super) fn for_each_advance<F>(font: &dyn Font, setting: &FontSetting, text: &str, mut f: F)
+where
+    F: FnMut(char, i32, i32),
+{
+    let mut current_x = 0;
+    let mut chars = RenderingCharIndices::from_str(text);
+    let whitespace = font.space_width(setting) as i32;
+    while let Some((flags, _, ch, _)) = chars.next() {
+        let peek_char = chars.peek_char();
 
-                let image_x = gx + x as i32;
-                let image_y = gy + y as i32;
+        if ch.is_whitespace() {
+            let advance = match peek_char {
+                Some(_) => whitespace + setting.letter_spacing,
+                None => 0,
+            };
+            f(ch, current_x, advance);
+            current_x += advance;
+            continue;
+        }
 
-                let image_width = canvas.width() as i32;
-                let image_height = canvas.height() as i32;
+        let glyph_id = font.glyph_id(ch);
+        let q_glyph: Glyph = glyph_id.with_scale_and_position(setting.size, point(0., 0.));
+        match font.outline_glyph(q_glyph, setting.size) {
+            Some(q) => {
+                let bb = q.px_bounds();
+                let advance = font.calculate_text_width(ch, peek_char, &flags, &bb, setting);
+                f(
+                    ch,
+                    current_x + font.tabular_offset(ch, &bb, setting),
+                    advance,
+                );
+                current_x += advance;
+            }
+            None => f(ch, current_x, 0),
+        }
+    }
+}
 
-                if image_x >= 0 && image_x < image_width && image_y >= 0 && image_y < image_height {
-                    let pixel = canvas.get_pixel(image_x as u32, image_y as u32);
-                    let weighted_color = weighted_sum(pixel, color, 1.0 - gv, gv);
-                    canvas.draw_pixel(image_x as u32, image_y as u32, weighted_color);
-                }
-            });
+/// Draws colored text on an image in place. `scale` is augmented font scaling on both the x and y axis (in pixels). Note that this function *does not* support newlines, you must do this manually
+pub(super) fn draw_text_mut(
+    canvas: &mut RgbaImage,
+    color: Rgba<u8>,
+    x: u32,
+    y: i32,
+    font: &dyn Font,
+    setting: &FontSetting,
+    text: &str,
+) {
+    let image_width = canvas.width() as i32;
+    let image_height = canvas.height() as i32;
+    let canvas: &mut [u8] = canvas;
+    // Coverage of a glyph is collected first, so that rows are mixed into canvas at once.
+    let mut coverage = vec![];
+    for_each_glyph(font, setting, text, |q, current_x| {
+        let bb = q.px_bounds();
+        let (width, height) = (bb.width() as usize, bb.height() as usize);
+        coverage.clear();
+        coverage.resize(width * height, 0.);
+        q.draw(|gx, gy, gv| coverage[gy as usize * width + gx as usize] = gv);
 
-            current_x += font.calculate_text_width(ch, peek_char, &flags, &bb, setting);
+        let mut left = current_x + x as i32;
+        if let KernSetting::Normal = setting.kern_setting {
+            left += bb.min.x as i32;
         }
-    }
+        let top = (bb.min.y + font.ascent(setting.size)) as i32 + y;
+
+        // Columns of the glyph that are inside of canvas.
+        let start = (-left).max(0) as usize;
+        let end = (image_width - left).clamp(0, width as i32) as usize;
+        if start >= end {
+            return;
+        }
+        for gy in 0..height {
+            let image_y = top + gy as i32;
+            if image_y < 0 || image_y >= image_height {
+                continue;
+            }
+            let offset =
+                (image_y as usize * image_width as usize + (left + start as i32) as usize) * 4;
+            let row = &coverage[gy * width..(gy + 1) * width];
+            mix_row(
+                &mut canvas[offset..offset + (end - start) * 4],
+                color,
+                &row[start..end],
+            );
+        }
+    });
 }
@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+// Rough Unicode script of char that is used to itemize text for font fallback.
+// Scripts that are usually covered by same font are grouped, like Han and Kana.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Script {
+    // Whitespace, punctuation, digits and combining marks that take script of surrounding text.
+    Common,
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Bengali,
+    Tamil,
+    Thai,
+    Lao,
+    Khmer,
+    Myanmar,
+    Georgian,
+    Hangul,
+    // Han, Hiragana and Katakana
+    Cjk,
+    Symbol,
+    Unknown,
+}
+
+pub(super) fn script(ch: char) -> Script {
+    match ch {
+        'a'..='z' | 'A'..='Z' => Script::Latin,
+        '\u{0000}'..='\u{00BF}' | '\u{00D7}' | '\u{00F7}' => Script::Common,
+        '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+        // Modifier letters and combining diacritical marks
+        '\u{02B0}'..='\u{036F}' => Script::Common,
+        '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Script::Greek,
+        '\u{0400}'..='\u{052F}' => Script::Cyrillic,
+        '\u{0530}'..='\u{058F}' => Script::Armenian,
+        '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+        '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' | '\u{08A0}'..='\u{08FF}' => {
+            Script::Arabic
+        }
+        '\u{0900}'..='\u{097F}' => Script::Devanagari,
+        '\u{0980}'..='\u{09FF}' => Script::Bengali,
+        '\u{0B80}'..='\u{0BFF}' => Script::Tamil,
+        '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+        '\u{0E80}'..='\u{0EFF}' => Script::Lao,
+        '\u{1000}'..='\u{109F}' => Script::Myanmar,
+        '\u{10A0}'..='\u{10FF}' => Script::Georgian,
+        '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+            Script::Hangul
+        }
+        '\u{1780}'..='\u{17FF}' => Script::Khmer,
+        // General punctuation, currency symbols, arrows and math operators
+        '\u{2000}'..='\u{22FF}' | '\u{FE00}'..='\u{FE0F}' => Script::Common,
+        '\u{2300}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}' => Script::Symbol,
+        // CJK symbols and punctuation, Hiragana, Katakana and Han
+        '\u{2E80}'..='\u{312F}' | '\u{31F0}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}' => Script::Cjk,
+        // Presentation forms of Hebrew and Arabic
+        '\u{FB1D}'..='\u{FB4F}' => Script::Hebrew,
+        '\u{FB50}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFC}' => Script::Arabic,
+        // Fullwidth forms are usually covered by CJK fonts.
+        '\u{FF00}'..='\u{FFEF}' => Script::Cjk,
+        '\u{20000}'..='\u{3FFFF}' => Script::Cjk,
+        // Variation selectors and tags for emoji
+        '\u{E0000}'..='\u{E01EF}' => Script::Common,
+        _ => Script::Unknown,
+    }
+}
+
+impl Script {
+    // Name of script to look for fonts that cover it. Common and unknown chars have no suggestion.
+    pub(super) fn name(&self) -> Option<&'static str> {
+        let name = match self {
+            Self::Common | Self::Unknown => return None,
+            Self::Latin => "Latin",
+            Self::Greek => "Greek",
+            Self::Cyrillic => "Cyrillic",
+            Self::Armenian => "Armenian",
+            Self::Hebrew => "Hebrew",
+            Self::Arabic => "Arabic",
+            Self::Devanagari => "Devanagari",
+            Self::Bengali => "Bengali",
+            Self::Tamil => "Tamil",
+            Self::Thai => "Thai",
+            Self::Lao => "Lao",
+            Self::Khmer => "Khmer",
+            Self::Myanmar => "Myanmar",
+            Self::Georgian => "Georgian",
+            Self::Hangul => "Hangul",
+            Self::Cjk => "CJK",
+            Self::Symbol => "Symbols",
+        };
+        Some(name)
+    }
+
+    // Script that is written without spaces between words, and needs dictionary to find word boundaries.
+    pub(super) fn is_complex(&self) -> bool {
+        matches!(self, Self::Thai | Self::Lao | Self::Khmer | Self::Myanmar)
+    }
+}
+
+// Whether paragraph is right-to-left, that is decided by its first strong char like rule P2 of Unicode bidi algorithm.
+// Chars between isolate initiators (LRI, RLI and FSI) and matching PDI are skipped, and LRM and RLM are strong chars.
+pub(super) fn is_rtl(text: &str) -> bool {
+    let mut isolates: u32 = 0;
+    for ch in text.chars() {
+        match ch {
+            '\u{2066}'..='\u{2068}' => isolates += 1,
+            '\u{2069}' => isolates = isolates.saturating_sub(1),
+            _ if isolates > 0 => {}
+            '\u{200E}' => return false,
+            '\u{200F}' => return true,
+            _ => match script(ch) {
+                Script::Common | Script::Symbol | Script::Unknown => {}
+                Script::Hebrew | Script::Arabic => return true,
+                _ => return false,
+            },
+        }
+    }
+    false
+}
+
+// Split text into byte ranges that have same script.
+// Common chars are merged into previous run, or next run at the start of text.
+pub(super) fn itemize(text: &str) -> Vec<Range<usize>> {
+    let mut runs = vec![];
+    let mut run_start = 0;
+    let mut run_script = Script::Common;
+
+    for (i, ch) in text.char_indices() {
+        let current = script(ch);
+        if current == Script::Common || current == run_script {
+            continue;
+        }
+        if run_script != Script::Common {
+            runs.push(run_start..i);
+            run_start = i;
+        }
+        run_script = current;
+    }
+
+    if run_start < text.len() {
+        runs.push(run_start..text.len());
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_itemize() {
+        let text = "(Hello, 世界。) Привет!";
+        let runs: Vec<&str> = itemize(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(runs, vec!["(Hello, ", "世界。) ", "Привет!"]);
+
+        assert_eq!(itemize("123 !"), vec![0..5]);
+        assert!(itemize("").is_empty());
+    }
+
+    #[test]
+    fn test_is_rtl() {
+        assert!(is_rtl("123 שלום world"));
+        assert!(is_rtl("«مرحبا»"));
+        assert!(!is_rtl("Hello שלום"));
+        assert!(!is_rtl("123 !"));
+        assert!(!is_rtl("\u{2067}שלום\u{2069} world"));
+        assert!(is_rtl("\u{2066}Hello\u{2069}\u{2069} שלום"));
+        assert!(is_rtl("\u{200F}Hello"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Draw texts in scripts that fonts can't draw with images rendered by other tools.
+//!
+//! Glyphs are drawn without shaping, so scripts like Arabic and Devanagari that join or reorder glyphs are not drawn correctly.
+//! Set [ScriptRenderer] with [`OGImageWriter::set_script_renderer()`](crate::writer::OGImageWriter::set_script_renderer),
+//! then each word of these scripts and words that no font has chars of are laid out with the rendered image,
+//! like images from an external shaping service or a browser.
+//! Words that are not rendered are drawn with fonts as usual.
+
+use crate::script::{script, Script};
+use image::RgbaImage;
+
+/// Image of rendered text, and the distance from the top of image to baseline in pixels.
+/// The image is placed on baseline of the line without resizing.
+#[derive(Debug, Clone)]
+pub struct ScriptImage {
+    pub image: RgbaImage,
+    pub baseline: u32,
+}
+
+/// Return image of word like `"مرحبا"` drawn in `font_size`.
+pub trait ScriptRenderer {
+    fn render(&self, text: &str, font_size: f32) -> Option<ScriptImage>;
+}
+
+impl<F> ScriptRenderer for F
+where
+    F: Fn(&str, f32) -> Option<ScriptImage>,
+{
+    fn render(&self, text: &str, font_size: f32) -> Option<ScriptImage> {
+        self(text, font_size)
+    }
+}
+
+// Whether script run is drawn incorrectly without shaping.
+pub(crate) fn needs_shaping(text: &str) -> bool {
+    text.chars().any(|ch| {
+        matches!(
+            script(ch),
+            Script::Arabic
+                | Script::Devanagari
+                | Script::Bengali
+                | Script::Tamil
+                | Script::Khmer
+                | Script::Myanmar
+        )
+    })
+}
+
+// Return byte length of word at the start of text, that is rendered as an image.
+// Words are split by whitespace so that lines can be broken between them.
+pub(crate) fn word_len(text: &str) -> usize {
+    text.find(char::is_whitespace).unwrap_or(text.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_needs_shaping() {
+        assert!(needs_shaping("مرحبا"));
+        assert!(needs_shaping("नमस्ते"));
+        assert!(!needs_shaping("שלום"));
+        assert!(!needs_shaping("Hello, 世界"));
+    }
+
+    #[test]
+    fn test_word_len() {
+        assert_eq!(word_len("مرحبا بالعالم"), "مرحبا".len());
+        assert_eq!(word_len("नमस्ते"), "नमस्ते".len());
+        assert_eq!(word_len(" a"), 0);
+    }
+
+    #[test]
+    fn test_script_renderer() {
+        use crate::style::{Style, WindowStyle};
+        use crate::writer::OGImageWriter;
+        use image::Rgba;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 300,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let words = Rc::new(RefCell::new(vec![]));
+        let rendered = words.clone();
+        writer.set_script_renderer(Box::new(move |text: &str, font_size: f32| {
+            rendered.borrow_mut().push((text.to_string(), font_size));
+            Some(ScriptImage {
+                image: RgbaImage::from_pixel(30, 20, Rgba([255, 0, 0, 255])),
+                baseline: 15,
+            })
+        }));
+        let style = Style {
+            font_size: 20.,
+            ..Style::default()
+        };
+        writer
+            .set_text("Hi مرحبا بالعالم", style, Some(font))
+            .unwrap();
+
+        let layout = writer.glyph_layout().unwrap();
+        assert_eq!(
+            *words.borrow(),
+            [("مرحبا".to_string(), 20.), ("بالعالم".to_string(), 20.)]
+        );
+        let texts: Vec<&str> = layout
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.text.as_str())
+            .collect();
+        assert_eq!(texts, ["H", "i", " ", "مرحبا", " ", "بالعالم"]);
+        let (hi, word) = (&layout.glyphs[0], &layout.glyphs[3]);
+        assert!((word.y - hi.y).abs() < 1.);
+        assert_eq!(word.advance, 30.);
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        let top = word.y as u32 - 15;
+        assert_eq!(image.get_pixel(word.x as u32, top), &Rgba([255, 0, 0, 255]));
+        assert_ne!(
+            image.get_pixel(word.x as u32, top + 20),
+            &Rgba([255, 0, 0, 255])
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use crate::script::script;
+
+// Byte indices of word boundaries in text of scripts that do not use spaces, like Thai.
+// Boundaries are found with dictionaries when `segmenter` feature is enabled,
+// otherwise these scripts are broken only at whitespace.
+pub(super) fn complex_word_breaks(text: &str) -> Vec<usize> {
+    if !text.chars().any(|ch| script(ch).is_complex()) {
+        return vec![];
+    }
+    segment(text)
+        .into_iter()
+        // Boundary that is not at char is ignored instead of panicking on slicing.
+        .filter(|&i| text.is_char_boundary(i))
+        .filter(|&i| {
+            let before = text[..i].chars().next_back();
+            let after = text[i..].chars().next();
+            matches!(
+                (before, after),
+                (Some(before), Some(after)) if script(before).is_complex() && script(after).is_complex()
+            )
+        })
+        .collect()
+}
+
+#[cfg(feature = "segmenter")]
+fn segment(text: &str) -> Vec<usize> {
+    icu_segmenter::LineSegmenter::new_dictionary()
+        .segment_str(text)
+        .collect()
+}
+
+#[cfg(not(feature = "segmenter"))]
+fn segment(_text: &str) -> Vec<usize> {
+    vec![]
+}
+
+#[cfg(all(test, feature = "segmenter"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complex_word_breaks() {
+        // "Hello" in Thai is "สวัสดี" and "world" is "ชาว" + "โลก".
+        let text = "Hi สวัสดีชาวโลก";
+        let breaks: Vec<&str> = complex_word_breaks(text)
+            .into_iter()
+            .map(|i| &text[i..])
+            .collect();
+        assert_eq!(breaks, vec!["ชาวโลก", "โลก"]);
+        assert!(complex_word_breaks("Hello world").is_empty());
+    }
+}
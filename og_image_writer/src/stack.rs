@@ -0,0 +1,145 @@
+//! Stack layers writers on the same rect, like a caption over an image.
+
+use super::blend::overlay;
+use super::element::Rect;
+use super::style::AnchorEdge;
+use super::writer::OGImageWriter;
+use crate::Error;
+use image::{imageops, RgbaImage};
+
+/// Writers that are painted over the first writer.
+/// The stack has the size of the window of the first writer, and the other layers are cut off by it.
+/// Set it by [`OGImageWriter::set_stack()`](crate::writer::OGImageWriter::set_stack).
+pub struct Stack {
+    layers: Vec<Layer>,
+}
+
+struct Layer {
+    writer: OGImageWriter,
+    edge: AnchorEdge,
+    offset: (i32, i32),
+}
+
+impl Stack {
+    pub fn new(base: OGImageWriter) -> Self {
+        Stack {
+            layers: vec![Layer {
+                writer: base,
+                edge: AnchorEdge::TopLeft,
+                offset: (0, 0),
+            }],
+        }
+    }
+
+    /// Put `layer` over the previous layers. It is aligned to `edge` of the first writer and moved by `offset`.
+    pub fn push(&mut self, layer: OGImageWriter, edge: AnchorEdge, offset: (i32, i32)) {
+        self.layers.push(Layer {
+            writer: layer,
+            edge,
+            offset,
+        });
+    }
+
+    pub(super) fn size(&self) -> (u32, u32) {
+        let window = &self.layers[0].writer.window;
+        (window.width, window.height)
+    }
+
+    // Texts of all layers from the bottom layer.
+    // Layer that fails to lay out has no texts, and its error is returned when it is painted.
+    pub(super) fn texts(&mut self) -> Vec<String> {
+        self.layers
+            .iter_mut()
+            .filter_map(|layer| layer.writer.alt_text().ok())
+            .flat_map(|alt| alt.texts)
+            .collect()
+    }
+
+    // Chars in all layers that are not covered by their fonts.
+    pub(super) fn missing_chars(&mut self) -> Vec<char> {
+        self.layers
+            .iter_mut()
+            .filter_map(|layer| layer.writer.check_coverage().ok())
+            .flatten()
+            .map(|missing| missing.ch)
+            .collect()
+    }
+
+    // Paint all layers and compose them into the first layer.
+    pub(super) fn paint(&mut self) -> Result<RgbaImage, Error> {
+        let (width, height) = self.size();
+        let bounds = Rect::new(0, 0, width, height);
+        let mut layers = self.layers.iter_mut();
+        let mut image = match layers.next() {
+            Some(base) => paint_layer(&mut base.writer)?,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        for layer in layers {
+            let top = paint_layer(&mut layer.writer)?;
+            let (x, y) = layer.edge.align(&bounds, top.width(), top.height());
+            let (x, y) = (x + layer.offset.0, y + layer.offset.1);
+            // Part of the layer outside of the top left corner is cut off before composing.
+            let (skip_x, skip_y) = ((-x).max(0) as u32, (-y).max(0) as u32);
+            if skip_x >= top.width() || skip_y >= top.height() {
+                continue;
+            }
+            let top = imageops::crop_imm(
+                &top,
+                skip_x,
+                skip_y,
+                top.width() - skip_x,
+                top.height() - skip_y,
+            )
+            .to_image();
+            overlay(&mut image, &top, x.max(0) as u32, y.max(0) as u32);
+        }
+        Ok(image)
+    }
+}
+
+fn paint_layer(writer: &mut OGImageWriter) -> Result<RgbaImage, Error> {
+    writer.paint()?;
+    match writer.context.image.take() {
+        Some(image) => Ok(image),
+        None => Err(Error::NotFoundContainerImage),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{Rgba, WindowStyle};
+
+    fn layer(width: u32, height: u32, color: [u8; 4]) -> OGImageWriter {
+        OGImageWriter::new(WindowStyle {
+            width,
+            height,
+            background_color: Some(Rgba(color)),
+            ..WindowStyle::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_paint_stack() {
+        let mut stack = Stack::new(layer(40, 20, [255, 0, 0, 255]));
+        stack.push(
+            layer(10, 10, [0, 255, 0, 255]),
+            AnchorEdge::BottomRight,
+            (0, 0),
+        );
+        stack.push(
+            layer(10, 10, [0, 0, 255, 255]),
+            AnchorEdge::TopLeft,
+            (-5, -5),
+        );
+        assert_eq!(stack.size(), (40, 20));
+
+        let image = stack.paint().unwrap();
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(4, 4).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(5, 5).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(30, 10).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(29, 9).0, [255, 0, 0, 255]);
+    }
+}
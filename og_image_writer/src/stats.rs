@@ -0,0 +1,107 @@
+//! Statistics of rendering returned by [`OGImageWriter::paint()`](crate::writer::OGImageWriter::paint).
+
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Counts of painted elements and time spent in each stage.
+/// Timings are always zero on `wasm32` because clock is not available.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderStats {
+    /// Number of characters drawn, excluding whitespaces.
+    pub glyphs: usize,
+    /// Number of lines of all texts after wrapping.
+    pub lines: usize,
+    /// Number of images composited, including containers.
+    pub images: usize,
+    pub custom_elements: usize,
+    /// Byte length of the encoded image. This is set only by [`OGImageWriter::generate()`](crate::writer::OGImageWriter::generate).
+    pub bytes_encoded: u64,
+    /// Time spent in laying out texts and elements, including `set_text` and `set_textarea`.
+    pub layout_time: Duration,
+    pub paint_time: Duration,
+    pub encode_time: Duration,
+}
+
+pub(crate) struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed();
+        #[cfg(target_arch = "wasm32")]
+        return Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[test]
+    fn test_render_stats() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        writer
+            .set_text("Hello World", Style::default(), Some(font))
+            .unwrap();
+
+        let stats = writer.paint().unwrap();
+        assert_eq!(stats.lines, 1);
+        assert_eq!(stats.glyphs, 10);
+        assert_eq!(stats.images, 0);
+
+        // Stats are reset after painting.
+        assert_eq!(writer.paint().unwrap().glyphs, 0);
+    }
+
+    #[test]
+    fn test_stages() {
+        use crate::ImageOutputFormat;
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let render = |font: Vec<u8>| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 400,
+                height: 100,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer
+                .set_text("Hello World", Style::default(), Some(font))
+                .unwrap();
+            writer
+        };
+
+        let mut writer = render(font.clone());
+        writer.layout().unwrap();
+        let stats = writer.rasterize().unwrap();
+        assert_eq!(stats.lines, 1);
+        let raster = writer.into_raster().unwrap();
+
+        let mut painted = render(font);
+        painted.paint().unwrap();
+        let expected = painted.encode(ImageOutputFormat::Png).unwrap();
+        let buf = std::thread::spawn(move || raster.encode(ImageOutputFormat::Png))
+            .join()
+            .unwrap()
+            .unwrap();
+        assert_eq!(buf, expected);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::animation::{write_chunk, PNG_SIGNATURE};
+use crate::blend::{overlay, overlay_cropped};
+use crate::painter::{fill_glyph_path, GlyphPath, Painter, TextRun};
+use crate::progressive::{paeth_filter, PNG_FILTER_PAETH};
+use crate::renderer::draw_text_mut;
+use crate::Error;
+use deflate::write::ZlibEncoder;
+use deflate::Compression;
+use image::{ImageError, Rgba, RgbaImage};
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+// Compressed data is written as IDAT chunk when it exceeds this size.
+const IDAT_SIZE: usize = 64 * 1024;
+
+// Painter that draws rows `top..top + height` of the window into a strip.
+pub(super) struct StripPainter {
+    pub(super) image: Rc<RefCell<RgbaImage>>,
+    pub(super) top: u32,
+}
+
+impl Painter for StripPainter {
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        let bottom = y.saturating_add(height);
+        let y = y.max(self.top);
+        if bottom <= y {
+            return Ok(());
+        }
+        self.image
+            .borrow_mut()
+            .fill_rect(x, y - self.top, width, bottom - y, color)
+    }
+
+    fn draw_image(&mut self, image: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        let mut strip = self.image.borrow_mut();
+        match y.checked_sub(self.top) {
+            Some(y) => overlay(&mut strip, image, x, y),
+            None => overlay_cropped(&mut strip, image, x, 0, self.top - y),
+        }
+        Ok(())
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        draw_text_mut(
+            &mut self.image.borrow_mut(),
+            run.color,
+            run.x,
+            run.y as i32 - self.top as i32,
+            run.font,
+            run.setting,
+            run.text,
+        );
+        Ok(())
+    }
+
+    fn draw_glyph_path(&mut self, path: &GlyphPath) -> Result<(), Error> {
+        fill_glyph_path(&mut self.image.borrow_mut(), path, self.top as i32);
+        Ok(())
+    }
+}
+
+// PNG encoder that receives rows strip by strip, so that the whole image is never kept in memory.
+pub(super) struct PngStripEncoder<W: Write> {
+    encoder: ZlibEncoder<IdatWriter<W>>,
+    previous: Vec<u8>,
+}
+
+impl<W: Write> PngStripEncoder<W> {
+    pub(super) fn new(mut out: W, width: u32, height: u32) -> Result<Self, Error> {
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        // Bit depth, color type of RGBA, compression method, filter method and no interlace.
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut buf = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut buf, b"IHDR", &ihdr);
+        out.write_all(&buf).map_err(ImageError::IoError)?;
+
+        let writer = IdatWriter { out, buf: vec![] };
+        Ok(PngStripEncoder {
+            encoder: ZlibEncoder::new(writer, Compression::Default),
+            previous: vec![0; width as usize * 4],
+        })
+    }
+
+    pub(super) fn write_strip(&mut self, strip: &RgbaImage) -> Result<(), Error> {
+        for row in strip.chunks_exact(self.previous.len()) {
+            let mut filtered = Vec::with_capacity(row.len() + 1);
+            filtered.push(PNG_FILTER_PAETH);
+            filtered.extend(paeth_filter(row, &self.previous));
+            self.encoder
+                .write_all(&filtered)
+                .map_err(ImageError::IoError)?;
+            self.previous.copy_from_slice(row);
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(self) -> Result<W, Error> {
+        let mut writer = self.encoder.finish().map_err(ImageError::IoError)?;
+        writer.write_idat().map_err(ImageError::IoError)?;
+        let mut buf = vec![];
+        write_chunk(&mut buf, b"IEND", &[]);
+        writer.out.write_all(&buf).map_err(ImageError::IoError)?;
+        Ok(writer.out)
+    }
+}
+
+// Split compressed data into IDAT chunks.
+struct IdatWriter<W: Write> {
+    out: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> IdatWriter<W> {
+    fn write_idat(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let mut chunk = Vec::with_capacity(self.buf.len() + 12);
+        write_chunk(&mut chunk, b"IDAT", &self.buf);
+        self.buf.clear();
+        self.out.write_all(&chunk)
+    }
+}
+
+impl<W: Write> Write for IdatWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= IDAT_SIZE {
+            self.write_idat()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::img::DecodedImage;
+    use crate::style::{AlignItems, JustifyContent, Rgba as StyleRgba, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use image::{ImageFormat, Rgba, RgbaImage};
+
+    fn set_elements(writer: &mut OGImageWriter) {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let style = Style {
+            font_size: 40.,
+            color: StyleRgba([255, 255, 255, 255]),
+            ..Style::default()
+        };
+        writer
+            .set_text("Strips of text", style, Some(font))
+            .unwrap();
+        let img = DecodedImage::from(RgbaImage::from_pixel(30, 30, Rgba([200, 100, 0, 128])));
+        writer
+            .set_img_with_decoded(&img, 30, 30, Style::default())
+            .unwrap();
+    }
+
+    fn window() -> WindowStyle {
+        WindowStyle {
+            width: 300,
+            height: 150,
+            background_color: Some(StyleRgba([70, 40, 90, 255])),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..WindowStyle::default()
+        }
+    }
+
+    #[test]
+    fn test_write_png_strips() {
+        let mut full = OGImageWriter::new(window()).unwrap();
+        set_elements(&mut full);
+        full.paint().unwrap();
+        let expected = full.into_rgba().unwrap();
+
+        let mut strips = OGImageWriter::with_strip_height(window(), 7).unwrap();
+        set_elements(&mut strips);
+        let mut buf = vec![];
+        strips.write_png_strips(&mut buf).unwrap();
+        let actual = image::load_from_memory_with_format(&buf, ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+
+        assert!(actual == expected);
+    }
+}
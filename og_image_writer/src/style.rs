@@ -1,3 +1,5 @@
+use super::element::Rect;
+use super::Error;
 pub use image::{Rgb, Rgba as ImageRgba};
 use std::marker::Copy;
 use wasm_bindgen::prelude::*;
@@ -10,7 +12,7 @@ pub enum KernSetting {
     Optical,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Rgba(pub [u8; 4]);
 
 impl Rgba {
@@ -19,6 +21,19 @@ impl Rgba {
     }
 }
 
+/// Color that is resolved with [Theme](crate::theme::Theme) of the writer.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Token {
+    Primary,
+    Secondary,
+    Background,
+    Surface,
+    Text,
+    Muted,
+    Accent,
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub enum WordBreak {
@@ -29,25 +44,139 @@ pub enum WordBreak {
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub enum WhiteSpace {
+    /// Collapse spaces. Lines are broken at newlines like the other values.
     Normal,
+    /// Break lines at newlines, and measure newlines without width.
     PreLine,
+    /// Preserve spaces, tabs and newlines, and do not wrap lines.
+    Pre,
 }
 
 impl WhiteSpace {
     pub(crate) fn is_pre(&self) -> bool {
         match self {
             Self::Normal => false,
-            Self::PreLine => true,
+            Self::PreLine | Self::Pre => true,
         }
     }
+
+    pub(crate) fn preserves_spaces(&self) -> bool {
+        matches!(self, Self::Pre)
+    }
+}
+
+/// Wrap text greedily, or adjust line breaks like CSS `text-wrap`.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum TextWrap {
+    Wrap,
+    /// Balance width of lines.
+    Balance,
+    /// Avoid a single word on the last line by moving a word from the previous line.
+    Pretty,
+}
+
+/// Change case of text like CSS `text-transform`.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    Capitalize,
+}
+
+/// Glyphs of numbers like CSS `font-variant-numeric`.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum FontVariantNumeric {
+    Normal,
+    /// Make all digits the same width, so that numbers in lines line up.
+    /// Each digit is centered in the advance of the widest digit in font.
+    TabularNums,
+}
+
+impl FontVariantNumeric {
+    pub(crate) fn is_tabular(&self) -> bool {
+        matches!(self, Self::TabularNums)
+    }
+}
+
+/// Metrics of fallback fonts that are used for line height and baseline.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum FallbackMetrics {
+    /// Each font uses its own ascent and descent, so line height can change where fallback fonts are used.
+    Font,
+    /// Fallback fonts use ascent and descent of the primary font like CSS `ascent-override` and `descent-override`,
+    /// so lines of mixed scripts have uniform height. The primary font is the first font of the text,
+    /// the font of parent style or the first font in `FontContext` in this order.
+    Primary,
+}
+
+/// Effect that is applied to glyphs of text.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum TextEffect {
+    None,
+    /// Embossed look that has dark inner shadow on the top edge of glyphs and light highlight below glyphs.
+    Letterpress,
+}
+
+/// BCP 47 language tag like `en-US` or `tr`.
+/// Only primary language subtag is used for now.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub fn new(tag: &str) -> Self {
+        LanguageTag(tag.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Primary language subtag like `tr` for `tr-TR`.
+    pub fn language(&self) -> &str {
+        self.0.split(['-', '_']).next().unwrap_or("")
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Margin(pub i32, pub i32, pub i32, pub i32);
 
+/// Sides of margin that are `auto` like CSS, in the same order as `Margin`.
+/// Auto margins on the main axis take free space, and auto margins on the cross axis align the element.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MarginAuto(pub bool, pub bool, pub bool, pub bool);
+
+impl MarginAuto {
+    // Start and end sides on the main axis.
+    pub(crate) fn main_axis(&self, is_row: bool) -> (bool, bool) {
+        match is_row {
+            true => (self.3, self.1),
+            false => (self.0, self.2),
+        }
+    }
+
+    // Start and end sides on the cross axis.
+    pub(crate) fn cross_axis(&self, is_row: bool) -> (bool, bool) {
+        self.main_axis(!is_row)
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct BorderRadius(pub u32, pub u32, pub u32, pub u32);
 
+/// Steps of spacing scale of [Theme](crate::theme::Theme) for each side, in the same order as `Margin`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SpaceToken(pub usize, pub usize, pub usize, pub usize);
+
+/// Step of radius scale of [Theme](crate::theme::Theme) for all corners.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RadiusToken(pub usize);
+
 /// Adjust the horizontal position.
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
@@ -55,6 +184,9 @@ pub enum AlignItems {
     Start,
     Center,
     End,
+    /// Align baselines of elements in row direction, like CSS flexbox.
+    /// Baseline of image is its bottom edge. This is same as `Start` in column direction.
+    Baseline,
 }
 
 /// Adjust the vertical position.
@@ -70,12 +202,115 @@ pub enum JustifyContent {
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub enum TextAlign {
+    /// Left for left-to-right paragraph, and right for right-to-left paragraph. See [Direction].
     Start,
     Center,
+    /// Right for left-to-right paragraph, and left for right-to-left paragraph.
     End,
+    Left,
+    Right,
+    /// Align decimal points of lines, like numbers in a table column.
+    /// Decimal point is the last `.` that is followed by digit, and lines without it are aligned at their end.
+    Decimal,
 }
 
-#[derive(Debug)]
+impl TextAlign {
+    // Physical alignment of `Start` and `End` in paragraph.
+    pub(crate) fn resolve(self, is_rtl: bool) -> Self {
+        match (self, is_rtl) {
+            (Self::Start, false) | (Self::End, true) => Self::Left,
+            (Self::Start, true) | (Self::End, false) => Self::Right,
+            _ => self,
+        }
+    }
+}
+
+/// Direction of paragraph that `TextAlign::Start` and `TextAlign::End` are resolved against.
+/// Glyphs are drawn from left to right in both directions.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum Direction {
+    /// Decided by the first strong char like Latin or Arabic letters, like HTML `dir="auto"`.
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+/// Vertical bar along the left of all lines of text, like blockquote.
+/// Lines are indented by `width` and `gap`, so the bar doesn't overlap text.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextBar {
+    pub color: Rgba,
+    pub width: u32,
+    /// Space between the bar and text.
+    pub gap: u32,
+}
+
+/// Numbers of lines drawn in gutter at the left of text, like code listings and poems.
+/// Lines that are wrapped from the previous line are not numbered.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LineNumbers {
+    /// Number of the first line.
+    pub start: u32,
+    pub color: Rgba,
+    /// Width of gutter. Numbers are aligned to the right of gutter.
+    pub width: u32,
+    /// Space between gutter and text.
+    pub gap: u32,
+}
+
+/// Random rotation and offset of each glyph for hand-placed look.
+/// Glyphs are moved in the same way for the same `seed`, so images are reproducible.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Jitter {
+    /// Max rotation in degrees.
+    pub rotation: f32,
+    /// Max offset in pixels on each axis.
+    pub offset: f32,
+    pub seed: u64,
+}
+
+/// Filled bar behind text that glyphs are cut out of, so that the background shows through text.
+/// Bar covers the box of lines and `padding` around it, and doesn't take space in layout.
+#[derive(Debug, Copy, Clone)]
+pub struct KnockoutBar {
+    pub color: Rgba,
+    pub padding: u32,
+    pub border_radius: BorderRadius,
+}
+
+/// How image of [Paint::Image] is sized to the box of text.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFit {
+    /// Image is scaled to cover the box keeping its aspect ratio, and cropped around its center.
+    Cover,
+    /// Image is stretched to the box.
+    Fill,
+}
+
+/// Fill of glyphs that is clipped to the shape of text, like CSS `background-clip: text`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    /// Encoded image data like PNG or JPEG.
+    Image(Vec<u8>, ImageFit),
+    /// Gradient from `from` to `to` along `angle` in degrees, like CSS `linear-gradient`.
+    /// `0.` is to top, and `90.` is to right.
+    LinearGradient { from: Rgba, to: Rgba, angle: f32 },
+}
+
+/// Adjust the vertical position of text in line when texts in `TextArea` have different sizes.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub enum VerticalAlign {
+    /// Align baselines of texts.
+    Baseline,
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, Clone)]
 pub enum TextOverflow {
     Clip,
     Ellipsis,
@@ -89,6 +324,62 @@ pub enum Position {
     Absolute,
 }
 
+/// Size of spacer element along `flex_direction`.
+#[derive(Debug, Copy, Clone)]
+pub enum Spacer {
+    /// Fixed size in pixels.
+    Fixed(u32),
+    /// Share of free space in the same way as `flex_grow`.
+    Flex(f32),
+}
+
+/// Edge or corner of the anchor element. Element is aligned inside of the anchor element on this edge.
+#[derive(Debug, Copy, Clone)]
+pub enum AnchorEdge {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl AnchorEdge {
+    // Position of a box of `width` and `height` that is aligned to this edge inside of `outer`.
+    pub(crate) fn align(&self, outer: &Rect, width: u32, height: u32) -> (i32, i32) {
+        let (horizontal, vertical) = match self {
+            AnchorEdge::TopLeft => (0, 0),
+            AnchorEdge::Top => (1, 0),
+            AnchorEdge::TopRight => (2, 0),
+            AnchorEdge::Left => (0, 1),
+            AnchorEdge::Center => (1, 1),
+            AnchorEdge::Right => (2, 1),
+            AnchorEdge::BottomLeft => (0, 2),
+            AnchorEdge::Bottom => (1, 2),
+            AnchorEdge::BottomRight => (2, 2),
+        };
+        // Start, center or end of the free space in `outer`.
+        (
+            outer.x as i32 + (outer.width as i32 - width as i32) * horizontal / 2,
+            outer.y as i32 + (outer.height as i32 - height as i32) * vertical / 2,
+        )
+    }
+}
+
+/// Pin element to another element regardless of where the other element is laid out.
+/// Anchored element is removed from the flow like `Position::Absolute`.
+#[derive(Debug, Clone)]
+pub struct Anchor {
+    /// Id of the anchor element that is set by `id` of its style or [set_text_with_id](crate::writer::OGImageWriter::set_text_with_id).
+    pub to: String,
+    pub edge: AnchorEdge,
+    /// Offset from the aligned position as `(x, y)`.
+    pub offset: (i32, i32),
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub enum FlexDirection {
@@ -96,28 +387,131 @@ pub enum FlexDirection {
     Row,
 }
 
+/// Part of source image that is shown in Img element.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageCrop {
+    /// Rectangle in pixels of source image. It is resized to fit in the element like whole image.
+    Rect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Image covers the element, and is cropped around the focal point instead of its center.
+    /// `x` and `y` are relative to the size of source image in `0.0..=1.0`.
+    /// `zoom` of `1.0` is the scale that just covers the element, and larger value zooms in to the focal point.
+    Focus { x: f32, y: f32, zoom: f32 },
+}
+
+/// Insets in pixels of source image that split it into 9 parts, in order of top, right, bottom and left.
+/// Corners are not scaled, edges are stretched along the edge, and the center is stretched to fill the rest.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct ImageSlice(pub u32, pub u32, pub u32, pub u32);
+
+/// Tile image in its original size to fill `width` and `height` of the element, like seamless textures.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageRepeat {
+    NoRepeat,
+    /// Tile along the top of the element.
+    RepeatX,
+    /// Tile along the left of the element.
+    RepeatY,
+    Repeat,
+}
+
+/// Frame that is drawn when animated image is passed, like animated GIF avatars.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFrame {
+    First,
+    Middle,
+    Last,
+    /// Index from 0. The last frame is used when index is out of frames.
+    Index(usize),
+}
+
 /// Style is used by `text` or `img` element.
 /// Text element is `inline-block`, so you can adjust text position by using `text_align`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Style {
     pub margin: Margin,
+    /// This is used instead of `margin` when it is set.
+    pub margin_token: Option<SpaceToken>,
+    pub margin_auto: MarginAuto,
+    /// Override `align_items` of the window for this element. Auto margins on the cross axis take precedence.
+    pub align_self: Option<AlignItems>,
+    /// Semantic tag of element like `title` or `logo`, that is exported with its region.
+    pub tag: Option<String>,
     /// For Text element
     pub line_height: f32,
     /// For Text element
     pub font_size: f32,
     /// For Text element
+    /// Font size of text that has only emoji is multiplied by this, like chat apps show such messages larger.
+    pub emoji_only_scale: f32,
+    /// For Text element
     pub letter_spacing: i32,
     /// For Text element
     pub kern_setting: KernSetting,
     /// For Text element
+    pub font_variant_numeric: FontVariantNumeric,
+    /// For Text element
+    pub fallback_metrics: FallbackMetrics,
+    /// For Text element
     pub word_break: WordBreak,
     /// For Text element
     pub white_space: WhiteSpace,
     /// For Text element
+    /// Tab stops are placed every `tab_size` spaces when `white_space` is `Pre`.
+    pub tab_size: u32,
+    /// For Text element
+    /// Balancing is applied to text that has at most 6 lines.
+    pub text_wrap: TextWrap,
+    /// For Text element
+    /// Positive value indents first line, and negative value indents the other lines as hanging indent.
+    pub text_indent: i32,
+    /// For Text element
+    pub text_bar: Option<TextBar>,
+    /// For Text element
+    pub line_numbers: Option<LineNumbers>,
+    /// For Text element
+    /// Text flows into balanced columns when this is more than 1.
+    pub columns: u32,
+    /// For Text element
+    /// Space between columns.
+    pub column_gap: u32,
+    /// For Text element
+    pub text_transform: TextTransform,
+    /// For Text element
+    /// Language of text. This is used for language specific case mapping like Turkish dotted `İ`.
+    pub lang: Option<LanguageTag>,
+    /// For Text element
     pub color: Rgba,
     /// For Text element
+    /// This is used instead of `color` when it is set.
+    pub color_token: Option<Token>,
+    /// For Text element
+    /// Glyphs are filled with this over the box of lines instead of their colors when it is set.
+    /// Filled text is drawn as image.
+    pub fill: Option<Paint>,
+    /// For Text element
+    /// Text is cut out of the bar instead of being drawn, and `fill` is ignored when it is set.
+    pub knockout: Option<KnockoutBar>,
+    /// For Text element
     pub text_align: TextAlign,
     /// For Text element
+    pub direction: Direction,
+    /// For Text element
+    pub vertical_align: VerticalAlign,
+    /// For Text element
+    /// Move text up by positive value, and down by negative value.
+    pub baseline_shift: i32,
+    /// For Text element
+    pub text_effect: TextEffect,
+    /// For Text element
+    /// Jittered glyphs are drawn as outlines instead of text.
+    pub jitter: Option<Jitter>,
+    /// For Text element
     pub max_height: Option<u32>,
     /// For Text element
     pub max_width: Option<u32>,
@@ -129,22 +523,106 @@ pub struct Style {
     pub right: Option<i32>,
     pub bottom: Option<i32>,
     pub left: Option<i32>,
+    /// Id of element that other elements can be pinned to with [Anchor].
+    pub id: Option<String>,
+    /// `top`, `right`, `bottom` and `left` are ignored when this is set.
+    pub anchor: Option<Anchor>,
+    /// Element takes this share of free space of the window along `flex_direction`.
+    /// Free space is placed after the element.
+    pub flex_grow: f32,
+    /// For Text element
+    /// Text takes this share of overflow in column, and lines that don't fit are cut off.
+    pub flex_shrink: f32,
+    /// For Img, Collage and Custom element
+    /// Ratio of width to height. Height of the element is computed from its width when this is set,
+    /// or width is computed from height when width is 0. Ratio must be finite and greater than 0.
+    pub aspect_ratio: Option<f32>,
     /// For Img element
     pub border_radius: BorderRadius,
+    /// For Img element
+    /// This is used instead of `border_radius` when it is set.
+    pub border_radius_token: Option<RadiusToken>,
+    /// For Img element
+    pub crop: Option<ImageCrop>,
+    /// For Img element
+    /// Image is stretched to exactly `width` and `height` of the element, and its corners keep their size.
+    pub image_slice: Option<ImageSlice>,
+    /// For Img element
+    /// `image_slice` is used instead when both are set.
+    pub image_repeat: ImageRepeat,
+    /// For Img element
+    /// Frame of animated image.
+    pub image_frame: ImageFrame,
+}
+
+impl Style {
+    // Size of element that is constrained by `aspect_ratio`.
+    // Size that doesn't fit in u32 saturates, so callers check it with limits.
+    pub(crate) fn constrained_size(&self, width: u32, height: u32) -> Result<(u32, u32), Error> {
+        let ratio = match self.aspect_ratio {
+            None => return Ok((width, height)),
+            Some(ratio) if ratio.is_finite() && ratio > 0. => ratio as f64,
+            Some(ratio) => return Err(Error::InvalidAspectRatio(ratio)),
+        };
+        Ok(match (width, height) {
+            (0, height) if height > 0 => ((height as f64 * ratio).round() as u32, height),
+            (width, _) => (width, (width as f64 / ratio).round() as u32),
+        })
+    }
+
+    // Indent of first line or the other lines. All lines are indented by gutter of `line_numbers` and `text_bar`.
+    pub(crate) fn line_indent(&self, is_first_line: bool) -> u32 {
+        let indent = match (self.text_indent, is_first_line) {
+            (indent, true) if indent > 0 => indent as u32,
+            (indent, false) if indent < 0 => indent.unsigned_abs(),
+            _ => 0,
+        };
+        indent + self.gutter_width() + self.text_bar.map_or(0, |bar| bar.width + bar.gap)
+    }
+
+    // Width of gutter of `line_numbers` that is placed before `text_bar`.
+    pub(crate) fn gutter_width(&self) -> u32 {
+        self.line_numbers
+            .map_or(0, |numbers| numbers.width + numbers.gap)
+    }
 }
 
 impl Default for Style {
     fn default() -> Self {
         Style {
             margin: Margin::default(),
+            margin_token: None,
+            margin_auto: MarginAuto::default(),
+            align_self: None,
+            tag: None,
             line_height: 1.5,
             font_size: 30.,
+            emoji_only_scale: 1.,
             letter_spacing: 0,
             kern_setting: KernSetting::Normal,
+            font_variant_numeric: FontVariantNumeric::Normal,
+            fallback_metrics: FallbackMetrics::Font,
             word_break: WordBreak::Normal,
             white_space: WhiteSpace::Normal,
+            tab_size: 8,
+            text_wrap: TextWrap::Wrap,
+            text_indent: 0,
+            text_bar: None,
+            line_numbers: None,
+            columns: 1,
+            column_gap: 0,
+            text_transform: TextTransform::None,
+            lang: None,
             color: Rgba([0, 0, 0, 255]),
+            color_token: None,
+            fill: None,
+            knockout: None,
             text_align: TextAlign::Start,
+            direction: Direction::Auto,
+            vertical_align: VerticalAlign::Baseline,
+            baseline_shift: 0,
+            text_effect: TextEffect::None,
+            jitter: None,
             max_height: None,
             max_width: None,
             text_overflow: TextOverflow::Clip,
@@ -153,7 +631,43 @@ impl Default for Style {
             right: None,
             bottom: None,
             left: None,
+            id: None,
+            anchor: None,
+            flex_grow: 0.,
+            flex_shrink: 0.,
+            aspect_ratio: None,
             border_radius: BorderRadius::default(),
+            border_radius_token: None,
+            crop: None,
+            image_slice: None,
+            image_repeat: ImageRepeat::NoRepeat,
+            image_frame: ImageFrame::First,
+        }
+    }
+}
+
+/// Properties of [Style] that are overridden for each char by [`TextArea::push_with_char_styles()`](crate::TextArea::push_with_char_styles).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StyleOverride {
+    pub color: Option<Rgba>,
+    pub font_size: Option<f32>,
+    pub letter_spacing: Option<i32>,
+    pub baseline_shift: Option<i32>,
+}
+
+impl StyleOverride {
+    pub(crate) fn apply(&self, style: &Style) -> Style {
+        Style {
+            color: self.color.unwrap_or(style.color),
+            // Color of override is used instead of color token.
+            color_token: match self.color {
+                Some(_) => None,
+                None => style.color_token,
+            },
+            font_size: self.font_size.unwrap_or(style.font_size),
+            letter_spacing: self.letter_spacing.unwrap_or(style.letter_spacing),
+            baseline_shift: self.baseline_shift.unwrap_or(style.baseline_shift),
+            ..style.clone()
         }
     }
 }
@@ -172,6 +686,8 @@ pub struct WindowStyle {
     pub height: u32,
     pub width: u32,
     pub background_color: Option<Rgba>,
+    /// This is used instead of `background_color` when it is set.
+    pub background_token: Option<Token>,
     pub align_items: AlignItems,
     pub justify_content: JustifyContent,
     /// This controls the direction in which the children of a node are laid out.
@@ -182,7 +698,7 @@ impl WindowStyle {
     pub fn logical_flex_row_position(&self) -> LogicalFlexRowPosition {
         match &self.flex_direction {
             FlexDirection::Column => match self.align_items {
-                AlignItems::Start => LogicalFlexRowPosition::Start,
+                AlignItems::Start | AlignItems::Baseline => LogicalFlexRowPosition::Start,
                 AlignItems::Center => LogicalFlexRowPosition::Center,
                 AlignItems::End => LogicalFlexRowPosition::End,
             },
@@ -201,6 +717,7 @@ impl Default for WindowStyle {
             height: 0,
             width: 0,
             background_color: None,
+            background_token: None,
             align_items: AlignItems::Start,
             justify_content: JustifyContent::Start,
             flex_direction: FlexDirection::Column,
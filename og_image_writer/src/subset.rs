@@ -0,0 +1,279 @@
+//! Subset fonts to the chars used by templates.
+//!
+//! Bundling a whole CJK font into a serverless binary costs memory and startup time,
+//! though a template uses only some chars. [subset_font] removes outlines of other glyphs.
+//!
+//! ```rust
+//! use og_image_writer::subset::subset_font;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let font = include_bytes!("../../fonts/Mplus1-Black.ttf");
+//!     let subset = subset_font(font, "Open Graphic Image Writer")?;
+//!     assert!(subset.len() < font.len());
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::Error;
+use ab_glyph::{Font, FontRef};
+
+// Flags of component in composite glyph.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+const CHECKSUM_MAGIC: u32 = 0xb1b0_afba;
+
+/// Return font data that has outlines only for chars in `charset` and `.notdef`.
+/// Glyph ids are kept, so metrics and kerning of the font are still valid.
+/// Include chars that are inserted by styles too, like `…` of `TextOverflow::Ellipsis`.
+///
+/// Only TrueType fonts that have `glyf` table are supported.
+pub fn subset_font(data: &[u8], charset: &str) -> Result<Vec<u8>, Error> {
+    let font = FontRef::try_from_slice(data).map_err(|_| Error::InvalidFontBytes)?;
+    let tables = read_tables(data)?;
+    let table = |tag: &[u8; 4]| {
+        tables
+            .iter()
+            .find(|table| &table.tag == tag)
+            .map(|table| &data[table.offset..table.offset + table.length])
+    };
+    let (glyf, loca) = match (table(b"glyf"), table(b"loca")) {
+        (Some(glyf), Some(loca)) => (glyf, loca),
+        _ => return Err(Error::UnsupportedFontFormat),
+    };
+    let head = table(b"head").ok_or(Error::InvalidFontBytes)?;
+    let maxp = table(b"maxp").ok_or(Error::InvalidFontBytes)?;
+
+    let num_glyphs = read_u16(maxp, 4)? as usize;
+    let is_long_loca = read_u16(head, 50)? != 0;
+    let offsets = (0..=num_glyphs)
+        .map(|i| match is_long_loca {
+            true => read_u32(loca, i * 4).map(|offset| offset as usize),
+            false => read_u16(loca, i * 2).map(|offset| offset as usize * 2),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let glyph = |id: usize| -> Result<&[u8], Error> {
+        glyf.get(offsets[id]..offsets[id + 1])
+            .ok_or(Error::InvalidFontBytes)
+    };
+
+    let mut is_used = vec![false; num_glyphs];
+    let mut stack = vec![0];
+    stack.extend(charset.chars().map(|ch| font.glyph_id(ch).0 as usize));
+    while let Some(id) = stack.pop() {
+        if id >= num_glyphs || is_used[id] {
+            continue;
+        }
+        is_used[id] = true;
+        stack.extend(components(glyph(id)?)?);
+    }
+
+    let mut new_glyf = vec![];
+    let mut new_loca = vec![];
+    for (id, &is_used) in is_used.iter().enumerate() {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if is_used {
+            new_glyf.extend_from_slice(glyph(id)?);
+            pad(&mut new_glyf);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    // Loca is always written in long format, and checksum adjustment is set after the whole font is written.
+    let mut new_head = head.to_vec();
+    new_head[8..12].copy_from_slice(&[0; 4]);
+    new_head[50..52].copy_from_slice(&1u16.to_be_bytes());
+
+    // Signature is invalid after subsetting.
+    let tables: Vec<(&[u8; 4], &[u8])> = tables
+        .iter()
+        .filter(|table| &table.tag != b"DSIG")
+        .map(|table| {
+            let data = match &table.tag {
+                b"glyf" => &new_glyf[..],
+                b"loca" => &new_loca[..],
+                b"head" => &new_head[..],
+                _ => &data[table.offset..table.offset + table.length],
+            };
+            (&table.tag, data)
+        })
+        .collect();
+
+    Ok(write_font(read_u32(data, 0)?, &tables))
+}
+
+struct TableRecord {
+    tag: [u8; 4],
+    offset: usize,
+    length: usize,
+}
+
+fn read_tables(data: &[u8]) -> Result<Vec<TableRecord>, Error> {
+    // Version of TrueType outlines. Font collections and CFF outlines are not supported.
+    match read_u32(data, 0)? {
+        0x0001_0000 | 0x7472_7565 => {}
+        _ => return Err(Error::UnsupportedFontFormat),
+    }
+    let num_tables = read_u16(data, 4)? as usize;
+    (0..num_tables)
+        .map(|i| {
+            let record = 12 + i * 16;
+            let tag = data
+                .get(record..record + 4)
+                .ok_or(Error::InvalidFontBytes)?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            if offset + length > data.len() {
+                return Err(Error::InvalidFontBytes);
+            }
+            Ok(TableRecord {
+                tag: [tag[0], tag[1], tag[2], tag[3]],
+                offset,
+                length,
+            })
+        })
+        .collect()
+}
+
+// Glyph ids of components when the glyph is composite.
+fn components(glyph: &[u8]) -> Result<Vec<usize>, Error> {
+    let mut ids = vec![];
+    if glyph.is_empty() || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(ids);
+    }
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        ids.push(read_u16(glyph, offset + 2)? as usize);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        offset += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(ids);
+        }
+    }
+}
+
+// See https://learn.microsoft.com/en-us/typography/opentype/spec/otff#table-directory
+fn write_font(version: u32, tables: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.max(1).leading_zeros() as u16;
+    let search_range: u16 = (1 << entry_selector) * 16;
+
+    let mut buf = vec![];
+    buf.extend_from_slice(&version.to_be_bytes());
+    buf.extend_from_slice(&num_tables.to_be_bytes());
+    buf.extend_from_slice(&search_range.to_be_bytes());
+    buf.extend_from_slice(&entry_selector.to_be_bytes());
+    buf.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if *tag == b"head" {
+            head_offset = Some(offset);
+        }
+        buf.extend_from_slice(*tag);
+        buf.extend_from_slice(&checksum(data).to_be_bytes());
+        buf.extend_from_slice(&(offset as u32).to_be_bytes());
+        buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in tables {
+        buf.extend_from_slice(data);
+        pad(&mut buf);
+    }
+
+    if let Some(head_offset) = head_offset {
+        let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&buf));
+        buf[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    buf
+}
+
+// Sum of big endian u32 values, and the last value is padded with zero.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut value = [0; 4];
+        value[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(value))
+    })
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize((buf.len() + 3) & !3, 0);
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    match data.get(offset..offset + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(Error::InvalidFontBytes),
+    }
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    match data.get(offset..offset + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(Error::InvalidFontBytes),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[test]
+    fn test_subset_font() {
+        let data = include_bytes!("../../fonts/Roboto-Light.ttf");
+        let subset = subset_font(data, "Hello").unwrap();
+        assert!(subset.len() < data.len() / 2);
+        assert_eq!(checksum(&subset), CHECKSUM_MAGIC);
+
+        let font = FontRef::try_from_slice(&subset).unwrap();
+        assert!(font.outline(font.glyph_id('H')).is_some());
+        assert!(font.outline(font.glyph_id('Z')).is_none());
+
+        let render = |font: Vec<u8>| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 200,
+                height: 100,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer
+                .set_text("Hello", Style::default(), Some(font))
+                .unwrap();
+            writer.paint().unwrap();
+            writer.into_vec().unwrap()
+        };
+        assert!(render(subset) == render(data.to_vec()));
+    }
+
+    #[test]
+    fn test_unsupported_font() {
+        let mut data = include_bytes!("../../fonts/Roboto-Light.ttf").to_vec();
+        data[0..4].copy_from_slice(b"OTTO");
+        assert!(matches!(
+            subset_font(&data, "a"),
+            Err(Error::UnsupportedFontFormat)
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+use super::painter::{GlyphPath, Painter, PathCommand, TextRun};
+use crate::Error;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, Rgba, RgbaImage};
+use std::fmt::Write;
+
+// SvgDocument records draw calls of Context as SVG elements.
+// Text is recorded as glyph paths, so the document does not depend on installed fonts.
+pub(super) struct SvgDocument {
+    width: u32,
+    height: u32,
+    body: String,
+}
+
+impl SvgDocument {
+    pub(super) fn new(width: u32, height: u32) -> Self {
+        SvgDocument {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub(super) fn finish(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height,
+        )
+    }
+}
+
+impl Painter for SvgDocument {
+    fn fill_rect(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        color: Rgba<u8>,
+    ) -> Result<(), Error> {
+        let _ = writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+            x,
+            y,
+            width,
+            height,
+            fill(color)
+        );
+        Ok(())
+    }
+
+    fn draw_image(&mut self, buf: &RgbaImage, x: u32, y: u32) -> Result<(), Error> {
+        let (width, height) = buf.dimensions();
+
+        let mut png = vec![];
+        PngEncoder::new(&mut png).encode(buf.as_raw(), width, height, ColorType::Rgba8)?;
+
+        let _ = writeln!(
+            self.body,
+            r#"<image x="{}" y="{}" width="{}" height="{}" href="data:image/png;base64,{}"/>"#,
+            x,
+            y,
+            width,
+            height,
+            base64(&png)
+        );
+
+        Ok(())
+    }
+
+    fn draw_text(&mut self, run: &TextRun) -> Result<(), Error> {
+        let mut path = String::new();
+        for glyph in run.glyph_paths() {
+            write_path_data(&mut path, &glyph.commands);
+        }
+
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let _ = writeln!(self.body, r#"<path d="{}" {}/>"#, path, fill(run.color()));
+        Ok(())
+    }
+
+    fn draw_glyph_path(&mut self, glyph: &GlyphPath) -> Result<(), Error> {
+        let mut path = String::new();
+        write_path_data(&mut path, &glyph.commands);
+
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let _ = writeln!(self.body, r#"<path d="{}" {}/>"#, path, fill(glyph.color));
+        Ok(())
+    }
+}
+
+fn write_path_data(path: &mut String, commands: &[PathCommand]) {
+    for command in commands {
+        let _ = match *command {
+            PathCommand::MoveTo(x, y) => write!(path, "M{:.2} {:.2}", x, y),
+            PathCommand::LineTo(x, y) => write!(path, "L{:.2} {:.2}", x, y),
+            PathCommand::QuadTo(x1, y1, x, y) => {
+                write!(path, "Q{:.2} {:.2} {:.2} {:.2}", x1, y1, x, y)
+            }
+            PathCommand::CubicTo(x1, y1, x2, y2, x, y) => write!(
+                path,
+                "C{:.2} {:.2} {:.2} {:.2} {:.2} {:.2}",
+                x1, y1, x2, y2, x, y
+            ),
+            PathCommand::Close => write!(path, "Z"),
+        };
+    }
+}
+
+fn fill(color: Rgba<u8>) -> String {
+    let [r, g, b, a] = color.0;
+    if a == 255 {
+        format!(r#"fill="rgb({},{},{})""#, r, g, b)
+    } else {
+        format!(
+            r#"fill="rgb({},{},{})" fill-opacity="{:.3}""#,
+            r,
+            g,
+            b,
+            a as f32 / 255.
+        )
+    }
+}
+
+const BASE64_TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() / 3 * 4 + 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_TABLE[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{self, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_into_svg_paints_elements() {
+        let svg = |paint: bool| {
+            let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 100,
+                background_color: Some(style::Rgba([255, 255, 255, 255])),
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer.record_svg().unwrap();
+            writer
+                .set_text("Hello World", Style::default(), Some(font))
+                .unwrap();
+            if paint {
+                writer.paint().unwrap();
+            }
+            writer.into_svg().unwrap()
+        };
+
+        // Elements are painted once whether they are painted before or not.
+        // Glyphs of the text run are written as one path.
+        let document = svg(false);
+        assert_eq!(document.matches("<path").count(), 1);
+        assert_eq!(document, svg(true));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::format;
+use crate::style::Style;
+use crate::writer::OGImageWriter;
+use crate::Error;
+use tera::{Context, Tera};
+
+impl OGImageWriter {
+    /// Render `template` with Tera, and set the rendered text like [set_text](Self::set_text).
+    /// Template can use filters and conditionals, like `{{ post.title | truncate(length=60) }}`,
+    /// and filters of [format](crate::format) module, like `{{ stars | compact }}`.
+    /// HTML is not escaped.
+    pub fn set_text_template(
+        &mut self,
+        template: &str,
+        context: &Context,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let mut tera = Tera::default();
+        format::register_filters(&mut tera);
+        let text = tera.render_str(template, context)?;
+        self.set_text(&text, style, font)
+    }
+
+    /// Render template named `name` registered to `tera`, and set the rendered text like [set_text](Self::set_text).
+    /// This is useful to keep templates in files.
+    pub fn set_text_from_tera(
+        &mut self,
+        tera: &Tera,
+        name: &str,
+        context: &Context,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        let text = tera.render(name, context)?;
+        self.set_text(&text, style, font)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::Element;
+    use crate::style::WindowStyle;
+
+    const FONT: &[u8] = include_bytes!("../../fonts/Roboto-Light.ttf");
+
+    #[test]
+    fn test_set_text_template() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+
+        let mut context = Context::new();
+        context.insert("title", "Hello & World");
+
+        let err = writer.set_text_template(
+            "{{ title | truncate(length=5) }",
+            &context,
+            Style::default(),
+            None,
+        );
+        assert!(matches!(err, Err(Error::TemplateError(_))));
+
+        // Text is rendered with filters of Tera and `format`, and HTML is not escaped.
+        context.insert("stars", &12400);
+        writer
+            .set_text_template(
+                "{{ title | truncate(length=5) }} {{ title }} {{ stars | compact }}",
+                &context,
+                Style::default(),
+                Some(FONT.to_vec()),
+            )
+            .unwrap();
+        match &writer.elements().unwrap()[0] {
+            Element::Text(Some(text)) => assert_eq!(text.text(), "Hello… Hello & World 12.4k"),
+            elm => panic!("unexpected element: {:?}", elm),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Ready-made card layouts.
+//!
+//! Each template builds [OGImageWriter] with elements that are already styled.
+//! You can generate the image immediately, or set more elements before generating.
+//!
+//! ```rust
+//! use og_image_writer::templates::TitleCard;
+//! use std::path::Path;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let font = Vec::from(include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]);
+//!
+//!     let mut writer = TitleCard::new("Open Graphic Image Writer", font).build()?;
+//!
+//!     writer.generate(Path::new("../examples/output_title_card.png"))?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::img::ImageInputFormat;
+use crate::style::{
+    AlignItems, BorderRadius, FlexDirection, JustifyContent, Margin, Rgba, Style, TextAlign,
+    TextOverflow, WindowStyle, WordBreak,
+};
+use crate::writer::OGImageWriter;
+use crate::Error;
+
+const PADDING: i32 = 60;
+
+/// Image data that is used in templates.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateImage<'a> {
+    pub data: &'a [u8],
+    pub format: ImageInputFormat,
+}
+
+impl<'a> TemplateImage<'a> {
+    pub fn new(data: &'a [u8], format: ImageInputFormat) -> Self {
+        TemplateImage { data, format }
+    }
+}
+
+/// Size and colors shared by templates.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateStyle {
+    pub width: u32,
+    pub height: u32,
+    pub background_color: Rgba,
+    /// Color of main text.
+    pub text_color: Rgba,
+    /// Color of secondary text like subtitle and date.
+    pub muted_color: Rgba,
+    /// Color for emphasis like site name and price.
+    pub accent_color: Rgba,
+}
+
+impl Default for TemplateStyle {
+    fn default() -> Self {
+        TemplateStyle {
+            width: 1200,
+            height: 630,
+            background_color: Rgba([24, 24, 37, 255]),
+            text_color: Rgba([255, 255, 255, 255]),
+            muted_color: Rgba([170, 170, 190, 255]),
+            accent_color: Rgba([255, 99, 132, 255]),
+        }
+    }
+}
+
+impl TemplateStyle {
+    fn window(&self, flex_direction: FlexDirection, align_items: AlignItems) -> WindowStyle {
+        WindowStyle {
+            width: self.width,
+            height: self.height,
+            background_color: Some(self.background_color),
+            background_token: None,
+            align_items,
+            justify_content: JustifyContent::Center,
+            flex_direction,
+        }
+    }
+}
+
+/// Centered title with optional subtitle and logo.
+#[derive(Debug, Clone)]
+pub struct TitleCard<'a> {
+    pub title: &'a str,
+    pub subtitle: Option<&'a str>,
+    /// Logo is displayed above title.
+    pub logo: Option<TemplateImage<'a>>,
+    pub font: Vec<u8>,
+    pub style: TemplateStyle,
+}
+
+impl<'a> TitleCard<'a> {
+    pub fn new(title: &'a str, font: Vec<u8>) -> Self {
+        TitleCard {
+            title,
+            subtitle: None,
+            logo: None,
+            font,
+            style: TemplateStyle::default(),
+        }
+    }
+
+    pub fn build(self) -> Result<OGImageWriter, Error> {
+        let style = self.style;
+        let mut writer =
+            OGImageWriter::new(style.window(FlexDirection::Column, AlignItems::Center))?;
+
+        if let Some(logo) = self.logo {
+            writer.set_img_with_data(
+                logo.data,
+                120,
+                120,
+                logo.format,
+                Style {
+                    margin: Margin(0, 0, 40, 0),
+                    ..Style::default()
+                },
+            )?;
+        }
+
+        writer.set_text(
+            self.title,
+            Style {
+                margin: Margin(0, PADDING, 0, PADDING),
+                line_height: 1.5,
+                font_size: 72.,
+                word_break: WordBreak::Normal,
+                color: style.text_color,
+                text_align: TextAlign::Center,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(style.height / 2),
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        writer.set_text_optional(
+            self.subtitle,
+            Style {
+                margin: Margin(20, PADDING, 0, PADDING),
+                font_size: 36.,
+                color: style.muted_color,
+                text_align: TextAlign::Center,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(style.height / 5),
+                ..Style::default()
+            },
+            Some(self.font),
+        )?;
+
+        Ok(writer)
+    }
+}
+
+/// Article title with site name and author row.
+#[derive(Debug, Clone)]
+pub struct ArticleCard<'a> {
+    pub title: &'a str,
+    pub author: &'a str,
+    /// Avatar is displayed in circle next to author.
+    pub avatar: Option<TemplateImage<'a>>,
+    pub site_name: Option<&'a str>,
+    pub date: Option<&'a str>,
+    pub font: Vec<u8>,
+    pub style: TemplateStyle,
+}
+
+impl<'a> ArticleCard<'a> {
+    pub fn new(title: &'a str, author: &'a str, font: Vec<u8>) -> Self {
+        ArticleCard {
+            title,
+            author,
+            avatar: None,
+            site_name: None,
+            date: None,
+            font,
+            style: TemplateStyle::default(),
+        }
+    }
+
+    pub fn build(self) -> Result<OGImageWriter, Error> {
+        let style = self.style;
+        let mut writer =
+            OGImageWriter::new(style.window(FlexDirection::Column, AlignItems::Start))?;
+
+        writer.set_text_optional(
+            self.site_name,
+            Style {
+                margin: Margin(0, PADDING, 20, PADDING),
+                font_size: 32.,
+                color: style.accent_color,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(50),
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        writer.set_text(
+            self.title,
+            Style {
+                margin: Margin(0, PADDING, 40, PADDING),
+                line_height: 1.4,
+                font_size: 64.,
+                word_break: WordBreak::Normal,
+                color: style.text_color,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(style.height / 2),
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        let avatar_size = 80;
+        let mut author_row = OGImageWriter::new(WindowStyle {
+            width: style.width.saturating_sub(PADDING as u32 * 2),
+            height: avatar_size,
+            background_color: Some(style.background_color),
+            background_token: None,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Start,
+            flex_direction: FlexDirection::Row,
+        })?;
+
+        if let Some(avatar) = self.avatar {
+            let radius = avatar_size / 2;
+            author_row.set_img_with_data(
+                avatar.data,
+                avatar_size,
+                avatar_size,
+                avatar.format,
+                Style {
+                    margin: Margin(0, 20, 0, 0),
+                    border_radius: BorderRadius(radius, radius, radius, radius),
+                    ..Style::default()
+                },
+            )?;
+        }
+
+        author_row.set_text(
+            self.author,
+            Style {
+                font_size: 32.,
+                color: style.text_color,
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        author_row.set_text_optional(
+            self.date,
+            Style {
+                margin: Margin(0, 0, 0, 24),
+                font_size: 28.,
+                color: style.muted_color,
+                ..Style::default()
+            },
+            Some(self.font),
+        )?;
+
+        writer.set_container(
+            author_row,
+            Style {
+                margin: Margin(0, PADDING, 0, PADDING),
+                ..Style::default()
+            },
+        )?;
+
+        Ok(writer)
+    }
+}
+
+/// Product image with name, description and price.
+#[derive(Debug, Clone)]
+pub struct ProductCard<'a> {
+    pub name: &'a str,
+    pub price: &'a str,
+    pub description: Option<&'a str>,
+    /// Image is displayed at the left side.
+    pub image: Option<TemplateImage<'a>>,
+    pub font: Vec<u8>,
+    pub style: TemplateStyle,
+}
+
+impl<'a> ProductCard<'a> {
+    pub fn new(name: &'a str, price: &'a str, font: Vec<u8>) -> Self {
+        ProductCard {
+            name,
+            price,
+            description: None,
+            image: None,
+            font,
+            style: TemplateStyle::default(),
+        }
+    }
+
+    pub fn build(self) -> Result<OGImageWriter, Error> {
+        let style = self.style;
+        let mut writer = OGImageWriter::new(style.window(FlexDirection::Row, AlignItems::Center))?;
+
+        let mut info_width = style.width.saturating_sub(PADDING as u32 * 2);
+        if let Some(image) = self.image {
+            let image_size = style.height.saturating_sub(PADDING as u32 * 2);
+            writer.set_img_with_data(
+                image.data,
+                image_size,
+                image_size,
+                image.format,
+                Style {
+                    margin: Margin(0, 0, 0, PADDING),
+                    border_radius: BorderRadius(16, 16, 16, 16),
+                    ..Style::default()
+                },
+            )?;
+            info_width = info_width.saturating_sub(image_size + PADDING as u32);
+        }
+
+        let mut info = OGImageWriter::new(WindowStyle {
+            width: info_width,
+            height: style.height.saturating_sub(PADDING as u32 * 2),
+            background_color: Some(style.background_color),
+            background_token: None,
+            align_items: AlignItems::Start,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+        })?;
+
+        info.set_text(
+            self.name,
+            Style {
+                margin: Margin(0, 0, 20, 0),
+                line_height: 1.4,
+                font_size: 56.,
+                word_break: WordBreak::Normal,
+                color: style.text_color,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(240),
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        info.set_text_optional(
+            self.description,
+            Style {
+                margin: Margin(0, 0, 30, 0),
+                font_size: 28.,
+                color: style.muted_color,
+                text_overflow: TextOverflow::Ellipsis,
+                max_height: Some(130),
+                ..Style::default()
+            },
+            Some(self.font.clone()),
+        )?;
+
+        info.set_text(
+            self.price,
+            Style {
+                font_size: 64.,
+                color: style.accent_color,
+                ..Style::default()
+            },
+            Some(self.font),
+        )?;
+
+        writer.set_container(
+            info,
+            Style {
+                margin: Margin(0, PADDING, 0, PADDING),
+                ..Style::default()
+            },
+        )?;
+
+        Ok(writer)
+    }
+}
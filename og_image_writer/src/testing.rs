@@ -0,0 +1,199 @@
+//! Helpers for snapshot testing your templates.
+//!
+//! ```rust,no_run
+//! use og_image_writer::{style, testing, writer::OGImageWriter};
+//! use std::path::Path;
+//!
+//! let writer = OGImageWriter::new(style::WindowStyle::default()).unwrap();
+//! let actual = testing::render(writer).unwrap();
+//! testing::assert_matches_reference(
+//!     &actual,
+//!     Path::new("tests/snapshots/card.png"),
+//!     testing::CompareOptions::default(),
+//! );
+//! ```
+
+use crate::writer::OGImageWriter;
+use crate::Error;
+use image::{ImageError, Rgba, RgbaImage};
+use std::path::{Path, PathBuf};
+
+/// Set this environment variable to overwrite reference images with actual images.
+pub const UPDATE_ENV: &str = "OG_IMAGE_UPDATE_SNAPSHOTS";
+
+// Maximum squared YIQ distance between two colors.
+const MAX_DELTA: f32 = 35215.;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CompareOptions {
+    /// Perceptual color difference between 0 and 1 that is allowed for each pixel.
+    pub threshold: f32,
+    /// Ratio of different pixels between 0 and 1 that is allowed for the image.
+    pub max_diff_ratio: f32,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        CompareOptions {
+            threshold: 0.1,
+            max_diff_ratio: 0.,
+        }
+    }
+}
+
+/// Result of [compare].
+#[derive(Debug, Clone)]
+pub struct Diff {
+    pub diff_pixels: usize,
+    pub total_pixels: usize,
+    /// Different pixels are red and the others are faded.
+    pub image: RgbaImage,
+}
+
+impl Diff {
+    pub fn ratio(&self) -> f32 {
+        self.diff_pixels as f32 / self.total_pixels.max(1) as f32
+    }
+}
+
+/// Paint the writer and return the image in memory.
+pub fn render(mut writer: OGImageWriter) -> Result<RgbaImage, Error> {
+    writer.paint()?;
+    writer.into_rgba()
+}
+
+/// Compare images pixel by pixel with perceptual color difference.
+/// Return `None` when dimensions are different.
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage, threshold: f32) -> Option<Diff> {
+    if expected.dimensions() != actual.dimensions() {
+        return None;
+    }
+
+    let max_delta = MAX_DELTA * threshold * threshold;
+    let mut image = RgbaImage::new(expected.width(), expected.height());
+    let mut diff_pixels = 0;
+    for ((a, b), out) in expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(image.pixels_mut())
+    {
+        if color_delta(a, b) > max_delta {
+            diff_pixels += 1;
+            *out = Rgba([255, 0, 0, 255]);
+        } else {
+            // Fade matched pixel to make different pixels stand out.
+            let gray = 255. - 0.1 * (255. - luma(blend(a)));
+            *out = Rgba([gray as u8, gray as u8, gray as u8, 255]);
+        }
+    }
+
+    Some(Diff {
+        diff_pixels,
+        total_pixels: (expected.width() * expected.height()) as usize,
+        image,
+    })
+}
+
+/// Compare actual image with reference image at `path`, and panic when they are different.
+/// On failure, actual image and diff image are saved next to the reference image
+/// as `*.actual.png` and `*.diff.png`.
+/// Reference image is created when it does not exist or [UPDATE_ENV] is set.
+pub fn assert_matches_reference(actual: &RgbaImage, path: &Path, options: CompareOptions) {
+    if std::env::var_os(UPDATE_ENV).is_some() || !path.exists() {
+        actual
+            .save(path)
+            .unwrap_or_else(|err| panic!("failed to save {}: {}", path.display(), err));
+        return;
+    }
+
+    let expected =
+        load(path).unwrap_or_else(|err| panic!("failed to load {}: {}", path.display(), err));
+
+    let actual_path = sibling(path, "actual");
+    let diff = match compare(&expected, actual, options.threshold) {
+        Some(diff) => diff,
+        None => {
+            let _ = actual.save(&actual_path);
+            panic!(
+                "dimensions are different from {}: expected {:?}, actual {:?}",
+                path.display(),
+                expected.dimensions(),
+                actual.dimensions()
+            );
+        }
+    };
+
+    if diff.ratio() > options.max_diff_ratio {
+        let diff_path = sibling(path, "diff");
+        let _ = actual.save(&actual_path);
+        let _ = diff.image.save(&diff_path);
+        panic!(
+            "{} pixels are different from {}. See {}",
+            diff.diff_pixels,
+            path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn load(path: &Path) -> Result<RgbaImage, ImageError> {
+    Ok(image::open(path)?.into_rgba8())
+}
+
+// `card.png` -> `card.{suffix}.png`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+// Blend alpha with white.
+fn blend(p: &Rgba<u8>) -> [f32; 3] {
+    let a = p.0[3] as f32 / 255.;
+    let c = |v: u8| 255. + (v as f32 - 255.) * a;
+    [c(p.0[0]), c(p.0[1]), c(p.0[2])]
+}
+
+fn luma(rgb: [f32; 3]) -> f32 {
+    yiq(rgb)[0]
+}
+
+fn yiq([r, g, b]: [f32; 3]) -> [f32; 3] {
+    [
+        r * 0.298_895_3 + g * 0.586_622_5 + b * 0.114_482_2,
+        r * 0.595_978 - g * 0.274_176_1 - b * 0.321_801_9,
+        r * 0.211_470_2 - g * 0.522_617_1 + b * 0.311_146_9,
+    ]
+}
+
+// Squared distance in YIQ color space.
+// See https://github.com/mapbox/pixelmatch
+fn color_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    if a == b {
+        return 0.;
+    }
+    let [y1, i1, q1] = yiq(blend(a));
+    let [y2, i2, q2] = yiq(blend(b));
+    let (y, i, q) = (y1 - y2, i1 - i2, q1 - q2);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        let mut actual = expected.clone();
+        // Small difference is allowed by threshold.
+        actual.put_pixel(0, 0, Rgba([250, 250, 250, 255]));
+        actual.put_pixel(1, 1, Rgba([0, 0, 0, 255]));
+
+        let diff = compare(&expected, &actual, 0.1).unwrap();
+        assert_eq!(diff.diff_pixels, 1);
+        assert_eq!(diff.image.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+        assert!((diff.ratio() - 1. / 16.).abs() < f32::EPSILON);
+
+        assert!(compare(&expected, &RgbaImage::new(2, 2), 0.1).is_none());
+    }
+}
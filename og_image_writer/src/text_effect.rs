@@ -0,0 +1,234 @@
+use super::painter::{GlyphPath, PathCommand, TextRun};
+use super::style::Jitter;
+use image::{GrayImage, Rgba, RgbaImage};
+
+const SHADOW_ALPHA: f32 = 0.6;
+const HIGHLIGHT_ALPHA: f32 = 0.45;
+
+// Overlay of letterpress effect that is drawn over the text run.
+// Dark inner shadow is put on the top edge of glyphs, and light highlight is put below glyphs.
+// Return the overlay and its position.
+pub(super) fn letterpress(run: &TextRun) -> Option<(RgbaImage, u32, u32)> {
+    let depth = (run.font_size() / 24.).round().max(1.) as u32;
+    let width = run.extents().width.ceil() as u32 + depth * 2;
+    // Descent is negative value.
+    let height = (run.ascent() - run.descent()).ceil() as u32 + depth * 2;
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    // Coverage of glyphs. The mask has margin of `depth` around the run.
+    let (left, top) = (run.x() as i32 - depth as i32, run.y() as i32 - depth as i32);
+    let mut mask = GrayImage::new(width, height);
+    run.for_each_glyph(|q, x, y| {
+        q.draw(|gx, gy, gv| {
+            let mx = x + gx as i32 - left;
+            let my = y + gy as i32 - top;
+            if mx >= 0 && my >= 0 && (mx as u32) < width && (my as u32) < height {
+                let pixel = mask.get_pixel_mut(mx as u32, my as u32);
+                pixel.0[0] = pixel.0[0].max((gv * 255.).round() as u8);
+            }
+        });
+    });
+
+    let coverage = |x: u32, y: u32| mask.get_pixel(x, y).0[0] as f32 / 255.;
+
+    let mut overlay = RgbaImage::new(width, height);
+    let mut is_empty = true;
+    for y in 0..height {
+        for x in 0..width {
+            let glyph = coverage(x, y);
+            // Average of glyphs that are moved down by 1 to depth pixels, so that the edge of shadow is soft.
+            let above = (1..=depth)
+                .map(|d| y.checked_sub(d).map(|y| coverage(x, y)).unwrap_or(0.))
+                .sum::<f32>()
+                / depth as f32;
+            let shadow = glyph * (1. - above);
+            let highlight = above * (1. - glyph);
+            let pixel = if shadow > highlight {
+                Rgba([0, 0, 0, (shadow * SHADOW_ALPHA * 255.).round() as u8])
+            } else {
+                Rgba([
+                    255,
+                    255,
+                    255,
+                    (highlight * HIGHLIGHT_ALPHA * 255.).round() as u8,
+                ])
+            };
+            if pixel.0[3] > 0 {
+                is_empty = false;
+                overlay.put_pixel(x, y, pixel);
+            }
+        }
+    }
+    if is_empty {
+        return None;
+    }
+
+    // Overlay can not be drawn at negative position, so the margin is cut off at the edge of image.
+    let (x, y) = (left.max(0) as u32, top.max(0) as u32);
+    let overlay = image::imageops::crop_imm(
+        &overlay,
+        (x as i32 - left) as u32,
+        (y as i32 - top) as u32,
+        width,
+        height,
+    )
+    .to_image();
+
+    Some((overlay, x, y))
+}
+
+// Rotate glyph around its center and move it by random amount decided by `seed` of jitter and `index` of glyph.
+pub(super) fn jitter_glyph(path: GlyphPath, jitter: &Jitter, index: u64) -> GlyphPath {
+    let mut state = jitter.seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let mut random = || next_random(&mut state) * 2. - 1.;
+    let (sin, cos) = (random() * jitter.rotation).to_radians().sin_cos();
+    let (dx, dy) = (random() * jitter.offset, random() * jitter.offset);
+
+    let points = path.commands.iter().flat_map(|command| match *command {
+        PathCommand::MoveTo(x, y) | PathCommand::LineTo(x, y) => vec![(x, y)],
+        PathCommand::QuadTo(x1, y1, x, y) => vec![(x1, y1), (x, y)],
+        PathCommand::CubicTo(x1, y1, x2, y2, x, y) => vec![(x1, y1), (x2, y2), (x, y)],
+        PathCommand::Close => vec![],
+    });
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for (x, y) in points {
+        (min_x, min_y) = (min_x.min(x), min_y.min(y));
+        (max_x, max_y) = (max_x.max(x), max_y.max(y));
+    }
+    let (cx, cy) = ((min_x + max_x) / 2., (min_y + max_y) / 2.);
+    let transform = |x: f32, y: f32| {
+        let (lx, ly) = (x - cx, y - cy);
+        (cx + lx * cos - ly * sin + dx, cy + lx * sin + ly * cos + dy)
+    };
+
+    let commands = path
+        .commands
+        .iter()
+        .map(|command| match *command {
+            PathCommand::MoveTo(x, y) => {
+                let (x, y) = transform(x, y);
+                PathCommand::MoveTo(x, y)
+            }
+            PathCommand::LineTo(x, y) => {
+                let (x, y) = transform(x, y);
+                PathCommand::LineTo(x, y)
+            }
+            PathCommand::QuadTo(x1, y1, x, y) => {
+                let ((x1, y1), (x, y)) = (transform(x1, y1), transform(x, y));
+                PathCommand::QuadTo(x1, y1, x, y)
+            }
+            PathCommand::CubicTo(x1, y1, x2, y2, x, y) => {
+                let ((x1, y1), (x2, y2), (x, y)) =
+                    (transform(x1, y1), transform(x2, y2), transform(x, y));
+                PathCommand::CubicTo(x1, y1, x2, y2, x, y)
+            }
+            PathCommand::Close => PathCommand::Close,
+        })
+        .collect();
+    let (x, y) = transform(path.x, path.y);
+    GlyphPath {
+        x,
+        y,
+        commands,
+        ..path
+    }
+}
+
+// Random number in `0.0..1.0` by SplitMix64, that is enough for visual noise and stable across platforms.
+fn next_random(state: &mut u64) -> f32 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::font::create_font;
+    use crate::renderer::FontSetting;
+
+    #[test]
+    fn test_letterpress() {
+        let font = create_font(Vec::from(
+            include_bytes!("../../fonts/Mplus1-Black.ttf") as &[u8]
+        ))
+        .unwrap();
+        let setting = FontSetting {
+            size: 48.,
+            ..FontSetting::default()
+        };
+        let run = TextRun::new("I", &font, &setting, Rgba([255, 255, 255, 255]), 0, 10);
+
+        let (overlay, x, y) = letterpress(&run).unwrap();
+        // Margin is cut off at the left edge.
+        assert_eq!((x, y), (0, 8));
+
+        let rows = |color: u8| {
+            overlay
+                .enumerate_pixels()
+                .filter(|(_, _, p)| p.0[3] > 0 && p.0[0] == color)
+                .map(|(_, y, _)| y)
+                .collect::<Vec<_>>()
+        };
+        let (shadow, highlight) = (rows(0), rows(255));
+        assert!(!shadow.is_empty() && !highlight.is_empty());
+        // Shadow is on the top edge, and highlight is below the glyph.
+        assert!(shadow.iter().min() < highlight.iter().min());
+        assert!(shadow.iter().max() < highlight.iter().max());
+    }
+
+    #[test]
+    fn test_jitter_glyph() {
+        use crate::style::{Style, WindowStyle};
+        use crate::writer::OGImageWriter;
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let paths = |jitter: Option<Jitter>| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 300,
+                height: 100,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            let style = Style {
+                jitter,
+                ..Style::default()
+            };
+            writer.set_text("Hello", style, Some(font.clone())).unwrap();
+            writer.glyph_paths().unwrap()
+        };
+        let jitter = |seed| {
+            Some(Jitter {
+                rotation: 10.,
+                offset: 3.,
+                seed,
+            })
+        };
+
+        let plain = paths(None);
+        let jittered = paths(jitter(1));
+        assert_eq!(jittered.len(), plain.len());
+        assert_eq!(jittered, paths(jitter(1)));
+        assert_ne!(jittered, paths(jitter(2)));
+        for (plain, jittered) in plain.iter().zip(&jittered) {
+            assert_ne!(plain.commands, jittered.commands);
+            assert!((plain.x - jittered.x).abs() < 10.);
+            assert!((plain.y - jittered.y).abs() < 10.);
+        }
+
+        let still = paths(Some(Jitter {
+            rotation: 0.,
+            offset: 0.,
+            seed: 1,
+        }));
+        assert_eq!(still.len(), plain.len());
+        for (plain, still) in plain.iter().zip(&still) {
+            assert!((plain.x - still.x).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! Text that is laid out along a circle or a polyline, like circular badges and stamps.
+
+use super::char::{has_no_glyph, strip_bidi_controls, RenderingCharIndices};
+use super::element::CustomElement;
+use super::font::{create_font, match_font_family, FontArc};
+use super::font_trait::Font;
+use super::painter::{outline_commands, GlyphPath, Painter};
+use super::renderer::FontSetting;
+use super::style::{KernSetting, Style};
+use crate::Error;
+use ab_glyph::{GlyphId, Point};
+use image::Rgba;
+use std::f32::consts::PI;
+
+/// Path that text is laid out along.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextPath {
+    /// Circle that has its center at the center of the element.
+    /// Text is centered at `angle` in degrees, which is measured clockwise from the top of the circle.
+    /// When `clockwise` is true, glyphs stand on the outside of the circle.
+    /// Otherwise text runs counterclockwise and glyphs stand on the inside, like the bottom of a stamp.
+    Arc {
+        radius: f32,
+        angle: f32,
+        clockwise: bool,
+    },
+    /// Polyline through points that are relative to the top left of the element.
+    /// Text starts at the first point, and glyphs beyond the last point are not drawn.
+    Polyline(Vec<(f32, f32)>),
+}
+
+impl TextPath {
+    // Position and direction in radians of the path at `distance` from the start of text.
+    // `center` is used as the center of arc.
+    fn point_at(&self, distance: f32, text_width: f32, center: f32) -> Option<(f32, f32, f32)> {
+        match self {
+            TextPath::Arc {
+                radius,
+                angle,
+                clockwise,
+            } => {
+                // Distance from the center of text.
+                let s = distance - text_width / 2.;
+                if *radius <= 0. || s.abs() > PI * radius {
+                    return None;
+                }
+                let theta = if *clockwise {
+                    angle.to_radians() + s / radius
+                } else {
+                    angle.to_radians() - s / radius
+                };
+                let x = center + radius * theta.sin();
+                let y = center - radius * theta.cos();
+                Some((x, y, if *clockwise { theta } else { theta + PI }))
+            }
+            TextPath::Polyline(points) => {
+                if distance < 0. {
+                    return None;
+                }
+                let mut rest = distance;
+                for segment in points.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+                    let (dx, dy) = (x1 - x0, y1 - y0);
+                    let len = dx.hypot(dy);
+                    if len == 0. {
+                        continue;
+                    }
+                    if rest <= len {
+                        let t = rest / len;
+                        return Some((x0 + dx * t, y0 + dy * t, dy.atan2(dx)));
+                    }
+                    rest -= len;
+                }
+                None
+            }
+        }
+    }
+}
+
+// Glyph that is placed at `start` along the path.
+struct PathGlyph {
+    id: GlyphId,
+    start: f32,
+    advance: f32,
+    // Horizontal offset of glyph origin from `start`.
+    bearing: f32,
+}
+
+/// Element that draws text along [TextPath]. Each glyph is rotated to follow the path.
+/// The element is set by [OGImageWriter::set_curved_text](crate::writer::OGImageWriter::set_curved_text),
+/// or by [OGImageWriter::set_custom](crate::writer::OGImageWriter::set_custom).
+pub struct CurvedText {
+    text: String,
+    path: TextPath,
+    font: FontArc,
+    setting: FontSetting,
+    color: Rgba<u8>,
+}
+
+impl CurvedText {
+    /// `font_size`, `letter_spacing`, `kern_setting` and `color` of `style` are applied to the text.
+    pub fn new(text: &str, path: TextPath, style: &Style, font: Vec<u8>) -> Result<Self, Error> {
+        Ok(CurvedText {
+            text: strip_bidi_controls(text).into_owned(),
+            path,
+            font: create_font(font)?,
+            setting: FontSetting {
+                size: style.font_size,
+                letter_spacing: style.letter_spacing,
+                kern_setting: style.kern_setting,
+                tabular_nums: style.font_variant_numeric.is_tabular(),
+                ..FontSetting::default()
+            },
+            color: Rgba(style.color.0),
+        })
+    }
+
+    fn glyphs(&self) -> Vec<PathGlyph> {
+        let mut glyphs = vec![];
+        let mut current = 0.;
+        let mut chars = RenderingCharIndices::from_str(&self.text);
+        while let Some((flags, _, ch, _)) = chars.next() {
+            let next = chars.peek_char();
+            let rect = self.font.get_glyph_rect(ch, &self.setting);
+            let advance = match &rect {
+                Some(rect) => self
+                    .font
+                    .calculate_text_width(ch, next, &flags, rect, &self.setting)
+                    as f32,
+                None if ch.is_whitespace() => {
+                    self.font.space_width(&self.setting) + self.setting.letter_spacing as f32
+                }
+                None => continue,
+            };
+            if let (Some(rect), false) = (rect, ch.is_whitespace()) {
+                glyphs.push(PathGlyph {
+                    id: self.font.glyph_id(ch),
+                    start: current,
+                    advance,
+                    bearing: match self.setting.kern_setting {
+                        KernSetting::Normal => 0.,
+                        _ => -rect.min.x,
+                    },
+                });
+            }
+            current += advance;
+        }
+        glyphs
+    }
+
+    fn text_width(&self, glyphs: &[PathGlyph]) -> f32 {
+        glyphs.last().map(|g| g.start + g.advance).unwrap_or(0.)
+    }
+
+    /// Outlines of glyphs in pixel coordinates when the element is placed at `x` and `y`.
+    /// Glyphs that are out of the path are skipped.
+    pub fn glyph_paths(&self, x: u32, y: u32) -> Vec<GlyphPath> {
+        let glyphs = self.glyphs();
+        let text_width = self.text_width(&glyphs);
+        let (size, _) = self.measure(0, 0);
+        let center = size as f32 / 2.;
+        let scale_factor = self.font.scale_factor(self.setting.size);
+
+        let mut paths = vec![];
+        for glyph in glyphs {
+            let (mx, my, direction) =
+                match self
+                    .path
+                    .point_at(glyph.start + glyph.advance / 2., text_width, center)
+                {
+                    Some(point) => point,
+                    None => continue,
+                };
+            let outline = match self.font.outline(glyph.id) {
+                Some(outline) => outline,
+                None => continue,
+            };
+
+            // Rotate glyph around the center of its advance that is put on the path.
+            let (sin, cos) = direction.sin_cos();
+            let transform = |lx: f32, ly: f32| {
+                (
+                    x as f32 + mx + lx * cos - ly * sin,
+                    y as f32 + my + lx * sin + ly * cos,
+                )
+            };
+            let half = glyph.advance / 2.;
+            let to_px = |p: Point| {
+                transform(
+                    glyph.bearing + p.x * scale_factor.horizontal - half,
+                    -p.y * scale_factor.vertical,
+                )
+            };
+            let (origin_x, origin_y) = transform(glyph.bearing - half, 0.);
+
+            paths.push(GlyphPath {
+                glyph_id: glyph.id.0,
+                x: origin_x,
+                y: origin_y,
+                color: self.color,
+                commands: outline_commands(&outline, to_px),
+            });
+        }
+        paths
+    }
+}
+
+impl CustomElement for CurvedText {
+    fn measure(&self, _max_width: u32, _max_height: u32) -> (u32, u32) {
+        let ascent = self.font.ascent(self.setting.size);
+        match &self.path {
+            TextPath::Arc { radius, .. } => {
+                let size = (2. * (radius.max(0.) + ascent)).ceil() as u32;
+                (size, size)
+            }
+            TextPath::Polyline(points) => {
+                let (width, height) = points
+                    .iter()
+                    .fold((0f32, 0f32), |(w, h), &(x, y)| (w.max(x), h.max(y)));
+                (
+                    (width + ascent).ceil() as u32,
+                    (height + ascent).ceil() as u32,
+                )
+            }
+        }
+    }
+
+    fn paint(
+        &self,
+        painter: &mut dyn Painter,
+        x: u32,
+        y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), Error> {
+        for path in self.glyph_paths(x, y) {
+            painter.draw_glyph_path(&path)?;
+        }
+        Ok(())
+    }
+
+    fn texts(&self) -> Vec<String> {
+        vec![self.text.clone()]
+    }
+
+    fn missing_chars(&self) -> Vec<char> {
+        self.text
+            .chars()
+            .filter(|&ch| !has_no_glyph(ch) && !match_font_family(ch, &self.font))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn curved_text(text: &str, path: TextPath) -> CurvedText {
+        CurvedText::new(
+            text,
+            path,
+            &Style {
+                font_size: 20.,
+                ..Style::default()
+            },
+            Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_arc_text_path() {
+        let text = curved_text(
+            "TOP",
+            TextPath::Arc {
+                radius: 100.,
+                angle: 0.,
+                clockwise: true,
+            },
+        );
+        let (width, height) = text.measure(0, 0);
+        assert_eq!(width, height);
+
+        let paths = text.glyph_paths(0, 0);
+        assert_eq!(paths.len(), 3);
+        let center = width as f32 / 2.;
+        for path in &paths {
+            // Glyphs touch the circle at the center of their advance, so origins are slightly outside.
+            let radius = (path.x - center).hypot(path.y - center);
+            assert!((100. ..101.).contains(&radius), "radius = {}", radius);
+            assert!(path.y < center);
+        }
+        assert!(paths.windows(2).all(|p| p[0].x < p[1].x));
+        // Text is centered at the top.
+        assert!(paths[0].x < center && paths[2].x > center);
+
+        // Text at the bottom runs from left to right too.
+        let text = curved_text(
+            "BOTTOM",
+            TextPath::Arc {
+                radius: 100.,
+                angle: 180.,
+                clockwise: false,
+            },
+        );
+        let paths = text.glyph_paths(0, 0);
+        assert_eq!(paths.len(), 6);
+        assert!(paths.iter().all(|p| p.y > center));
+        assert!(paths.windows(2).all(|p| p[0].x < p[1].x));
+    }
+
+    #[test]
+    fn test_polyline_text_path() {
+        let text = curved_text("Hi o", TextPath::Polyline(vec![(0., 50.), (200., 50.)]));
+        let paths = text.glyph_paths(10, 0);
+        // Space does not have outline.
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0].x, 10.);
+        assert!(paths.iter().all(|p| (p.y - 50.).abs() < 0.01));
+
+        // Glyphs beyond the end of path are skipped.
+        let text = curved_text("Hello", TextPath::Polyline(vec![(0., 50.), (20., 50.)]));
+        assert!(text.glyph_paths(0, 0).len() < 5);
+    }
+}
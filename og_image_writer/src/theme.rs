@@ -0,0 +1,341 @@
+//! Theme is a set of design tokens that styles can reference.
+//!
+//! Set `color_token` of [Style](crate::style::Style) or `background_token` of [WindowStyle](crate::style::WindowStyle),
+//! and the color is resolved with theme of the writer when painting.
+//! So you can switch light and dark card with [`OGImageWriter::set_theme()`](crate::writer::OGImageWriter::set_theme).
+//! Set `margin_token` or `border_radius_token` of [Style](crate::style::Style) to use spacing and radius scales of the theme.
+//! They are resolved when the element is laid out, so set theme before setting elements.
+
+use super::style::{BorderRadius, Margin, RadiusToken, Rgba, SpaceToken, Style, Token};
+
+/// Colors referenced by [Token].
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub primary: Rgba,
+    pub secondary: Rgba,
+    pub background: Rgba,
+    pub surface: Rgba,
+    pub text: Rgba,
+    pub muted: Rgba,
+    pub accent: Rgba,
+}
+
+impl Palette {
+    pub fn get(&self, token: Token) -> Rgba {
+        match token {
+            Token::Primary => self.primary,
+            Token::Secondary => self.secondary,
+            Token::Background => self.background,
+            Token::Surface => self.surface,
+            Token::Text => self.text,
+            Token::Muted => self.muted,
+            Token::Accent => self.accent,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Name used by [`OGImageWriter::generate_variants()`](crate::writer::OGImageWriter::generate_variants).
+    pub name: String,
+    pub palette: Palette,
+    /// Fonts that are pushed to [FontContext](crate::font_context::FontContext) as fallback fonts in order.
+    pub fonts: Vec<Vec<u8>>,
+    /// Spacing scale referenced by [space](Self::space) and [SpaceToken].
+    pub spacing: Vec<i32>,
+    /// Radius scale referenced by [radius](Self::radius) and [RadiusToken].
+    pub radii: Vec<u32>,
+}
+
+impl Theme {
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            palette: Palette {
+                primary: Rgba([79, 70, 229, 255]),
+                secondary: Rgba([14, 165, 233, 255]),
+                background: Rgba([255, 255, 255, 255]),
+                surface: Rgba([243, 244, 246, 255]),
+                text: Rgba([17, 24, 39, 255]),
+                muted: Rgba([107, 114, 128, 255]),
+                accent: Rgba([219, 39, 119, 255]),
+            },
+            fonts: vec![],
+            spacing: vec![0, 4, 8, 16, 24, 32, 48, 64],
+            radii: vec![0, 4, 8, 16, 32],
+        }
+    }
+
+    /// Dark theme shares fonts and scales with light theme.
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            palette: Palette {
+                primary: Rgba([129, 140, 248, 255]),
+                secondary: Rgba([56, 189, 248, 255]),
+                background: Rgba([17, 24, 39, 255]),
+                surface: Rgba([31, 41, 55, 255]),
+                text: Rgba([249, 250, 251, 255]),
+                muted: Rgba([156, 163, 175, 255]),
+                accent: Rgba([244, 114, 182, 255]),
+            },
+            ..Theme::light()
+        }
+    }
+
+    pub fn color(&self, token: Token) -> Rgba {
+        self.palette.get(token)
+    }
+
+    /// Return spacing at `step` of the scale.
+    /// The last spacing is returned when `step` is out of the scale.
+    pub fn space(&self, step: usize) -> i32 {
+        scale(&self.spacing, step).unwrap_or(0)
+    }
+
+    /// Return radius for all corners at `step` of the scale.
+    /// The last radius is returned when `step` is out of the scale.
+    pub fn radius(&self, step: usize) -> BorderRadius {
+        let r = scale(&self.radii, step).unwrap_or(0);
+        BorderRadius(r, r, r, r)
+    }
+
+    // Elements that are laid out with `other` can be painted with this theme, because only colors are different.
+    pub(crate) fn has_same_layout(&self, other: &Theme) -> bool {
+        self.fonts == other.fonts && self.spacing == other.spacing && self.radii == other.radii
+    }
+
+    // Replace margin and border radius of style with the scales when their tokens are set.
+    pub(crate) fn resolve(&self, mut style: Style) -> Style {
+        if let Some(SpaceToken(top, right, bottom, left)) = style.margin_token {
+            style.margin = Margin(
+                self.space(top),
+                self.space(right),
+                self.space(bottom),
+                self.space(left),
+            );
+        }
+        if let Some(RadiusToken(step)) = style.border_radius_token {
+            style.border_radius = self.radius(step);
+        }
+        style
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::light()
+    }
+}
+
+fn scale<T: Copy>(values: &[T], step: usize) -> Option<T> {
+    values.get(step).or_else(|| values.last()).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::style::{AlignItems, JustifyContent, Style, WindowStyle};
+    use crate::writer::OGImageWriter;
+    use crate::Error;
+
+    #[test]
+    fn test_scale() {
+        let theme = Theme::light();
+        assert_eq!(theme.space(0), 0);
+        assert_eq!(theme.space(3), 16);
+        // Out of the scale
+        assert_eq!(theme.space(100), 64);
+        assert_eq!(theme.radius(2).0, 8);
+
+        let empty = Theme {
+            spacing: vec![],
+            ..Theme::light()
+        };
+        assert_eq!(empty.space(1), 0);
+    }
+
+    #[test]
+    fn test_generate_variants() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let themes = [Theme::light(), Theme::dark()].map(|theme| Theme {
+            fonts: vec![font.clone()],
+            ..theme
+        });
+        let card = |theme: &Theme| {
+            let mut writer = OGImageWriter::new(WindowStyle {
+                width: 100,
+                height: 50,
+                background_token: Some(Token::Background),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..WindowStyle::default()
+            })
+            .unwrap();
+            writer.set_theme(theme.clone()).unwrap();
+            // Text is drawn with the font of the theme.
+            writer
+                .set_text(
+                    "Hello",
+                    Style {
+                        color_token: Some(Token::Text),
+                        ..Style::default()
+                    },
+                    None,
+                )
+                .unwrap();
+            writer
+        };
+        let mut writer = card(&themes[0]);
+
+        let dest = std::env::temp_dir().join(format!(
+            "og_image_writer_variants_{}_{{theme}}.png",
+            std::process::id()
+        ));
+        let variants = writer
+            .generate_variants(&themes, dest.to_str().unwrap())
+            .unwrap();
+
+        let images: Vec<_> = variants
+            .iter()
+            .zip(["light", "dark"])
+            .map(|((path, stats), name)| {
+                assert!(path.to_str().unwrap().ends_with(&format!("_{}.png", name)));
+                assert!(stats.bytes_encoded > 0);
+                assert_eq!(stats.glyphs, 5);
+                let image = image::open(path).unwrap().to_rgba8();
+                std::fs::remove_file(path).unwrap();
+                image
+            })
+            .collect();
+        assert_eq!(images.len(), 2);
+        // Layout is shared, so it is counted only in the first variant.
+        assert_eq!(variants[1].1.layout_time, std::time::Duration::ZERO);
+        // Each variant is the same as the card that is painted with the theme.
+        for (image, theme) in images.iter().zip(&themes) {
+            let mut expected = card(theme);
+            expected.paint().unwrap();
+            assert!(*image == expected.into_rgba().unwrap());
+        }
+        assert_eq!(images[0].get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(images[1].get_pixel(0, 0).0, [17, 24, 39, 255]);
+
+        // Theme that changes layout needs elements set again.
+        let wide = Theme {
+            spacing: vec![0, 100],
+            ..themes[1].clone()
+        };
+        writer.set_theme(themes[0].clone()).unwrap();
+        assert!(matches!(
+            writer.generate_variants(&[wide], dest.to_str().unwrap()),
+            Err(Error::ThemeChangesLayout(name)) if name == "dark"
+        ));
+    }
+
+    #[test]
+    fn test_generate_variants_with() {
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        let light = Theme {
+            fonts: vec![font.clone()],
+            ..Theme::light()
+        };
+        let dark = Theme {
+            fonts: vec![font],
+            ..Theme::dark()
+        };
+        let spacious = Theme {
+            name: "spacious".to_string(),
+            spacing: vec![0, 20],
+            ..dark.clone()
+        };
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 50,
+            background_token: Some(Token::Background),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+
+        let dest = std::env::temp_dir().join(format!(
+            "og_image_writer_variants_with_{}_{{theme}}.png",
+            std::process::id()
+        ));
+        let builds = std::cell::Cell::new(0);
+        let variants = writer
+            .generate_variants_with(&[light, dark, spacious], dest.to_str().unwrap(), |writer| {
+                builds.set(builds.get() + 1);
+                writer.set_text(
+                    "Hello",
+                    Style {
+                        margin_token: Some(SpaceToken(1, 0, 0, 1)),
+                        color_token: Some(Token::Text),
+                        ..Style::default()
+                    },
+                    None,
+                )
+            })
+            .unwrap();
+
+        // Dark theme is painted with layout of light theme, and spacious theme is laid out again.
+        assert_eq!(builds.get(), 2);
+        let images: Vec<_> = variants
+            .iter()
+            .map(|(path, _)| {
+                let image = image::open(path).unwrap().to_rgba8();
+                std::fs::remove_file(path).unwrap();
+                image
+            })
+            .collect();
+        let top = |image: &image::RgbaImage| {
+            let background = *image.get_pixel(0, 0);
+            (0..image.height())
+                .find(|y| (0..image.width()).any(|x| *image.get_pixel(x, *y) != background))
+                .unwrap()
+        };
+        assert_eq!(top(&images[0]), top(&images[1]));
+        assert!(top(&images[2]) > top(&images[1]));
+    }
+
+    #[test]
+    fn test_resolve_tokens() {
+        let img = crate::img::DecodedImage::from(image::RgbaImage::from_pixel(
+            40,
+            40,
+            image::Rgba([255, 0, 0, 255]),
+        ));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 100,
+            height: 100,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        writer
+            .set_theme(Theme {
+                spacing: vec![0, 10, 20],
+                radii: vec![0, 20],
+                ..Theme::light()
+            })
+            .unwrap();
+        writer
+            .set_img_with_decoded(
+                &img,
+                40,
+                40,
+                Style {
+                    margin_token: Some(SpaceToken(2, 0, 0, 1)),
+                    border_radius_token: Some(RadiusToken(1)),
+                    ..Style::default()
+                },
+            )
+            .unwrap();
+
+        let rect = writer.elements().unwrap()[0].border_box();
+        assert_eq!((rect.x, rect.y), (10, 20));
+
+        writer.paint().unwrap();
+        let image = writer.into_rgba().unwrap();
+        // Corner is cut by radius of the theme.
+        assert_eq!(image.get_pixel(10, 20).0[3], 0);
+        assert_eq!(image.get_pixel(30, 40).0, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Validate layout without painting.
+
+use super::element::{Element, Rect};
+use super::writer::OGImageWriter;
+use crate::Error;
+use image::RgbaImage;
+
+/// Image is warned when it is upscaled more than this ratio.
+const MAX_IMAGE_SCALE: f32 = 2.;
+/// Text that is smaller than this font size needs higher contrast.
+/// See https://www.w3.org/TR/WCAG21/#contrast-minimum
+const LARGE_TEXT_SIZE: f32 = 24.;
+
+/// Problem found by [`OGImageWriter::validate()`].
+/// `index` is index of element in the order it was set to the writer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// Text is cut by `max_height`.
+    TextOverflow { index: usize },
+    /// Element is not fully contained in the window.
+    OutsideWindow { index: usize },
+    /// Image is upscaled more than 2x from its source.
+    ImageUpscaled { index: usize, scale: f32 },
+    /// Contrast ratio between text and background is lower than WCAG minimum.
+    LowContrast { index: usize, ratio: f32 },
+}
+
+impl OGImageWriter {
+    /// Run layout without painting, and return problems of the layout.
+    /// Call this after setting all elements. You can still paint the writer after validation.
+    pub fn validate(&mut self) -> Result<Vec<Warning>, Error> {
+        self.process()?;
+
+        let image = match &self.context.image {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+
+        let mut warnings = vec![];
+        let len = self.tree.0.len();
+        for (i, elm) in self.tree.0.iter().enumerate() {
+            let index = self.element_index(i, len);
+            match elm {
+                Element::Img(Some(img)) => {
+                    if !self.contains(i, &img.rect) {
+                        warnings.push(Warning::OutsideWindow { index });
+                    }
+                    if img.scale > MAX_IMAGE_SCALE {
+                        warnings.push(Warning::ImageUpscaled {
+                            index,
+                            scale: img.scale,
+                        });
+                    }
+                }
+                Element::Custom(Some(custom)) => {
+                    if !self.contains(i, &custom.rect) {
+                        warnings.push(Warning::OutsideWindow { index });
+                    }
+                }
+                Element::Text(Some(text)) => {
+                    if text.is_overflow {
+                        warnings.push(Warning::TextOverflow { index });
+                    }
+                    if !text.fragments.iter().all(|f| self.contains(i, &f.rect)) {
+                        warnings.push(Warning::OutsideWindow { index });
+                    }
+
+                    let color = match text.style.color_token {
+                        Some(token) => self.theme.color(token),
+                        None => text.style.color,
+                    };
+                    let minimum = if text.style.font_size < LARGE_TEXT_SIZE {
+                        4.5
+                    } else {
+                        3.
+                    };
+                    let ratio = text
+                        .fragments
+                        .iter()
+                        .filter_map(|f| average_color(image, &f.rect))
+                        .map(|background| contrast_ratio(color.0, background))
+                        .fold(f32::INFINITY, f32::min);
+                    if ratio < minimum {
+                        warnings.push(Warning::LowContrast { index, ratio });
+                    }
+                }
+                _ => return Err(Error::NullElement),
+            }
+        }
+
+        warnings.sort_by_key(|warning| match warning {
+            Warning::TextOverflow { index }
+            | Warning::OutsideWindow { index }
+            | Warning::ImageUpscaled { index, .. }
+            | Warning::LowContrast { index, .. } => *index,
+        });
+
+        Ok(warnings)
+    }
+
+    // Element at `position` in the tree that is clamped at the start of the window overflows it.
+    fn contains(&self, position: usize, rect: &Rect) -> bool {
+        !self.clamped.contains(&position)
+            && rect.x as u64 + rect.width as u64 <= self.window.width as u64
+            && rect.y as u64 + rect.height as u64 <= self.window.height as u64
+    }
+}
+
+// Average color of the image in rect. Alpha is blended with white.
+fn average_color(image: &RgbaImage, rect: &Rect) -> Option<[u8; 4]> {
+    let x_end = rect.x.saturating_add(rect.width).min(image.width());
+    let y_end = rect.y.saturating_add(rect.height).min(image.height());
+
+    let mut sum = [0u64; 3];
+    let mut count = 0u64;
+    for y in rect.y..y_end {
+        for x in rect.x..x_end {
+            let [r, g, b, a] = image.get_pixel(x, y).0;
+            let alpha = a as u64;
+            for (s, c) in sum.iter_mut().zip([r, g, b]) {
+                *s += (c as u64 * alpha + 255 * (255 - alpha)) / 255;
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+    Some([
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+        255,
+    ])
+}
+
+// See https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+fn contrast_ratio(a: [u8; 4], b: [u8; 4]) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+// See https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+fn luminance(color: [u8; 4]) -> f32 {
+    let channel = |c: u8| {
+        let c = c as f32 / 255.;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * channel(color[0]) + 0.7152 * channel(color[1]) + 0.0722 * channel(color[2])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::img::DecodedImage;
+    use crate::style::{Margin, Rgba, Style, WindowStyle};
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = [0, 0, 0, 255];
+        let white = [255, 255, 255, 255];
+        assert!((contrast_ratio(black, white) - 21.).abs() < 0.01);
+        assert!((contrast_ratio(white, white) - 1.).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 200,
+            background_color: Some(Rgba([255, 255, 255, 255])),
+            ..WindowStyle::default()
+        })
+        .unwrap();
+
+        let font = Vec::from(include_bytes!("../../fonts/Roboto-Light.ttf") as &[u8]);
+        writer
+            .set_text(
+                "Readable",
+                Style {
+                    color: Rgba([0, 0, 0, 255]),
+                    ..Style::default()
+                },
+                Some(font.clone()),
+            )
+            .unwrap();
+        writer
+            .set_text(
+                "This is Open Graphic Image Writer for Web Developer.",
+                Style {
+                    color: Rgba([240, 240, 240, 255]),
+                    max_height: Some(20),
+                    ..Style::default()
+                },
+                Some(font),
+            )
+            .unwrap();
+
+        let warnings = writer.validate().unwrap();
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                Warning::TextOverflow { index: 1 },
+                Warning::LowContrast { index: 1, .. }
+            ]
+        ));
+
+        // Layout is not applied twice.
+        writer.paint().unwrap();
+    }
+
+    #[test]
+    fn test_validate_negative_margin() {
+        let img = DecodedImage::from(RgbaImage::new(20, 20));
+        let mut writer = OGImageWriter::new(WindowStyle {
+            width: 400,
+            height: 200,
+            ..WindowStyle::default()
+        })
+        .unwrap();
+        let margin = |margin| Style {
+            margin,
+            ..Style::default()
+        };
+        writer
+            .set_img_with_decoded(&img, 20, 20, margin(Margin(0, 0, 0, -10)))
+            .unwrap();
+        writer
+            .set_img_with_decoded(&img, 20, 20, margin(Margin(-40, 0, 0, 0)))
+            .unwrap();
+        writer
+            .set_img_with_decoded(&img, 20, 20, margin(Margin(40, 0, 0, 10)))
+            .unwrap();
+
+        let warnings = writer.validate().unwrap();
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                Warning::OutsideWindow { index: 0 },
+                Warning::OutsideWindow { index: 1 }
+            ]
+        ));
+    }
+}
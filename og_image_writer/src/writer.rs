@@ -1,17 +1,82 @@
 use crate::Error;
-use image::{ImageError, RgbaImage};
+use image::{
+    imageops::{self, FilterType},
+    ImageError, RgbaImage,
+};
 
-use super::context::{Context, ImageOutputFormat};
-use super::element::{Element, Fragment, Img, Text};
-use super::font::create_font;
-use super::font_context::{FontContext, FontIndexStore};
+use super::alt_text::AltText;
+use super::animation::{encode_frames, AnimationFormat, AnimationFrame};
+use super::asset::{AssetResolver, ASSET_SCHEME};
+use super::cache::ElementCache;
+use super::cancel::{Budget, CancellationToken};
+use super::char::{display_text, LINE_SEPARATOR};
+use super::collage::{Collage, CollageImage};
+use super::context::{encode_image, Context, ImageOutputFormat, OutputChannels};
+use super::element::{Custom, CustomElement, Element, Fragment, Img, Rect, Text};
+use super::emoji_image::EmojiResolver;
+use super::fill::{fill_mask, knockout_mask, lines_rect};
+use super::font::{create_font, match_font_family, FontArc};
+use super::font_context::{FontContext, FontIndexStore, SharedFont};
 use super::font_trait::Font;
-use super::glyph::Glyph;
-use super::img::ImageInputFormat;
-use super::layout::{SplitText, TextArea};
+use super::img::{check_size, DecodedImage, ImageInputFormat};
+use super::incremental::{bounds, copy_rect, intersects, Incremental};
+use super::layout::{ruby_size, GlyphRun, SplitText, TextArea};
+use super::limits::Limits;
+use super::line_breaker::LineBuffers;
+use super::measure::{LineBox, TextMeasurement};
+use super::named::NamedText;
+use super::painter::{GlyphPath, GlyphPathRecorder, Painter, SharedImage, TextRun};
+use super::palette::PaletteOptions;
+use super::progressive::ProgressiveFormat;
+use super::region::RegionMap;
 use super::renderer::FontSetting;
-use super::style::{Style, WindowStyle};
-use std::{cell::RefCell, ops::Range, path::Path, str};
+use super::script_image::{ScriptImage, ScriptRenderer};
+use super::stack::Stack;
+use super::stats::{RenderStats, Stopwatch};
+use super::strip::{PngStripEncoder, StripPainter};
+use super::style::{
+    JustifyContent, LineNumbers, Spacer, Style, TextBar, TextEffect, VerticalAlign, WindowStyle,
+};
+use super::text_effect::{jitter_glyph, letterpress};
+use super::text_path::{CurvedText, TextPath};
+use super::theme::Theme;
+use std::collections::HashMap;
+use std::io::Write;
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+    str,
+    sync::Arc,
+    time::Instant,
+};
+
+/// Painted image that is shared by writers created with [`OGImageWriter::from_base()`].
+/// Cloning this is cheap, so the same base can be passed to writers in other threads.
+#[derive(Debug, Clone)]
+pub struct BaseLayer(Arc<RgbaImage>);
+
+/// Painted image returned by [`OGImageWriter::into_raster()`].
+/// This can be sent to other threads, so the image is encoded while the next image is laid out.
+#[derive(Debug, Clone)]
+pub struct Raster {
+    image: RgbaImage,
+    channels: OutputChannels,
+}
+
+impl Raster {
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Encode the image with output channels of the writer.
+    pub fn encode(self, format: ImageOutputFormat) -> Result<Vec<u8>, Error> {
+        encode_image(self.image, self.channels, format)
+    }
+}
 
 #[derive(Default)]
 pub(super) struct Content {
@@ -21,6 +86,9 @@ pub(super) struct Content {
 
 pub struct Tree(pub(super) Vec<Element>);
 
+// Function that sets elements for each theme of `generate_variants_with`.
+type BuildVariant<'a> = &'a dyn Fn(&mut OGImageWriter) -> Result<(), Error>;
+
 /// This struct write text to image.
 /// You can set text or img with `set_*` method.
 /// And you can set style with `set_*_style` method.
@@ -30,11 +98,38 @@ pub struct OGImageWriter {
     pub(super) window: WindowStyle,
     pub(super) content: Content,
     pub(super) font_context: FontContext,
+    pub(super) frames: Vec<AnimationFrame>,
+    pub(super) theme: Theme,
+    pub(super) is_laid_out: bool,
+    // Whether the element being laid out is moved over the start of the window.
+    pub(super) is_clamped: bool,
+    // Positions in the laid out tree of elements that are clamped at the start of the window.
+    pub(super) clamped: Vec<usize>,
+    pub(super) limits: Limits,
+    pub(super) budget: Budget,
+    // Stats accumulated until painting.
+    pub(super) stats: RenderStats,
+    pub(super) emoji_resolver: Option<Box<dyn EmojiResolver>>,
+    pub(super) script_renderer: Option<Box<dyn ScriptRenderer>>,
+    pub(super) asset_resolver: Option<Box<dyn AssetResolver>>,
+    pub(super) incremental: Option<Incremental>,
+    // Texts that are set with id, until painting.
+    pub(super) named: HashMap<String, NamedText>,
+    pub(super) element_cache: Option<ElementCache>,
+    // Buffers of line breaking that are reused by texts of the next renders.
+    pub(super) line_buffers: RefCell<LineBuffers>,
 }
 
 impl OGImageWriter {
     /// Set window style. Window act like CSS `flexbox`.
     pub fn new(window: WindowStyle) -> Result<Self, Error> {
+        OGImageWriter::with_limits(window, Limits::default())
+    }
+
+    /// Same as [new](Self::new), but elements are checked with [Limits].
+    pub fn with_limits(window: WindowStyle, limits: Limits) -> Result<Self, Error> {
+        limits.check_window(window.width, window.height)?;
+
         let context = Context::new(window.width, window.height);
 
         let mut this = OGImageWriter {
@@ -43,6 +138,21 @@ impl OGImageWriter {
             window,
             content: Content::default(),
             font_context: FontContext::new(),
+            frames: vec![],
+            theme: Theme::default(),
+            is_laid_out: false,
+            is_clamped: false,
+            clamped: vec![],
+            limits,
+            budget: Budget::default(),
+            stats: RenderStats::default(),
+            emoji_resolver: None,
+            script_renderer: None,
+            asset_resolver: None,
+            incremental: None,
+            named: HashMap::new(),
+            element_cache: None,
+            line_buffers: RefCell::default(),
         };
 
         this.process_background()?;
@@ -50,6 +160,21 @@ impl OGImageWriter {
         Ok(this)
     }
 
+    /// Same as [new](Self::new), but only a strip of `strip_height` rows is kept in memory.
+    /// Render the image with [write_png_strips](Self::write_png_strips) instead of [paint](Self::paint).
+    pub fn with_strip_height(window: WindowStyle, strip_height: u32) -> Result<Self, Error> {
+        let limits = Limits::default();
+        limits.check_window(window.width, window.height)?;
+
+        let height = window.height;
+        let context = Context::new(window.width, strip_height.clamp(1, height.max(1)));
+        let mut this = OGImageWriter::from_context(window, context, limits)?;
+        this.window.height = height;
+        this.process_background()?;
+
+        Ok(this)
+    }
+
     /// Set window style. Window act like CSS `flexbox`.
     /// Height and width are set by specified image.
     pub fn from_data(
@@ -57,8 +182,32 @@ impl OGImageWriter {
         data: &[u8],
         format: ImageInputFormat,
     ) -> Result<Self, Error> {
+        OGImageWriter::from_data_with_limits(window, data, format, Limits::default())
+    }
+
+    /// Same as [from_data](Self::from_data), but specified image and elements are checked with [Limits].
+    pub fn from_data_with_limits(
+        window: WindowStyle,
+        data: &[u8],
+        format: ImageInputFormat,
+        limits: Limits,
+    ) -> Result<Self, Error> {
+        limits.check_image_data(data, format)?;
+
         let context = Context::from_data(data, format)?;
 
+        OGImageWriter::from_context(window, context, limits)
+    }
+
+    /// Create writer that paints elements over the [BaseLayer] created by [snapshot](Self::snapshot).
+    /// Height and width are set by the base layer.
+    pub fn from_base(window: WindowStyle, base: &BaseLayer) -> Result<Self, Error> {
+        let context = Context::from_image(base.0.as_ref().clone());
+        OGImageWriter::from_context(window, context, Limits::default())
+    }
+
+    // Writer that paints elements over the image of context.
+    fn from_context(window: WindowStyle, context: Context, limits: Limits) -> Result<Self, Error> {
         let image = match &context.image {
             Some(image) => image,
             None => return Err(Error::NotFoundContainerImage),
@@ -66,6 +215,7 @@ impl OGImageWriter {
 
         let width = image.width();
         let height = image.height();
+        limits.check_window(width, height)?;
 
         Ok(OGImageWriter {
             context,
@@ -77,11 +227,26 @@ impl OGImageWriter {
             },
             content: Content::default(),
             font_context: FontContext::new(),
+            frames: vec![],
+            theme: Theme::default(),
+            is_laid_out: false,
+            is_clamped: false,
+            clamped: vec![],
+            limits,
+            budget: Budget::default(),
+            stats: RenderStats::default(),
+            emoji_resolver: None,
+            script_renderer: None,
+            asset_resolver: None,
+            incremental: None,
+            named: HashMap::new(),
+            element_cache: None,
+            line_buffers: RefCell::default(),
         })
     }
 
     pub(super) fn create_tree() -> Tree {
-        Tree(Vec::with_capacity(2))
+        Tree(vec![])
     }
 
     /// Set text you want to write to image.
@@ -92,15 +257,78 @@ impl OGImageWriter {
         style: Style,
         font: Option<Vec<u8>>,
     ) -> Result<(), Error> {
+        self.set_text_with(text, style, || font.map(create_font).transpose())
+    }
+
+    /// Same as [set_text](Self::set_text), but nothing is set when `text` is `None` or empty.
+    /// Missing element takes no space, so the other elements are laid out as if it were not there.
+    pub fn set_text_optional(
+        &mut self,
+        text: Option<&str>,
+        style: Style,
+        font: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
+        match text {
+            Some(text) if !text.is_empty() => self.set_text(text, style, font),
+            _ => Ok(()),
+        }
+    }
+
+    /// Same as [set_text](Self::set_text), but font data of [SharedFont] is not copied.
+    pub fn set_text_with_font(
+        &mut self,
+        text: &str,
+        style: Style,
+        font: &SharedFont,
+    ) -> Result<(), Error> {
+        self.set_text_with(text, style, || Ok(Some(font.0.clone())))
+    }
+
+    /// Set text with font of `family` that is resolved with the [asset resolver](Self::set_asset_resolver).
+    /// When the resolver doesn't have it, regular font of the family in [FontContext::load_dir] is used.
+    pub fn set_text_with_font_family(
+        &mut self,
+        text: &str,
+        style: Style,
+        family: &str,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        if let Some(font) = self
+            .asset_resolver
+            .as_ref()
+            .and_then(|r| r.resolve_font(family))
+        {
+            return self.set_text(text, style, Some(font));
+        }
+        match self.font_context.font(family, 400) {
+            Ok(Some(font)) => self.set_text_with_font(text, style, &font),
+            Ok(None) => Err(Error::NotFoundAsset(family.to_string()).in_element(index, Some(text))),
+            Err(err) => Err(err.in_element(index, Some(text))),
+        }
+    }
+
+    // Font is created after the element is checked with limits.
+    pub(super) fn set_text_with(
+        &mut self,
+        text: &str,
+        style: Style,
+        font: impl FnOnce() -> Result<Option<FontArc>, Error>,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(text.chars().count())
+            .map_err(|err| err.in_element(index, Some(text)))?;
+
         let textarea = RefCell::new(TextArea::new());
         textarea.borrow_mut().push_text(text);
 
-        let font = match font {
-            Some(data) => Some(create_font(data)?),
-            None => None,
-        };
+        let font = font().map_err(|err| err.in_element(index, Some(text)))?;
 
+        let stopwatch = Stopwatch::start();
         self.process_text(textarea, style, font)
+            .map_err(|err| err.in_element(index, Some(text)))?;
+        self.stats.layout_time += stopwatch.elapsed();
+
+        Ok(())
     }
 
     /// Set [TextArea](super::TextArea) to image.
@@ -110,14 +338,95 @@ impl OGImageWriter {
         style: Style,
         font: Option<Vec<u8>>,
     ) -> Result<(), Error> {
+        self.set_textarea_with(textarea, style, || font.map(create_font).transpose())
+    }
+
+    /// Same as [set_textarea](Self::set_textarea), but chars are drawn with the first font in `fonts` that has them,
+    /// like CSS font stack such as `Inter, Noto Sans JP, Noto Emoji`. Fonts in [FontContext] are used after `fonts`.
+    pub fn set_textarea_with_fonts(
+        &mut self,
+        mut textarea: TextArea,
+        style: Style,
+        fonts: &[SharedFont],
+    ) -> Result<(), Error> {
+        let mut fonts = fonts.iter().map(|font| font.0.clone());
+        let font = fonts.next();
+        textarea.1 = fonts.map(|font| Arc::new(font) as Arc<dyn Font>).collect();
+        self.set_textarea_with(textarea, style, || Ok(font))
+    }
+
+    /// Same as [set_text](Self::set_text), but with font stack like [set_textarea_with_fonts](Self::set_textarea_with_fonts).
+    pub fn set_text_with_fonts(
+        &mut self,
+        text: &str,
+        style: Style,
+        fonts: &[SharedFont],
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        let mut textarea = TextArea::new();
+        textarea.push_text(text);
+        self.set_textarea_with_fonts(textarea, style, fonts)
+            .map_err(|err| err.in_element(index, Some(text)))
+    }
+
+    // Font is created after the element is checked with limits.
+    fn set_textarea_with(
+        &mut self,
+        textarea: TextArea,
+        style: Style,
+        font: impl FnOnce() -> Result<Option<FontArc>, Error>,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(textarea.char_count())
+            .map_err(|err| err.in_element(index, None))?;
+
+        let font = font().map_err(|err| err.in_element(index, None))?;
+        let stopwatch = Stopwatch::start();
+        self.process_text(RefCell::new(textarea), style, font)
+            .map_err(|err| err.in_element(index, None))?;
+        self.stats.layout_time += stopwatch.elapsed();
+
+        Ok(())
+    }
+
+    /// Measure text without painting, so that you can decide layout before setting text.
+    /// Text is broken into lines in `max_width`, and `max_height` and `text_overflow` of `style` are applied.
+    pub fn measure_text(
+        &self,
+        textarea: &TextArea,
+        style: &Style,
+        font: Option<Vec<u8>>,
+        max_width: u32,
+    ) -> Result<TextMeasurement, Error> {
         let font = match font {
             Some(data) => Some(create_font(data)?),
             None => None,
         };
-        self.process_text(RefCell::new(textarea), style, font)
+
+        let mut textarea = textarea.clone();
+        let layout = self.layout_text(&mut textarea, style, &font, max_width as f32)?;
+
+        Ok(TextMeasurement {
+            text: layout.text,
+            width: layout.metrics.max_line_width.ceil() as u32,
+            height: layout.metrics.total_height,
+            lines: layout
+                .lines
+                .into_iter()
+                .map(|line| LineBox {
+                    range: line.range,
+                    x: line.rect.x,
+                    y: line.rect.y,
+                    width: line.rect.width,
+                    height: line.rect.height,
+                })
+                .collect(),
+            is_overflow: layout.is_overflow,
+        })
     }
 
     /// Set image you want to write to image. And set the image element style.
+    /// Source like `asset://logo.png` is resolved with the [asset resolver](Self::set_asset_resolver).
     pub fn set_img(
         &mut self,
         src: &str,
@@ -125,7 +434,18 @@ impl OGImageWriter {
         height: u32,
         style: Style,
     ) -> Result<(), Error> {
-        self.process_img_with_src(src, width, height, style)
+        if let Some(uri) = src.strip_prefix(ASSET_SCHEME) {
+            let resolved = self
+                .asset_resolver
+                .as_ref()
+                .and_then(|r| r.resolve_image(uri));
+            return self.set_asset_img(src, resolved, width, height, style);
+        }
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.limits.check_image_src(src))
+            .and_then(|_| self.process_img_with_src(src, width, height, style))
+            .map_err(|err| err.in_element(index, None))
     }
 
     /// Set image you want to write to image. And set the image element style.
@@ -136,42 +456,582 @@ impl OGImageWriter {
         height: u32,
         format: ImageInputFormat,
         style: Style,
-    ) -> Result<(), ImageError> {
-        self.process_img_with_data(data, width, height, format, style)
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.limits.check_image_data(data, format))
+            .and_then(|_| self.process_img_with_data(data, width, height, format, style))
+            .map_err(|err| err.in_element(index, None))
+    }
+
+    /// Set icon that is resolved by name with the [asset resolver](Self::set_asset_resolver), as square image of `size`.
+    pub fn set_icon(&mut self, name: &str, size: u32, style: Style) -> Result<(), Error> {
+        let resolved = self
+            .asset_resolver
+            .as_ref()
+            .and_then(|r| r.resolve_icon(name));
+        self.set_asset_img(name, resolved, size, size, style)
+    }
+
+    fn set_asset_img(
+        &mut self,
+        name: &str,
+        data: Option<Vec<u8>>,
+        width: u32,
+        height: u32,
+        style: Style,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        let data =
+            data.ok_or_else(|| Error::NotFoundAsset(name.to_string()).in_element(index, None))?;
+        let format = ImageInputFormat::guess(&data)
+            .map_err(|err| Error::from(err).in_element(index, None))?;
+        self.set_img_with_data(&data, width, height, format, style)
+    }
+
+    /// Same as [set_img_with_data](Self::set_img_with_data), but nothing is set when `data` is `None`.
+    pub fn set_img_with_data_optional(
+        &mut self,
+        data: Option<&[u8]>,
+        width: u32,
+        height: u32,
+        format: ImageInputFormat,
+        style: Style,
+    ) -> Result<(), Error> {
+        match data {
+            Some(data) => self.set_img_with_data(data, width, height, format, style),
+            None => Ok(()),
+        }
+    }
+
+    /// Set image that is decoded in advance, so that the same image is not decoded for each render.
+    pub fn set_img_with_decoded(
+        &mut self,
+        image: &DecodedImage,
+        width: u32,
+        height: u32,
+        style: Style,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| {
+                self.limits
+                    .check_image_pixels(image.width(), image.height())
+            })
+            .and_then(|_| self.process_img_with_decoded(image, width, height, style))
+            .map_err(|err| err.in_element(index, None))
+    }
+
+    /// Set images that are composed by [Collage]. And set the collage element style.
+    /// The collage is laid out like image, and `border_radius` of style rounds the whole collage.
+    pub fn set_collage(&mut self, collage: Collage, style: Style) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| {
+                collage.images.iter().try_for_each(|image| match image {
+                    CollageImage::Src(src) => self.limits.check_image_src(src),
+                    CollageImage::Data(data, format) => self.limits.check_image_data(data, *format),
+                })
+            })
+            .and_then(|_| self.process_collage(collage, style))
+            .map_err(|err| err.in_element(index, None))
+    }
+
+    /// Set element implemented by [CustomElement] trait. And set the element style.
+    /// The element is laid out like image with the size returned by [CustomElement::measure].
+    pub fn set_custom(
+        &mut self,
+        element: Box<dyn CustomElement>,
+        style: Style,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.process_custom(element, style))
+            .map_err(|err| err.in_element(index, None))
+    }
+
+    /// Set text that is laid out along [TextPath]. And set the element style.
+    /// The element is laid out like image, and `font_size`, `letter_spacing`, `kern_setting` and `color` of style are applied to the text.
+    pub fn set_curved_text(
+        &mut self,
+        text: &str,
+        path: TextPath,
+        style: Style,
+        font: Vec<u8>,
+    ) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(text.chars().count())
+            .map_err(|err| err.in_element(index, Some(text)))?;
+        let element = CurvedText::new(text, path, &style, font)
+            .map_err(|err| err.in_element(index, Some(text)))?;
+        self.process_custom(Box::new(element), style)
+            .map_err(|err| err.in_element(index, Some(text)))
     }
 
     /// Set generated image by [OGImageWriter](Self) on parent image
-    pub fn set_container(&mut self, writer: &mut OGImageWriter, style: Style) -> Result<(), Error> {
-        writer.paint()?;
+    /// The container is laid out with the size of its window, and it is painted when the parent is painted.
+    /// Limits of the container writer are not inherited, so set [Limits] to the container too.
+    pub fn set_container(&mut self, writer: OGImageWriter, style: Style) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.process_container(Stack::new(writer), style))
+            .map_err(|err| err.in_element(index, None))
+    }
+
+    /// Set writers of [Stack] that are layered on the same rect.
+    /// The stack is laid out like [set_container](Self::set_container) with the first writer.
+    pub fn set_stack(&mut self, stack: Stack, style: Style) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.process_container(stack, style))
+            .map_err(|err| err.in_element(index, None))
+    }
 
-        self.process_container(writer, style)?;
+    /// Set empty element that takes space along `flex_direction`, so that spacing is visible in the template instead of margins.
+    pub fn set_spacer(&mut self, spacer: Spacer) -> Result<(), Error> {
+        let index = self.tree.0.len();
+        self.check_element(0)
+            .and_then(|_| self.process_spacer(spacer))
+            .map_err(|err| err.in_element(index, None))
+    }
 
-        Ok(())
+    /// Abort setting and painting elements with [Error::Cancelled] when the token is cancelled.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.budget.token = Some(token);
+    }
+
+    /// Abort setting and painting elements with [Error::DeadlineExceeded] after `deadline`.
+    /// Note that [Instant] is not available on `wasm32-unknown-unknown`.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.budget.deadline = Some(deadline);
+    }
+
+    // Check limits for new element.
+    pub(super) fn check_element(&self, text_len: usize) -> Result<(), Error> {
+        self.budget.check()?;
+        self.limits.check_elements(self.tree.0.len() + 1)?;
+        self.limits.check_text(text_len)
     }
 
     /// Generate your image.
-    pub fn generate(&mut self, dest: &Path) -> Result<(), Error> {
-        self.paint()?;
+    pub fn generate(&mut self, dest: &Path) -> Result<RenderStats, Error> {
+        let mut stats = self.paint()?;
+
+        let stopwatch = Stopwatch::start();
+        stats.bytes_encoded = self.context.save(dest)?;
+        stats.encode_time = stopwatch.elapsed();
 
-        self.context.save(dest)
+        Ok(stats)
     }
 
-    pub fn paint(&mut self) -> Result<(), Error> {
-        self.process();
+    /// Generate your image as PNG that is quantized to indexed palette.
+    pub fn generate_palette(
+        &mut self,
+        dest: &Path,
+        options: PaletteOptions,
+    ) -> Result<RenderStats, Error> {
+        let mut stats = self.paint()?;
 
-        while let Some(elm) = self.tree.0.pop() {
-            match elm {
-                Element::Img(Some(img)) => self.paint_img(img)?,
-                Element::Text(Some(text)) => self.paint_text(text)?,
-                _ => return Err(Error::NullElement),
+        let stopwatch = Stopwatch::start();
+        stats.bytes_encoded = self.context.save_palette(dest, options)?;
+        stats.encode_time = stopwatch.elapsed();
+
+        Ok(stats)
+    }
+
+    /// Generate your image as interlaced PNG or progressive JPEG.
+    pub fn generate_progressive(
+        &mut self,
+        dest: &Path,
+        format: ProgressiveFormat,
+    ) -> Result<RenderStats, Error> {
+        let mut stats = self.paint()?;
+
+        let stopwatch = Stopwatch::start();
+        stats.bytes_encoded = self.context.save_progressive(dest, format)?;
+        stats.encode_time = stopwatch.elapsed();
+
+        Ok(stats)
+    }
+
+    /// Paint elements. This is the same as [layout](Self::layout) followed by [rasterize](Self::rasterize).
+    pub fn paint(&mut self) -> Result<RenderStats, Error> {
+        self.layout()?;
+        self.rasterize()
+    }
+
+    /// Place elements in the window without painting. Elements are laid out only once until painting,
+    /// so this can be called before [rasterize](Self::rasterize) to measure the stages separately.
+    pub fn layout(&mut self) -> Result<(), Error> {
+        self.budget.check()?;
+        let stopwatch = Stopwatch::start();
+        self.process()?;
+        self.stats.layout_time += stopwatch.elapsed();
+        Ok(())
+    }
+
+    /// Paint laid out elements on the image, and return stats of layout and painting.
+    /// Elements are removed after painting, like [paint](Self::paint).
+    pub fn rasterize(&mut self) -> Result<RenderStats, Error> {
+        self.layout()?;
+
+        let stopwatch = Stopwatch::start();
+        match self.incremental.take() {
+            Some(mut incremental) => {
+                let result = self.paint_incremental(&mut incremental);
+                self.incremental = Some(incremental);
+                result?;
+                // Elements are set again for the next paint.
+                self.content = Content::default();
             }
+            None => self.paint_tree(None)?,
         }
+        self.stats.paint_time = stopwatch.elapsed();
+        self.clear_tree();
+
+        Ok(std::mem::take(&mut self.stats))
+    }
+
+    // Remove painted elements, so that elements are set again for the next paint.
+    fn clear_tree(&mut self) {
+        self.tree.0.clear();
+        self.is_laid_out = false;
+        self.named.clear();
 
         self.font_context.clear();
+    }
+
+    /// Paint elements in horizontal strips and write the image to `out` as PNG.
+    /// Each strip is encoded as soon as it is painted, so peak memory is bounded by the strip height
+    /// of [with_strip_height](Self::with_strip_height). This is useful for 4K images or constrained runtimes.
+    /// Strips are painted on image, so painter and SVG recording are not used.
+    pub fn write_png_strips<W: Write>(&mut self, out: W) -> Result<RenderStats, Error> {
+        self.budget.check()?;
+        let stopwatch = Stopwatch::start();
+        self.process()?;
+        self.stats.layout_time += stopwatch.elapsed();
+
+        let painter = self.context.painter.take();
+        let stopwatch = Stopwatch::start();
+        let result = self.paint_strips(out);
+        self.context.painter = painter;
+        self.stats.paint_time = stopwatch.elapsed();
+        self.clear_tree();
+
+        result?;
+        Ok(std::mem::take(&mut self.stats))
+    }
+
+    // Strip of `with_strip_height` has only background color, so it is used as background of every strip.
+    // Otherwise rows of the strip are cropped from the background image.
+    fn paint_strips<W: Write>(&mut self, out: W) -> Result<(), Error> {
+        let (width, height) = (self.window.width, self.window.height);
+        check_size("output image", width, height)?;
+        let strip_height = match &self.context.image {
+            Some(image) => image.height().max(1),
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        let mut encoder = PngStripEncoder::new(out, width, height)?;
+        for top in (0..height).step_by(strip_height as usize) {
+            let rows = strip_height.min(height - top);
+            let source_top = if strip_height >= height { top } else { 0 };
+            let strip = match &self.context.image {
+                Some(image) => imageops::crop_imm(image, 0, source_top, width, rows).to_image(),
+                None => return Err(Error::NotFoundContainerImage),
+            };
+            let image = Rc::new(RefCell::new(strip));
+            self.context.painter = Some(Box::new(StripPainter {
+                image: image.clone(),
+                top,
+            }));
+            let result = self.paint_tree(Some(&Rect::new(0, top, width, rows)));
+            self.context.painter = None;
+            result?;
+            encoder.write_strip(&image.borrow())?;
+        }
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Copy the painted image, so that static parts like background, logo and frame are painted only once.
+    /// Call this after [paint](Self::paint), and create writers for dynamic elements with [from_base](Self::from_base).
+    pub fn snapshot(&self) -> Result<BaseLayer, Error> {
+        match &self.context.image {
+            Some(image) => Ok(BaseLayer(Arc::new(image.clone()))),
+            None => Err(Error::NotFoundContainerImage),
+        }
+    }
+
+    /// Generate image for each theme from elements that are set already, and return the written path and stats of each image.
+    /// `{theme}` in `dest_pattern` is replaced with name of the theme, like `og-{theme}.png`.
+    /// Elements are laid out once and painted with colors of each theme, so layout time is counted only in the first stats.
+    /// Themes must have the same fonts and scales as theme of the writer, otherwise [Error::ThemeChangesLayout] is returned.
+    pub fn generate_variants(
+        &mut self,
+        themes: &[Theme],
+        dest_pattern: &str,
+    ) -> Result<Vec<(PathBuf, RenderStats)>, Error> {
+        let current = self.theme.clone();
+        self.paint_variants(themes, dest_pattern, Some(current), None)
+    }
+
+    /// Same as [generate_variants](Self::generate_variants), but elements are set by `build` after [set_theme](Self::set_theme),
+    /// so themes can have different fonts and scales. Elements are set and laid out again only when fonts or scales change,
+    /// and the other themes share the layout. Call this before setting elements.
+    pub fn generate_variants_with(
+        &mut self,
+        themes: &[Theme],
+        dest_pattern: &str,
+        build: impl Fn(&mut OGImageWriter) -> Result<(), Error>,
+    ) -> Result<Vec<(PathBuf, RenderStats)>, Error> {
+        self.paint_variants(themes, dest_pattern, None, Some(&build))
+    }
+
+    // `laid_out` is theme that elements are laid out with. Elements are built again for theme that changes layout.
+    fn paint_variants(
+        &mut self,
+        themes: &[Theme],
+        dest_pattern: &str,
+        mut laid_out: Option<Theme>,
+        build: Option<BuildVariant>,
+    ) -> Result<Vec<(PathBuf, RenderStats)>, Error> {
+        let background = match &self.context.image {
+            Some(image) => image.clone(),
+            None => return Err(Error::NotFoundContainerImage),
+        };
+
+        let mut variants = Vec::with_capacity(themes.len());
+        let result = themes.iter().try_for_each(|theme| {
+            // Each variant starts from the window that is not painted yet.
+            self.context.image = Some(background.clone());
+            if !laid_out
+                .as_ref()
+                .is_some_and(|prev| prev.has_same_layout(theme))
+            {
+                let build = build.ok_or_else(|| Error::ThemeChangesLayout(theme.name.clone()))?;
+                self.clear_tree();
+                self.content = Content::default();
+                self.set_theme(theme.clone())?;
+                build(self)?;
+                laid_out = Some(theme.clone());
+            }
+            // Elements are laid out only once for themes that share layout.
+            if !self.is_laid_out {
+                self.layout()?;
+            }
+            self.theme = theme.clone();
+            self.process_background()?;
+
+            let stopwatch = Stopwatch::start();
+            self.paint_tree(None)?;
+            self.stats.paint_time = stopwatch.elapsed();
+
+            let dest = PathBuf::from(dest_pattern.replace("{theme}", &theme.name));
+            let stopwatch = Stopwatch::start();
+            self.stats.bytes_encoded = self.context.save(&dest)?;
+            self.stats.encode_time = stopwatch.elapsed();
+            variants.push((dest, std::mem::take(&mut self.stats)));
+            Ok(())
+        });
+        self.clear_tree();
+        result.map(|_| variants)
+    }
 
+    /// Set theme that is referenced by `color_token` and `background_token`.
+    /// Fonts of the theme are pushed to [FontContext], so call this before setting elements.
+    pub fn set_theme(&mut self, theme: Theme) -> Result<(), Error> {
+        for font in &theme.fonts {
+            self.font_context.push(font.clone())?;
+        }
+        self.theme = theme;
+        self.process_background()
+    }
+
+    /// Paint elements with your own painter instead of the default image.
+    /// Background color of window is filled with the painter immediately.
+    /// The image is not changed while the painter is set.
+    pub fn set_painter(&mut self, painter: Box<dyn Painter>) -> Result<(), Error> {
+        self.context.painter = Some(painter);
+        self.process_background()
+    }
+
+    /// Draw emoji in texts with images returned by the resolver instead of fonts.
+    /// Call this before setting texts. Container writers need their own resolver.
+    pub fn set_emoji_resolver(&mut self, resolver: Box<dyn EmojiResolver>) {
+        self.emoji_resolver = Some(resolver);
+    }
+
+    /// Draw words in scripts that need shaping and words that no font has chars of with images returned by the renderer.
+    /// Call this before setting texts. Container writers need their own renderer.
+    pub fn set_script_renderer(&mut self, renderer: Box<dyn ScriptRenderer>) {
+        self.script_renderer = Some(renderer);
+    }
+
+    /// Resolve images, icons and font families that are referenced by name with the resolver.
+    /// Container writers need their own resolver.
+    pub fn set_asset_resolver(&mut self, resolver: Box<dyn AssetResolver>) {
+        self.asset_resolver = Some(resolver);
+    }
+
+    /// Set channels of the encoded image. Alpha channel is kept by default.
+    /// Use [OutputChannels::Rgb] for JPEG or platforms that do not support transparency.
+    pub fn set_output_channels(&mut self, channels: OutputChannels) {
+        self.context.channels = channels;
+    }
+
+    /// Paint only elements that are changed from the previous [paint](Self::paint), for fast preview loops.
+    /// Set the same elements again after each paint, and the rect of changed elements is painted over the background.
+    /// The current image is used as the background, so call this after setting theme and before setting elements.
+    /// Custom elements are always painted again, because they can not be compared.
+    pub fn set_incremental(&mut self, enabled: bool) -> Result<(), Error> {
+        self.incremental = match (enabled, &self.context.image) {
+            (false, _) => None,
+            (true, Some(image)) => Some(Incremental::new(image.clone())),
+            (true, None) => return Err(Error::NotFoundContainerImage),
+        };
+        Ok(())
+    }
+
+    /// Remove the painter set by [set_painter](Self::set_painter) and return it.
+    pub fn take_painter(&mut self) -> Option<Box<dyn Painter>> {
+        self.context.painter.take()
+    }
+
+    /// Lay out elements and return them in the order they were set, so that you can inspect the composed scene.
+    /// Call this after setting all elements and before painting, because painting clears the tree.
+    pub fn elements(&mut self) -> Result<Vec<&Element>, Error> {
+        self.process()?;
+        let len = self.tree.0.len();
+        let mut elements = vec![None; len];
+        for (i, elm) in self.tree.0.iter().enumerate() {
+            elements[self.element_index(i, len)] = Some(elm);
+        }
+        Ok(elements.into_iter().flatten().collect())
+    }
+
+    /// Lay out elements and return their regions with tags, like [elements](Self::elements).
+    pub fn regions(&mut self) -> Result<RegionMap, Error> {
+        let (width, height) = (self.window.width, self.window.height);
+        Ok(RegionMap::new(width, height, &self.elements()?))
+    }
+
+    /// Write regions as JSON sidecar file of the image. Call this before painting like [regions](Self::regions).
+    pub fn write_regions(&mut self, dest: &Path) -> Result<(), Error> {
+        fs::write(dest, self.regions()?.to_json()).map_err(ImageError::IoError)?;
         Ok(())
     }
 
+    /// Lay out elements and return texts in reading order, including texts in containers.
+    /// Use it as alt text of the image. Call this before painting like [regions](Self::regions).
+    pub fn alt_text(&mut self) -> Result<AltText, Error> {
+        Ok(AltText::new(&self.elements()?))
+    }
+
+    /// Write alt text as JSON sidecar file of the image. Call this before painting like [regions](Self::regions).
+    pub fn write_alt_text(&mut self, dest: &Path) -> Result<(), Error> {
+        fs::write(dest, self.alt_text()?.to_json()).map_err(ImageError::IoError)?;
+        Ok(())
+    }
+
+    /// Lay out elements and return outlines of glyphs in painting order instead of painting them.
+    /// This is useful to generate vector formats, or effects like animated tracing.
+    /// Texts in containers are not included because containers are drawn as images.
+    pub fn glyph_paths(&mut self) -> Result<Vec<GlyphPath>, Error> {
+        let paths = Rc::new(RefCell::new(vec![]));
+        let painter = self
+            .context
+            .painter
+            .replace(Box::new(GlyphPathRecorder(paths.clone())));
+        let result = self.paint();
+        self.context.painter = painter;
+        result?;
+
+        Ok(paths.take())
+    }
+
+    /// Start recording draw calls as SVG document.
+    /// Call this before setting elements, then get the document with [into_svg](Self::into_svg) or [generate_svg](Self::generate_svg).
+    /// Text is written as glyph paths, and images are embedded as base64 encoded PNG.
+    pub fn record_svg(&mut self) -> Result<(), Error> {
+        let background_color = self.window.background_color.map(|c| c.as_image_rgba());
+        self.context.start_svg(background_color)
+    }
+
+    /// Generate your image as SVG document.
+    pub fn generate_svg(&mut self, dest: &Path) -> Result<(), Error> {
+        self.paint()?;
+
+        self.context.save_svg(dest)
+    }
+
+    /// Return the recorded SVG document. Elements that are not painted yet are painted, like [generate_svg](Self::generate_svg).
+    pub fn into_svg(mut self) -> Result<String, Error> {
+        self.paint()?;
+
+        self.context.into_svg()
+    }
+
+    /// Paint current elements as an animation frame that is displayed for `delay` milliseconds.
+    /// After the frame is captured, the image is restored to the background,
+    /// so you can set elements for the next frame to the same writer.
+    pub fn push_frame(&mut self, delay: u32) -> Result<(), Error> {
+        let background = match &self.context.image {
+            Some(image) => image.clone(),
+            None => return Err(Error::NotFoundContainerImage),
+        };
+
+        self.paint()?;
+
+        let image = match self.context.image.replace(background) {
+            Some(image) => image,
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        self.content = Content::default();
+        self.frames.push(AnimationFrame::new(image, delay));
+
+        Ok(())
+    }
+
+    /// Crop transparent pixels around painted content, for stickers and badges rather than full-bleed cards.
+    /// Call this after [paint](Self::paint) and before encoding.
+    pub fn trim_transparent(&mut self) -> Result<(), Error> {
+        self.context.trim(|pixel| pixel.0[3] > 0)
+    }
+
+    /// Crop borders that have the same color as the top left pixel.
+    /// Colors whose channels differ by at most `tolerance` are regarded as the same color.
+    /// Call this after [paint](Self::paint) and before encoding.
+    pub fn trim_uniform(&mut self, tolerance: u8) -> Result<(), Error> {
+        let border = match &self.context.image {
+            Some(image) if image.width() > 0 && image.height() > 0 => *image.get_pixel(0, 0),
+            Some(_) => return Ok(()),
+            None => return Err(Error::NotFoundContainerImage),
+        };
+        self.context.trim(|pixel| {
+            pixel
+                .0
+                .iter()
+                .zip(border.0)
+                .any(|(&a, b)| a.abs_diff(b) > tolerance)
+        })
+    }
+
+    /// Generate animated image from frames pushed by [push_frame](Self::push_frame).
+    pub fn generate_animation(
+        &mut self,
+        dest: &Path,
+        format: AnimationFormat,
+    ) -> Result<(), Error> {
+        let buf = encode_frames(std::mem::take(&mut self.frames), format)?;
+        fs::write(dest, buf).map_err(ImageError::IoError)?;
+        Ok(())
+    }
+
+    /// Return the encoded animated image data from frames pushed by [push_frame](Self::push_frame).
+    pub fn encode_animation(self, format: AnimationFormat) -> Result<Vec<u8>, Error> {
+        encode_frames(self.frames, format)
+    }
+
     /// Return the raw image data.
     pub fn into_vec(self) -> Result<Vec<u8>, Error> {
         self.context.into_vec()
@@ -186,203 +1046,552 @@ impl OGImageWriter {
         self.context.encode(format)
     }
 
-    fn paint_img(&mut self, img: Img) -> Result<(), Error> {
-        self.context.draw_image(img.buf, img.rect.x, img.rect.y)
+    /// Return the painted image that is encoded by [`Raster::encode()`] on any thread.
+    pub fn into_raster(self) -> Result<Raster, Error> {
+        let (image, channels) = self.context.into_output()?;
+        Ok(Raster { image, channels })
+    }
+
+    /// Return PNG data that is quantized to indexed palette.
+    pub fn encode_palette(self, options: PaletteOptions) -> Result<Vec<u8>, Error> {
+        self.context.encode_palette(options)
+    }
+
+    /// Return the encoded image data that is displayed progressively while it is loaded.
+    pub fn encode_progressive(self, format: ProgressiveFormat) -> Result<Vec<u8>, Error> {
+        self.context.encode_progressive(format)
+    }
+
+    // Paint the rect of changed elements on the background, and copy it to the image.
+    // Every element is painted when draw calls are sent to painter or SVG.
+    fn paint_incremental(&mut self, incremental: &mut Incremental) -> Result<(), Error> {
+        let dirty = incremental.update(&self.tree.0);
+        if self.context.painter.is_some() || self.context.svg.is_some() {
+            return self.paint_tree(None);
+        }
+        let dirty = match dirty {
+            Some(dirty) => dirty,
+            None => return Ok(()),
+        };
+
+        let mut image = self
+            .context
+            .image
+            .replace(incremental.background.clone())
+            .ok_or(Error::NotFoundContainerImage)?;
+        let result = self.paint_tree(Some(&dirty));
+        if let Some(painted) = &self.context.image {
+            copy_rect(&mut image, painted, &dirty);
+        }
+        self.context.image = Some(image);
+        result
+    }
+
+    // Paint laid out elements without consuming tree, so the same layout can be painted again.
+    // Elements outside of `dirty` are skipped when it is set.
+    fn paint_tree(&mut self, dirty: Option<&Rect>) -> Result<(), Error> {
+        let tree = std::mem::take(&mut self.tree.0);
+        let len = tree.len();
+        let (width, height) = (self.window.width, self.window.height);
+        let result = tree.iter().enumerate().rev().try_for_each(|(i, elm)| {
+            self.budget.check()?;
+            if let Some(dirty) = dirty {
+                if !intersects(dirty, &bounds(elm, width, height)) {
+                    return Ok(());
+                }
+            }
+            let index = self.element_index(i, len);
+            match elm {
+                Element::Img(Some(img)) => {
+                    self.stats.images += 1;
+                    self.paint_img(img)
+                }
+                Element::Text(Some(text)) => self
+                    .paint_text(text)
+                    .map_err(|err| err.in_element(index, Some(&text.text))),
+                Element::Custom(Some(custom)) => {
+                    self.stats.custom_elements += 1;
+                    self.paint_custom(custom)
+                }
+                _ => Err(Error::NullElement),
+            }
+            .map_err(|err| err.in_element(index, None))
+        });
+        self.tree.0 = tree;
+        // Pending draw calls are applied even when painting fails, so that nothing is left in the batch.
+        let flushed = self.context.flush();
+        result?;
+        flushed
+    }
+
+    // Index of element in the order it was set, from position in laid out tree.
+    pub(super) fn element_index(&self, position: usize, len: usize) -> usize {
+        // Tree is reversed by layout unless `JustifyContent::End`.
+        if matches!(self.window.justify_content, JustifyContent::End) {
+            len - 1 - position
+        } else {
+            position
+        }
+    }
+
+    fn paint_img(&mut self, img: &Img) -> Result<(), Error> {
+        self.context.draw_image(&img.buf, img.rect.x, img.rect.y)
+    }
+
+    fn paint_custom(&mut self, custom: &Custom) -> Result<(), Error> {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = custom.rect;
+        self.context
+            .draw_custom(custom.element.as_ref(), x, y, width, height)
+    }
+
+    fn paint_text(&mut self, text_elm: &Text) -> Result<(), Error> {
+        if let Some(numbers) = &text_elm.style.line_numbers {
+            self.paint_line_numbers(text_elm, numbers)?;
+        }
+        if let Some(bar) = &text_elm.style.text_bar {
+            self.paint_text_bar(text_elm, bar)?;
+        }
+        match (&text_elm.style.knockout, &text_elm.style.fill) {
+            (Some(bar), _) => {
+                let mask = self.paint_lines_to_mask(text_elm)?;
+                let rect = lines_rect(&text_elm.fragments);
+                match knockout_mask(&mask, bar, &rect) {
+                    Some((image, x, y)) => self.context.draw_image(&image, x, y),
+                    None => Ok(()),
+                }
+            }
+            (None, Some(paint)) => {
+                let mask = self.paint_lines_to_mask(text_elm)?;
+                let rect = lines_rect(&text_elm.fragments);
+                match fill_mask(&mask, paint, &rect)? {
+                    Some((image, x, y)) => self.context.draw_image(&image, x, y),
+                    None => Ok(()),
+                }
+            }
+            (None, None) => self.paint_lines(text_elm),
+        }
+    }
+
+    // Draw lines into offscreen image of window size instead of the painter, to use it as mask.
+    fn paint_lines_to_mask(&mut self, text_elm: &Text) -> Result<RgbaImage, Error> {
+        let mask = Rc::new(RefCell::new(RgbaImage::new(
+            self.window.width,
+            self.window.height,
+        )));
+        let painter = self
+            .context
+            .painter
+            .replace(Box::new(SharedImage(mask.clone())));
+        let result = self.paint_lines(text_elm);
+        self.context.painter = painter;
+        result?;
+        Ok(mask.take())
     }
 
-    fn paint_text(&mut self, text_elm: Text) -> Result<(), Error> {
+    fn paint_lines(&mut self, text_elm: &Text) -> Result<(), Error> {
+        for fragment in &text_elm.fragments {
+            self.budget.check()?;
+            let text = &text_elm.text[fragment.range.clone()];
+            self.stats.lines += 1;
+            self.stats.glyphs += text.chars().filter(|ch| !ch.is_whitespace()).count();
+            let mut current_width = 0;
+            let mut ruby_runs = vec![];
+            let line_ascent = text_elm.line_ascent(fragment, &self.font_context);
+            let advances = self.fragment_advances(text_elm, fragment)?;
+            let runs = text_elm.textarea.glyph_runs(fragment.range.clone())?;
+            for (run, advance) in runs.iter().zip(advances.iter()) {
+                let run_start = current_width;
+                self.paint_glyph_run(text_elm, fragment, run, line_ascent, current_width)?;
+                current_width += advance;
+                push_ruby_run(&mut ruby_runs, run.split_text, run_start, current_width);
+            }
+
+            for (split_text, start, end) in ruby_runs {
+                self.paint_ruby(text_elm, fragment, split_text, start, end)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Draw number of each line that starts after line break, with the first font that has digits.
+    fn paint_line_numbers(&mut self, text_elm: &Text, numbers: &LineNumbers) -> Result<(), Error> {
+        let style = &text_elm.style;
+        let setting = FontSetting {
+            size: style.font_size,
+            tabular_nums: true,
+            ..FontSetting::default()
+        };
+        let parent_font = text_elm
+            .font
+            .as_ref()
+            .map(|font| font as &dyn Font)
+            .filter(|&font| match_font_family('0', font));
+        let global_font = match parent_font {
+            Some(_) => None,
+            None => Some(self.font_context.select_font_family('0')?),
+        };
+
+        let mut number = numbers.start;
+        for fragment in &text_elm.fragments {
+            let start = fragment.range.start;
+            if start > 0 && !text_elm.text[..start].ends_with(['\n', LINE_SEPARATOR]) {
+                continue;
+            }
+            let label = number.to_string();
+            number += 1;
+
+            let line_ascent = text_elm.line_ascent(fragment, &self.font_context);
+            let context = &mut self.context;
+            let mut draw = |font: &dyn Font| {
+                let width = context.measure_text(font, &setting, &label).width as u32;
+                let right = fragment.rect.x.saturating_sub(fragment.inset) + numbers.width;
+                let offset = (line_ascent - font.ascent(setting.size)).max(0.) as u32;
+                context.draw_text(
+                    numbers.color.as_image_rgba(),
+                    right.saturating_sub(width),
+                    fragment.rect.y + offset,
+                    font,
+                    &setting,
+                    &label,
+                )
+            };
+            match (parent_font, &global_font) {
+                (Some(font), _) => draw(font)?,
+                (None, Some(idx)) => self.font_context.with(idx, draw)?,
+                (None, None) => return Err(Error::NotFoundSpecifiedFontFamily),
+            }
+        }
+        Ok(())
+    }
+
+    // Draw bar from the top of the first line to the bottom of the last line in each column.
+    fn paint_text_bar(&mut self, text_elm: &Text, bar: &TextBar) -> Result<(), Error> {
+        // Column, left, top and bottom of bars.
+        let mut bars: Vec<(usize, u32, u32, u32)> = vec![];
+        for fragment in &text_elm.fragments {
+            let rect = &fragment.rect;
+            match bars
+                .iter_mut()
+                .find(|(column, ..)| *column == fragment.column)
+            {
+                Some((_, _, top, bottom)) => {
+                    *top = (*top).min(rect.y);
+                    *bottom = (*bottom).max(rect.y + rect.height);
+                }
+                None => bars.push((
+                    fragment.column,
+                    rect.x.saturating_sub(fragment.inset) + text_elm.style.gutter_width(),
+                    rect.y,
+                    rect.y + rect.height,
+                )),
+            }
+        }
+        for (_, x, top, bottom) in bars {
+            let color = bar.color.as_image_rgba();
+            self.context
+                .fill_rect(x, top, bar.width, bottom - top, color)?;
+        }
+        Ok(())
+    }
+
+    // Advance of each glyph run in the fragment. Runs are measured only when they are not measured in layout.
+    pub(crate) fn fragment_advances<'a>(
+        &self,
+        text_elm: &Text,
+        fragment: &'a Fragment,
+    ) -> Result<Cow<'a, [u32]>, Error> {
+        match &fragment.advances {
+            Some(advances) => Ok(Cow::Borrowed(advances)),
+            None => self
+                .run_advances(
+                    &text_elm.text,
+                    fragment.range.clone(),
+                    &text_elm.style,
+                    &text_elm.font,
+                    &text_elm.textarea,
+                )
+                .map(Cow::Owned),
+        }
+    }
+
+    // Draw the run at `current_width` in the fragment.
+    fn paint_glyph_run(
+        &mut self,
+        text_elm: &Text,
+        fragment: &Fragment,
+        run: &GlyphRun,
+        line_ascent: f32,
+        current_width: u32,
+    ) -> Result<(), Error> {
+        #[allow(clippy::too_many_arguments)]
         fn render_text(
             text: &str,
-            range: &mut Range<usize>,
+            range: Range<usize>,
             font: &dyn Font,
             context: &mut Context,
-            current_width: &mut u32,
+            current_width: u32,
             style: &Style,
+            theme: &Theme,
             fragment: &Fragment,
+            line_ascent: f32,
         ) -> Result<(), Error> {
-            let next_text = &text[range.clone()];
+            let next_text = &*display_text(&text[range.clone()], range.end == text.len());
 
             let setting = FontSetting {
                 size: style.font_size,
                 letter_spacing: style.letter_spacing,
                 kern_setting: style.kern_setting,
                 is_pre: style.white_space.is_pre(),
+                preserve_spaces: style.white_space.preserves_spaces(),
+                tabular_nums: style.font_variant_numeric.is_tabular(),
             };
 
-            context.draw_text(
-                style.color.as_image_rgba(),
-                fragment.rect.x + *current_width,
-                fragment.rect.y,
-                font,
-                &setting,
-                next_text,
-            )?;
+            let color = match style.color_token {
+                Some(token) => theme.color(token),
+                None => style.color,
+            };
 
-            *range = range.end..range.end;
-            *current_width += font.text_extents(next_text, &setting).width as u32;
+            let x = fragment.rect.x + current_width;
+            let y = text_run_y(font, style, fragment, line_ascent);
+            match style.jitter {
+                Some(jitter) => {
+                    let run = TextRun::new(next_text, font, &setting, color.as_image_rgba(), x, y);
+                    // Glyphs are numbered by their position in text, so that each glyph keeps its jitter.
+                    let start = (fragment.range.start + range.start) as u64;
+                    for (i, path) in run.glyph_paths().into_iter().enumerate() {
+                        context.draw_glyph_path(&jitter_glyph(path, &jitter, start + i as u64))?;
+                    }
+                }
+                None => {
+                    context.draw_text(color.as_image_rgba(), x, y, font, &setting, next_text)?
+                }
+            }
+
+            if let TextEffect::Letterpress = style.text_effect {
+                let run = TextRun::new(next_text, font, &setting, color.as_image_rgba(), x, y);
+                if let Some((overlay, x, y)) = letterpress(&run) {
+                    context.draw_image(&overlay, x, y)?;
+                }
+            }
 
             Ok(())
         }
 
-        let style = text_elm.style;
-        let mut current_split_text: Option<&SplitText> = None;
-        let mut current_glyph: Option<&Glyph> = None;
-        for fragment in &text_elm.fragments {
-            let text = &text_elm.text[fragment.range.clone()];
-            let mut range = 0..0;
-            let mut current_width = 0;
-            for (i, ch) in text.char_indices() {
-                let ch_len = ch.to_string().len();
-                let (split_text, glyph) = text_elm.textarea.get_glyphs_from_char_range(
-                    fragment.range.start + i..fragment.range.start + i + ch_len,
-                );
-                let contained = match (split_text, glyph) {
-                    (Some(split_text), Some(glyph)) => {
-                        match (&current_split_text, &current_glyph) {
-                            (Some(current_split_text), Some(current_glyph)) => {
-                                split_text.range.start >= current_split_text.range.start
-                                    && split_text.range.end <= current_split_text.range.end
-                                    && glyph.font_index_store == current_glyph.font_index_store
-                            }
-                            (None, None) => {
-                                current_split_text = Some(split_text);
-                                current_glyph = Some(glyph);
-                                true
-                            }
-                            _ => return Err(Error::OutOfRangeText),
-                        }
-                    }
-                    _ => return Err(Error::OutOfRangeText),
-                };
-
-                if !contained {
-                    // current_split_text is always Some.
-                    let style = match current_split_text {
-                        Some(current_split_text) => match &current_split_text.style {
-                            Some(style) => style,
-                            None => &style,
-                        },
-                        None => &style,
-                    };
-
-                    match current_glyph {
-                        Some(glyph) => match &glyph.font_index_store {
-                            FontIndexStore::Global(idx) => {
-                                let context = &mut self.context;
-                                self.font_context.with(idx, |font| {
-                                    render_text(
-                                        text,
-                                        &mut range,
-                                        font,
-                                        context,
-                                        &mut current_width,
-                                        style,
-                                        fragment,
-                                    )
-                                })?;
-                            }
-                            FontIndexStore::Parent(_) => {
-                                let font = match &text_elm.font {
-                                    Some(font) => font,
-                                    None => return Err(Error::NotFoundSpecifiedFontFamily),
-                                };
-                                render_text(
-                                    text,
-                                    &mut range,
-                                    font,
-                                    &mut self.context,
-                                    &mut current_width,
-                                    style,
-                                    fragment,
-                                )?;
-                            }
-                            FontIndexStore::Child(_) => match current_split_text {
-                                Some(temp_split_text) => match &temp_split_text.font {
-                                    Some(font) => render_text(
-                                        text,
-                                        &mut range,
-                                        &**font,
-                                        &mut self.context,
-                                        &mut current_width,
-                                        style,
-                                        fragment,
-                                    )?,
-                                    None => return Err(Error::NotFoundSpecifiedFontFamily),
-                                },
-                                None => return Err(Error::OutOfRangeText),
-                            },
-                        },
-                        None => return Err(Error::NotFoundSpecifiedFontFamily),
-                    };
-
-                    current_split_text = split_text;
-                    current_glyph = glyph;
-                }
-                range.end = i + ch_len;
-            }
-            if !range.is_empty() {
-                let style = match current_split_text {
-                    Some(inner_split_text) => match &inner_split_text.style {
-                        Some(style) => style,
-                        None => &style,
-                    },
-                    None => &style,
-                };
-
-                match current_glyph {
-                    Some(glyph) => match &glyph.font_index_store {
-                        FontIndexStore::Global(idx) => {
-                            let context = &mut self.context;
-                            self.font_context.with(idx, |font| {
-                                render_text(
-                                    text,
-                                    &mut range,
-                                    font,
-                                    context,
-                                    &mut current_width,
-                                    style,
-                                    fragment,
-                                )
-                            })?;
-                        }
-                        FontIndexStore::Parent(_) => {
-                            let font = match &text_elm.font {
-                                Some(font) => font,
-                                None => return Err(Error::NotFoundSpecifiedFontFamily),
-                            };
-                            render_text(
-                                text,
-                                &mut range,
-                                font,
-                                &mut self.context,
-                                &mut current_width,
-                                style,
-                                fragment,
-                            )?;
-                        }
-                        FontIndexStore::Child(_) => match current_split_text {
-                            Some(split_text) => match &split_text.font {
-                                Some(font) => {
-                                    render_text(
-                                        text,
-                                        &mut range,
-                                        &**font,
-                                        &mut self.context,
-                                        &mut current_width,
-                                        style,
-                                        fragment,
-                                    )?;
-                                }
-                                None => return Err(Error::NotFoundSpecifiedFontFamily),
-                            },
-                            None => return Err(Error::OutOfRangeText),
-                        },
-                    },
-                    None => return Err(Error::OutOfRangeText),
-                };
+        // Emoji image is drawn as a square of font size, centered vertically in the line.
+        fn render_emoji(
+            image: &RgbaImage,
+            context: &mut Context,
+            current_width: u32,
+            style: &Style,
+            fragment: &Fragment,
+        ) -> Result<(), Error> {
+            let size = style.font_size as u32;
+            let image = imageops::resize(image, size, size, FilterType::Triangle);
+            let y = fragment.rect.y + fragment.rect.height.saturating_sub(size) / 2;
+            context.draw_image(&image, fragment.rect.x + current_width, y)?;
+
+            Ok(())
+        }
+
+        // Rendered image is drawn without resizing, and its baseline is placed on baseline of the line.
+        fn render_script_image(
+            image: &ScriptImage,
+            context: &mut Context,
+            current_width: u32,
+            style: &Style,
+            fragment: &Fragment,
+            line_ascent: f32,
+        ) -> Result<(), Error> {
+            let ascent = image.baseline as f32;
+            let y = run_y(
+                ascent,
+                image.image.height() as f32,
+                style,
+                fragment,
+                line_ascent,
+            );
+            context.draw_image(&image.image, fragment.rect.x + current_width, y)?;
+
+            Ok(())
+        }
+
+        let text = &text_elm.text[fragment.range.clone()];
+        let range = run.range.start - fragment.range.start..run.range.end - fragment.range.start;
+        let style = run.split_text.style.as_ref().unwrap_or(&text_elm.style);
+        let not_found = || Error::NotFoundSpecifiedFontFamily.at_char(run.range.start);
+        match &run.glyph.font_index_store {
+            FontIndexStore::Global(idx) => {
+                let context = &mut self.context;
+                let theme = &self.theme;
+                self.font_context.with(idx, |font| {
+                    render_text(
+                        text,
+                        range,
+                        font,
+                        context,
+                        current_width,
+                        style,
+                        theme,
+                        fragment,
+                        line_ascent,
+                    )
+                })
             }
+            FontIndexStore::Parent(idx) => match text_elm
+                .textarea
+                .parent_font(idx, text_elm.font.as_ref().map(|font| font as &dyn Font))
+            {
+                Some(font) => render_text(
+                    text,
+                    range,
+                    font,
+                    &mut self.context,
+                    current_width,
+                    style,
+                    &self.theme,
+                    fragment,
+                    line_ascent,
+                ),
+                None => Err(not_found()),
+            },
+            FontIndexStore::Child(idx) => match run.split_text.font(idx) {
+                Some(font) => render_text(
+                    text,
+                    range,
+                    font,
+                    &mut self.context,
+                    current_width,
+                    style,
+                    &self.theme,
+                    fragment,
+                    line_ascent,
+                ),
+                None => Err(not_found()),
+            },
+            FontIndexStore::Emoji(idx) => match run.split_text.emoji.get(idx.0) {
+                Some(image) => {
+                    render_emoji(image, &mut self.context, current_width, style, fragment)
+                }
+                None => Err(Error::OutOfRangeText.at_char(run.range.start)),
+            },
+            FontIndexStore::Script(idx) => match run.split_text.script_images.get(idx.0) {
+                Some(image) => render_script_image(
+                    image,
+                    &mut self.context,
+                    current_width,
+                    style,
+                    fragment,
+                    line_ascent,
+                ),
+                None => Err(Error::OutOfRangeText.at_char(run.range.start)),
+            },
         }
+    }
 
-        Ok(())
+    // Draw ruby centered above base text between `start` and `end` in the fragment.
+    fn paint_ruby(
+        &mut self,
+        text_elm: &Text,
+        fragment: &Fragment,
+        split_text: &SplitText,
+        start: u32,
+        end: u32,
+    ) -> Result<(), Error> {
+        let ruby = match &split_text.ruby {
+            Some(ruby) => ruby,
+            None => return Ok(()),
+        };
+        let style = split_text.style.as_ref().unwrap_or(&text_elm.style);
+        let setting = FontSetting {
+            size: ruby_size(style),
+            letter_spacing: 0,
+            kern_setting: style.kern_setting,
+            is_pre: false,
+            preserve_spaces: false,
+            tabular_nums: false,
+        };
+        let color = match style.color_token {
+            Some(token) => self.theme.color(token),
+            None => style.color,
+        };
+
+        let context = &mut self.context;
+        let mut draw = |font: &dyn Font| {
+            let width = context.measure_text(font, &setting, ruby).width as u32;
+            let x = (fragment.rect.x + (start + end) / 2).saturating_sub(width / 2);
+            let y = fragment.rect.y.saturating_sub(setting.size as u32);
+            context.draw_text(color.as_image_rgba(), x, y, font, &setting, ruby)
+        };
+
+        let chars: Vec<char> = ruby.chars().collect();
+        let has_all = |font: &dyn Font| chars.iter().all(|&ch| match_font_family(ch, font));
+        let parent_font = text_elm.font.as_ref().map(|font| font as &dyn Font);
+        let font = split_text
+            .fonts
+            .iter()
+            .map(|font| &**font)
+            .chain(parent_font)
+            .chain(text_elm.textarea.1.iter().map(|font| &**font))
+            .find(|&font| has_all(font));
+        match font {
+            Some(font) => draw(font),
+            None => match self.font_context.select_font_family_for_run(&chars) {
+                Some(idx) => self.font_context.with(&idx, draw),
+                None => Err(Error::NotFoundSpecifiedFontFamily
+                    .at_char(fragment.range.start + split_text.range.start)),
+            },
+        }
+    }
+}
+
+// Record horizontal position of text that has ruby.
+// Base text can be drawn with multiple runs when it has some fonts.
+fn push_ruby_run<'a>(
+    runs: &mut Vec<(&'a SplitText, u32, u32)>,
+    split_text: &'a SplitText,
+    start: u32,
+    end: u32,
+) {
+    if split_text.ruby.is_none() {
+        return;
+    }
+    match runs.last_mut() {
+        Some((last, _, last_end)) if std::ptr::eq(*last, split_text) => *last_end = end,
+        _ => runs.push((split_text, start, end)),
     }
 }
+// Top of text run in the line, that is moved by `vertical_align` and `baseline_shift`.
+pub(super) fn text_run_y(
+    font: &dyn Font,
+    style: &Style,
+    fragment: &Fragment,
+    line_ascent: f32,
+) -> u32 {
+    let ascent = font.ascent(style.font_size);
+    let height = ascent + font.descent(style.font_size);
+    run_y(ascent, height, style, fragment, line_ascent)
+}
+
+// Top of run that has `ascent` and `height`, like text in font or rendered image.
+pub(super) fn run_y(
+    ascent: f32,
+    height: f32,
+    style: &Style,
+    fragment: &Fragment,
+    line_ascent: f32,
+) -> u32 {
+    // Baseline offset is negative when fallback font is taller than the primary font of `fallback_metrics`.
+    let offset = match style.vertical_align {
+        VerticalAlign::Baseline => line_ascent - ascent,
+        VerticalAlign::Top => 0.,
+        VerticalAlign::Middle => ((fragment.rect.height as f32 - height) / 2.).max(0.),
+        VerticalAlign::Bottom => (fragment.rect.height as f32 - height).max(0.),
+    };
+    (fragment.rect.y as f32 + offset - style.baseline_shift as f32).max(0.) as u32
+}
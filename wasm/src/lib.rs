@@ -1,16 +1,20 @@
 mod style;
 
 use og_image_writer::{
-    font_context::FontContext, style::Style, writer::OGImageWriter, Error, ImageOutputFormat,
-    TextArea,
+    font_context::FontContext,
+    style::{Spacer, Style},
+    writer::OGImageWriter,
+    Error, ImageOutputFormat, OutputChannels, ProgressiveFormat, TextArea,
 };
 use std::panic;
 use std::path::Path;
 use wasm_bindgen::prelude::*;
 
-use style::{from_js_style, from_js_window_style, JsStyle, JsWindowStyle};
+use style::{from_js_rgba, from_js_style, from_js_window_style, JsRgba, JsStyle, JsWindowStyle};
 
 pub use og_image_writer::img::ImageInputFormat;
+pub use og_image_writer::AnimationFormat;
+pub use og_image_writer::PaletteOptions;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "wee_alloc")] {
@@ -48,6 +52,13 @@ impl JsImageOutputFormat {
             JsImageOutputFormat::Jpeg => ImageOutputFormat::Jpeg(option.q),
         }
     }
+
+    fn into_progressive(self, option: JsImageOutputFormatOption) -> ProgressiveFormat {
+        match self {
+            JsImageOutputFormat::Png => ProgressiveFormat::Png,
+            JsImageOutputFormat::Jpeg => ProgressiveFormat::Jpeg(option.q),
+        }
+    }
 }
 
 #[wasm_bindgen(js_name = FontContext)]
@@ -72,6 +83,10 @@ impl JsFontContext {
         self.context.clear();
     }
 
+    pub fn prewarm(&self, chars: String, sizes: Vec<f32>) {
+        self.context.prewarm(&chars, &sizes);
+    }
+
     pub fn len(&self) -> usize {
         self.context.len()
     }
@@ -108,6 +123,11 @@ impl JsTextArea {
         self.0.push(JsSplitText { text, style, font });
     }
 
+    /// Push text and break line after it regardless of `white_space`.
+    pub fn push_line(&mut self, text: String, style: Option<JsStyle>, font: Option<Vec<u8>>) {
+        self.push(format!("{}\n", text), style, font);
+    }
+
     fn into_textarea(self) -> Result<TextArea, Error> {
         let mut textarea = TextArea::new();
         for mut split_text in self.0 {
@@ -145,11 +165,53 @@ impl JsOGImageWriter {
         }
     }
 
+    pub fn with_strip_height(style: JsWindowStyle, strip_height: u32) -> Self {
+        let style = from_js_window_style(style);
+
+        JsOGImageWriter {
+            writer: OGImageWriter::with_strip_height(style, strip_height).unwrap(),
+        }
+    }
+
     pub fn set_text(&mut self, text: String, style: JsStyle, font: Option<Vec<u8>>) {
         let style = from_js_style(style);
         self.writer.set_text(&text, style, font).unwrap();
     }
 
+    pub fn set_text_optional(
+        &mut self,
+        text: Option<String>,
+        style: JsStyle,
+        font: Option<Vec<u8>>,
+    ) {
+        let style = from_js_style(style);
+        self.writer
+            .set_text_optional(text.as_deref(), style, font)
+            .unwrap();
+    }
+
+    pub fn set_text_with_id(
+        &mut self,
+        id: String,
+        text: String,
+        style: JsStyle,
+        font: Option<Vec<u8>>,
+    ) {
+        let style = from_js_style(style);
+        self.writer
+            .set_text_with_id(&id, &text, style, font)
+            .unwrap();
+    }
+
+    pub fn update_text(&mut self, id: String, text: String) {
+        self.writer.update_text(&id, &text).unwrap();
+    }
+
+    pub fn update_text_style(&mut self, id: String, style: JsStyle) {
+        let style = from_js_style(style);
+        self.writer.update_text_style(&id, style).unwrap();
+    }
+
     pub fn set_textarea(&mut self, textarea: JsTextArea, style: JsStyle, font: Option<Vec<u8>>) {
         let style = from_js_style(style);
         self.writer
@@ -171,11 +233,17 @@ impl JsOGImageWriter {
             .unwrap();
     }
 
-    pub fn set_container(&mut self, writer: &mut JsOGImageWriter, style: JsStyle) {
+    pub fn set_container(&mut self, writer: JsOGImageWriter, style: JsStyle) {
         let style = from_js_style(style);
-        self.writer
-            .set_container(&mut writer.writer, style)
-            .unwrap();
+        self.writer.set_container(writer.writer, style).unwrap();
+    }
+
+    pub fn set_spacer(&mut self, size: u32) {
+        self.writer.set_spacer(Spacer::Fixed(size)).unwrap();
+    }
+
+    pub fn set_flex_spacer(&mut self, grow: f32) {
+        self.writer.set_spacer(Spacer::Flex(grow)).unwrap();
     }
 
     pub fn generate(&mut self, dest: String) {
@@ -186,6 +254,40 @@ impl JsOGImageWriter {
         self.writer.paint().unwrap();
     }
 
+    pub fn trim_transparent(&mut self) {
+        self.writer.trim_transparent().unwrap();
+    }
+
+    pub fn trim_uniform(&mut self, tolerance: u8) {
+        self.writer.trim_uniform(tolerance).unwrap();
+    }
+
+    /// Alpha channel is dropped after blending onto `background` when it is passed.
+    pub fn set_output_channels(&mut self, background: Option<JsRgba>) {
+        self.writer.set_output_channels(match background {
+            Some(color) => OutputChannels::Rgb(from_js_rgba(color)),
+            None => OutputChannels::Rgba,
+        });
+    }
+
+    pub fn set_incremental(&mut self, enabled: bool) {
+        self.writer.set_incremental(enabled).unwrap();
+    }
+
+    pub fn write_png_strips(&mut self) -> Vec<u8> {
+        let mut buf = vec![];
+        self.writer.write_png_strips(&mut buf).unwrap();
+        buf
+    }
+
+    pub fn push_frame(&mut self, delay: u32) {
+        self.writer.push_frame(delay).unwrap();
+    }
+
+    pub fn encode_animation(self, format: AnimationFormat) -> Vec<u8> {
+        self.writer.encode_animation(format).unwrap()
+    }
+
     pub fn into_vec(self) -> Vec<u8> {
         self.writer.into_vec().unwrap()
     }
@@ -194,4 +296,17 @@ impl JsOGImageWriter {
         let f = f.into(option);
         self.writer.encode(f).unwrap()
     }
+
+    pub fn encode_progressive(
+        self,
+        f: JsImageOutputFormat,
+        option: JsImageOutputFormatOption,
+    ) -> Vec<u8> {
+        let f = f.into_progressive(option);
+        self.writer.encode_progressive(f).unwrap()
+    }
+
+    pub fn encode_palette(self, options: PaletteOptions) -> Vec<u8> {
+        self.writer.encode_palette(options).unwrap()
+    }
 }
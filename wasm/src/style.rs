@@ -1,6 +1,8 @@
 use og_image_writer::style::{
-    AlignItems, BorderRadius, FlexDirection, JustifyContent, KernSetting, Margin, Position, Rgba,
-    Style, TextAlign, TextOverflow, WhiteSpace, WindowStyle, WordBreak,
+    AlignItems, BorderRadius, Direction, FallbackMetrics, FlexDirection, FontVariantNumeric,
+    ImageFrame, ImageRepeat, JustifyContent, KernSetting, LanguageTag, Margin, MarginAuto,
+    Position, Rgba, Style, TextAlign, TextEffect, TextOverflow, TextTransform, TextWrap,
+    VerticalAlign, WhiteSpace, WindowStyle, WordBreak,
 };
 use std::marker::Copy;
 use wasm_bindgen::prelude::*;
@@ -74,24 +76,80 @@ impl JsMargin {
     }
 }
 
+#[wasm_bindgen(js_name = MarginAuto)]
+#[derive(Default, Copy, Clone)]
+pub struct JsMarginAuto {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+#[wasm_bindgen(js_class = MarginAuto)]
+impl JsMarginAuto {
+    pub fn new(top: bool, right: bool, bottom: bool, left: bool) -> JsMarginAuto {
+        JsMarginAuto {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
+
 #[wasm_bindgen(js_name = Style)]
 pub struct JsStyle {
     pub margin: JsMargin,
+    pub margin_auto: JsMarginAuto,
+    pub align_self: Option<AlignItems>,
     /// For Text element
     pub line_height: f32,
     /// For Text element
     pub font_size: f32,
     /// For Text element
+    pub emoji_only_scale: f32,
+    /// For Text element
     pub letter_spacing: i32,
     pub kern_setting: KernSetting,
     /// For Text element
+    pub font_variant_numeric: FontVariantNumeric,
+    /// For Text element
+    pub fallback_metrics: FallbackMetrics,
+    /// For Text element
     pub word_break: WordBreak,
     /// For Text element
     pub white_space: WhiteSpace,
     /// For Text element
+    /// Tab stops are placed every `tab_size` spaces when `white_space` is `Pre`.
+    pub tab_size: u32,
+    /// For Text element
+    /// Balancing is applied to text that has at most 6 lines.
+    pub text_wrap: TextWrap,
+    /// For Text element
+    /// Positive value indents first line, and negative value indents the other lines as hanging indent.
+    pub text_indent: i32,
+    /// For Text element
+    /// Text flows into balanced columns when this is more than 1.
+    pub columns: u32,
+    /// For Text element
+    /// Space between columns.
+    pub column_gap: u32,
+    /// For Text element
+    pub text_transform: TextTransform,
+    /// For Text element
+    lang: Option<String>,
+    /// For Text element
     pub color: JsRgba,
     /// For Text element
     pub text_align: TextAlign,
+    pub direction: Direction,
+    /// For Text element
+    pub vertical_align: VerticalAlign,
+    /// For Text element
+    /// Move text up by positive value, and down by negative value.
+    pub baseline_shift: i32,
+    /// For Text element
+    pub text_effect: TextEffect,
     /// For Text element
     pub max_height: Option<u32>,
     /// For Text element
@@ -104,8 +162,17 @@ pub struct JsStyle {
     pub right: Option<i32>,
     pub bottom: Option<i32>,
     pub left: Option<i32>,
+    pub flex_grow: f32,
+    /// For Text element
+    pub flex_shrink: f32,
+    /// For Img, Collage and Custom element
+    /// Ratio of width to height. Height of the element is computed from its width when this is set,
+    /// or width is computed from height when width is 0. Ratio must be finite and greater than 0.
+    pub aspect_ratio: Option<f32>,
     /// For Img element
     pub border_radius: JsBorderRadius,
+    /// For Img element
+    pub image_repeat: ImageRepeat,
 }
 
 #[wasm_bindgen(js_class = Style)]
@@ -118,18 +185,35 @@ impl JsStyle {
     pub fn set_text_overflow(&mut self, value: JsTextOverflow) {
         self.text_overflow = value.into_serde().unwrap();
     }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_lang(&mut self, value: Option<String>) {
+        self.lang = value;
+    }
 }
 
 impl Default for JsStyle {
     fn default() -> Self {
         JsStyle {
             margin: JsMargin::default(),
+            margin_auto: JsMarginAuto::default(),
+            align_self: None,
             line_height: 1.5,
             font_size: 30.,
+            emoji_only_scale: 1.,
             letter_spacing: 0,
             kern_setting: KernSetting::Normal,
+            font_variant_numeric: FontVariantNumeric::Normal,
+            fallback_metrics: FallbackMetrics::Font,
             word_break: WordBreak::Normal,
             white_space: WhiteSpace::Normal,
+            tab_size: 8,
+            text_wrap: TextWrap::Wrap,
+            text_indent: 0,
+            columns: 1,
+            column_gap: 0,
+            text_transform: TextTransform::None,
+            lang: None,
             color: JsRgba {
                 r: 0,
                 g: 0,
@@ -137,6 +221,10 @@ impl Default for JsStyle {
                 a: 255,
             },
             text_align: TextAlign::Start,
+            direction: Direction::Auto,
+            vertical_align: VerticalAlign::Baseline,
+            baseline_shift: 0,
+            text_effect: TextEffect::None,
             max_height: None,
             max_width: None,
             text_overflow: "clip".to_string(),
@@ -145,7 +233,11 @@ impl Default for JsStyle {
             right: None,
             bottom: None,
             left: None,
+            flex_grow: 0.,
+            flex_shrink: 0.,
+            aspect_ratio: None,
             border_radius: JsBorderRadius::default(),
+            image_repeat: ImageRepeat::NoRepeat,
         }
     }
 }
@@ -181,6 +273,10 @@ impl Default for JsWindowStyle {
     }
 }
 
+pub fn from_js_rgba(color: JsRgba) -> Rgba {
+    Rgba([color.r, color.g, color.b, color.a])
+}
+
 pub fn from_js_style(style: JsStyle) -> Style {
     Style {
         margin: Margin(
@@ -189,14 +285,43 @@ pub fn from_js_style(style: JsStyle) -> Style {
             style.margin.bottom,
             style.margin.left,
         ),
+        margin_token: None,
+        margin_auto: MarginAuto(
+            style.margin_auto.top,
+            style.margin_auto.right,
+            style.margin_auto.bottom,
+            style.margin_auto.left,
+        ),
+        align_self: style.align_self,
+        tag: None,
         line_height: style.line_height,
         font_size: style.font_size,
+        emoji_only_scale: style.emoji_only_scale,
         letter_spacing: style.letter_spacing,
         kern_setting: style.kern_setting,
+        font_variant_numeric: style.font_variant_numeric,
+        fallback_metrics: style.fallback_metrics,
         word_break: style.word_break,
         white_space: style.white_space,
+        tab_size: style.tab_size,
+        text_wrap: style.text_wrap,
+        text_indent: style.text_indent,
+        text_bar: None,
+        line_numbers: None,
+        columns: style.columns,
+        column_gap: style.column_gap,
+        text_transform: style.text_transform,
+        lang: style.lang.as_deref().map(LanguageTag::new),
         color: Rgba([style.color.r, style.color.g, style.color.b, style.color.a]),
+        color_token: None,
+        fill: None,
+        knockout: None,
         text_align: style.text_align,
+        direction: style.direction,
+        vertical_align: style.vertical_align,
+        baseline_shift: style.baseline_shift,
+        text_effect: style.text_effect,
+        jitter: None,
         max_height: style.max_height,
         max_width: style.max_width,
         text_overflow: {
@@ -211,12 +336,22 @@ pub fn from_js_style(style: JsStyle) -> Style {
         right: style.right,
         bottom: style.bottom,
         left: style.left,
+        id: None,
+        anchor: None,
+        flex_grow: style.flex_grow,
+        flex_shrink: style.flex_shrink,
+        aspect_ratio: style.aspect_ratio,
         border_radius: BorderRadius(
             style.border_radius.top_left,
             style.border_radius.top_right,
             style.border_radius.bottom_left,
             style.border_radius.bottom_right,
         ),
+        border_radius_token: None,
+        crop: None,
+        image_slice: None,
+        image_repeat: style.image_repeat,
+        image_frame: ImageFrame::First,
     }
 }
 
@@ -227,6 +362,7 @@ pub fn from_js_window_style(style: JsWindowStyle) -> WindowStyle {
         background_color: style
             .background_color
             .map(|color| Rgba([color.r, color.g, color.b, color.a])),
+        background_token: None,
         align_items: style.align_items,
         justify_content: style.justify_content,
         flex_direction: style.flex_direction,